@@ -4,6 +4,7 @@ mod anvil;
 mod block;
 mod decode_array;
 mod idle;
+mod many_entities;
 mod many_players;
 mod packet;
 mod var_int;
@@ -18,6 +19,7 @@ criterion_group! {
     var_int::var_int,
     var_long::var_long,
     many_players::many_players,
+    many_entities::many_entities,
 }
 
 criterion_main!(benches);
@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use bevy_app::prelude::*;
+use criterion::{Criterion, Throughput};
+use valence::client::VisibleEntityLayers;
+use valence::entity::cow::CowEntityBundle;
+use valence::entity::{EntityLayerId, Position};
+use valence::keepalive::KeepaliveSettings;
+use valence::layer::chunk::UnloadedChunk;
+use valence::layer::{ChunkLayer, EntityLayer};
+use valence::network::NetworkPlugin;
+use valence::registry::{BiomeRegistry, DimensionTypeRegistry};
+use valence::testing::create_mock_client;
+use valence::{ident, DefaultPlugins, Server, ServerSettings};
+use valence_server::CompressionThreshold;
+
+const ENTITY_COUNT: usize = 200;
+
+/// Benches a tick in which many entities come into view of a client at once.
+pub fn many_entities(c: &mut Criterion) {
+    let mut app = App::new();
+
+    app.insert_resource(ServerSettings {
+        compression_threshold: CompressionThreshold(256),
+        ..Default::default()
+    });
+
+    app.insert_resource(KeepaliveSettings {
+        period: Duration::MAX,
+    });
+
+    app.add_plugins(DefaultPlugins.build().disable::<NetworkPlugin>());
+
+    app.update(); // Initialize plugins.
+
+    let mut chunk_layer = ChunkLayer::new(
+        ident!("overworld"),
+        app.world.resource::<DimensionTypeRegistry>(),
+        app.world.resource::<BiomeRegistry>(),
+        app.world.resource::<Server>(),
+    );
+
+    for z in -4..4 {
+        for x in -4..4 {
+            chunk_layer.insert_chunk([x, z], UnloadedChunk::new());
+        }
+    }
+
+    let chunk_layer = app.world.spawn(chunk_layer).id();
+
+    // The entities live on their own layer so that they can be brought in and
+    // out of view by changing the client's visible entity layers.
+    let entity_layer = EntityLayer::new(app.world.resource::<Server>());
+    let entity_layer = app.world.spawn(entity_layer).id();
+
+    for i in 0..ENTITY_COUNT {
+        let x = (i % 20) as f64 * 2.0 - 20.0;
+        let z = (i / 20) as f64 * 2.0 - 10.0;
+
+        app.world.spawn(CowEntityBundle {
+            position: Position::new([x, 64.0, z]),
+            layer: EntityLayerId(entity_layer),
+            ..Default::default()
+        });
+    }
+
+    let (mut bundle, mut helper) = create_mock_client("client");
+
+    bundle.visible_chunk_layer.0 = chunk_layer;
+    bundle.player.layer.0 = chunk_layer;
+    bundle.player.position.set([0.0, 64.0, 0.0]);
+
+    let client = app.world.spawn(bundle).id();
+
+    app.update();
+
+    helper.confirm_initial_pending_teleports();
+
+    let set_visible = |app: &mut App, visible: bool| {
+        let mut layers = app.world.get_mut::<VisibleEntityLayers>(client).unwrap();
+
+        if visible {
+            layers.0.insert(entity_layer);
+        } else {
+            layers.0.remove(&entity_layer);
+        }
+    };
+
+    // Measure how much the client receives when all entities come into view, so
+    // the throughput is reported alongside the time.
+    set_visible(&mut app, true);
+    app.update();
+
+    let byte_count: usize = helper
+        .collect_received()
+        .0
+        .iter()
+        .map(|f| f.body.len())
+        .sum();
+
+    set_visible(&mut app, false);
+    app.update();
+    helper.clear_received();
+
+    let mut group = c.benchmark_group("many_entities");
+    group.throughput(Throughput::Bytes(byte_count as u64));
+
+    group.bench_function("enter_view", |b| {
+        b.iter(|| {
+            set_visible(&mut app, true);
+            app.update();
+
+            set_visible(&mut app, false);
+            app.update();
+
+            helper.clear_received();
+        });
+    });
+}
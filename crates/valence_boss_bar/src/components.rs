@@ -1,6 +1,7 @@
 use std::borrow::Cow;
+use std::collections::BTreeSet;
 
-use bevy_ecs::prelude::{Bundle, Component};
+use bevy_ecs::prelude::{Bundle, Component, Entity};
 use derive_more::{Deref, DerefMut};
 use valence_entity::EntityLayerId;
 use valence_server::protocol::packets::play::boss_bar_s2c::{
@@ -59,6 +60,51 @@ impl ToPacketAction for BossBarFlags {
     }
 }
 
+/// An optional component that shows a boss bar to an explicit set of clients
+/// instead of to the clients viewing its [`EntityLayerId`].
+///
+/// Adding a client to the set sends it the boss bar, and removing it hides the
+/// boss bar again. Changes to the boss bar are only sent to the clients in the
+/// set. Despawned clients are removed from the set automatically.
+///
+/// Adding this component to a boss bar that was already shown through its layer
+/// hides it from the viewers of the layer first.
+#[derive(Component, Clone, Default, Debug)]
+pub struct BossBarViewers {
+    pub(crate) current: BTreeSet<Entity>,
+    /// The clients the boss bar was sent to as of the last tick.
+    pub(crate) old: BTreeSet<Entity>,
+}
+
+impl BossBarViewers {
+    pub fn new(clients: impl IntoIterator<Item = Entity>) -> Self {
+        Self {
+            current: clients.into_iter().collect(),
+            old: BTreeSet::new(),
+        }
+    }
+
+    /// Shows the boss bar to the given client. Returns `false` if the client
+    /// was already a viewer.
+    pub fn insert(&mut self, client: Entity) -> bool {
+        self.current.insert(client)
+    }
+
+    /// Hides the boss bar from the given client. Returns `false` if the client
+    /// was not a viewer.
+    pub fn remove(&mut self, client: Entity) -> bool {
+        self.current.remove(&client)
+    }
+
+    pub fn contains(&self, client: Entity) -> bool {
+        self.current.contains(&client)
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = Entity> + '_ {
+        self.current.iter().copied()
+    }
+}
+
 /// Trait for converting a component to a boss bar action.
 pub(crate) trait ToPacketAction {
     fn to_packet_action(&self) -> BossBarAction;
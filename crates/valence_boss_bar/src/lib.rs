@@ -23,7 +23,8 @@ use std::borrow::Cow;
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use valence_server::client::{
-    Client, OldViewDistance, OldVisibleEntityLayers, ViewDistance, VisibleEntityLayers,
+    Client, OldViewDistance, OldVisibleEntityLayers, UpdateClientsSet, ViewDistance,
+    VisibleEntityLayers,
 };
 use valence_server::layer::UpdateLayersPreClientSet;
 pub use valence_server::protocol::packets::play::boss_bar_s2c::{
@@ -53,12 +54,31 @@ impl Plugin for BossBarPlugin {
                 boss_bar_despawn,
             )
                 .before(UpdateLayersPreClientSet),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                (
+                    update_boss_bar_for_viewers::<BossBarTitle>,
+                    update_boss_bar_for_viewers::<BossBarHealth>,
+                    update_boss_bar_for_viewers::<BossBarStyle>,
+                    update_boss_bar_for_viewers::<BossBarFlags>,
+                ),
+                remove_despawned_boss_bar_viewers,
+                update_boss_bar_viewers,
+                boss_bar_despawn_for_viewers,
+            )
+                .chain()
+                .in_set(UpdateClientsSet),
         );
     }
 }
 
 fn update_boss_bar<T: Component + ToPacketAction>(
-    boss_bars_query: Query<(&UniqueId, &T, &EntityLayerId, Option<&Position>), Changed<T>>,
+    boss_bars_query: Query<
+        (&UniqueId, &T, &EntityLayerId, Option<&Position>),
+        (Changed<T>, Without<BossBarViewers>),
+    >,
     mut entity_layers_query: Query<&mut EntityLayer>,
 ) {
     for (id, part, entity_layer_id, pos) in boss_bars_query.iter() {
@@ -91,15 +111,18 @@ fn update_boss_bar_layer_view(
         ),
         Changed<VisibleEntityLayers>,
     >,
-    boss_bars_query: Query<(
-        &UniqueId,
-        &BossBarTitle,
-        &BossBarHealth,
-        &BossBarStyle,
-        &BossBarFlags,
-        &EntityLayerId,
-        Option<&Position>,
-    )>,
+    boss_bars_query: Query<
+        (
+            &UniqueId,
+            &BossBarTitle,
+            &BossBarHealth,
+            &BossBarStyle,
+            &BossBarFlags,
+            &EntityLayerId,
+            Option<&Position>,
+        ),
+        Without<BossBarViewers>,
+    >,
 ) {
     for (
         mut client,
@@ -185,15 +208,18 @@ fn update_boss_bar_chunk_view(
         ),
         Changed<Position>,
     >,
-    boss_bars_query: Query<(
-        &UniqueId,
-        &BossBarTitle,
-        &BossBarHealth,
-        &BossBarStyle,
-        &BossBarFlags,
-        &EntityLayerId,
-        &Position,
-    )>,
+    boss_bars_query: Query<
+        (
+            &UniqueId,
+            &BossBarTitle,
+            &BossBarHealth,
+            &BossBarStyle,
+            &BossBarFlags,
+            &EntityLayerId,
+            &Position,
+        ),
+        Without<BossBarViewers>,
+    >,
 ) {
     for (
         mut client,
@@ -243,7 +269,10 @@ fn update_boss_bar_chunk_view(
 }
 
 fn boss_bar_despawn(
-    boss_bars_query: Query<(&UniqueId, &EntityLayerId, Option<&Position>), With<Despawned>>,
+    boss_bars_query: Query<
+        (&UniqueId, &EntityLayerId, Option<&Position>),
+        (With<Despawned>, Without<BossBarViewers>),
+    >,
     mut entity_layer_query: Query<&mut EntityLayer>,
 ) {
     for (id, entity_layer_id, position) in boss_bars_query.iter() {
@@ -262,3 +291,151 @@ fn boss_bar_despawn(
         }
     }
 }
+
+fn add_action<'a>(
+    title: &'a BossBarTitle,
+    health: &BossBarHealth,
+    style: &BossBarStyle,
+    flags: &BossBarFlags,
+) -> BossBarAction<'a> {
+    BossBarAction::Add {
+        title: Cow::Borrowed(&title.0),
+        health: health.0,
+        color: style.color,
+        division: style.division,
+        flags: *flags,
+    }
+}
+
+/// Forgets the viewers that were despawned, so that they don't pile up in
+/// long-lived boss bars.
+fn remove_despawned_boss_bar_viewers(
+    mut removed_clients: RemovedComponents<Client>,
+    despawned_clients: Query<Entity, (With<Client>, Added<Despawned>)>,
+    mut boss_bars_query: Query<&mut BossBarViewers>,
+) {
+    let mut despawned: Vec<Entity> = removed_clients.iter().collect();
+    despawned.extend(despawned_clients.iter());
+
+    if despawned.is_empty() {
+        return;
+    }
+
+    for mut viewers in &mut boss_bars_query {
+        // Despawned clients don't need a remove packet.
+        let viewers = viewers.bypass_change_detection();
+
+        for client in &despawned {
+            viewers.current.remove(client);
+            viewers.old.remove(client);
+        }
+    }
+}
+
+fn update_boss_bar_viewers(
+    mut boss_bars_query: Query<
+        (
+            Ref<UniqueId>,
+            &BossBarTitle,
+            &BossBarHealth,
+            &BossBarStyle,
+            &BossBarFlags,
+            &mut BossBarViewers,
+            Option<&EntityLayerId>,
+            Option<&Position>,
+        ),
+        Changed<BossBarViewers>,
+    >,
+    mut clients_query: Query<(
+        &mut Client,
+        Option<&VisibleEntityLayers>,
+        Option<&Position>,
+        Option<&ViewDistance>,
+    )>,
+) {
+    for (id, title, health, style, flags, mut viewers, layer_id, boss_bar_position) in
+        &mut boss_bars_query
+    {
+        // The boss bar was shown to the viewers of its layer until now, unless it
+        // was just spawned. They must forget it before the explicit viewers are
+        // sent the boss bar.
+        if viewers.is_added() && !id.is_added() {
+            if let Some(layer_id) = layer_id {
+                for (mut client, visible_layers, position, view_distance) in &mut clients_query {
+                    let in_view = match (boss_bar_position, position, view_distance) {
+                        (Some(boss_bar_position), Some(position), Some(view_distance)) => {
+                            ChunkView::new(position.to_chunk_pos(), view_distance.get())
+                                .contains(boss_bar_position.to_chunk_pos())
+                        }
+                        _ => true,
+                    };
+
+                    if in_view && visible_layers.is_some_and(|l| l.0.contains(&layer_id.0)) {
+                        client.write_packet(&BossBarS2c {
+                            id: id.0,
+                            action: BossBarAction::Remove,
+                        });
+                    }
+                }
+            }
+        }
+
+        let viewers = viewers.bypass_change_detection();
+
+        for &removed in viewers.old.difference(&viewers.current) {
+            if let Ok((mut client, ..)) = clients_query.get_mut(removed) {
+                client.write_packet(&BossBarS2c {
+                    id: id.0,
+                    action: BossBarAction::Remove,
+                });
+            }
+        }
+
+        for &added in viewers.current.difference(&viewers.old) {
+            if let Ok((mut client, ..)) = clients_query.get_mut(added) {
+                client.write_packet(&BossBarS2c {
+                    id: id.0,
+                    action: add_action(title, health, style, flags),
+                });
+            }
+        }
+
+        viewers.old.clone_from(&viewers.current);
+    }
+}
+
+fn update_boss_bar_for_viewers<T: Component + ToPacketAction>(
+    boss_bars_query: Query<(&UniqueId, &T, &BossBarViewers), Changed<T>>,
+    mut clients_query: Query<&mut Client>,
+) {
+    for (id, part, viewers) in &boss_bars_query {
+        let packet = BossBarS2c {
+            id: id.0,
+            action: part.to_packet_action(),
+        };
+
+        // Only the clients that already have the boss bar need the update. New
+        // viewers get the up-to-date boss bar in `update_boss_bar_viewers`.
+        for &viewer in &viewers.old {
+            if let Ok(mut client) = clients_query.get_mut(viewer) {
+                client.write_packet(&packet);
+            }
+        }
+    }
+}
+
+fn boss_bar_despawn_for_viewers(
+    boss_bars_query: Query<(&UniqueId, &BossBarViewers), With<Despawned>>,
+    mut clients_query: Query<&mut Client>,
+) {
+    for (id, viewers) in &boss_bars_query {
+        for viewer in viewers.iter() {
+            if let Ok(mut client) = clients_query.get_mut(viewer) {
+                client.write_packet(&BossBarS2c {
+                    id: id.0,
+                    action: BossBarAction::Remove,
+                });
+            }
+        }
+    }
+}
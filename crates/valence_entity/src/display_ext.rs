@@ -0,0 +1,242 @@
+use valence_math::{EulerRot, Quat};
+
+use super::*;
+
+/// The transformation applied to the model of a [`display`](crate::display)
+/// entity, made up of its [`Translation`](display::Translation),
+/// [`LeftRotation`](display::LeftRotation), [`Scale`](display::Scale) and
+/// [`RightRotation`](display::RightRotation) components.
+///
+/// The client applies the right rotation first, then the scale, the left
+/// rotation and finally the translation.
+///
+/// # Examples
+///
+/// ```
+/// use valence_entity::block_display::BlockDisplayEntityBundle;
+/// use valence_entity::{DisplayTransform, EulerAngle};
+/// use valence_math::Vec3;
+///
+/// let tilt = EulerAngle {
+///     pitch: 0.0,
+///     yaw: 0.0,
+///     roll: 30.0,
+/// };
+///
+/// let transform =
+///     DisplayTransform::from_scale(Vec3::splat(3.0)).with_left_rotation(tilt.to_quat());
+///
+/// let (translation, left_rotation, scale, right_rotation) = transform.into_components();
+///
+/// let bundle = BlockDisplayEntityBundle {
+///     display_translation: translation,
+///     display_left_rotation: left_rotation,
+///     display_scale: scale,
+///     display_right_rotation: right_rotation,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct DisplayTransform {
+    pub translation: Vec3,
+    pub left_rotation: Quat,
+    pub scale: Vec3,
+    pub right_rotation: Quat,
+}
+
+impl DisplayTransform {
+    /// The transform that leaves the model unchanged.
+    pub const IDENTITY: Self = Self {
+        translation: Vec3::ZERO,
+        left_rotation: Quat::IDENTITY,
+        scale: Vec3::ONE,
+        right_rotation: Quat::IDENTITY,
+    };
+
+    /// Creates a transform that only scales the model.
+    pub const fn from_scale(scale: Vec3) -> Self {
+        Self {
+            scale,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Creates a transform that only translates the model.
+    pub const fn from_translation(translation: Vec3) -> Self {
+        Self {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub const fn with_translation(mut self, translation: Vec3) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    pub const fn with_left_rotation(mut self, rotation: Quat) -> Self {
+        self.left_rotation = rotation;
+        self
+    }
+
+    pub const fn with_scale(mut self, scale: Vec3) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub const fn with_right_rotation(mut self, rotation: Quat) -> Self {
+        self.right_rotation = rotation;
+        self
+    }
+
+    /// Splits the transform into its components. The returned tuple is a
+    /// [`Bundle`], so it can be inserted into an existing display entity to
+    /// replace its transform.
+    pub fn into_components(
+        self,
+    ) -> (
+        display::Translation,
+        display::LeftRotation,
+        display::Scale,
+        display::RightRotation,
+    ) {
+        (
+            display::Translation(self.translation),
+            display::LeftRotation(self.left_rotation),
+            display::Scale(self.scale),
+            display::RightRotation(self.right_rotation),
+        )
+    }
+}
+
+impl Default for DisplayTransform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl EulerAngle {
+    /// Converts the angles, in degrees, to a quaternion. The rotation is
+    /// applied around the Y axis (yaw) first, then the X axis (pitch) and
+    /// finally the Z axis (roll).
+    pub fn to_quat(self) -> Quat {
+        Quat::from_euler(
+            EulerRot::YXZ,
+            self.yaw.to_radians(),
+            self.pitch.to_radians(),
+            self.roll.to_radians(),
+        )
+    }
+}
+
+/// The horizontal alignment of the lines of a
+/// [`text_display`](crate::text_display) entity, stored in its
+/// [`TextDisplayFlags`](text_display::TextDisplayFlags).
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum TextAlignment {
+    #[default]
+    Center,
+    Left,
+    Right,
+}
+
+impl text_display::TextDisplayFlags {
+    const ALIGN_LEFT: i8 = 1 << 3;
+    const ALIGN_RIGHT: i8 = 1 << 4;
+
+    /// Gets the text alignment from bits 3 and 4.
+    pub const fn alignment(&self) -> TextAlignment {
+        if self.0 & Self::ALIGN_LEFT != 0 {
+            TextAlignment::Left
+        } else if self.0 & Self::ALIGN_RIGHT != 0 {
+            TextAlignment::Right
+        } else {
+            TextAlignment::Center
+        }
+    }
+
+    /// Sets the text alignment in bits 3 and 4.
+    pub fn set_alignment(&mut self, alignment: TextAlignment) {
+        self.0 &= !(Self::ALIGN_LEFT | Self::ALIGN_RIGHT);
+
+        match alignment {
+            TextAlignment::Center => {}
+            TextAlignment::Left => self.0 |= Self::ALIGN_LEFT,
+            TextAlignment::Right => self.0 |= Self::ALIGN_RIGHT,
+        }
+    }
+}
+
+impl text_display::Background {
+    /// A fully transparent background.
+    pub const TRANSPARENT: Self = Self(0);
+
+    /// Creates a background from a color in `0xAARRGGBB` form.
+    pub const fn from_argb(argb: u32) -> Self {
+        Self(argb as i32)
+    }
+
+    /// Returns the background color in `0xAARRGGBB` form.
+    pub const fn argb(&self) -> u32 {
+        self.0 as u32
+    }
+}
+
+impl text_display::TextOpacity {
+    /// Creates a text opacity from an alpha value, where `255` is fully
+    /// opaque.
+    pub const fn from_alpha(alpha: u8) -> Self {
+        Self(alpha as i8)
+    }
+
+    /// Returns the alpha value of the text, where `255` is fully opaque.
+    pub const fn alpha(&self) -> u8 {
+        self.0 as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn euler_angle_to_quat() {
+        let quat = EulerAngle {
+            pitch: 0.0,
+            yaw: 90.0,
+            roll: 0.0,
+        }
+        .to_quat();
+
+        assert!(quat.abs_diff_eq(Quat::from_rotation_y(90_f32.to_radians()), 1e-6));
+        assert!((quat * Vec3::X).abs_diff_eq(Vec3::NEG_Z, 1e-6));
+    }
+
+    #[test]
+    fn text_display_alignment() {
+        let mut flags = text_display::TextDisplayFlags(0);
+        flags.set_shadow(true);
+
+        assert_eq!(flags.alignment(), TextAlignment::Center);
+
+        flags.set_alignment(TextAlignment::Left);
+        assert_eq!(flags.alignment(), TextAlignment::Left);
+        assert_eq!(flags.0, 0b01001);
+
+        flags.set_alignment(TextAlignment::Right);
+        assert_eq!(flags.alignment(), TextAlignment::Right);
+        assert_eq!(flags.0, 0b10001);
+
+        flags.set_alignment(TextAlignment::Center);
+        assert_eq!(flags.0, 0b00001);
+    }
+
+    #[test]
+    fn text_display_background_argb() {
+        let bg = text_display::Background::from_argb(0x80ff0000);
+
+        assert_eq!(bg.argb(), 0x80ff0000);
+        assert!(bg.0 < 0);
+        assert_eq!(text_display::TextOpacity::from_alpha(255).0, -1);
+    }
+}
@@ -123,6 +123,11 @@ flags! {
     spider::SpiderFlags {
         climbing_wall: 0,
     }
+    text_display::TextDisplayFlags {
+        shadow: 0,
+        see_through: 1,
+        default_background: 2,
+    }
 }
 
 #[cfg(test)]
@@ -54,6 +54,7 @@ impl Plugin for HitboxPlugin {
                     update_player_hitbox,
                     update_item_frame_hitbox,
                     update_slime_hitbox,
+                    update_interaction_hitbox,
                     update_painting_hitbox,
                     update_shulker_hitbox,
                 ),
@@ -86,6 +87,19 @@ impl HitboxShape {
         self.0
     }
 
+    /// The height above the entity's position at which the client seats its
+    /// [`Passengers`](crate::Passengers).
+    ///
+    /// The client derives this from the vehicle's dimensions. For most
+    /// entities it is three quarters of the hitbox height, although some
+    /// vehicles such as boats, horses and striders use their own offsets.
+    /// Entities whose dimensions come from tracked data, like
+    /// [`interaction`](crate::interaction) entities and slimes, can be used to
+    /// choose an arbitrary seat height.
+    pub fn passenger_riding_offset(&self) -> f64 {
+        (self.0.max().y - self.0.min().y) * 0.75
+    }
+
     pub(crate) fn centered(&mut self, size: DVec3) {
         self.0 = Aabb::from_bottom_size(DVec3::ZERO, size);
     }
@@ -101,6 +115,55 @@ impl Hitbox {
     }
 }
 
+impl EntityKind {
+    /// Returns the `[width, height]` of this kind of entity in its default
+    /// state. That is, as an adult with the default pose and tracked data, so
+    /// a player is 0.6 blocks wide and 1.8 blocks tall.
+    ///
+    /// Returns `None` for entities whose size depends on more than their
+    /// default state, such as paintings, or that have no hitbox handled by
+    /// Valence.
+    pub const fn dimensions(self) -> Option<[f64; 2]> {
+        let [width, height, _] = match self {
+            EntityKind::PLAYER => [0.6, 1.8, 0.6],
+            EntityKind::WARDEN => [0.9, 2.9, 0.9],
+            EntityKind::AREA_EFFECT_CLOUD => [6.0, 0.5, 6.0],
+            EntityKind::ARMOR_STAND => [0.5, 1.975, 0.5],
+            EntityKind::ZOMBIE
+            | EntityKind::DROWNED
+            | EntityKind::HUSK
+            | EntityKind::ZOMBIE_VILLAGER
+            | EntityKind::ZOMBIFIED_PIGLIN
+            | EntityKind::PIGLIN => [0.6, 1.95, 0.6],
+            EntityKind::ZOGLIN => [1.39648, 1.4, 1.39648],
+            EntityKind::GOAT => [0.9, 1.3, 0.9],
+            EntityKind::TURTLE => [1.2, 0.4, 1.2],
+            EntityKind::SLIME | EntityKind::MAGMA_CUBE => [0.5202, 0.5202, 0.5202],
+            EntityKind::INTERACTION | EntityKind::SHULKER => [1.0, 1.0, 1.0],
+            kind => match constant_hitbox_size(kind) {
+                Some(size) => size,
+                None => match passive_adult_hitbox_size(kind) {
+                    Some(size) => size,
+                    None => return None,
+                },
+            },
+        };
+
+        Some([width, height])
+    }
+
+    /// Returns the hitbox of this kind of entity in its default state when
+    /// standing at `pos`. See [`Self::dimensions`].
+    pub fn hitbox(self, pos: DVec3) -> Option<Aabb> {
+        let [width, height] = self.dimensions()?;
+
+        Some(Aabb::from_bottom_size(
+            pos,
+            DVec3::new(width, height, width),
+        ))
+    }
+}
+
 fn add_hitbox_component(
     settings: Res<EntityHitboxSettings>,
     mut commands: Commands,
@@ -141,86 +204,91 @@ fn update_constant_hitbox(
     >,
 ) {
     for (mut hitbox, entity_kind) in hitbox_query.iter_mut() {
-        let size = match *entity_kind {
-            EntityKind::ALLAY => [0.6, 0.35, 0.6],
-            EntityKind::CHEST_BOAT | EntityKind::BOAT => [1.375, 0.5625, 1.375],
-            EntityKind::FROG => [0.5, 0.5, 0.5],
-            EntityKind::TADPOLE => [0.4, 0.3, 0.4],
-            EntityKind::SPECTRAL_ARROW | EntityKind::ARROW => [0.5, 0.5, 0.5],
-            EntityKind::AXOLOTL => [1.3, 0.6, 1.3],
-            EntityKind::BAT => [0.5, 0.9, 0.5],
-            EntityKind::BLAZE => [0.6, 1.8, 0.6],
-            EntityKind::CAT => [0.6, 0.7, 0.6],
-            EntityKind::CAVE_SPIDER => [0.7, 0.5, 0.7],
-            EntityKind::COD => [0.5, 0.3, 0.5],
-            EntityKind::CREEPER => [0.6, 1.7, 0.6],
-            EntityKind::DOLPHIN => [0.9, 0.6, 0.9],
-            EntityKind::DRAGON_FIREBALL => [1.0, 1.0, 1.0],
-            EntityKind::ELDER_GUARDIAN => [1.9975, 1.9975, 1.9975],
-            EntityKind::END_CRYSTAL => [2.0, 2.0, 2.0],
-            EntityKind::ENDER_DRAGON => [16.0, 8.0, 16.0],
-            EntityKind::ENDERMAN => [0.6, 2.9, 0.6],
-            EntityKind::ENDERMITE => [0.4, 0.3, 0.4],
-            EntityKind::EVOKER => [0.6, 1.95, 0.6],
-            EntityKind::EVOKER_FANGS => [0.5, 0.8, 0.5],
-            EntityKind::EXPERIENCE_ORB => [0.5, 0.5, 0.5],
-            EntityKind::EYE_OF_ENDER => [0.25, 0.25, 0.25],
-            EntityKind::FALLING_BLOCK => [0.98, 0.98, 0.98],
-            EntityKind::FIREWORK_ROCKET => [0.25, 0.25, 0.25],
-            EntityKind::GHAST => [4.0, 4.0, 4.0],
-            EntityKind::GIANT => [3.6, 12.0, 3.6],
-            EntityKind::GLOW_SQUID | EntityKind::SQUID => [0.8, 0.8, 0.8],
-            EntityKind::GUARDIAN => [0.85, 0.85, 0.85],
-            EntityKind::ILLUSIONER => [0.6, 1.95, 0.6],
-            EntityKind::IRON_GOLEM => [1.4, 2.7, 1.4],
-            EntityKind::ITEM => [0.25, 0.25, 0.25],
-            EntityKind::FIREBALL => [1.0, 1.0, 1.0],
-            EntityKind::LEASH_KNOT => [0.375, 0.5, 0.375],
-            EntityKind::LIGHTNING /* | EntityKind::MARKER - marker hitbox */ => [0.0; 3],
-            EntityKind::LLAMA_SPIT => [0.25, 0.25, 0.25],
-            EntityKind::MINECART
-            | EntityKind::CHEST_MINECART
-            | EntityKind::TNT_MINECART
-            | EntityKind::HOPPER_MINECART
-            | EntityKind::FURNACE_MINECART
-            | EntityKind::SPAWNER_MINECART
-            | EntityKind::COMMAND_BLOCK_MINECART => [0.98, 0.7, 0.98],
-            EntityKind::PARROT => [0.5, 0.9, 0.5],
-            EntityKind::PHANTOM => [0.9, 0.5, 0.9],
-            EntityKind::PIGLIN_BRUTE => [0.6, 1.95, 0.6],
-            EntityKind::PILLAGER => [0.6, 1.95, 0.6],
-            EntityKind::TNT => [0.98, 0.98, 0.98],
-            EntityKind::PUFFERFISH => [0.7, 0.7, 0.7],
-            EntityKind::RAVAGER => [1.95, 2.2, 1.95],
-            EntityKind::SALMON => [0.7, 0.4, 0.7],
-            EntityKind::SHULKER_BULLET => [0.3125, 0.3125, 0.3125],
-            EntityKind::SILVERFISH => [0.4, 0.3, 0.4],
-            EntityKind::SMALL_FIREBALL => [0.3125, 0.3125, 0.3125],
-            EntityKind::SNOW_GOLEM => [0.7, 1.9, 0.7],
-            EntityKind::SPIDER => [1.4, 0.9, 1.4],
-            EntityKind::STRAY => [0.6, 1.99, 0.6],
-            EntityKind::EGG => [0.25, 0.25, 0.25],
-            EntityKind::ENDER_PEARL => [0.25, 0.25, 0.25],
-            EntityKind::EXPERIENCE_BOTTLE => [0.25, 0.25, 0.25],
-            EntityKind::POTION => [0.25, 0.25, 0.25],
-            EntityKind::TRIDENT => [0.5, 0.5, 0.5],
-            EntityKind::TRADER_LLAMA => [0.9, 1.87, 0.9],
-            EntityKind::TROPICAL_FISH => [0.5, 0.4, 0.5],
-            EntityKind::VEX => [0.4, 0.8, 0.4],
-            EntityKind::VINDICATOR => [0.6, 1.95, 0.6],
-            EntityKind::WITHER => [0.9, 3.5, 0.9],
-            EntityKind::WITHER_SKELETON => [0.7, 2.4, 0.7],
-            EntityKind::WITHER_SKULL => [0.3125, 0.3125, 0.3125],
-            EntityKind::FISHING_BOBBER => [0.25, 0.25, 0.25],
-            _ => {
-                continue;
-            }
+        if let Some(size) = constant_hitbox_size(*entity_kind) {
+            hitbox.centered(size.into());
         }
-        .into();
-        hitbox.centered(size);
     }
 }
 
+/// The `[width, height, width]` of entities whose hitbox never changes.
+const fn constant_hitbox_size(kind: EntityKind) -> Option<[f64; 3]> {
+    Some(match kind {
+        EntityKind::ALLAY => [0.6, 0.35, 0.6],
+        EntityKind::CHEST_BOAT | EntityKind::BOAT => [1.375, 0.5625, 1.375],
+        EntityKind::FROG => [0.5, 0.5, 0.5],
+        EntityKind::TADPOLE => [0.4, 0.3, 0.4],
+        EntityKind::SPECTRAL_ARROW | EntityKind::ARROW => [0.5, 0.5, 0.5],
+        EntityKind::AXOLOTL => [1.3, 0.6, 1.3],
+        EntityKind::BAT => [0.5, 0.9, 0.5],
+        EntityKind::BLAZE => [0.6, 1.8, 0.6],
+        EntityKind::CAT => [0.6, 0.7, 0.6],
+        EntityKind::CAVE_SPIDER => [0.7, 0.5, 0.7],
+        EntityKind::COD => [0.5, 0.3, 0.5],
+        EntityKind::CREEPER => [0.6, 1.7, 0.6],
+        EntityKind::DOLPHIN => [0.9, 0.6, 0.9],
+        EntityKind::DRAGON_FIREBALL => [1.0, 1.0, 1.0],
+        EntityKind::ELDER_GUARDIAN => [1.9975, 1.9975, 1.9975],
+        EntityKind::END_CRYSTAL => [2.0, 2.0, 2.0],
+        EntityKind::ENDER_DRAGON => [16.0, 8.0, 16.0],
+        EntityKind::ENDERMAN => [0.6, 2.9, 0.6],
+        EntityKind::ENDERMITE => [0.4, 0.3, 0.4],
+        EntityKind::EVOKER => [0.6, 1.95, 0.6],
+        EntityKind::EVOKER_FANGS => [0.5, 0.8, 0.5],
+        EntityKind::EXPERIENCE_ORB => [0.5, 0.5, 0.5],
+        EntityKind::EYE_OF_ENDER => [0.25, 0.25, 0.25],
+        EntityKind::FALLING_BLOCK => [0.98, 0.98, 0.98],
+        EntityKind::FIREWORK_ROCKET => [0.25, 0.25, 0.25],
+        EntityKind::GHAST => [4.0, 4.0, 4.0],
+        EntityKind::GIANT => [3.6, 12.0, 3.6],
+        EntityKind::GLOW_SQUID | EntityKind::SQUID => [0.8, 0.8, 0.8],
+        EntityKind::GUARDIAN => [0.85, 0.85, 0.85],
+        EntityKind::ILLUSIONER => [0.6, 1.95, 0.6],
+        EntityKind::IRON_GOLEM => [1.4, 2.7, 1.4],
+        EntityKind::ITEM => [0.25, 0.25, 0.25],
+        EntityKind::FIREBALL => [1.0, 1.0, 1.0],
+        EntityKind::LEASH_KNOT => [0.375, 0.5, 0.375],
+        EntityKind::LIGHTNING /* | EntityKind::MARKER - marker hitbox */ => [0.0; 3],
+        EntityKind::LLAMA_SPIT => [0.25, 0.25, 0.25],
+        EntityKind::MINECART
+        | EntityKind::CHEST_MINECART
+        | EntityKind::TNT_MINECART
+        | EntityKind::HOPPER_MINECART
+        | EntityKind::FURNACE_MINECART
+        | EntityKind::SPAWNER_MINECART
+        | EntityKind::COMMAND_BLOCK_MINECART => [0.98, 0.7, 0.98],
+        EntityKind::PARROT => [0.5, 0.9, 0.5],
+        EntityKind::PHANTOM => [0.9, 0.5, 0.9],
+        EntityKind::PIGLIN_BRUTE => [0.6, 1.95, 0.6],
+        EntityKind::PILLAGER => [0.6, 1.95, 0.6],
+        EntityKind::SKELETON => [0.6, 1.99, 0.6],
+        EntityKind::TNT => [0.98, 0.98, 0.98],
+        EntityKind::PUFFERFISH => [0.7, 0.7, 0.7],
+        EntityKind::RAVAGER => [1.95, 2.2, 1.95],
+        EntityKind::SALMON => [0.7, 0.4, 0.7],
+        EntityKind::SHULKER_BULLET => [0.3125, 0.3125, 0.3125],
+        EntityKind::SILVERFISH => [0.4, 0.3, 0.4],
+        EntityKind::SMALL_FIREBALL => [0.3125, 0.3125, 0.3125],
+        EntityKind::SNOW_GOLEM => [0.7, 1.9, 0.7],
+        EntityKind::SPIDER => [1.4, 0.9, 1.4],
+        EntityKind::STRAY => [0.6, 1.99, 0.6],
+        EntityKind::EGG => [0.25, 0.25, 0.25],
+        EntityKind::ENDER_PEARL => [0.25, 0.25, 0.25],
+        EntityKind::EXPERIENCE_BOTTLE => [0.25, 0.25, 0.25],
+        EntityKind::POTION => [0.25, 0.25, 0.25],
+        EntityKind::TRIDENT => [0.5, 0.5, 0.5],
+        EntityKind::TRADER_LLAMA => [0.9, 1.87, 0.9],
+        EntityKind::TROPICAL_FISH => [0.5, 0.4, 0.5],
+        EntityKind::VEX => [0.4, 0.8, 0.4],
+        EntityKind::VINDICATOR => [0.6, 1.95, 0.6],
+        EntityKind::WITCH => [0.6, 1.95, 0.6],
+        EntityKind::WITHER => [0.9, 3.5, 0.9],
+        EntityKind::WITHER_SKELETON => [0.7, 2.4, 0.7],
+        EntityKind::WITHER_SKULL => [0.3125, 0.3125, 0.3125],
+        EntityKind::FISHING_BOBBER => [0.25, 0.25, 0.25],
+        _ => return None,
+    })
+}
+
 fn update_warden_hitbox(
     mut query: Query<
         (&mut HitboxShape, &entity::Pose),
@@ -292,11 +360,6 @@ fn update_passive_child_hitbox(
 ) {
     for (entity, mut hitbox, entity_kind, child) in query.iter_mut() {
         let big_s = match *entity_kind {
-            EntityKind::BEE => [0.7, 0.6, 0.7],
-            EntityKind::CAMEL => [1.7, 2.375, 1.7],
-            EntityKind::CHICKEN => [0.4, 0.7, 0.4],
-            EntityKind::DONKEY => [1.5, 1.39648, 1.5],
-            EntityKind::FOX => [0.6, 0.7, 0.6],
             EntityKind::GOAT => {
                 if pose_query
                     .get(entity)
@@ -307,19 +370,6 @@ fn update_passive_child_hitbox(
                     [0.9, 1.3, 0.9]
                 }
             }
-            EntityKind::HOGLIN => [1.39648, 1.4, 1.39648],
-            EntityKind::HORSE | EntityKind::SKELETON_HORSE | EntityKind::ZOMBIE_HORSE => {
-                [1.39648, 1.6, 1.39648]
-            }
-            EntityKind::LLAMA => [0.9, 1.87, 0.9],
-            EntityKind::MULE => [1.39648, 1.6, 1.39648],
-            EntityKind::MOOSHROOM => [0.9, 1.4, 0.9],
-            EntityKind::OCELOT => [0.6, 0.7, 0.6],
-            EntityKind::PANDA => [1.3, 1.25, 1.3],
-            EntityKind::PIG => [0.9, 0.9, 0.9],
-            EntityKind::POLAR_BEAR => [1.4, 1.4, 1.4],
-            EntityKind::RABBIT => [0.4, 0.5, 0.4],
-            EntityKind::SHEEP => [0.9, 1.3, 0.9],
             EntityKind::TURTLE => {
                 hitbox.centered(
                     if child.0 {
@@ -331,16 +381,45 @@ fn update_passive_child_hitbox(
                 );
                 continue;
             }
-            EntityKind::VILLAGER => [0.6, 1.95, 0.6],
-            EntityKind::WOLF => [0.6, 0.85, 0.6],
-            _ => {
-                continue;
-            }
+            kind => match passive_adult_hitbox_size(kind) {
+                Some(size) => size,
+                None => continue,
+            },
         };
         hitbox.centered(child_hitbox(child.0, big_s.into()));
     }
 }
 
+/// The `[width, height, width]` of adult passive entities, for those whose
+/// hitbox only changes when they are a child.
+const fn passive_adult_hitbox_size(kind: EntityKind) -> Option<[f64; 3]> {
+    Some(match kind {
+        EntityKind::BEE => [0.7, 0.6, 0.7],
+        EntityKind::CAMEL => [1.7, 2.375, 1.7],
+        EntityKind::CHICKEN => [0.4, 0.7, 0.4],
+        EntityKind::COW | EntityKind::MOOSHROOM => [0.9, 1.4, 0.9],
+        EntityKind::DONKEY => [1.5, 1.39648, 1.5],
+        EntityKind::FOX => [0.6, 0.7, 0.6],
+        EntityKind::HOGLIN => [1.39648, 1.4, 1.39648],
+        EntityKind::HORSE | EntityKind::SKELETON_HORSE | EntityKind::ZOMBIE_HORSE => {
+            [1.39648, 1.6, 1.39648]
+        }
+        EntityKind::LLAMA => [0.9, 1.87, 0.9],
+        EntityKind::MULE => [1.39648, 1.6, 1.39648],
+        EntityKind::OCELOT => [0.6, 0.7, 0.6],
+        EntityKind::PANDA => [1.3, 1.25, 1.3],
+        EntityKind::PIG => [0.9, 0.9, 0.9],
+        EntityKind::POLAR_BEAR => [1.4, 1.4, 1.4],
+        EntityKind::RABBIT => [0.4, 0.5, 0.4],
+        EntityKind::SHEEP => [0.9, 1.3, 0.9],
+        EntityKind::SNIFFER => [1.9, 1.75, 1.9],
+        EntityKind::STRIDER => [0.9, 1.7, 0.9],
+        EntityKind::VILLAGER | EntityKind::WANDERING_TRADER => [0.6, 1.95, 0.6],
+        EntityKind::WOLF => [0.6, 0.85, 0.6],
+        _ => return None,
+    })
+}
+
 fn update_zombie_hitbox(
     mut query: Query<
         (&mut HitboxShape, &zombie::Baby),
@@ -441,6 +520,21 @@ fn update_slime_hitbox(
     }
 }
 
+fn update_interaction_hitbox(
+    mut query: Query<
+        (&mut HitboxShape, &interaction::Width, &interaction::Height),
+        Or<(
+            Changed<interaction::Width>,
+            Changed<interaction::Height>,
+            Added<HitboxShape>,
+        )>,
+    >,
+) {
+    for (mut hitbox, width, height) in query.iter_mut() {
+        hitbox.centered([width.0 as f64, height.0 as f64, width.0 as f64].into());
+    }
+}
+
 fn update_painting_hitbox(
     mut query: Query<
         (&mut HitboxShape, &painting::Variant, &Look),
@@ -547,3 +641,33 @@ fn update_shulker_hitbox(
         hitbox.0 = Aabb::new(min, max);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entity_kind_dimensions() {
+        assert_eq!(EntityKind::PLAYER.dimensions(), Some([0.6, 1.8]));
+        assert_eq!(EntityKind::COW.dimensions(), Some([0.9, 1.4]));
+        assert_eq!(EntityKind::CREEPER.dimensions(), Some([0.6, 1.7]));
+        assert_eq!(EntityKind::PAINTING.dimensions(), None);
+
+        let hitbox = EntityKind::PLAYER
+            .hitbox(DVec3::new(10.0, 64.0, -5.0))
+            .unwrap();
+
+        assert!(hitbox.min().abs_diff_eq(DVec3::new(9.7, 64.0, -5.3), 1e-9));
+        assert!(hitbox.max().abs_diff_eq(DVec3::new(10.3, 65.8, -4.7), 1e-9));
+        assert!(hitbox.intersects(
+            EntityKind::ZOMBIE
+                .hitbox(DVec3::new(10.5, 65.0, -5.0))
+                .unwrap()
+        ));
+        assert!(!hitbox.intersects(
+            EntityKind::ZOMBIE
+                .hitbox(DVec3::new(11.0, 64.0, -5.0))
+                .unwrap()
+        ));
+    }
+}
@@ -18,21 +18,28 @@
 )]
 #![allow(clippy::type_complexity)]
 
+mod display_ext;
 mod flags;
 pub mod hitbox;
 pub mod manager;
 pub mod query;
 pub mod tracked_data;
 
+use std::borrow::Cow;
+
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use derive_more::{Deref, DerefMut};
+pub use display_ext::{DisplayTransform, TextAlignment};
 pub use manager::EntityManager;
 use paste::paste;
 use tracing::warn;
 use tracked_data::TrackedData;
 use valence_math::{DVec3, Vec3};
-use valence_protocol::{BlockPos, ChunkPos, Decode, Encode, VarInt};
+use valence_protocol::encode::WritePacket;
+use valence_protocol::packets::play::entity_equipment_update_s2c::EquipmentEntry;
+use valence_protocol::packets::play::EntityPassengersSetS2c;
+use valence_protocol::{BlockPos, ChunkPos, Decode, Encode, ItemStack, VarInt};
 use valence_server_common::{Despawned, UniqueId};
 
 include!(concat!(env!("OUT_DIR"), "/entity.rs"));
@@ -79,16 +86,27 @@ impl Plugin for EntityPlugin {
             )
             .add_systems(
                 PostUpdate,
-                (remove_despawned_from_manager, init_entities)
+                (
+                    remove_despawned_from_manager,
+                    init_entities,
+                    remove_despawned_passengers,
+                    update_vehicles,
+                    update_passenger_ids,
+                )
                     .chain()
                     .in_set(InitEntitiesSet),
             )
+            .add_systems(
+                PostUpdate,
+                (update_fire_ticks, update_face_movement).before(UpdateTrackedDataSet),
+            )
             .add_systems(
                 PostUpdate,
                 (
                     clear_status_changes,
                     clear_animation_changes,
                     clear_tracked_data_changes,
+                    clear_equipment_changes,
                     update_old_position,
                     update_old_layer_id,
                 )
@@ -111,6 +129,33 @@ fn update_old_layer_id(mut query: Query<(&EntityLayerId, &mut OldEntityLayerId)>
     }
 }
 
+fn update_fire_ticks(mut entities: Query<(&mut FireTicks, &mut entity::Flags)>) {
+    for (mut fire_ticks, mut flags) in &mut entities {
+        let on_fire = fire_ticks.0 > 0;
+
+        if flags.on_fire() != on_fire {
+            flags.set_on_fire(on_fire);
+        }
+
+        if on_fire {
+            fire_ticks.0 -= 1;
+        }
+    }
+}
+
+fn update_face_movement(
+    mut entities: Query<(&Position, &OldPosition, &mut Look), With<FaceMovement>>,
+) {
+    for (pos, old_pos, mut look) in &mut entities {
+        let delta = old_pos.delta(*pos);
+        let mut new_look = *look;
+        new_look.face_movement(delta.as_vec3());
+
+        // Avoid sending rotation updates while standing still.
+        look.set_if_neq(new_look);
+    }
+}
+
 fn remove_despawned_from_manager(
     entities: Query<&EntityId, (With<EntityKind>, With<Despawned>)>,
     mut manager: ResMut<EntityManager>,
@@ -143,6 +188,86 @@ fn init_entities(
     }
 }
 
+/// Dismounts despawned passengers so that their IDs aren't sent to clients.
+fn remove_despawned_passengers(
+    despawned: Query<(), Added<Despawned>>,
+    mut vehicles: Query<&mut Passengers>,
+) {
+    if despawned.is_empty() {
+        return;
+    }
+
+    for mut passengers in &mut vehicles {
+        if passengers.entities.iter().any(|&e| despawned.contains(e)) {
+            passengers.entities.retain(|&e| !despawned.contains(e));
+        }
+    }
+}
+
+/// Keeps the [`Vehicle`] of passengers in sync with [`Passengers`].
+fn update_vehicles(
+    mut vehicles: Query<
+        (Entity, &mut Passengers, Option<&Despawned>),
+        Or<(Changed<Passengers>, Added<Despawned>)>,
+    >,
+    mut commands: Commands,
+) {
+    for (vehicle, mut passengers, despawned) in &mut vehicles {
+        // Only bookkeeping is changed here, which clients don't need to be sent.
+        let passengers = passengers.bypass_change_detection();
+
+        let current: &[Entity] = if despawned.is_some() {
+            &[]
+        } else {
+            &passengers.entities
+        };
+
+        for &rider in &passengers.old_entities {
+            if !current.contains(&rider) {
+                // The rider may have been moved to another vehicle in the same tick.
+                commands.add(move |world: &mut World| {
+                    if let Some(mut rider) = world.get_entity_mut(rider) {
+                        if rider.get::<Vehicle>() == Some(&Vehicle(vehicle)) {
+                            rider.remove::<Vehicle>();
+                        }
+                    }
+                });
+            }
+        }
+
+        for &rider in current {
+            if !passengers.old_entities.contains(&rider) {
+                commands.add(move |world: &mut World| {
+                    if let Some(mut rider) = world.get_entity_mut(rider) {
+                        rider.insert(Vehicle(vehicle));
+                    }
+                });
+            }
+        }
+
+        passengers.old_entities.clear();
+        passengers.old_entities.extend_from_slice(current);
+    }
+}
+
+fn update_passenger_ids(
+    mut vehicles: Query<&mut Passengers, Changed<Passengers>>,
+    ids: Query<&EntityId>,
+) {
+    for mut passengers in &mut vehicles {
+        let passengers = passengers.bypass_change_detection();
+
+        passengers.ids.clear();
+        passengers.ids.extend(
+            passengers
+                .entities
+                .iter()
+                .filter_map(|&entity| ids.get(entity).ok())
+                .map(|id| VarInt(id.get())),
+        );
+    }
+}
+
 fn clear_status_changes(mut statuses: Query<&mut EntityStatuses, Changed<EntityStatuses>>) {
     for mut statuses in &mut statuses {
         statuses.0 = 0;
@@ -163,7 +288,18 @@ fn clear_tracked_data_changes(mut tracked_data: Query<&mut TrackedData, Changed<
     }
 }
 
+fn clear_equipment_changes(mut equipment: Query<&mut Equipment, Changed<Equipment>>) {
+    for mut equipment in &mut equipment {
+        equipment.changed = 0;
+    }
+}
+
 /// Contains the entity layer an entity is on.
+///
+/// Changing this moves the entity to another layer, such as when moving it
+/// between worlds. Clients that can no longer see the entity have it despawned
+/// and clients that can now see it have it spawned. Clients viewing both
+/// layers keep the entity without interruption.
 #[derive(Component, Copy, Clone, PartialEq, Eq, Debug, Deref)]
 pub struct EntityLayerId(pub Entity);
 
@@ -234,6 +370,12 @@ impl PartialEq<OldPosition> for Position {
 
 /// The value of [`Position`] from the end of the previous tick.
 ///
+/// This is where viewers last saw the entity, so comparing it to the current
+/// [`Position`] gives the distance the entity moved during this tick (see
+/// [`OldPosition::delta`]). It is updated in [`ClearEntityChangesSet`]. For an
+/// entity spawned on the previous tick, it is the position the entity was
+/// spawned at.
+///
 /// **NOTE**: You should not modify this component after the entity is spawned.
 #[derive(Component, Clone, PartialEq, Default, Debug, Deref)]
 pub struct OldPosition(DVec3);
@@ -254,6 +396,13 @@ impl OldPosition {
     pub fn to_block_pos(&self) -> BlockPos {
         BlockPos::from_pos(self.0)
     }
+
+    /// Returns the displacement from this position to `pos`. For an entity's
+    /// own [`Position`], this is how far it has moved since the end of the
+    /// previous tick.
+    pub fn delta(&self, pos: Position) -> DVec3 {
+        pos.0 - self.0
+    }
 }
 
 impl PartialEq<Position> for OldPosition {
@@ -307,8 +456,26 @@ impl Look {
 
         self.pitch = -(dir.y).asin().to_degrees();
     }
+
+    /// Sets the yaw to face the horizontal direction of `movement`, leaving
+    /// the pitch unchanged. The current yaw is kept if the movement is too
+    /// small to have a meaningful direction.
+    pub fn face_movement(&mut self, movement: Vec3) {
+        if movement.x.abs() < 1e-4 && movement.z.abs() < 1e-4 {
+            return;
+        }
+
+        let yaw = f32::atan2(-movement.x, movement.z).to_degrees();
+
+        // Moving north gives -180, which is the same as 180.
+        self.yaw = if yaw <= -180.0 { yaw + 360.0 } else { yaw };
+    }
 }
 
+/// Whether the entity is on the ground.
+///
+/// For clients this is the value reported in their most recent movement packet.
+/// Clients are free to lie about it, so it should be treated as advisory.
 #[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug, Deref, DerefMut)]
 pub struct OnGround(pub bool);
 
@@ -339,6 +506,38 @@ impl Default for EntityId {
 #[derive(Component, Copy, Clone, PartialEq, Default, Debug, Deref, DerefMut)]
 pub struct HeadYaw(pub f32);
 
+/// A marker [`Component`] that turns an entity's body in the direction it
+/// moved every tick, such as an NPC walking along a path.
+///
+/// Only the yaw of [`Look`] is changed, so the head can still be turned
+/// independently with [`HeadYaw`]. The facing is kept while the entity
+/// stands still. See [`Look::face_movement`].
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct FaceMovement;
+
+/// An optional component with the number of ticks an entity keeps burning
+/// for.
+///
+/// While the count is above zero, the entity's
+/// [`on_fire`](entity::Flags::on_fire) flag is set and the count goes down
+/// by one every tick. Once it reaches zero, the flag is cleared. This only
+/// shows the flames; no fire damage is dealt.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug, Deref, DerefMut)]
+pub struct FireTicks(pub u32);
+
+impl FireTicks {
+    /// Sets the entity on fire for at least `ticks` ticks. If it is already
+    /// burning for longer, the time is left unchanged instead of adding up.
+    pub fn ignite(&mut self, ticks: u32) {
+        self.0 = self.0.max(ticks);
+    }
+
+    /// Puts out the fire on the next tick.
+    pub fn extinguish(&mut self) {
+        self.0 = 0;
+    }
+}
+
 /// Entity velocity in m/s.
 #[derive(Component, Copy, Clone, Default, Debug, Deref, DerefMut)]
 pub struct Velocity(pub Vec3);
@@ -349,6 +548,193 @@ impl Velocity {
     }
 }
 
+/// The entities riding this entity, in seat order.
+///
+/// The client decides where passengers are drawn based on the vehicle's
+/// dimensions (see [`HitboxShape::passenger_riding_offset`]). All passengers
+/// share one seat unless the vehicle has several, like boats and camels. To
+/// give each rider of a large custom mount its own seat, have each one ride a
+/// separate invisible [`interaction`] entity with a suitable height that is
+/// moved along with the mount.
+///
+/// Passengers are not moved with their vehicle on the server, so their
+/// [`Position`] should be kept near the vehicle.
+///
+/// To dismount every passenger, clear the list instead of removing the
+/// component. Passengers that are [`Despawned`] are dismounted automatically.
+///
+/// [`HitboxShape::passenger_riding_offset`]: hitbox::HitboxShape::passenger_riding_offset
+#[derive(Component, Clone, Default, Debug)]
+pub struct Passengers {
+    entities: Vec<Entity>,
+    /// The entities that were given a [`Vehicle`] pointing to this entity.
+    old_entities: Vec<Entity>,
+    /// Protocol IDs of `entities`, updated in [`InitEntitiesSet`].
+    ids: Vec<VarInt>,
+}
+
+impl Passengers {
+    pub fn new(entities: impl IntoIterator<Item = Entity>) -> Self {
+        Self {
+            entities: entities.into_iter().collect(),
+            old_entities: vec![],
+            ids: vec![],
+        }
+    }
+
+    pub fn get(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    pub fn set(&mut self, entities: impl IntoIterator<Item = Entity>) {
+        self.entities.clear();
+        self.entities.extend(entities);
+    }
+
+    pub fn push(&mut self, entity: Entity) {
+        self.entities.push(entity);
+    }
+
+    /// Removes `entity` from the passengers. Returns whether it was riding.
+    pub fn remove(&mut self, entity: Entity) -> bool {
+        let len = self.entities.len();
+        self.entities.retain(|&e| e != entity);
+        self.entities.len() != len
+    }
+
+    pub fn clear(&mut self) {
+        self.entities.clear();
+    }
+
+    /// Writes the packet that seats the passengers on the vehicle `vehicle_id`
+    /// for the client whose own entity has the ID `viewer`. Clients know their
+    /// own entity by the ID 0, so `viewer` is written as 0.
+    pub fn write_packet(
+        &self,
+        vehicle_id: EntityId,
+        viewer: EntityId,
+        mut writer: impl WritePacket,
+    ) {
+        let to_client_id = |id: VarInt| {
+            if id.0 == viewer.get() {
+                VarInt(0)
+            } else {
+                id
+            }
+        };
+
+        let passengers: Cow<[VarInt]> = if self.ids.contains(&VarInt(viewer.get())) {
+            self.ids.iter().copied().map(to_client_id).collect()
+        } else {
+            Cow::Borrowed(&self.ids)
+        };
+
+        writer.write_packet(&EntityPassengersSetS2c {
+            entity_id: to_client_id(VarInt(vehicle_id.get())),
+            passengers,
+        });
+    }
+}
+
+/// The entity this entity is riding.
+///
+/// This is kept in sync with the [`Passengers`] of the vehicle in
+/// [`InitEntitiesSet`], so it is inserted and removed the tick the passengers
+/// change. Change the vehicle's [`Passengers`] to mount or dismount instead of
+/// touching this component.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Vehicle(Entity);
+
+impl Vehicle {
+    pub fn get(self) -> Entity {
+        self.0
+    }
+}
+
+/// The items held and worn by an entity, such as a zombie holding a sword.
+///
+/// Clients viewing the entity are sent the equipment when the entity is
+/// spawned for them. All the slots changed in the same tick are sent together
+/// in one packet. Clients don't see the equipment of their own entity through
+/// this component; use the inventory for that instead.
+#[derive(Component, Clone, PartialEq, Default, Debug)]
+pub struct Equipment {
+    items: [Option<ItemStack>; 6],
+    /// Contains a set bit for each modified slot in `items`.
+    changed: u8,
+}
+
+/// A slot of [`Equipment`].
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum EquipmentSlot {
+    MainHand,
+    OffHand,
+    Feet,
+    Legs,
+    Chest,
+    Head,
+}
+
+impl EquipmentSlot {
+    pub const ALL: [Self; 6] = [
+        Self::MainHand,
+        Self::OffHand,
+        Self::Feet,
+        Self::Legs,
+        Self::Chest,
+        Self::Head,
+    ];
+}
+
+impl Equipment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, slot: EquipmentSlot) -> Option<&ItemStack> {
+        self.items[slot as usize].as_ref()
+    }
+
+    /// Puts `item` in the given slot and returns the previous item. Viewers
+    /// are only sent the slot if the item is different.
+    pub fn set(&mut self, slot: EquipmentSlot, item: Option<ItemStack>) -> Option<ItemStack> {
+        let old = std::mem::replace(&mut self.items[slot as usize], item);
+
+        if old != self.items[slot as usize] {
+            self.changed |= 1 << slot as u8;
+        }
+
+        old
+    }
+
+    /// Returns the slots that are not empty and their items.
+    pub fn iter(&self) -> impl Iterator<Item = (EquipmentSlot, &ItemStack)> + '_ {
+        EquipmentSlot::ALL
+            .into_iter()
+            .filter_map(|slot| Some((slot, self.get(slot)?)))
+    }
+
+    pub(crate) fn init_entries(&self) -> Vec<EquipmentEntry> {
+        self.iter()
+            .map(|(slot, item)| EquipmentEntry {
+                slot: slot as i8,
+                item: Some(item.clone()),
+            })
+            .collect()
+    }
+
+    pub(crate) fn update_entries(&self) -> Vec<EquipmentEntry> {
+        EquipmentSlot::ALL
+            .into_iter()
+            .filter(|&slot| (self.changed >> slot as u8) & 1 == 1)
+            .map(|slot| EquipmentEntry {
+                slot: slot as i8,
+                item: self.get(slot).cloned(),
+            })
+            .collect()
+    }
+}
+
 // TODO: don't make statuses and animations components.
 
 #[derive(Component, Copy, Clone, Default, Debug, Deref, DerefMut)]
@@ -42,4 +42,26 @@ impl EntityManager {
     pub fn get_by_id(&self, entity_id: i32) -> Option<Entity> {
         self.id_to_entity.get(&entity_id).cloned()
     }
+
+    /// Returns an iterator over all Minecraft entities, sorted by entity ID in
+    /// ascending order. Since IDs are handed out in increasing order, this is
+    /// usually the order in which the entities were spawned.
+    ///
+    /// Unlike the iteration order of queries, this order is deterministic and
+    /// is not affected by other entities being added or removed. It can be
+    /// used with [`Query::get`] to process entities in a reproducible order.
+    ///
+    /// The entities are sorted on every call, so prefer [`Self::get_by_id`]
+    /// for looking up single entities.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (EntityId, Entity)> + ExactSizeIterator {
+        let mut entities: Vec<_> = self
+            .id_to_entity
+            .iter()
+            .map(|(&id, &entity)| (EntityId(id), entity))
+            .collect();
+
+        entities.sort_unstable_by_key(|&(id, _)| id.0);
+
+        entities.into_iter()
+    }
 }
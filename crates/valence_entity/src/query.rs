@@ -6,9 +6,9 @@ use bevy_ecs::world::Ref;
 use valence_math::DVec3;
 use valence_protocol::encode::WritePacket;
 use valence_protocol::packets::play::{
-    EntityAnimationS2c, EntityPositionS2c, EntitySetHeadYawS2c, EntitySpawnS2c, EntityStatusS2c,
-    EntityTrackerUpdateS2c, EntityVelocityUpdateS2c, ExperienceOrbSpawnS2c, MoveRelativeS2c,
-    PlayerSpawnS2c, RotateAndMoveRelativeS2c, RotateS2c,
+    EntityAnimationS2c, EntityEquipmentUpdateS2c, EntityPositionS2c, EntitySetHeadYawS2c,
+    EntitySpawnS2c, EntityStatusS2c, EntityTrackerUpdateS2c, EntityVelocityUpdateS2c,
+    ExperienceOrbSpawnS2c, MoveRelativeS2c, PlayerSpawnS2c, RotateAndMoveRelativeS2c, RotateS2c,
 };
 use valence_protocol::var_int::VarInt;
 use valence_protocol::ByteAngle;
@@ -16,8 +16,9 @@ use valence_server_common::UniqueId;
 
 use crate::tracked_data::TrackedData;
 use crate::{
-    EntityAnimations, EntityId, EntityKind, EntityLayerId, EntityStatuses, HeadYaw, Look,
-    ObjectData, OldEntityLayerId, OldPosition, OnGround, Position, Velocity,
+    EntityAnimations, EntityId, EntityKind, EntityLayerId, EntityStatuses, Equipment, HeadYaw,
+    Look, ObjectData, OldEntityLayerId, OldPosition, OnGround, Passengers, Position, Vehicle,
+    Velocity,
 };
 
 #[derive(WorldQuery)]
@@ -31,56 +32,101 @@ pub struct EntityInitQuery {
     pub object_data: &'static ObjectData,
     pub velocity: &'static Velocity,
     pub tracked_data: &'static TrackedData,
+    pub passengers: Option<&'static Passengers>,
+    pub vehicle: Option<&'static Vehicle>,
+    pub equipment: Option<&'static Equipment>,
 }
 
 impl EntityInitQueryItem<'_> {
     /// Writes the appropriate packets to initialize an entity. This will spawn
     /// the entity and initialize tracked data. `pos` is the initial position of
-    /// the entity.
-    pub fn write_init_packets(&self, pos: DVec3, mut writer: impl WritePacket) {
-        match *self.kind {
-            EntityKind::MARKER => {}
-            EntityKind::EXPERIENCE_ORB => {
-                writer.write_packet(&ExperienceOrbSpawnS2c {
-                    entity_id: self.entity_id.get().into(),
-                    position: pos,
-                    count: self.object_data.0 as i16,
-                });
-            }
-            EntityKind::PLAYER => {
-                writer.write_packet(&PlayerSpawnS2c {
+    /// the entity and `viewer` is the entity ID of the client the packets are
+    /// written to.
+    ///
+    /// The packets are wrapped in a bundle so that the client applies the spawn
+    /// and the initial tracked data in the same frame.
+    ///
+    /// If the entity is riding a [`Vehicle`], `vehicle` should hold the entity
+    /// ID and passengers of that vehicle. They are written in the same bundle so
+    /// that the entity is seated even if the vehicle was spawned for the client
+    /// first.
+    pub fn write_init_packets(
+        &self,
+        pos: DVec3,
+        viewer: EntityId,
+        vehicle: Option<(EntityId, &Passengers)>,
+        mut writer: impl WritePacket,
+    ) {
+        if *self.kind == EntityKind::MARKER {
+            // Markers are never sent to clients.
+            return;
+        }
+
+        writer.write_bundle(|writer| {
+            match *self.kind {
+                EntityKind::EXPERIENCE_ORB => {
+                    writer.write_packet(&ExperienceOrbSpawnS2c {
+                        entity_id: self.entity_id.get().into(),
+                        position: pos,
+                        count: self.object_data.0 as i16,
+                    });
+                }
+                EntityKind::PLAYER => {
+                    writer.write_packet(&PlayerSpawnS2c {
+                        entity_id: self.entity_id.get().into(),
+                        player_uuid: self.uuid.0,
+                        position: pos,
+                        yaw: ByteAngle::from_degrees(self.look.yaw),
+                        pitch: ByteAngle::from_degrees(self.look.pitch),
+                    });
+
+                    // Player spawn packet doesn't include head yaw for some reason.
+                    writer.write_packet(&EntitySetHeadYawS2c {
+                        entity_id: self.entity_id.get().into(),
+                        head_yaw: ByteAngle::from_degrees(self.head_yaw.0),
+                    });
+                }
+                _ => writer.write_packet(&EntitySpawnS2c {
                     entity_id: self.entity_id.get().into(),
-                    player_uuid: self.uuid.0,
+                    object_uuid: self.uuid.0,
+                    kind: self.kind.get().into(),
                     position: pos,
-                    yaw: ByteAngle::from_degrees(self.look.yaw),
                     pitch: ByteAngle::from_degrees(self.look.pitch),
-                });
+                    yaw: ByteAngle::from_degrees(self.look.yaw),
+                    head_yaw: ByteAngle::from_degrees(self.head_yaw.0),
+                    data: self.object_data.0.into(),
+                    velocity: self.velocity.to_packet_units(),
+                }),
+            }
 
-                // Player spawn packet doesn't include head yaw for some reason.
-                writer.write_packet(&EntitySetHeadYawS2c {
+            if let Some(init_data) = self.tracked_data.init_data() {
+                writer.write_packet(&EntityTrackerUpdateS2c {
                     entity_id: self.entity_id.get().into(),
-                    head_yaw: ByteAngle::from_degrees(self.head_yaw.0),
+                    tracked_values: init_data.into(),
                 });
             }
-            _ => writer.write_packet(&EntitySpawnS2c {
-                entity_id: self.entity_id.get().into(),
-                object_uuid: self.uuid.0,
-                kind: self.kind.get().into(),
-                position: pos,
-                pitch: ByteAngle::from_degrees(self.look.pitch),
-                yaw: ByteAngle::from_degrees(self.look.yaw),
-                head_yaw: ByteAngle::from_degrees(self.head_yaw.0),
-                data: self.object_data.0.into(),
-                velocity: self.velocity.to_packet_units(),
-            }),
-        }
 
-        if let Some(init_data) = self.tracked_data.init_data() {
-            writer.write_packet(&EntityTrackerUpdateS2c {
-                entity_id: self.entity_id.get().into(),
-                tracked_values: init_data.into(),
-            });
-        }
+            if let Some(passengers) = self.passengers {
+                if !passengers.get().is_empty() {
+                    passengers.write_packet(*self.entity_id, viewer, &mut *writer);
+                }
+            }
+
+            if let Some((vehicle_id, passengers)) = vehicle {
+                passengers.write_packet(vehicle_id, viewer, &mut *writer);
+            }
+
+            if let Some(equipment) = self.equipment {
+                let entries = equipment.init_entries();
+
+                if !entries.is_empty() {
+                    writer.write_packet(&EntityEquipmentUpdateS2c {
+                        entity_id: self.entity_id.get().into(),
+                        equipment: entries,
+                    });
+                }
+            }
+        });
     }
 }
 
@@ -98,10 +144,22 @@ pub struct UpdateEntityQuery {
     pub tracked_data: &'static TrackedData,
     pub statuses: &'static EntityStatuses,
     pub animations: &'static EntityAnimations,
+    pub passengers: Option<Ref<'static, Passengers>>,
+    pub equipment: Option<&'static Equipment>,
 }
 
 impl UpdateEntityQueryItem<'_> {
+    /// Writes the packets for everything that changed about the entity this
+    /// tick. Equivalent to [`Self::write_movement_packets`] followed by
+    /// [`Self::write_state_packets`].
     pub fn write_update_packets(&self, mut writer: impl WritePacket) {
+        self.write_movement_packets(&mut writer);
+        self.write_state_packets(&mut writer);
+    }
+
+    /// Writes the packets for changes to the entity's position, look, velocity
+    /// and head yaw.
+    pub fn write_movement_packets(&self, mut writer: impl WritePacket) {
         // TODO: @RJ I saw you're using UpdateEntityPosition and UpdateEntityRotation sometimes. These two packets are actually broken on the client and will erase previous position/rotation https://bugs.mojang.com/browse/MC-255263 -Moulberry
 
         let entity_id = VarInt(self.id.get());
@@ -147,6 +205,29 @@ impl UpdateEntityQueryItem<'_> {
             });
         }
 
+        self.write_velocity_and_head_yaw_packets(&mut writer);
+    }
+
+    /// Like [`Self::write_movement_packets`], but changes to the entity's
+    /// position and look are always sent as an absolute teleport instead of
+    /// relative movement.
+    pub fn write_absolute_movement_packets(&self, mut writer: impl WritePacket) {
+        if self.pos.0 != self.old_pos.get() || self.look.is_changed() {
+            writer.write_packet(&EntityPositionS2c {
+                entity_id: VarInt(self.id.get()),
+                position: self.pos.0,
+                yaw: ByteAngle::from_degrees(self.look.yaw),
+                pitch: ByteAngle::from_degrees(self.look.pitch),
+                on_ground: self.on_ground.0,
+            });
+        }
+
+        self.write_velocity_and_head_yaw_packets(&mut writer);
+    }
+
+    fn write_velocity_and_head_yaw_packets(&self, mut writer: impl WritePacket) {
+        let entity_id = VarInt(self.id.get());
+
         if self.velocity.is_changed() {
             writer.write_packet(&EntityVelocityUpdateS2c {
                 entity_id,
@@ -160,6 +241,41 @@ impl UpdateEntityQueryItem<'_> {
                 head_yaw: ByteAngle::from_degrees(self.head_yaw.0),
             });
         }
+    }
+
+    /// Writes packets that set the entity's absolute position, look, velocity
+    /// and head yaw regardless of what changed this tick. Used to bring viewers
+    /// back in sync after movement packets were withheld.
+    pub fn write_resync_packets(&self, mut writer: impl WritePacket) {
+        let entity_id = VarInt(self.id.get());
+
+        writer.write_packet(&EntityPositionS2c {
+            entity_id,
+            position: self.pos.0,
+            yaw: ByteAngle::from_degrees(self.look.yaw),
+            pitch: ByteAngle::from_degrees(self.look.pitch),
+            on_ground: self.on_ground.0,
+        });
+
+        writer.write_packet(&EntityVelocityUpdateS2c {
+            entity_id,
+            velocity: self.velocity.to_packet_units(),
+        });
+
+        writer.write_packet(&EntitySetHeadYawS2c {
+            entity_id,
+            head_yaw: ByteAngle::from_degrees(self.head_yaw.0),
+        });
+    }
+
+    /// Writes the packets for changes to the entity's tracked data, statuses,
+    /// animations and equipment.
+    ///
+    /// Changes to the entity's [`Passengers`] are not included because the
+    /// packet differs for each client riding the entity. Write them with
+    /// [`Passengers::write_packet`] instead.
+    pub fn write_state_packets(&self, mut writer: impl WritePacket) {
+        let entity_id = VarInt(self.id.get());
 
         if let Some(update_data) = self.tracked_data.update_data() {
             writer.write_packet(&EntityTrackerUpdateS2c {
@@ -189,5 +305,16 @@ impl UpdateEntityQueryItem<'_> {
                 }
             }
         }
+
+        if let Some(equipment) = self.equipment {
+            let entries = equipment.update_entries();
+
+            if !entries.is_empty() {
+                writer.write_packet(&EntityEquipmentUpdateS2c {
+                    entity_id,
+                    equipment: entries,
+                });
+            }
+        }
     }
 }
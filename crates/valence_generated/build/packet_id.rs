@@ -18,10 +18,27 @@ pub fn build() -> anyhow::Result<TokenStream> {
     let packets: Vec<Packet> = serde_json::from_str(include_str!("../extracted/packets.json"))?;
 
     let mut consts = TokenStream::new();
+    let mut paths = vec![];
 
     for packet in packets {
         let stripped_name = packet.name.strip_suffix("Packet").unwrap_or(&packet.name);
 
+        // The name of the packet type in `valence_protocol`, e.g.
+        // `PlayerMoveC2SPacket.Full` is `play::FullC2s`.
+        let type_name = stripped_name
+            .strip_suffix("C2S")
+            .or_else(|| stripped_name.strip_suffix("S2C"))
+            .unwrap_or(stripped_name);
+        let type_suffix = match packet.side.as_str() {
+            "serverbound" => "C2s",
+            _ => "S2c",
+        };
+
+        let module = ident(&packet.state);
+        let type_ident = ident(format!("{type_name}{type_suffix}"));
+
+        paths.push(quote!(#module::#type_ident));
+
         let name_ident = ident(stripped_name.to_shouty_snake_case());
         let id = packet.id;
 
@@ -33,5 +50,17 @@ pub fn build() -> anyhow::Result<TokenStream> {
         }]);
     }
 
-    Ok(consts)
+    Ok(quote! {
+        #consts
+
+        /// Invokes the given macro with the path of every vanilla packet type,
+        /// relative to `valence_protocol::packets`, separated by commas.
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! for_each_packet {
+            ($m:ident) => {
+                $m! { #(#paths),* }
+            };
+        }
+    })
 }
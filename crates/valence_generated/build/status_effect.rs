@@ -0,0 +1,170 @@
+use heck::ToPascalCase;
+use proc_macro2::TokenStream;
+use quote::quote;
+use serde::Deserialize;
+use valence_build_utils::{ident, rerun_if_changed};
+
+#[derive(Deserialize, Debug)]
+pub struct StatusEffect {
+    id: u16,
+    name: String,
+    translation_key: String,
+    category: String,
+}
+
+pub fn build() -> anyhow::Result<TokenStream> {
+    rerun_if_changed(["extracted/effects.json"]);
+
+    let effects =
+        serde_json::from_str::<Vec<StatusEffect>>(include_str!("../extracted/effects.json"))?;
+
+    let effect_count = effects.len();
+
+    let effect_from_raw_id_arms = effects
+        .iter()
+        .map(|effect| {
+            let id = &effect.id;
+            let name = ident(effect.name.to_pascal_case());
+
+            quote! {
+                #id => Some(Self::#name),
+            }
+        })
+        .collect::<TokenStream>();
+
+    let effect_to_raw_id_arms = effects
+        .iter()
+        .map(|effect| {
+            let id = &effect.id;
+            let name = ident(effect.name.to_pascal_case());
+
+            quote! {
+                Self::#name => #id,
+            }
+        })
+        .collect::<TokenStream>();
+
+    let effect_from_ident_arms = effects
+        .iter()
+        .map(|effect| {
+            let ident_name = format!("minecraft:{}", &effect.name);
+            let name = ident(effect.name.to_pascal_case());
+
+            quote! {
+                #ident_name => Some(Self::#name),
+            }
+        })
+        .collect::<TokenStream>();
+
+    let effect_to_ident_arms = effects
+        .iter()
+        .map(|effect| {
+            let str_name = &effect.name;
+            let name = ident(str_name.to_pascal_case());
+
+            quote! {
+                Self::#name => ident!(#str_name),
+            }
+        })
+        .collect::<TokenStream>();
+
+    let effect_translation_key_arms = effects
+        .iter()
+        .map(|effect| {
+            let key = &effect.translation_key;
+            let name = ident(effect.name.to_pascal_case());
+
+            quote! {
+                Self::#name => #key,
+            }
+        })
+        .collect::<TokenStream>();
+
+    let effect_category_arms = effects
+        .iter()
+        .map(|effect| {
+            let category = ident(effect.category.to_pascal_case());
+            let name = ident(effect.name.to_pascal_case());
+
+            quote! {
+                Self::#name => StatusEffectCategory::#category,
+            }
+        })
+        .collect::<TokenStream>();
+
+    let effect_variants = effects
+        .iter()
+        .map(|effect| ident(effect.name.to_pascal_case()))
+        .collect::<Vec<_>>();
+
+    Ok(quote! {
+        use valence_ident::{Ident, ident};
+
+        /// Represents a status effect from the game
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        pub enum StatusEffect {
+            #(#effect_variants,)*
+        }
+
+        /// Whether a status effect helps or hurts the entity it is applied to.
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        pub enum StatusEffectCategory {
+            Beneficial,
+            Harmful,
+            Neutral,
+        }
+
+        impl StatusEffect {
+            /// Constructs a status effect from a raw effect ID.
+            ///
+            /// If the given ID is invalid, `None` is returned.
+            pub const fn from_raw(id: u16) -> Option<Self> {
+                match id {
+                    #effect_from_raw_id_arms
+                    _ => None
+                }
+            }
+
+            /// Gets the raw effect ID from the status effect
+            pub const fn to_raw(self) -> u16 {
+                match self {
+                    #effect_to_raw_id_arms
+                }
+            }
+
+            /// Construct a status effect from its snake_case name.
+            ///
+            /// Returns `None` if the name is invalid.
+            pub fn from_ident(id: Ident<&str>) -> Option<Self> {
+                match id.as_str() {
+                    #effect_from_ident_arms
+                    _ => None
+                }
+            }
+
+            /// Gets the identifier of this status effect.
+            pub const fn to_ident(self) -> Ident<&'static str> {
+                match self {
+                    #effect_to_ident_arms
+                }
+            }
+
+            /// Gets the translation key of this status effect.
+            pub const fn translation_key(self) -> &'static str {
+                match self {
+                    #effect_translation_key_arms
+                }
+            }
+
+            /// Gets the category of this status effect.
+            pub const fn category(self) -> StatusEffectCategory {
+                match self {
+                    #effect_category_arms
+                }
+            }
+
+            /// An array of all status effects.
+            pub const ALL: [Self; #effect_count] = [#(Self::#effect_variants,)*];
+        }
+    })
+}
@@ -8,6 +8,10 @@ pub mod sound {
     include!(concat!(env!("OUT_DIR"), "/sound.rs"));
 }
 
+pub mod status_effect {
+    include!(concat!(env!("OUT_DIR"), "/status_effect.rs"));
+}
+
 /// Contains constants for every vanilla packet ID.
 pub mod packet_id {
     include!(concat!(env!("OUT_DIR"), "/packet_id.rs"));
@@ -0,0 +1,120 @@
+use bevy_ecs::prelude::*;
+use valence_server::hand_swing::HandSwingEvent;
+use valence_server::{Hand, ItemKind};
+
+use crate::{HeldItem, Inventory};
+
+/// The number of attacks per second a player can make at full charge while
+/// holding an item of the given kind, as in vanilla.
+pub const fn attack_speed(item: ItemKind) -> f64 {
+    match item {
+        ItemKind::WoodenSword
+        | ItemKind::StoneSword
+        | ItemKind::IronSword
+        | ItemKind::GoldenSword
+        | ItemKind::DiamondSword
+        | ItemKind::NetheriteSword => 1.6,
+        ItemKind::WoodenAxe | ItemKind::StoneAxe => 0.8,
+        ItemKind::IronAxe => 0.9,
+        ItemKind::GoldenAxe | ItemKind::DiamondAxe | ItemKind::NetheriteAxe => 1.0,
+        ItemKind::WoodenPickaxe
+        | ItemKind::StonePickaxe
+        | ItemKind::IronPickaxe
+        | ItemKind::GoldenPickaxe
+        | ItemKind::DiamondPickaxe
+        | ItemKind::NetheritePickaxe => 1.2,
+        ItemKind::WoodenShovel
+        | ItemKind::StoneShovel
+        | ItemKind::IronShovel
+        | ItemKind::GoldenShovel
+        | ItemKind::DiamondShovel
+        | ItemKind::NetheriteShovel => 1.0,
+        ItemKind::WoodenHoe | ItemKind::GoldenHoe => 1.0,
+        ItemKind::StoneHoe => 2.0,
+        ItemKind::IronHoe => 3.0,
+        ItemKind::DiamondHoe | ItemKind::NetheriteHoe => 4.0,
+        ItemKind::Trident => 1.1,
+        _ => 4.0,
+    }
+}
+
+/// The charge of a client's attacks, which fills up after each swing as shown
+/// by the attack indicator.
+///
+/// Swinging the main hand or switching to a different kind of item in the main
+/// hand starts the charge over, as in vanilla. Changes to the held stack that
+/// keep its kind, such as its count or durability, don't. The charge is only
+/// updated at the end of each tick, so the [`progress`](Self::progress) read
+/// while handling an attack is the charge the attack was made with.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct AttackCooldown {
+    ticks_since_reset: u32,
+    held_item: ItemKind,
+}
+
+impl Default for AttackCooldown {
+    fn default() -> Self {
+        Self {
+            ticks_since_reset: 0,
+            held_item: ItemKind::Air,
+        }
+    }
+}
+
+impl AttackCooldown {
+    /// The number of ticks since the charge last started over.
+    pub fn ticks_since_reset(&self) -> u32 {
+        self.ticks_since_reset
+    }
+
+    /// The number of ticks it takes to fully charge an attack with the held
+    /// item.
+    pub fn cooldown_ticks(&self) -> f64 {
+        20.0 / attack_speed(self.held_item)
+    }
+
+    /// Returns the charge of an attack made now, from `0.0` to `1.0`. Damage
+    /// is usually scaled by this value.
+    pub fn progress(&self) -> f64 {
+        // Vanilla adds half a tick for attacks.
+        ((self.ticks_since_reset as f64 + 0.5) / self.cooldown_ticks()).clamp(0.0, 1.0)
+    }
+
+    /// Whether an attack made now would be fully charged.
+    pub fn is_fully_charged(&self) -> bool {
+        self.progress() >= 1.0
+    }
+
+    /// Starts the charge over.
+    pub fn reset(&mut self) {
+        self.ticks_since_reset = 0;
+    }
+}
+
+pub(crate) fn update_attack_cooldowns(
+    mut swings: EventReader<HandSwingEvent>,
+    mut clients: Query<(&mut AttackCooldown, &Inventory, &HeldItem)>,
+) {
+    for (mut cooldown, inv, held) in &mut clients {
+        let held_item = inv
+            .slot(held.slot())
+            .map_or(ItemKind::Air, |stack| stack.item);
+
+        if cooldown.held_item != held_item {
+            cooldown.held_item = held_item;
+            cooldown.reset();
+        } else {
+            cooldown.ticks_since_reset = cooldown.ticks_since_reset.saturating_add(1);
+        }
+    }
+
+    for swing in swings.iter() {
+        if swing.hand != Hand::Main {
+            continue;
+        }
+
+        if let Ok((mut cooldown, _, _)) = clients.get_mut(swing.client) {
+            cooldown.reset();
+        }
+    }
+}
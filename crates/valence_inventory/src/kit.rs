@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use valence_server::ItemStack;
+
+use crate::{Inventory, InventoryKind};
+
+/// The player inventory slots of the armor pieces, from head to feet.
+const ARMOR_SLOTS: [u16; 4] = [5, 6, 7, 8];
+
+/// A named loadout of items, such as a class in a minigame, which can be given
+/// to a player with [`Inventory::give_kit`].
+///
+/// # Examples
+///
+/// ```
+/// # use valence_inventory::*;
+/// # use valence_server::{ItemKind, ItemStack};
+/// let archer = Kit::new("Archer")
+///     .with_item(36, ItemStack::new(ItemKind::Bow, 1, None))
+///     .with_item(37, ItemStack::new(ItemKind::Arrow, 64, None))
+///     .with_armor([
+///         None,
+///         Some(ItemStack::new(ItemKind::LeatherChestplate, 1, None)),
+///         None,
+///         None,
+///     ]);
+///
+/// let mut inv = Inventory::new(InventoryKind::Player);
+/// inv.give_kit(&archer).unwrap();
+///
+/// assert_eq!(inv.slot(36).unwrap().item, ItemKind::Bow);
+/// assert_eq!(inv.slot(6).unwrap().item, ItemKind::LeatherChestplate);
+/// ```
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct Kit {
+    pub name: String,
+    /// The items of the kit, keyed by their player inventory slot.
+    pub items: BTreeMap<u16, ItemStack>,
+    /// The armor of the kit, from head to feet. Slots without armor are left
+    /// alone when the kit is given.
+    pub armor: [Option<ItemStack>; 4],
+    /// Whether giving the kit should also empty every slot the kit doesn't
+    /// have an item for.
+    pub clear_other_slots: bool,
+}
+
+impl Kit {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    #[must_use]
+    pub fn with_item(mut self, slot: u16, item: ItemStack) -> Self {
+        self.items.insert(slot, item);
+        self
+    }
+
+    #[must_use]
+    pub fn with_armor(mut self, armor: [Option<ItemStack>; 4]) -> Self {
+        self.armor = armor;
+        self
+    }
+
+    #[must_use]
+    pub fn with_clear_other_slots(mut self, clear_other_slots: bool) -> Self {
+        self.clear_other_slots = clear_other_slots;
+        self
+    }
+}
+
+impl Inventory {
+    /// Gives a [`Kit`] to this player inventory.
+    ///
+    /// The slots of the kit's items and armor are replaced by the kit's
+    /// contents, even if they held something else before. Other slots are
+    /// left alone unless [`Kit::clear_other_slots`] is set, in which case they
+    /// are emptied.
+    ///
+    /// Returns an error and leaves the inventory untouched if this is not a
+    /// player inventory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the kit's slots is out of bounds.
+    #[track_caller]
+    pub fn give_kit(&mut self, kit: &Kit) -> Result<(), NotPlayerInventory> {
+        if self.kind != InventoryKind::Player {
+            return Err(NotPlayerInventory(self.kind));
+        }
+
+        if kit.clear_other_slots {
+            for idx in 0..self.slot_count() {
+                if !kit.items.contains_key(&idx) {
+                    self.set_slot(idx, None);
+                }
+            }
+        }
+
+        for (slot, armor) in ARMOR_SLOTS.into_iter().zip(&kit.armor) {
+            if let Some(armor) = armor {
+                self.set_slot(slot, armor.clone());
+            }
+        }
+
+        for (&slot, item) in &kit.items {
+            self.set_slot(slot, item.clone());
+        }
+
+        Ok(())
+    }
+}
+
+/// The error returned by [`Inventory::give_kit`] when the inventory is not a
+/// player inventory. Contains the kind of the inventory.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct NotPlayerInventory(pub InventoryKind);
+
+impl fmt::Display for NotPlayerInventory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "kits can only be given to player inventories, not {:?}",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for NotPlayerInventory {}
+
+#[cfg(test)]
+mod tests {
+    use valence_server::ItemKind;
+
+    use super::*;
+
+    #[test]
+    fn give_kit_replaces_slots() {
+        let kit = Kit::new("Miner")
+            .with_item(36, ItemStack::new(ItemKind::IronPickaxe, 1, None))
+            .with_item(37, ItemStack::new(ItemKind::Torch, 16, None));
+
+        let mut inv = Inventory::new(InventoryKind::Player);
+        inv.set_slot(36, ItemStack::new(ItemKind::Stick, 1, None));
+        inv.set_slot(7, ItemStack::new(ItemKind::IronLeggings, 1, None));
+        inv.set_slot(20, ItemStack::new(ItemKind::Diamond, 3, None));
+
+        inv.give_kit(&kit).unwrap();
+
+        assert_eq!(inv.slot(36).unwrap().item, ItemKind::IronPickaxe);
+        assert_eq!(inv.slot(37).unwrap().item, ItemKind::Torch);
+        // The kit has no leggings, so the old ones are kept.
+        assert_eq!(inv.slot(7).unwrap().item, ItemKind::IronLeggings);
+        assert_eq!(inv.slot(20).unwrap().item, ItemKind::Diamond);
+
+        inv.give_kit(&kit.with_clear_other_slots(true)).unwrap();
+
+        assert_eq!(inv.slots().flatten().count(), 2);
+        assert_eq!(inv.slot(36).unwrap().item, ItemKind::IronPickaxe);
+        assert_eq!(inv.slot(37).unwrap().item, ItemKind::Torch);
+    }
+
+    #[test]
+    fn give_kit_to_wrong_inventory_kind() {
+        let kit = Kit::new("Miner").with_item(36, ItemStack::new(ItemKind::IronPickaxe, 1, None));
+
+        let mut inv = Inventory::new(InventoryKind::Generic9x3);
+
+        assert_eq!(
+            inv.give_kit(&kit),
+            Err(NotPlayerInventory(InventoryKind::Generic9x3))
+        );
+        assert_eq!(inv.slots().flatten().count(), 0);
+    }
+}
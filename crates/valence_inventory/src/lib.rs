@@ -28,20 +28,28 @@ use bevy_ecs::prelude::*;
 use derive_more::{Deref, DerefMut};
 use tracing::{debug, warn};
 use valence_server::client::{Client, FlushPacketsSet, SpawnClientsSet};
-use valence_server::event_loop::{EventLoopPreUpdate, PacketEvent};
+use valence_server::event_loop::{EventLoopPreUpdate, EventLoopUpdate, PacketEvent};
+use valence_server::interact_cooldown::HeldItemKinds;
 pub use valence_server::protocol::packets::play::click_slot_c2s::{ClickMode, SlotChange};
 use valence_server::protocol::packets::play::open_screen_s2c::WindowType;
 pub use valence_server::protocol::packets::play::player_action_c2s::PlayerAction;
 use valence_server::protocol::packets::play::{
     ClickSlotC2s, CloseHandledScreenC2s, CloseScreenS2c, CreativeInventoryActionC2s, InventoryS2c,
     OpenScreenS2c, PlayerActionC2s, ScreenHandlerSlotUpdateS2c, UpdateSelectedSlotC2s,
+    UpdateSelectedSlotS2c,
 };
 use valence_server::protocol::{VarInt, WritePacket};
+use valence_server::status::RequestRespawnEvent;
 use valence_server::text::IntoText;
 use valence_server::{GameMode, ItemKind, ItemStack, Text};
 
+mod attack_cooldown;
+mod kit;
 mod validate;
 
+pub use attack_cooldown::{attack_speed, AttackCooldown};
+pub use kit::{Kit, NotPlayerInventory};
+
 pub struct InventoryPlugin;
 
 impl Plugin for InventoryPlugin {
@@ -56,6 +64,9 @@ impl Plugin for InventoryPlugin {
                 update_client_on_close_inventory.before(update_open_inventories),
                 update_open_inventories,
                 update_player_inventories,
+                update_held_items,
+                update_held_item_kinds,
+                attack_cooldown::update_attack_cooldowns,
             )
                 .before(FlushPacketsSet),
         )
@@ -69,7 +80,9 @@ impl Plugin for InventoryPlugin {
                 handle_player_actions,
             ),
         )
+        .add_systems(EventLoopUpdate, reapply_initial_inventory)
         .init_resource::<InventorySettings>()
+        .init_resource::<InitialInventory>()
         .add_event::<ClickSlotEvent>()
         .add_event::<DropItemStackEvent>()
         .add_event::<CreativeInventoryActionEvent>()
@@ -404,7 +417,10 @@ impl ClientInventoryState {
 /// Indicates which hotbar slot the player is currently holding.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Component, Deref)]
 pub struct HeldItem {
+    #[deref]
     held_item_slot: u16,
+    /// The slot the client is known to be holding.
+    client_held_item_slot: u16,
 }
 
 impl HeldItem {
@@ -413,6 +429,28 @@ impl HeldItem {
     pub fn slot(&self) -> u16 {
         self.held_item_slot
     }
+
+    /// The index of the currently held item in the hotbar, in the range 0-8
+    /// inclusive.
+    pub fn hotbar_slot(&self) -> u8 {
+        (self.held_item_slot - PLAYER_INVENTORY_MAIN_SLOTS_COUNT) as u8
+    }
+
+    /// Changes the hotbar slot the player is holding, from 0 to 8 inclusive.
+    /// The client is sent the new slot at the end of the tick.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `hotbar_slot` is greater than 8.
+    #[track_caller]
+    pub fn set_hotbar_slot(&mut self, hotbar_slot: u8) {
+        assert!(
+            hotbar_slot <= 8,
+            "hotbar slot {hotbar_slot} is out of bounds (must be 0-8)"
+        );
+
+        self.held_item_slot = convert_hotbar_slot_id(hotbar_slot as u16);
+    }
 }
 
 /// The item stack that the client thinks it's holding under the mouse
@@ -581,10 +619,16 @@ impl<'a> InventoryWindowMut<'a> {
 }
 
 /// Attach the necessary inventory components to new clients.
-fn init_new_client_inventories(clients: Query<Entity, Added<Client>>, mut commands: Commands) {
+fn init_new_client_inventories(
+    clients: Query<Entity, Added<Client>>,
+    initial: Res<InitialInventory>,
+    mut commands: Commands,
+) {
     for entity in &clients {
         commands.entity(entity).insert((
-            Inventory::new(InventoryKind::Player),
+            initial
+                .get()
+                .unwrap_or_else(|| Inventory::new(InventoryKind::Player)),
             CursorItem(None),
             ClientInventoryState {
                 window_id: 0,
@@ -595,11 +639,32 @@ fn init_new_client_inventories(clients: Query<Entity, Added<Client>>, mut comman
             HeldItem {
                 // First slot of the hotbar.
                 held_item_slot: 36,
+                client_held_item_slot: 36,
             },
+            AttackCooldown::default(),
+            HeldItemKinds::default(),
         ));
     }
 }
 
+/// Gives the [`InitialInventory`] back to clients that request a respawn, if
+/// [`InitialInventory::reapply_on_respawn`] is set.
+fn reapply_initial_inventory(
+    mut events: EventReader<RequestRespawnEvent>,
+    initial: Res<InitialInventory>,
+    mut clients: Query<&mut Inventory, With<Client>>,
+) {
+    if !initial.reapply_on_respawn {
+        return;
+    }
+
+    for event in events.iter() {
+        if let (Some(inv), Ok(mut client_inv)) = (initial.get(), clients.get_mut(event.client)) {
+            *client_inv = inv;
+        }
+    }
+}
+
 /// Send updates for each client's player inventory.
 fn update_player_inventories(
     mut query: Query<
@@ -1237,6 +1302,7 @@ fn handle_update_selected_slot(
                     continue;
                 }
                 held.held_item_slot = convert_hotbar_slot_id(pkt.slot);
+                held.client_held_item_slot = held.held_item_slot;
 
                 events.send(UpdateSelectedSlotEvent {
                     client: packet.client,
@@ -1247,6 +1313,37 @@ fn handle_update_selected_slot(
     }
 }
 
+/// Sends the held item slot to clients when it was changed by the server.
+fn update_held_items(mut clients: Query<(&mut Client, &mut HeldItem), Changed<HeldItem>>) {
+    for (mut client, mut held) in &mut clients {
+        if held.held_item_slot != held.client_held_item_slot {
+            client.write_packet(&UpdateSelectedSlotS2c {
+                slot: held.hotbar_slot(),
+            });
+
+            held.bypass_change_detection().client_held_item_slot = held.held_item_slot;
+        }
+    }
+}
+
+/// Keeps the [`HeldItemKinds`] used for the use item cooldown in sync with the
+/// player's inventory.
+fn update_held_item_kinds(
+    mut clients: Query<
+        (&mut HeldItemKinds, &Inventory, &HeldItem),
+        Or<(Changed<Inventory>, Changed<HeldItem>)>,
+    >,
+) {
+    for (mut kinds, inv, held) in &mut clients {
+        let kind = |slot| inv.slot(slot).map_or(ItemKind::Air, |stack| stack.item);
+
+        kinds.set_if_neq(HeldItemKinds {
+            main_hand: kind(held.slot()),
+            off_hand: kind(45),
+        });
+    }
+}
+
 /// Convert a slot that is outside a target inventory's range to a slot that is
 /// inside the player's inventory.
 #[doc(hidden)]
@@ -1401,6 +1498,50 @@ impl Default for InventorySettings {
     }
 }
 
+/// The player inventory given to every client when it joins, such as a lobby
+/// hotbar kit. By default, clients start out with an empty inventory.
+///
+/// # Examples
+///
+/// ```
+/// # use valence_inventory::*;
+/// # use valence_server::{ItemKind, ItemStack};
+/// let mut kit = Inventory::new(InventoryKind::Player);
+/// kit.set_slot(36, ItemStack::new(ItemKind::Compass, 1, None));
+///
+/// let initial = InitialInventory {
+///     inventory: Some(kit),
+///     reapply_on_respawn: true,
+/// };
+/// # let _ = initial;
+/// ```
+#[derive(Resource, Clone, Default, Debug)]
+pub struct InitialInventory {
+    /// The inventory copied onto new clients. Must be an
+    /// [`InventoryKind::Player`] inventory, otherwise it is ignored.
+    pub inventory: Option<Inventory>,
+    /// Whether to give the inventory again when a client requests to respawn,
+    /// replacing whatever the client had before.
+    pub reapply_on_respawn: bool,
+}
+
+impl InitialInventory {
+    /// Returns a copy of the configured inventory, marked to be sent to the
+    /// client in full.
+    fn get(&self) -> Option<Inventory> {
+        let inv = self.inventory.as_ref()?;
+
+        if inv.kind != InventoryKind::Player {
+            warn!("initial inventory is not a player inventory");
+            return None;
+        }
+
+        let mut inv = inv.clone();
+        inv.changed = u64::MAX;
+        Some(inv)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
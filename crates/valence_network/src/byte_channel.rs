@@ -232,6 +232,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn byte_channel_coalesces_sends() {
+        let (mut sender, mut receiver) = byte_channel(64);
+
+        for chunk in ["first", " second", " third"] {
+            sender.try_send(chunk.as_bytes().into()).unwrap();
+        }
+
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            BytesMut::from("first second third".as_bytes())
+        );
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+
+        // Bytes sent after the receiver took the buffer are delivered separately.
+        sender.try_send("fourth".as_bytes().into()).unwrap();
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            BytesMut::from("fourth".as_bytes())
+        );
+    }
+
     #[tokio::test]
     async fn byte_channel_async() {
         let (mut sender, mut receiver) = byte_channel(4);
@@ -2,6 +2,7 @@
 
 use std::io;
 use std::net::SocketAddr;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 use anyhow::{bail, ensure, Context};
@@ -124,6 +125,16 @@ pub struct HandshakeData {
     pub server_address: String,
     /// The port that the client used to connect.
     pub server_port: u16,
+    /// The state the client wants to switch to, which tells a fresh login
+    /// apart from a transfer from another server.
+    pub next_state: HandshakeNextState,
+}
+
+impl HandshakeData {
+    /// Whether the client was transferred here from another server.
+    pub fn is_transfer(&self) -> bool {
+        self.next_state == HandshakeNextState::Transfer
+    }
 }
 
 async fn handle_handshake(
@@ -139,6 +150,7 @@ async fn handle_handshake(
         protocol_version: handshake.protocol_version.0,
         server_address: handshake.server_address.0.to_owned(),
         server_port: handshake.server_port,
+        next_state: handshake.next_state,
     };
 
     // TODO: this is borked.
@@ -152,7 +164,7 @@ async fn handle_handshake(
         HandshakeNextState::Status => handle_status(shared, io, remote_addr, handshake)
             .await
             .context("handling status"),
-        HandshakeNextState::Login => {
+        HandshakeNextState::Login | HandshakeNextState::Transfer => {
             match handle_login(&shared, &mut io, remote_addr, handshake)
                 .await
                 .context("handling login")?
@@ -226,6 +238,7 @@ async fn handle_status(
                     "sample": player_sample,
                 },
                 "description": description,
+                "enforcesSecureChat": shared.0.enforce_secure_chat,
             });
 
             if !favicon_png.is_empty() {
@@ -268,6 +281,17 @@ async fn handle_login(
         return Ok(None);
     }
 
+    if handshake.is_transfer() && !shared.0.accepts_transfers {
+        io.send_packet(&LoginDisconnectS2c {
+            reason: "This server does not accept transfers"
+                .color(Color::RED)
+                .into(),
+        })
+        .await?;
+
+        return Ok(None);
+    }
+
     let LoginHelloC2s {
         username,
         profile_id: _, // TODO
@@ -275,7 +299,7 @@ async fn handle_login(
 
     let username = username.0.to_owned();
 
-    let info = match shared.connection_mode() {
+    let mut info = match shared.connection_mode() {
         ConnectionMode::Online { .. } => login_online(shared, io, remote_addr, username).await?,
         ConnectionMode::Offline => login_offline(remote_addr, username)?,
         ConnectionMode::BungeeCord => {
@@ -284,6 +308,8 @@ async fn handle_login(
         ConnectionMode::Velocity { secret } => login_velocity(io, username, secret).await?,
     };
 
+    info.transferred = handshake.is_transfer();
+
     if shared.0.threshold.0 > 0 {
         io.send_packet(&LoginCompressionS2c {
             threshold: shared.0.threshold.0.into(),
@@ -293,8 +319,8 @@ async fn handle_login(
         io.set_compression(shared.0.threshold);
     }
 
-    let cleanup = match shared.0.callbacks.inner.login(shared, &info).await {
-        Ok(f) => CleanupOnDrop(Some(f)),
+    let cleanup = match run_login_callback(shared, &info).await {
+        Ok(cleanup) => cleanup,
         Err(reason) => {
             info!("disconnect at login: \"{reason}\"");
             io.send_packet(&LoginDisconnectS2c {
@@ -315,6 +341,50 @@ async fn handle_login(
     Ok(Some((info, cleanup)))
 }
 
+/// Runs the [`login`](crate::NetworkCallbacks::login) callback and enforces
+/// the player limit. If the client is accepted, it is counted as online until
+/// the returned cleanup is dropped.
+async fn run_login_callback(
+    shared: &SharedNetworkState,
+    info: &NewClientInfo,
+) -> Result<CleanupOnDrop, Text> {
+    let cleanup = shared.0.callbacks.inner.login(shared, info).await?;
+
+    let max_players = shared.0.max_players;
+
+    let has_slot = shared
+        .0
+        .player_count
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+            (n < max_players).then_some(n + 1)
+        })
+        .is_ok();
+
+    if !has_slot {
+        if shared
+            .0
+            .callbacks
+            .inner
+            .bypass_max_players(shared, info)
+            .await
+        {
+            shared.0.player_count.fetch_add(1, Ordering::SeqCst);
+        } else {
+            cleanup();
+            return Err(shared.0.server_full_message.clone());
+        }
+    }
+
+    let shared = shared.clone();
+
+    Ok(CleanupOnDrop(Some(Box::new(move || {
+        cleanup();
+
+        let prev = shared.0.player_count.fetch_sub(1, Ordering::SeqCst);
+        debug_assert_ne!(prev, 0, "player count underflowed");
+    }))))
+}
+
 /// Login procedure for online mode.
 async fn login_online(
     shared: &SharedNetworkState,
@@ -410,6 +480,7 @@ async fn login_online(
         username,
         ip: remote_addr.ip(),
         properties: Properties(profile.properties),
+        transferred: false,
     })
 }
 
@@ -428,6 +499,7 @@ fn login_offline(remote_addr: SocketAddr, username: String) -> anyhow::Result<Ne
         uuid: offline_uuid(username.as_str())?,
         username,
         properties: Default::default(),
+        transferred: false,
         ip: remote_addr.ip(),
     })
 }
@@ -466,6 +538,7 @@ fn login_bungeecord(
         uuid,
         username,
         properties: Properties(properties),
+        transferred: false,
         ip,
     })
 }
@@ -540,15 +613,26 @@ async fn login_velocity(
         uuid,
         username,
         properties: Properties(properties),
+        transferred: false,
         ip: remote_addr,
     })
 }
 
 #[cfg(test)]
 mod tests {
+    use std::net::Ipv4Addr;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+
+    use bevy_app::App;
     use sha1::Digest;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use valence_server::client::{ClientBundle, ClientBundleArgs, Transferred};
+    use valence_server::protocol::Packet;
+    use valence_server::ServerPlugin;
 
     use super::*;
+    use crate::{CleanupFn, NetworkSettings};
 
     #[test]
     fn auth_digest_usernames() {
@@ -565,4 +649,180 @@ mod tests {
             "88e16a1019277b15d58faf0541e11910eb756f6"
         );
     }
+
+    struct BypassForOps;
+
+    #[async_trait::async_trait]
+    impl crate::NetworkCallbacks for BypassForOps {
+        async fn bypass_max_players(
+            &self,
+            _shared: &SharedNetworkState,
+            info: &NewClientInfo,
+        ) -> bool {
+            info.username == "op"
+        }
+    }
+
+    fn shared_state(max_players: usize) -> SharedNetworkState {
+        let mut app = App::new();
+        app.add_plugins(ServerPlugin);
+        app.insert_resource(NetworkSettings {
+            callbacks: BypassForOps.into(),
+            max_players,
+            server_full_message: "No room left".into_text(),
+            ..Default::default()
+        });
+        crate::build_plugin(&mut app).unwrap();
+
+        app.world.resource::<SharedNetworkState>().clone()
+    }
+
+    fn login(shared: &SharedNetworkState, username: &str) -> Result<CleanupOnDrop, Text> {
+        let info = NewClientInfo {
+            username: username.into(),
+            uuid: Uuid::nil(),
+            ip: Ipv4Addr::LOCALHOST.into(),
+            properties: Default::default(),
+            transferred: false,
+        };
+
+        shared
+            .0
+            .tokio_handle
+            .block_on(run_login_callback(shared, &info))
+    }
+
+    #[test]
+    fn full_server_rejection_is_not_counted() {
+        let shared = shared_state(1);
+
+        assert_eq!(shared.online_count(), 0);
+
+        let first = login(&shared, "test").expect("first client should be accepted");
+        assert_eq!(shared.online_count(), 1);
+
+        assert!(login(&shared, "test").is_err(), "server should be full");
+        assert_eq!(shared.online_count(), 1);
+
+        drop(first);
+        assert_eq!(shared.online_count(), 0);
+
+        let _second = login(&shared, "test").expect("slot should be free again");
+        assert_eq!(shared.online_count(), 1);
+    }
+
+    #[test]
+    fn max_players_rejects_with_configured_message() {
+        let shared = shared_state(2);
+
+        let _clients = [login(&shared, "a").unwrap(), login(&shared, "b").unwrap()];
+
+        let Err(reason) = login(&shared, "c") else {
+            panic!("third client should be rejected");
+        };
+        assert_eq!(reason, "No room left".into_text());
+        assert_eq!(shared.online_count(), 2);
+
+        let _op = login(&shared, "op").expect("ops should bypass the limit");
+        assert_eq!(shared.online_count(), 3);
+    }
+
+    /// Records whether the last client to log in was transferred.
+    struct RecordTransfer(Arc<AtomicBool>);
+
+    #[async_trait::async_trait]
+    impl crate::NetworkCallbacks for RecordTransfer {
+        async fn login(
+            &self,
+            _shared: &SharedNetworkState,
+            info: &NewClientInfo,
+        ) -> Result<CleanupFn, Text> {
+            self.0.store(info.transferred, Ordering::SeqCst);
+            Ok(Box::new(|| {}))
+        }
+    }
+
+    /// Logs a client in with a transfer handshake. Returns whether the login
+    /// callback saw a transferred client, and either the new client if it was
+    /// accepted or the packets sent to it before it was disconnected.
+    fn transfer_login(accepts_transfers: bool) -> (bool, Option<ClientBundleArgs>, Vec<u8>) {
+        let transferred = Arc::new(AtomicBool::new(false));
+
+        let mut app = App::new();
+        app.add_plugins(ServerPlugin);
+        app.insert_resource(NetworkSettings {
+            callbacks: RecordTransfer(transferred.clone()).into(),
+            connection_mode: ConnectionMode::Offline,
+            accepts_transfers,
+            ..Default::default()
+        });
+        crate::build_plugin(&mut app).unwrap();
+
+        let shared = app.world.resource::<SharedNetworkState>().clone();
+
+        let (args, received) = shared.0.tokio_handle.block_on(async {
+            let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+            let mut client = TcpStream::connect(listener.local_addr().unwrap())
+                .await
+                .unwrap();
+            let (server, remote_addr) = listener.accept().await.unwrap();
+
+            let mut enc = PacketEncoder::new();
+            enc.append_packet(&HandshakeC2s {
+                protocol_version: PROTOCOL_VERSION.into(),
+                server_address: "localhost".into(),
+                server_port: 25565,
+                next_state: HandshakeNextState::Transfer,
+            })
+            .unwrap();
+            enc.append_packet(&LoginHelloC2s {
+                username: "test".into(),
+                profile_id: None,
+            })
+            .unwrap();
+            client.write_all(&enc.take()).await.unwrap();
+
+            let io = PacketIo::new(server, PacketEncoder::new(), PacketDecoder::new());
+            handle_handshake(shared.clone(), io, remote_addr)
+                .await
+                .unwrap();
+
+            let args = shared.0.new_clients_recv.try_recv().ok();
+
+            // The server closed its end of the connection if the client was rejected.
+            let mut received = vec![];
+            if args.is_none() {
+                client.read_to_end(&mut received).await.unwrap();
+            }
+
+            (args, received)
+        });
+
+        (transferred.load(Ordering::SeqCst), args, received)
+    }
+
+    #[test]
+    fn transfer_handshake_marks_client_transferred() {
+        let (transferred, args, _) = transfer_login(true);
+
+        assert!(transferred);
+        assert_eq!(
+            ClientBundle::new(args.expect("transfer should be accepted")).transferred,
+            Transferred(true)
+        );
+    }
+
+    #[test]
+    fn transfers_rejected_by_default() {
+        let (transferred, args, received) = transfer_login(false);
+
+        assert!(!transferred, "login callback should not run");
+        assert!(args.is_none(), "transfer should be rejected");
+
+        let mut dec = PacketDecoder::new();
+        dec.queue_slice(&received);
+
+        let frame = dec.try_next_packet().unwrap().unwrap();
+        assert_eq!(frame.id, LoginDisconnectS2c::ID);
+    }
 }
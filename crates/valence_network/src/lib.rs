@@ -46,8 +46,10 @@ use tokio::sync::Semaphore;
 use tokio::time;
 use tracing::error;
 use uuid::Uuid;
+use valence_lang::keys;
 use valence_protocol::text::IntoText;
 use valence_server::client::{ClientBundle, ClientBundleArgs, Properties, SpawnClientsSet};
+use valence_server::message::ChatSettings;
 use valence_server::{CompressionThreshold, Server, Text, MINECRAFT_VERSION, PROTOCOL_VERSION};
 
 pub struct NetworkPlugin;
@@ -67,6 +69,11 @@ fn build_plugin(app: &mut App) -> anyhow::Result<()> {
         .context("missing server resource")?
         .compression_threshold();
 
+    let enforce_secure_chat = app
+        .world
+        .get_resource::<ChatSettings>()
+        .is_some_and(|s| s.enforce_secure_chat);
+
     let settings = app
         .world
         .get_resource_or_insert_with(NetworkSettings::default);
@@ -100,8 +107,11 @@ fn build_plugin(app: &mut App) -> anyhow::Result<()> {
         )),
         player_count: AtomicUsize::new(0),
         max_players: settings.max_players,
+        server_full_message: settings.server_full_message.clone(),
         connection_mode: settings.connection_mode.clone(),
+        accepts_transfers: settings.accepts_transfers,
         threshold,
+        enforce_secure_chat,
         tokio_handle,
         _tokio_runtime: runtime,
         new_clients_send,
@@ -158,10 +168,18 @@ impl SharedNetworkState {
         &self.0.connection_mode
     }
 
-    pub fn player_count(&self) -> &AtomicUsize {
-        &self.0.player_count
+    /// Returns the number of clients that are currently online.
+    ///
+    /// A client is counted from the moment [`NetworkCallbacks::login`] accepts
+    /// it until its connection is dropped, which normally happens when the
+    /// client entity is despawned. Clients rejected at login are never
+    /// counted.
+    pub fn online_count(&self) -> usize {
+        self.0.player_count.load(Ordering::SeqCst)
     }
 
+    /// Returns the configured [maximum number of
+    /// players](NetworkSettings::max_players).
     pub fn max_players(&self) -> usize {
         self.0.max_players
     }
@@ -177,8 +195,13 @@ struct SharedNetworkStateInner {
     //// The number of clients in the play state, past the login state.
     player_count: AtomicUsize,
     max_players: usize,
+    server_full_message: Text,
     connection_mode: ConnectionMode,
+    /// Whether clients transferred from another server can log in.
+    accepts_transfers: bool,
     threshold: CompressionThreshold,
+    /// Whether the server list advertises that secure chat is enforced.
+    enforce_secure_chat: bool,
     tokio_handle: Handle,
     // Holding a runtime handle is not enough to keep tokio working. We need
     // to store the runtime here so we don't drop it.
@@ -209,6 +232,9 @@ pub struct NewClientInfo {
     /// The client's properties from the game profile. Typically contains a
     /// `textures` property with the skin and cape of the player.
     pub properties: Properties,
+    /// Whether the client was transferred here from another server rather
+    /// than connecting on its own. See [`HandshakeData::is_transfer`].
+    pub transferred: bool,
 }
 
 /// Settings for [`NetworkPlugin`]. Note that mutations to these fields have no
@@ -232,10 +258,26 @@ pub struct NetworkSettings {
     ///
     /// The default value is left unspecified and may change in future versions.
     pub max_connections: usize,
+    /// The maximum number of clients that can be online at the same time.
+    ///
+    /// Clients that log in while the server is full are disconnected with
+    /// [`server_full_message`](Self::server_full_message) before they are
+    /// spawned, unless [`NetworkCallbacks::bypass_max_players`] lets them
+    /// through. Unlike [`max_connections`](Self::max_connections), this does
+    /// not include connections that are still logging in or pinging the
+    /// server list.
+    ///
     /// # Default Value
     ///
     /// `20`
     pub max_players: usize,
+    /// The disconnect reason shown to clients that are rejected because the
+    /// server is full.
+    ///
+    /// # Default Value
+    ///
+    /// The `multiplayer.disconnect.server_full` translation.
+    pub server_full_message: Text,
     /// The socket address the server will be bound to.
     ///
     /// # Default Value
@@ -274,6 +316,16 @@ pub struct NetworkSettings {
     ///
     /// The default value is left unspecified and may change in future versions.
     pub outgoing_byte_limit: usize,
+    /// Whether clients transferred here from another server are allowed to
+    /// log in. This is the `accepts-transfers` property of a vanilla server.
+    ///
+    /// When disabled, transferred clients are disconnected before logging in.
+    /// Use [`NewClientInfo::transferred`] to handle accepted transfers.
+    ///
+    /// # Default Value
+    ///
+    /// `false`
+    pub accepts_transfers: bool,
 }
 
 impl Default for NetworkSettings {
@@ -283,12 +335,14 @@ impl Default for NetworkSettings {
             tokio_handle: None,
             max_connections: 1024,
             max_players: 20,
+            server_full_message: Text::translate(keys::MULTIPLAYER_DISCONNECT_SERVER_FULL, []),
             address: SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), 25565).into(),
             connection_mode: ConnectionMode::Online {
                 prevent_proxy_connections: false,
             },
             incoming_byte_limit: 2097152, // 2 MiB
             outgoing_byte_limit: 8388608, // 8 MiB
+            accepts_transfers: false,
         }
     }
 }
@@ -342,7 +396,7 @@ pub trait NetworkCallbacks: Send + Sync + 'static {
         #![allow(unused_variables)]
 
         ServerListPing::Respond {
-            online_players: shared.player_count().load(Ordering::Relaxed) as i32,
+            online_players: shared.online_count() as i32,
             max_players: shared.max_players() as i32,
             player_sample: vec![],
             description: "A Valence Server".into_text(),
@@ -377,6 +431,7 @@ pub trait NetworkCallbacks: Send + Sync + 'static {
                 protocol_version: protocol,
                 server_address: hostname,
                 server_port: port,
+                next_state: Default::default(),
             },
             _ => HandshakeData::default(),
         };
@@ -433,9 +488,19 @@ pub trait NetworkCallbacks: Send + Sync + 'static {
     /// appropriate place to perform asynchronous operations such as
     /// database queries which may take some time to complete.
     ///
+    /// Accepted clients are added to the
+    /// [online count](SharedNetworkState::online_count) automatically, so
+    /// implementations do not need to track it themselves. The
+    /// [player limit](NetworkSettings::max_players) is enforced after this
+    /// method accepts a client.
+    ///
+    /// Clients transferred from another server can be told apart from fresh
+    /// logins with [`NewClientInfo::transferred`]. They only get this far if
+    /// [`NetworkSettings::accepts_transfers`] is enabled.
+    ///
     /// # Default Implementation
     ///
-    /// TODO
+    /// The client is accepted with a cleanup function that does nothing.
     ///
     /// [`Client`]: valence::client::Client
     async fn login(
@@ -443,32 +508,26 @@ pub trait NetworkCallbacks: Send + Sync + 'static {
         shared: &SharedNetworkState,
         info: &NewClientInfo,
     ) -> Result<CleanupFn, Text> {
-        let _ = info;
-
-        let max_players = shared.max_players();
-
-        let success = shared
-            .player_count()
-            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
-                if n < max_players {
-                    Some(n + 1)
-                } else {
-                    None
-                }
-            })
-            .is_ok();
+        #![allow(unused_variables)]
 
-        if success {
-            let shared = shared.clone();
+        Ok(Box::new(|| {}))
+    }
 
-            Ok(Box::new(move || {
-                let prev = shared.player_count().fetch_sub(1, Ordering::SeqCst);
-                debug_assert_ne!(prev, 0, "player count underflowed");
-            }))
-        } else {
-            // TODO: use correct translation key.
-            Err("Server Full".into_text())
-        }
+    /// Called for each client accepted by [`login`](Self::login) to determine
+    /// if it may join while the server is full. Returning `true` lets the
+    /// client exceed [`NetworkSettings::max_players`], which is useful for
+    /// operators or whitelisted players. Clients that bypass the limit are
+    /// still included in the online count.
+    ///
+    /// This method is called from within a tokio runtime.
+    ///
+    /// # Default Implementation
+    ///
+    /// Returns `false`.
+    async fn bypass_max_players(&self, shared: &SharedNetworkState, info: &NewClientInfo) -> bool {
+        #![allow(unused_variables)]
+
+        false
     }
 
     /// Called upon every client login to obtain the full URL to use for session
@@ -172,6 +172,8 @@ impl PacketIo {
 
         let writer_task = tokio::spawn(async move {
             loop {
+                // Everything sent since the last write is received as one buffer, so
+                // packets flushed in the same tick are coalesced into a single write.
                 let bytes = match outgoing_receiver.recv_async().await {
                     Ok(bytes) => bytes,
                     Err(e) => {
@@ -191,6 +193,7 @@ impl PacketIo {
             uuid: info.uuid,
             ip: info.ip,
             properties: info.properties.0,
+            transferred: info.transferred,
             conn: Box::new(RealClientConnection {
                 send: outgoing_sender,
                 recv: incoming_receiver,
@@ -23,6 +23,7 @@ use std::borrow::Cow;
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use derive_more::{Deref, DerefMut};
+use rustc_hash::FxHashMap;
 use valence_server::client::{Client, Properties, Username};
 use valence_server::keepalive::Ping;
 use valence_server::layer::UpdateLayersPreClientSet;
@@ -55,6 +56,7 @@ impl Plugin for PlayerListPlugin {
                     update_header_footer,
                     add_new_clients_to_player_list,
                     apply_deferred, // So new clients get the packets for their own entry.
+                    update_groups,
                     update_entries,
                     init_player_list_for_clients,
                     remove_despawned_entries,
@@ -69,6 +71,8 @@ impl Plugin for PlayerListPlugin {
 #[derive(Resource)]
 pub struct PlayerList {
     cached_update_packets: Vec<u8>,
+    /// Update packets for the entries in each [`PlayerListGroup`].
+    group_update_packets: FxHashMap<Entity, Vec<u8>>,
     header: Text,
     footer: Text,
     changed_header_or_footer: bool,
@@ -81,6 +85,7 @@ impl PlayerList {
     fn new() -> Self {
         Self {
             cached_update_packets: vec![],
+            group_update_packets: FxHashMap::default(),
             header: Text::default(),
             footer: Text::default(),
             changed_header_or_footer: false,
@@ -115,6 +120,14 @@ impl PlayerList {
 
         self.footer = txt;
     }
+
+    /// Returns the buffer for update packets of entries in `group`.
+    fn update_packets(&mut self, group: Option<&PlayerListGroup>) -> &mut Vec<u8> {
+        match group {
+            Some(group) => self.group_update_packets.entry(group.0).or_default(),
+            None => &mut self.cached_update_packets,
+        }
+    }
 }
 
 /// Bundle for spawning new player list entries. All components are required
@@ -154,6 +167,64 @@ impl Default for Listed {
     }
 }
 
+/// Splits the player list into separate lists, such as one for every world.
+///
+/// A player list entry with this component is only shown to clients in the
+/// same group, and a client with this component only sees the entries in its
+/// group. Entries without a group are shown to every client. The group is
+/// identified by an entity, which is usually the layer of the world the
+/// client is in, but can be any entity.
+///
+/// Changing the group of a client removes the entries of the old group from
+/// its player list and adds the entries of the new group. The client's own
+/// entry moves to the new group along with it.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct PlayerListGroup(pub Entity);
+
+/// Returns if an entry in `entry_group` is shown to a client in
+/// `client_group`.
+fn is_visible(
+    client_group: Option<&PlayerListGroup>,
+    entry_group: Option<&PlayerListGroup>,
+) -> bool {
+    entry_group.is_none() || entry_group == client_group
+}
+
+type EntryItem<'a> = (
+    &'a UniqueId,
+    &'a Username,
+    &'a Properties,
+    &'a GameMode,
+    &'a Ping,
+    &'a DisplayName,
+    &'a Listed,
+);
+
+/// The actions to add an entry with all of its data.
+fn add_entry_actions() -> packet::PlayerListActions {
+    packet::PlayerListActions::new()
+        .with_add_player(true)
+        .with_update_game_mode(true)
+        .with_update_listed(true)
+        .with_update_latency(true)
+        .with_update_display_name(true)
+}
+
+fn packet_entry(
+    (uuid, username, props, game_mode, ping, display_name, listed): EntryItem,
+) -> packet::PlayerListEntry {
+    packet::PlayerListEntry {
+        player_uuid: uuid.0,
+        username: &username.0,
+        properties: Cow::Borrowed(&props.0),
+        chat_data: None,
+        listed: listed.0,
+        ping: ping.0,
+        game_mode: *game_mode,
+        display_name: display_name.0.as_ref().map(Cow::Borrowed),
+    }
+}
+
 fn update_header_footer(player_list: ResMut<PlayerList>, server: Res<Server>) {
     if player_list.changed_header_or_footer {
         let player_list = player_list.into_inner();
@@ -189,51 +260,24 @@ fn add_new_clients_to_player_list(
 }
 
 fn init_player_list_for_clients(
-    mut clients: Query<&mut Client, (Added<Client>, Without<Despawned>)>,
-    player_list: Res<PlayerList>,
-    entries: Query<
-        (
-            &UniqueId,
-            &Username,
-            &Properties,
-            &GameMode,
-            &Ping,
-            &DisplayName,
-            &Listed,
-        ),
-        With<PlayerListEntry>,
+    mut clients: Query<
+        (&mut Client, Option<&PlayerListGroup>),
+        (Added<Client>, Without<Despawned>),
     >,
+    player_list: Res<PlayerList>,
+    entries: Query<(EntryItem, Option<&PlayerListGroup>), With<PlayerListEntry>>,
 ) {
     if player_list.manage_clients {
-        for mut client in &mut clients {
-            let actions = packet::PlayerListActions::new()
-                .with_add_player(true)
-                .with_update_game_mode(true)
-                .with_update_listed(true)
-                .with_update_latency(true)
-                .with_update_display_name(true);
-
+        for (mut client, client_group) in &mut clients {
             let entries: Vec<_> = entries
                 .iter()
-                .map(
-                    |(uuid, username, props, game_mode, ping, display_name, listed)| {
-                        packet::PlayerListEntry {
-                            player_uuid: uuid.0,
-                            username: &username.0,
-                            properties: Cow::Borrowed(&props.0),
-                            chat_data: None,
-                            listed: listed.0,
-                            ping: ping.0,
-                            game_mode: *game_mode,
-                            display_name: display_name.0.as_ref().map(Cow::Borrowed),
-                        }
-                    },
-                )
+                .filter(|(_, group)| is_visible(client_group, *group))
+                .map(|(item, _)| packet_entry(item))
                 .collect();
 
             if !entries.is_empty() {
                 client.write_packet(&PlayerListS2c {
-                    actions,
+                    actions: add_entry_actions(),
                     entries: Cow::Owned(entries),
                 });
             }
@@ -248,30 +292,146 @@ fn init_player_list_for_clients(
     }
 }
 
+/// Updates the player lists of clients when entries or clients change their
+/// [`PlayerListGroup`].
+fn update_groups(
+    changed_groups: Query<Entity, Changed<PlayerListGroup>>,
+    mut removed_groups: RemovedComponents<PlayerListGroup>,
+    mut clients: Query<(Entity, &mut Client, Option<&PlayerListGroup>), Without<Despawned>>,
+    entries: Query<
+        (
+            Entity,
+            Ref<PlayerListEntry>,
+            EntryItem,
+            Option<&PlayerListGroup>,
+        ),
+        Without<Despawned>,
+    >,
+    groups: Query<&PlayerListGroup>,
+    // The groups as of the last time this system ran.
+    mut old_groups: Local<FxHashMap<Entity, PlayerListGroup>>,
+) {
+    let mut moved = vec![];
+
+    for entity in removed_groups.iter() {
+        // The group might have been removed and inserted again.
+        if !groups.contains(entity) {
+            if let Some(old_group) = old_groups.remove(&entity) {
+                moved.push((entity, Some(old_group), None));
+            }
+        }
+    }
+
+    for entity in &changed_groups {
+        let group = *groups.get(entity).unwrap();
+        let old_group = old_groups.insert(entity, group);
+
+        if old_group != Some(group) {
+            moved.push((entity, old_group, Some(group)));
+        }
+    }
+
+    for (moved_entity, old_group, new_group) in moved {
+        let (old_group, new_group) = (old_group.as_ref(), new_group.as_ref());
+
+        // Show or hide the moved entry for the other clients.
+        if let Ok((_, entry, item, _)) = entries.get(moved_entity) {
+            // New entries are sent along with the other updates.
+            if !entry.is_added() {
+                for (client_entity, mut client, client_group) in &mut clients {
+                    if client_entity == moved_entity || client.is_added() {
+                        continue;
+                    }
+
+                    let was_visible = is_visible(client_group, old_group);
+                    let is_visible = is_visible(client_group, new_group);
+
+                    if was_visible && !is_visible {
+                        client.write_packet(&PlayerRemoveS2c {
+                            uuids: Cow::Borrowed(&[item.0 .0]),
+                        });
+                    } else if !was_visible && is_visible {
+                        client.write_packet(&PlayerListS2c {
+                            actions: add_entry_actions(),
+                            entries: Cow::Borrowed(&[packet_entry(item)]),
+                        });
+                    }
+                }
+            }
+        }
+
+        // Swap the entries of the old group for those of the new group in the
+        // moved client's player list.
+        if let Ok((_, mut client, _)) = clients.get_mut(moved_entity) {
+            if client.is_added() {
+                continue;
+            }
+
+            let mut added = vec![];
+            let mut removed = vec![];
+
+            for (entry_entity, entry, item, entry_group) in &entries {
+                if entry_entity == moved_entity || entry.is_added() {
+                    continue;
+                }
+
+                let was_visible = is_visible(old_group, entry_group);
+                let is_visible = is_visible(new_group, entry_group);
+
+                if was_visible && !is_visible {
+                    removed.push(item.0 .0);
+                } else if !was_visible && is_visible {
+                    added.push(packet_entry(item));
+                }
+            }
+
+            if !removed.is_empty() {
+                client.write_packet(&PlayerRemoveS2c {
+                    uuids: Cow::Owned(removed),
+                });
+            }
+
+            if !added.is_empty() {
+                client.write_packet(&PlayerListS2c {
+                    actions: add_entry_actions(),
+                    entries: Cow::Owned(added),
+                });
+            }
+        }
+    }
+}
+
 fn remove_despawned_entries(
-    entries: Query<&UniqueId, (Added<Despawned>, With<PlayerListEntry>)>,
+    entries: Query<
+        (&UniqueId, Option<&PlayerListGroup>),
+        (Added<Despawned>, With<PlayerListEntry>),
+    >,
     player_list: ResMut<PlayerList>,
     server: Res<Server>,
-    mut removed: Local<Vec<Uuid>>,
+    mut removed: Local<FxHashMap<Option<PlayerListGroup>, Vec<Uuid>>>,
 ) {
     if player_list.manage_clients {
-        debug_assert!(removed.is_empty());
+        for (uuid, group) in &entries {
+            removed.entry(group.copied()).or_default().push(uuid.0);
+        }
 
-        removed.extend(entries.iter().map(|uuid| uuid.0));
+        let player_list = player_list.into_inner();
 
-        if !removed.is_empty() {
-            let player_list = player_list.into_inner();
+        for (group, uuids) in removed.iter_mut() {
+            if uuids.is_empty() {
+                continue;
+            }
 
             let mut w = PacketWriter::new(
-                &mut player_list.cached_update_packets,
+                player_list.update_packets(group.as_ref()),
                 server.compression_threshold(),
             );
 
             w.write_packet(&PlayerRemoveS2c {
-                uuids: Cow::Borrowed(&removed),
+                uuids: Cow::Borrowed(uuids),
             });
 
-            removed.clear();
+            uuids.clear();
         }
     }
 }
@@ -286,6 +446,7 @@ fn update_entries(
             Ref<Ping>,
             Ref<DisplayName>,
             Ref<Listed>,
+            Option<&PlayerListGroup>,
         ),
         (
             With<PlayerListEntry>,
@@ -305,12 +466,7 @@ fn update_entries(
 ) {
     let player_list = player_list.into_inner();
 
-    let mut writer = PacketWriter::new(
-        &mut player_list.cached_update_packets,
-        server.compression_threshold(),
-    );
-
-    for (uuid, username, props, game_mode, ping, display_name, listed) in &entries {
+    for (uuid, username, props, game_mode, ping, display_name, listed, group) in &entries {
         let mut actions = packet::PlayerListActions::new();
 
         // Did a change occur that would force us to overwrite the entry? This also adds
@@ -364,6 +520,11 @@ fn update_entries(
             display_name: display_name.0.as_ref().map(|x| x.into()),
         };
 
+        let mut writer = PacketWriter::new(
+            player_list.update_packets(group),
+            server.compression_threshold(),
+        );
+
         writer.write_packet(&PlayerListS2c {
             actions,
             entries: Cow::Borrowed(&[entry]),
@@ -373,15 +534,32 @@ fn update_entries(
 
 fn write_player_list_changes(
     mut player_list: ResMut<PlayerList>,
-    mut clients: Query<&mut Client, Without<Despawned>>,
+    mut clients: Query<(&mut Client, Option<&PlayerListGroup>), Without<Despawned>>,
 ) {
-    if !player_list.cached_update_packets.is_empty() {
-        for mut client in &mut clients {
+    let has_group_updates = player_list
+        .group_update_packets
+        .values()
+        .any(|bytes| !bytes.is_empty());
+
+    if !player_list.cached_update_packets.is_empty() || has_group_updates {
+        for (mut client, group) in &mut clients {
             if !client.is_added() {
                 client.write_packet_bytes(&player_list.cached_update_packets);
+
+                if let Some(bytes) = group.and_then(|g| player_list.group_update_packets.get(&g.0))
+                {
+                    client.write_packet_bytes(bytes);
+                }
             }
         }
 
         player_list.cached_update_packets.clear();
+
+        // Drop the buffers of groups that went quiet.
+        player_list.group_update_packets.retain(|_, bytes| {
+            let keep = !bytes.is_empty();
+            bytes.clear();
+            keep
+        });
     }
 }
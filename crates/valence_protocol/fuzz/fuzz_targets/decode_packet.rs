@@ -0,0 +1,27 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use valence_protocol::packets::decode_packet;
+use valence_protocol::{PacketSide, PacketState};
+
+// The first byte selects the state and side, the rest is the packet.
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, bytes)) = data.split_first() else {
+        return;
+    };
+
+    let state = match selector & 0b11 {
+        0 => PacketState::Handshaking,
+        1 => PacketState::Status,
+        2 => PacketState::Login,
+        _ => PacketState::Play,
+    };
+
+    let side = if selector & 0b100 == 0 {
+        PacketSide::Clientbound
+    } else {
+        PacketSide::Serverbound
+    };
+
+    let _ = decode_packet(state, side, bytes);
+});
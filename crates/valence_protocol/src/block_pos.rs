@@ -3,13 +3,20 @@ use std::io::Write;
 use std::ops::{Add, Sub};
 
 use anyhow::bail;
-use valence_math::DVec3;
+use valence_math::{DVec3, IVec3};
 
 use crate::chunk_pos::ChunkPos;
 use crate::direction::Direction;
 use crate::{Decode, Encode};
 
 /// Represents an absolute block position in world space.
+///
+/// Valence uses the same coordinate system as the game: `y` points up, and
+/// north is `-z`. Block positions are integers naming the block whose minimum
+/// corner is at that point, while entity positions are [`DVec3`]s in the same
+/// space. Converting a [`DVec3`] to a `BlockPos` floors each component, so
+/// `(-0.5, 64.0, 0.5)` is in block `(-1, 64, 0)`.
+/// Converting a `BlockPos` to a [`DVec3`] gives the block's minimum corner.
 #[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct BlockPos {
     pub x: i32,
@@ -32,10 +39,56 @@ impl BlockPos {
         }
     }
 
+    /// Returns the position of the chunk containing this block. Negative
+    /// coordinates are floored, so `(-1, 0, -1)` is in chunk `(-1, -1)`.
     pub const fn to_chunk_pos(self) -> ChunkPos {
         ChunkPos::from_block_pos(self)
     }
 
+    /// Returns the index of the chunk section containing this block, counting
+    /// up from the section at `min_y`. Returns `None` if the block is below
+    /// `min_y`.
+    ///
+    /// ```
+    /// use valence_protocol::BlockPos;
+    ///
+    /// assert_eq!(BlockPos::new(0, -64, 0).section_index(-64), Some(0));
+    /// assert_eq!(BlockPos::new(0, 0, 0).section_index(-64), Some(4));
+    /// assert_eq!(BlockPos::new(0, -65, 0).section_index(-64), None);
+    /// ```
+    pub const fn section_index(self, min_y: i32) -> Option<usize> {
+        let y = self.y as i64 - min_y as i64;
+
+        if y < 0 {
+            None
+        } else {
+            Some((y / 16) as usize)
+        }
+    }
+
+    /// Returns the coordinates of this block relative to the corner of its
+    /// chunk section, each in `0..16`.
+    pub const fn section_offset(self) -> [u32; 3] {
+        [
+            self.x.rem_euclid(16) as u32,
+            self.y.rem_euclid(16) as u32,
+            self.z.rem_euclid(16) as u32,
+        ]
+    }
+
+    /// Returns the six blocks sharing a face with this one, in the order of
+    /// [`Direction`]'s variants.
+    pub const fn neighbors(self) -> [BlockPos; 6] {
+        [
+            self.get_in_direction(Direction::Down),
+            self.get_in_direction(Direction::Up),
+            self.get_in_direction(Direction::North),
+            self.get_in_direction(Direction::South),
+            self.get_in_direction(Direction::West),
+            self.get_in_direction(Direction::East),
+        ]
+    }
+
     /// Get a new [`BlockPos`] that is adjacent to this position in `dir`
     /// direction.
     ///
@@ -109,6 +162,36 @@ impl From<BlockPos> for [i32; 3] {
     }
 }
 
+impl From<DVec3> for BlockPos {
+    fn from(pos: DVec3) -> Self {
+        Self::from_pos(pos)
+    }
+}
+
+impl From<[f64; 3]> for BlockPos {
+    fn from(pos: [f64; 3]) -> Self {
+        Self::from_pos(pos.into())
+    }
+}
+
+impl From<BlockPos> for DVec3 {
+    fn from(pos: BlockPos) -> Self {
+        DVec3::new(pos.x as f64, pos.y as f64, pos.z as f64)
+    }
+}
+
+impl From<IVec3> for BlockPos {
+    fn from(pos: IVec3) -> Self {
+        BlockPos::new(pos.x, pos.y, pos.z)
+    }
+}
+
+impl From<BlockPos> for IVec3 {
+    fn from(pos: BlockPos) -> Self {
+        IVec3::new(pos.x, pos.y, pos.z)
+    }
+}
+
 impl fmt::Debug for BlockPos {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt::Display::fmt(self, f)
@@ -149,6 +232,71 @@ impl Sub for BlockPos {
 mod tests {
     use super::*;
 
+    #[test]
+    fn negative_coords_floor_to_chunk() {
+        assert_eq!(
+            BlockPos::new(-1, 0, -1).to_chunk_pos(),
+            ChunkPos::new(-1, -1)
+        );
+        assert_eq!(
+            BlockPos::new(-16, 0, 15).to_chunk_pos(),
+            ChunkPos::new(-1, 0)
+        );
+        assert_eq!(
+            BlockPos::new(-17, 0, 16).to_chunk_pos(),
+            ChunkPos::new(-2, 1)
+        );
+
+        assert_eq!(BlockPos::new(-1, -1, -1).section_offset(), [15, 15, 15]);
+        assert_eq!(BlockPos::new(16, 17, -16).section_offset(), [0, 1, 0]);
+
+        assert_eq!(BlockPos::new(0, -1, 0).section_index(0), None);
+        assert_eq!(
+            BlockPos::new(0, i32::MIN, 0).section_index(i32::MIN),
+            Some(0)
+        );
+        assert_eq!(
+            BlockPos::new(0, i32::MAX, 0).section_index(i32::MIN),
+            Some(268435455)
+        );
+    }
+
+    #[test]
+    fn conversions_floor_negative_coords() {
+        assert_eq!(
+            BlockPos::from(DVec3::new(-0.5, 64.0, 0.5)),
+            BlockPos::new(-1, 64, 0)
+        );
+        assert_eq!(
+            BlockPos::from([-16.1, -0.0, -15.9]),
+            BlockPos::new(-17, 0, -16)
+        );
+        assert_eq!(
+            ChunkPos::from(DVec3::new(-0.5, 64.0, -16.5)),
+            ChunkPos::new(-1, -2)
+        );
+
+        let pos = BlockPos::new(-3, -64, 7);
+        assert_eq!(DVec3::from(pos), DVec3::new(-3.0, -64.0, 7.0));
+        assert_eq!(BlockPos::from(DVec3::from(pos)), pos);
+        assert_eq!(BlockPos::from(IVec3::from(pos)), pos);
+        assert_eq!(ChunkPos::from(pos), pos.to_chunk_pos());
+    }
+
+    #[test]
+    fn neighbors() {
+        let pos = BlockPos::new(-1, 5, 3);
+        let neighbors = pos.neighbors();
+
+        assert_eq!(neighbors[Direction::Down as usize], BlockPos::new(-1, 4, 3));
+        assert_eq!(neighbors[Direction::East as usize], BlockPos::new(0, 5, 3));
+
+        for neighbor in neighbors {
+            let diff = neighbor - pos;
+            assert_eq!(diff.x.abs() + diff.y.abs() + diff.z.abs(), 1);
+        }
+    }
+
     #[test]
     fn position() {
         let xzs = [
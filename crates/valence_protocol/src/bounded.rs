@@ -9,7 +9,8 @@ use derive_more::{AsRef, Deref, DerefMut, From};
 /// What exactly `MAX` represents depends on the type `T`. Here are some
 /// instances:
 /// - **arrays/slices**: The maximum number of elements.
-/// - **strings**: The maximum number of utf16 code units.
+/// - **strings**: The maximum number of utf16 code units. The utf8 encoding is
+///   additionally limited to `MAX * 3` bytes.
 /// - **[`RawBytes`]**: The maximum number of bytes.
 ///
 /// [`RawBytes`]: crate::RawBytes
@@ -3,7 +3,9 @@ use valence_math::DVec3;
 use crate::block_pos::BlockPos;
 use crate::{Decode, Encode};
 
-/// The X and Z position of a chunk.
+/// The X and Z position of a chunk. A chunk spans 16×16 blocks horizontally,
+/// so chunk `(x, z)` contains blocks from `(16x, 16z)` to `(16x + 15, 16z +
+/// 15)`.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Hash, Debug, Encode, Decode)]
 pub struct ChunkPos {
     /// The X position of the chunk.
@@ -42,6 +44,18 @@ impl From<(i32, i32)> for ChunkPos {
     }
 }
 
+impl From<BlockPos> for ChunkPos {
+    fn from(pos: BlockPos) -> Self {
+        Self::from_block_pos(pos)
+    }
+}
+
+impl From<DVec3> for ChunkPos {
+    fn from(pos: DVec3) -> Self {
+        Self::from_pos(pos)
+    }
+}
+
 impl From<ChunkPos> for (i32, i32) {
     fn from(pos: ChunkPos) -> Self {
         (pos.x, pos.z)
@@ -4,7 +4,7 @@ use anyhow::{bail, ensure, Context};
 use bytes::{Buf, BytesMut};
 
 use crate::var_int::{VarInt, VarIntDecodeError};
-#[cfg(feature = "encryption")]
+#[cfg(feature = "compression")]
 use crate::CompressionThreshold;
 use crate::{Decode, Packet, MAX_PACKET_SIZE};
 
@@ -70,10 +70,10 @@ impl PacketDecoder {
 
             // Is this packet compressed?
             if data_len > 0 {
+                // The vanilla client compresses packets with a length equal to the threshold.
                 ensure!(
-                    data_len > self.threshold.0,
-                    "decompressed packet length of {data_len} is <= the compression threshold of \
-                     {}",
+                    data_len >= self.threshold.0,
+                    "decompressed packet length of {data_len} is < the compression threshold of {}",
                     self.threshold.0
                 );
 
@@ -8,6 +8,7 @@ use anyhow::ensure;
 use bytes::{BufMut, BytesMut};
 use tracing::warn;
 
+use crate::packets::play::BundleSplitterS2c;
 use crate::var_int::VarInt;
 use crate::{CompressionThreshold, Encode, Packet, MAX_PACKET_SIZE};
 
@@ -204,6 +205,22 @@ pub trait WritePacket {
     /// Copies raw packet data directly into this object. Don't use this unless
     /// you know what you're doing.
     fn write_packet_bytes(&mut self, bytes: &[u8]);
+
+    /// Wraps the packets written by `f` in a bundle, so that the client
+    /// applies all of them in the same frame. This is useful for groups of
+    /// packets that would otherwise cause flickering when applied one at a
+    /// time.
+    ///
+    /// Bundles cannot be nested. `f` must not write
+    /// [`BundleSplitterS2c`] or call this method again.
+    fn write_bundle(&mut self, f: impl FnOnce(&mut Self))
+    where
+        Self: Sized,
+    {
+        self.write_packet(&BundleSplitterS2c);
+        f(self);
+        self.write_packet(&BundleSplitterS2c);
+    }
 }
 
 impl<W: WritePacket> WritePacket for &mut W {
@@ -1,6 +1,6 @@
 use crate::{Decode, Encode};
 
-#[derive(Copy, Clone, PartialEq, Eq, Default, Debug, Encode, Decode)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default, Debug, Encode, Decode)]
 pub enum Hand {
     #[default]
     Main,
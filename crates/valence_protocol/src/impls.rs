@@ -12,6 +12,8 @@ mod tuple;
 
 use std::mem;
 
+use crate::MAX_PACKET_SIZE;
+
 /// Prevents preallocating too much memory in case we get a malicious or invalid
 /// sequence length.
 fn cautious_capacity<Element>(size_hint: usize) -> usize {
@@ -23,3 +25,23 @@ fn cautious_capacity<Element>(size_hint: usize) -> usize {
         size_hint.min(MAX_PREALLOC_BYTES / mem::size_of::<Element>())
     }
 }
+
+/// Checks that a sequence of `len` elements could fit in the `remaining` bytes
+/// of input before any of them are decoded. Every element that isn't zero-sized
+/// takes at least one byte to encode. Zero-sized elements take no input at all,
+/// so their count is bounded by [`MAX_PACKET_SIZE`] instead.
+fn ensure_sequence_fits<Element>(len: usize, remaining: usize) -> anyhow::Result<()> {
+    let max_len = if mem::size_of::<Element>() == 0 {
+        MAX_PACKET_SIZE as usize
+    } else {
+        remaining
+    };
+
+    anyhow::ensure!(
+        len <= max_len,
+        "sequence length of {len} exceeds the maximum of {max_len} (remaining input is \
+         {remaining} bytes)"
+    );
+
+    Ok(())
+}
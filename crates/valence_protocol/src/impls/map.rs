@@ -4,7 +4,7 @@ use std::io::Write;
 
 use anyhow::ensure;
 
-use crate::impls::cautious_capacity;
+use crate::impls::{cautious_capacity, ensure_sequence_fits};
 use crate::{Decode, Encode, VarInt};
 
 impl<T> Encode for BTreeSet<T>
@@ -40,6 +40,7 @@ where
             "attempt to decode B-tree set with negative length"
         );
         let len = len as usize;
+        ensure_sequence_fits::<T>(len, r.len())?;
 
         let mut set = BTreeSet::new();
 
@@ -85,6 +86,7 @@ where
         let len = VarInt::decode(r)?.0;
         ensure!(len >= 0, "attempt to decode hash set with negative length");
         let len = len as usize;
+        ensure_sequence_fits::<T>(len, r.len())?;
 
         let mut set = HashSet::with_capacity_and_hasher(cautious_capacity::<T>(len), S::default());
 
@@ -134,6 +136,7 @@ where
             "attempt to decode B-tree map with negative length"
         );
         let len = len as usize;
+        ensure_sequence_fits::<(K, V)>(len, r.len())?;
 
         let mut map = BTreeMap::new();
 
@@ -181,6 +184,7 @@ where
         let len = VarInt::decode(r)?.0;
         ensure!(len >= 0, "attempt to decode hash map with negative length");
         let len = len as usize;
+        ensure_sequence_fits::<(K, V)>(len, r.len())?;
 
         let mut map =
             HashMap::with_capacity_and_hasher(cautious_capacity::<(K, V)>(len), S::default());
@@ -4,7 +4,7 @@ use std::slice;
 
 use anyhow::ensure;
 
-use crate::impls::cautious_capacity;
+use crate::impls::{cautious_capacity, ensure_sequence_fits};
 use crate::{Bounded, Decode, Encode, VarInt};
 
 /// Like tuples, fixed-length arrays are encoded and decoded without a VarInt
@@ -140,6 +140,7 @@ impl<'a, T: Decode<'a>> Decode<'a> for Vec<T> {
         let len = VarInt::decode(r)?.0;
         ensure!(len >= 0, "attempt to decode Vec with negative length");
         let len = len as usize;
+        ensure_sequence_fits::<T>(len, r.len())?;
 
         let mut vec = Vec::with_capacity(cautious_capacity::<T>(len));
 
@@ -161,8 +162,9 @@ impl<'a, T: Decode<'a>, const MAX_LEN: usize> Decode<'a> for Bounded<Vec<T>, MAX
             len <= MAX_LEN,
             "length of Vec exceeds max of {MAX_LEN} (got {len})"
         );
+        ensure_sequence_fits::<T>(len, r.len())?;
 
-        let mut vec = Vec::with_capacity(len);
+        let mut vec = Vec::with_capacity(cautious_capacity::<T>(len));
 
         for _ in 0..len {
             vec.push(T::decode(r)?);
@@ -183,3 +185,34 @@ impl<'a, T: Decode<'a>, const MAX_LEN: usize> Decode<'a> for Bounded<Box<[T]>, M
         Ok(Bounded::<Vec<_>, MAX_LEN>::decode(r)?.map_into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn huge_sequence_length_is_rejected() {
+        let mut buf = vec![];
+        VarInt(1_000_000_000).encode(&mut buf).unwrap();
+        buf.extend_from_slice(&[1, 2, 3, 4]);
+
+        let mut r = buf.as_slice();
+        assert!(Vec::<u64>::decode(&mut r).is_err());
+
+        let mut r = buf.as_slice();
+        assert!(Bounded::<Vec<VarInt>, { i32::MAX as usize }>::decode(&mut r).is_err());
+
+        let mut r = buf.as_slice();
+        assert!(Box::<[u8]>::decode(&mut r).is_err());
+
+        // Zero-sized elements don't consume any input.
+        let mut r = buf.as_slice();
+        assert!(Vec::<()>::decode(&mut r).is_err());
+
+        let mut buf = vec![];
+        VarInt(10).encode(&mut buf).unwrap();
+
+        let mut r = buf.as_slice();
+        assert_eq!(Vec::<()>::decode(&mut r).unwrap().len(), 10);
+    }
+}
@@ -9,6 +9,12 @@ use crate::{Bounded, Decode, Encode, VarInt};
 const DEFAULT_MAX_STRING_CHARS: usize = 32767;
 const MAX_TEXT_CHARS: usize = 262144;
 
+/// The maximum length in bytes of the UTF-8 encoding of a string with at most
+/// `max_chars` UTF-16 code units, as enforced by the vanilla game.
+const fn max_string_bytes(max_chars: usize) -> usize {
+    max_chars.saturating_mul(3)
+}
+
 impl Encode for str {
     fn encode(&self, w: impl Write) -> anyhow::Result<()> {
         Bounded::<_, DEFAULT_MAX_STRING_CHARS>(self).encode(w)
@@ -24,6 +30,14 @@ impl<const MAX_CHARS: usize> Encode for Bounded<&'_ str, MAX_CHARS> {
             "char count of string exceeds maximum (expected <= {MAX_CHARS}, got {char_count})"
         );
 
+        let max_bytes = max_string_bytes(MAX_CHARS);
+
+        ensure!(
+            self.len() <= max_bytes,
+            "byte length of string exceeds maximum (expected <= {max_bytes}, got {})",
+            self.len()
+        );
+
         VarInt(self.len() as i32).encode(&mut w)?;
         Ok(w.write_all(self.as_bytes())?)
     }
@@ -40,6 +54,14 @@ impl<'a, const MAX_CHARS: usize> Decode<'a> for Bounded<&'a str, MAX_CHARS> {
         let len = VarInt::decode(r)?.0;
         ensure!(len >= 0, "attempt to decode string with negative length");
         let len = len as usize;
+
+        // Reject oversized strings before looking at their contents.
+        let max_bytes = max_string_bytes(MAX_CHARS);
+        ensure!(
+            len <= max_bytes,
+            "byte length of string exceeds maximum (expected <= {max_bytes}, got {len})"
+        );
+
         ensure!(
             len <= r.len(),
             "not enough data remaining ({} bytes) to decode string of {len} bytes",
@@ -112,3 +134,39 @@ impl Decode<'_> for Text {
         Self::from_str(str).context("deserializing text JSON")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<const MAX_CHARS: usize>(s: &str) -> anyhow::Result<String> {
+        let mut buf = vec![];
+        Bounded::<_, MAX_CHARS>(s).encode(&mut buf)?;
+
+        let mut r = buf.as_slice();
+        Ok(Bounded::<&str, MAX_CHARS>::decode(&mut r)?.0.into())
+    }
+
+    #[test]
+    fn bounded_string_counts_utf16_units() {
+        // Four UTF-16 code units encoded in twelve bytes.
+        assert_eq!(round_trip::<4>("€€€€").unwrap(), "€€€€");
+        // Each emoji is a surrogate pair, so this is exactly four units.
+        assert_eq!(round_trip::<4>("😀😀").unwrap(), "😀😀");
+
+        assert!(round_trip::<4>("😀😀a").is_err());
+        assert!(round_trip::<4>("€€€€€").is_err());
+    }
+
+    #[test]
+    fn bounded_string_byte_limit() {
+        // A length prefix larger than three bytes per unit is rejected on its own.
+        let mut buf = vec![];
+        VarInt(13).encode(&mut buf).unwrap();
+        buf.extend_from_slice(&[b'a'; 13]);
+
+        let mut r = buf.as_slice();
+        let err = Bounded::<&str, 4>::decode(&mut r).unwrap_err();
+        assert!(err.to_string().contains("byte length"), "{err}");
+    }
+}
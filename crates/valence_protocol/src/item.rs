@@ -125,4 +125,21 @@ mod tests {
         stack.set_count(201);
         assert_eq!(stack.count, ItemStack::STACK_MAX);
     }
+
+    #[test]
+    fn item_stack_slot_round_trip() {
+        let stack = Some(ItemStack::new(ItemKind::Cobblestone, 64, None));
+
+        let mut buf = vec![];
+        stack.encode(&mut buf).unwrap();
+        None::<ItemStack>.encode(&mut buf).unwrap();
+
+        // The empty slot is a single `false`.
+        assert_eq!(buf.last(), Some(&0));
+
+        let mut r = buf.as_slice();
+        assert_eq!(Option::<ItemStack>::decode(&mut r).unwrap(), stack);
+        assert_eq!(Option::<ItemStack>::decode(&mut r).unwrap(), None);
+        assert!(r.is_empty());
+    }
 }
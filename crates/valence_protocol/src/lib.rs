@@ -75,8 +75,9 @@ pub use item::{ItemKind, ItemStack};
 pub use packets::play::particle_s2c::Particle;
 pub use raw::RawBytes;
 pub use sound::Sound;
+pub use status_effect::StatusEffect;
 pub use text::Text;
-pub use valence_generated::{block, packet_id};
+pub use valence_generated::{block, packet_id, status_effect};
 pub use valence_ident::Ident;
 pub use valence_protocol_macros::{Decode, Encode, Packet};
 pub use var_int::VarInt;
@@ -290,7 +291,7 @@ pub trait Packet: std::fmt::Debug {
 }
 
 /// The side a packet is intended for.
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum PacketSide {
     /// Server -> Client
     Clientbound,
@@ -299,7 +300,7 @@ pub enum PacketSide {
 }
 
 /// The statein  which a packet is used.
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum PacketState {
     Handshaking,
     Status,
@@ -369,6 +370,27 @@ mod tests {
         assert_has_impls::<TupleStructWithGenerics>();
     }
 
+    #[test]
+    fn write_bundle_wraps_packets() {
+        let mut enc = PacketEncoder::new();
+
+        enc.write_bundle(|enc| {
+            enc.write_packet(&UnitStruct);
+            enc.write_packet(&EmptyStruct {});
+        });
+
+        let mut dec = PacketDecoder::new();
+        dec.queue_bytes(enc.take());
+
+        let mut ids = vec![];
+        while let Some(frame) = dec.try_next_packet().unwrap() {
+            ids.push(frame.id);
+        }
+
+        let splitter = packets::play::BundleSplitterS2c::ID;
+        assert_eq!(ids, [splitter, UnitStruct::ID, EmptyStruct::ID, splitter]);
+    }
+
     #[test]
     fn packet_name() {
         assert_eq!(RegularStruct::NAME, "RegularStruct");
@@ -464,4 +486,117 @@ mod tests {
         check_test_packet(&mut dec, "fourth");
         check_test_packet(&mut dec, "third");
     }
+
+    #[cfg(all(feature = "compression", feature = "encryption"))]
+    #[test]
+    fn compressed_encrypted_round_trip() {
+        let long_string = "abc".repeat(10_000);
+
+        let mut enc = PacketEncoder::new();
+        enc.set_compression(256.into());
+        enc.enable_encryption(&CRYPT_KEY);
+
+        // Large enough to be compressed.
+        enc.append_packet(&TestPacket::new(&long_string)).unwrap();
+        // Small enough to be sent uncompressed with a data length of zero.
+        enc.append_packet(&TestPacket::new("small")).unwrap();
+        enc.append_packet(&TestPacket::new(&long_string)).unwrap();
+
+        let buf = enc.take();
+
+        let mut dec = PacketDecoder::new();
+        dec.set_compression(256.into());
+        dec.enable_encryption(&CRYPT_KEY);
+
+        // Feed the data in small pieces so that packets are split across reads.
+        for chunk in buf.chunks(777) {
+            dec.queue_slice(chunk);
+        }
+
+        check_test_packet(&mut dec, &long_string);
+        check_test_packet(&mut dec, "small");
+        check_test_packet(&mut dec, &long_string);
+        assert!(dec.try_next_packet().unwrap().is_none());
+    }
+
+    #[cfg(all(feature = "compression", feature = "encryption"))]
+    #[test]
+    fn compression_is_inside_encryption() {
+        use aes::cipher::generic_array::GenericArray;
+        use aes::cipher::{BlockDecryptMut, BlockSizeUser, KeyIvInit};
+
+        type Decryptor = cfb8::Decryptor<aes::Aes128>;
+
+        let long_string = "abc".repeat(10_000);
+
+        let mut enc = PacketEncoder::new();
+        enc.set_compression(256.into());
+        enc.enable_encryption(&CRYPT_KEY);
+        enc.append_packet(&TestPacket::new(&long_string)).unwrap();
+        enc.append_packet(&TestPacket::new("small")).unwrap();
+
+        let mut buf = enc.take();
+
+        // Decrypting the whole stream must leave plain compressed frames.
+        let mut cipher = Decryptor::new_from_slices(&CRYPT_KEY, &CRYPT_KEY).unwrap();
+
+        for chunk in buf.chunks_mut(Decryptor::block_size()) {
+            cipher.decrypt_block_mut(GenericArray::from_mut_slice(chunk));
+        }
+
+        let mut dec = PacketDecoder::new();
+        dec.set_compression(256.into());
+        dec.queue_bytes(buf);
+
+        check_test_packet(&mut dec, &long_string);
+        check_test_packet(&mut dec, "small");
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compressed_packet_at_threshold() {
+        let mut enc = PacketEncoder::new();
+        enc.append_packet(&TestPacket::new("edge")).unwrap();
+
+        let frame = enc.take();
+        // Packet length VarInt is a single byte for this packet.
+        let data_len = frame.len() as i32 - 1;
+
+        // Compress the packet the way the vanilla client does when its length is
+        // exactly the threshold.
+        enc.set_compression((data_len - 1).into());
+        enc.append_packet(&TestPacket::new("edge")).unwrap();
+
+        let mut dec = PacketDecoder::new();
+        dec.set_compression(data_len.into());
+        dec.queue_bytes(enc.take());
+
+        check_test_packet(&mut dec, "edge");
+    }
+
+    #[test]
+    fn status_effect_raw_ids() {
+        use crate::status_effect::StatusEffectCategory;
+
+        assert_eq!(StatusEffect::from_raw(0), None);
+        assert_eq!(StatusEffect::from_raw(1), Some(StatusEffect::Speed));
+        assert_eq!(StatusEffect::Darkness.to_raw(), 33);
+        assert_eq!(
+            StatusEffect::from_ident(ident!("glowing").as_str_ident()),
+            Some(StatusEffect::Glowing)
+        );
+        assert_eq!(
+            StatusEffect::Poison.translation_key(),
+            "effect.minecraft.poison"
+        );
+        assert_eq!(
+            StatusEffect::Wither.category(),
+            StatusEffectCategory::Harmful
+        );
+
+        for effect in StatusEffect::ALL {
+            assert_eq!(StatusEffect::from_raw(effect.to_raw()), Some(effect));
+            assert_eq!(StatusEffect::from_ident(effect.to_ident()), Some(effect));
+        }
+    }
 }
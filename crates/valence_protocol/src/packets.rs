@@ -3,6 +3,12 @@
 //! Packets are grouped in submodules according to the protocol stage they're
 //! used in. Names are derived from the FabricMC Yarn mappings for consistency.
 
+use std::fmt;
+
+use anyhow::{bail, ensure, Context};
+
+use crate::{Decode, Packet, PacketSide, PacketState, VarInt};
+
 pub mod handshaking {
     pub mod handshake_c2s;
     pub use handshake_c2s::HandshakeC2s;
@@ -364,3 +370,188 @@ pub mod status {
     pub mod query_response_s2c;
     pub use query_response_s2c::QueryResponseS2c;
 }
+
+/// A packet decoded by [`decode_packet`].
+#[derive(Debug)]
+pub struct DecodedPacket<'a> {
+    /// The ID of the decoded packet.
+    pub id: i32,
+    /// The name of the decoded packet, as given by [`Packet::NAME`].
+    pub name: &'static str,
+    /// The decoded packet itself.
+    pub packet: Box<dyn fmt::Debug + 'a>,
+}
+
+/// Decodes a single packet from `bytes` without knowing its type in advance.
+///
+/// `bytes` is the uncompressed packet data: the packet ID as a [`VarInt`]
+/// followed by the packet body. The packet type is chosen from the ID and the
+/// given protocol `state` and `side`.
+///
+/// An error is returned if the ID does not name a packet in that state and
+/// side, if the body is malformed, or if any bytes are left over after
+/// decoding. This function never panics given arbitrary input, which makes it
+/// suitable as a fuzzing entry point.
+pub fn decode_packet(
+    state: PacketState,
+    side: PacketSide,
+    bytes: &[u8],
+) -> anyhow::Result<DecodedPacket<'_>> {
+    fn decode_as<'a, P>(mut r: &'a [u8]) -> anyhow::Result<DecodedPacket<'a>>
+    where
+        P: Packet + Decode<'a> + fmt::Debug + 'a,
+    {
+        let pkt = P::decode(&mut r).with_context(|| format!("failed to decode '{}'", P::NAME))?;
+
+        ensure!(
+            r.is_empty(),
+            "missed {} bytes while decoding '{}'",
+            r.len(),
+            P::NAME
+        );
+
+        Ok(DecodedPacket {
+            id: P::ID,
+            name: P::NAME,
+            packet: Box::new(pkt),
+        })
+    }
+
+    macro_rules! dispatch {
+        ($($ty:ty),* $(,)?) => {{
+            let mut r = bytes;
+            let id = VarInt::decode(&mut r).context("failed to decode packet ID")?.0;
+
+            $(
+                if <$ty as Packet>::STATE == state
+                    && <$ty as Packet>::SIDE == side
+                    && <$ty as Packet>::ID == id
+                {
+                    return decode_as::<$ty>(r);
+                }
+            )*
+
+            bail!("unknown packet ID {id:#04x} for {side:?} packets in the {state:?} state")
+        }}
+    }
+
+    valence_generated::for_each_packet!(dispatch)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::{Encode, PacketEncoder};
+
+    const STATES: [PacketState; 4] = [
+        PacketState::Handshaking,
+        PacketState::Status,
+        PacketState::Login,
+        PacketState::Play,
+    ];
+
+    const SIDES: [PacketSide; 2] = [PacketSide::Clientbound, PacketSide::Serverbound];
+
+    #[test]
+    fn decode_known_packet() {
+        let mut enc = PacketEncoder::new();
+        enc.append_packet(&play::KeepAliveC2s { id: 12345 })
+            .unwrap();
+        let mut bytes = enc.take();
+
+        // Strip the packet length prefix.
+        let len = VarInt::decode(&mut &bytes[..]).unwrap();
+        let _ = bytes.split_to(len.written_size());
+
+        let pkt = decode_packet(PacketState::Play, PacketSide::Serverbound, &bytes).unwrap();
+        assert_eq!(pkt.id, play::KeepAliveC2s::ID);
+        assert_eq!(pkt.name, play::KeepAliveC2s::NAME);
+
+        // Trailing bytes are rejected.
+        let mut trailing = bytes.to_vec();
+        trailing.push(0);
+        assert!(decode_packet(PacketState::Play, PacketSide::Serverbound, &trailing).is_err());
+    }
+
+    #[test]
+    fn decode_unknown_packet_id() {
+        let mut bytes = vec![];
+        VarInt(0x7f).encode(&mut bytes).unwrap();
+
+        for state in STATES {
+            for side in SIDES {
+                assert!(decode_packet(state, side, &bytes).is_err());
+            }
+        }
+
+        assert!(decode_packet(PacketState::Play, PacketSide::Serverbound, &[]).is_err());
+        assert!(decode_packet(PacketState::Play, PacketSide::Serverbound, &[0xff; 6]).is_err());
+
+        let mut bytes = vec![];
+        VarInt(-1).encode(&mut bytes).unwrap();
+        assert!(decode_packet(PacketState::Play, PacketSide::Clientbound, &bytes).is_err());
+    }
+
+    #[test]
+    fn decode_regression_inputs() {
+        fn packet(id: i32, body: impl Encode) -> Vec<u8> {
+            let mut bytes = vec![];
+            VarInt(id).encode(&mut bytes).unwrap();
+            body.encode(&mut bytes).unwrap();
+            bytes
+        }
+
+        let inputs = [
+            // Huge entity list length with no entities following.
+            (
+                PacketSide::Clientbound,
+                packet(play::EntitiesDestroyS2c::ID, VarInt(i32::MAX)),
+            ),
+            // Negative sequence length.
+            (
+                PacketSide::Clientbound,
+                packet(play::EntitiesDestroyS2c::ID, VarInt(-1)),
+            ),
+            // Huge string length.
+            (
+                PacketSide::Serverbound,
+                packet(play::ChatMessageC2s::ID, VarInt(i32::MAX)),
+            ),
+            // Invalid UTF-8 in a string.
+            (
+                PacketSide::Serverbound,
+                packet(play::ChatMessageC2s::ID, (VarInt(2), [0xc3_u8, 0x28])),
+            ),
+        ];
+
+        for (side, bytes) in inputs {
+            assert!(decode_packet(PacketState::Play, side, &bytes).is_err());
+        }
+    }
+
+    #[test]
+    fn decode_random_bytes_does_not_panic() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let mut buf = vec![];
+
+        for _ in 0..20_000 {
+            let len = rng.gen_range(0..64);
+            buf.clear();
+            buf.extend((0..len).map(|_| rng.gen::<u8>()));
+
+            // Bias the first byte towards valid packet IDs so that bodies are
+            // actually exercised.
+            if let Some(first) = buf.first_mut() {
+                *first &= 0x7f;
+            }
+
+            for state in STATES {
+                for side in SIDES {
+                    let _ = decode_packet(state, side, &buf);
+                }
+            }
+        }
+    }
+}
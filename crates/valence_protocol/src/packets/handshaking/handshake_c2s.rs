@@ -9,10 +9,42 @@ pub struct HandshakeC2s<'a> {
     pub next_state: HandshakeNextState,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Encode, Decode)]
+/// The state the client wants to switch to after the handshake.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, Encode, Decode)]
 pub enum HandshakeNextState {
+    #[default]
     #[packet(tag = 1)]
     Status,
     #[packet(tag = 2)]
     Login,
+    /// Like [`Login`](Self::Login), but the client was sent to this server by
+    /// another one. Clients before 1.20.5 never send this, though proxies
+    /// may.
+    #[packet(tag = 3)]
+    Transfer,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Decode, Encode};
+
+    #[test]
+    fn handshake_next_state_tags() {
+        for (state, tag) in [
+            (HandshakeNextState::Status, 1),
+            (HandshakeNextState::Login, 2),
+            (HandshakeNextState::Transfer, 3),
+        ] {
+            let mut buf = vec![];
+            state.encode(&mut buf).unwrap();
+            assert_eq!(buf, [tag]);
+
+            let mut r = buf.as_slice();
+            assert_eq!(HandshakeNextState::decode(&mut r).unwrap(), state);
+        }
+
+        let mut r: &[u8] = &[4];
+        assert!(HandshakeNextState::decode(&mut r).is_err());
+    }
 }
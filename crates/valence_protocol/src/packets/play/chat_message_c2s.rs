@@ -12,3 +12,51 @@ pub struct ChatMessageC2s<'a> {
     // the message was acknowledged by the client
     pub acknowledgement: FixedBitSet<20, 3>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_captured() {
+        #[rustfmt::skip]
+        let unsigned: &[u8] = &[
+            // Message.
+            5, b'h', b'e', b'l', b'l', b'o',
+            // Timestamp.
+            0x00, 0x00, 0x01, 0x8a, 0x5c, 0x2f, 0x3e, 0x10,
+            // Salt.
+            0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0,
+            // No signature.
+            0,
+            // Message count.
+            3,
+            // Acknowledged messages.
+            0b111, 0, 0,
+        ];
+
+        let mut r = unsigned;
+        let pkt = ChatMessageC2s::decode(&mut r).unwrap();
+        assert!(r.is_empty());
+
+        assert_eq!(pkt.message.0, "hello");
+        assert_eq!(pkt.timestamp, 0x0000_018a_5c2f_3e10);
+        assert_eq!(pkt.salt, 0x1234_5678_9abc_def0);
+        assert!(pkt.signature.is_none());
+        assert_eq!(pkt.message_count.0, 3);
+
+        // The same message signed by the client.
+        let mut signed = unsigned[..22].to_vec();
+        signed.push(1);
+        signed.extend([0xab; 256]);
+        signed.extend(&unsigned[23..]);
+
+        let mut r = signed.as_slice();
+        let pkt = ChatMessageC2s::decode(&mut r).unwrap();
+        assert!(r.is_empty());
+
+        assert_eq!(pkt.message.0, "hello");
+        assert_eq!(pkt.signature, Some(&[0xab; 256]));
+        assert_eq!(pkt.message_count.0, 3);
+    }
+}
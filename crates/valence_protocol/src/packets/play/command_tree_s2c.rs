@@ -3,7 +3,8 @@ use std::io::Write;
 
 use anyhow::bail;
 use byteorder::WriteBytesExt;
-use valence_ident::Ident;
+use thiserror::Error;
+use valence_ident::{ident, Ident};
 
 use crate::{Decode, Encode, Packet, VarInt};
 
@@ -34,8 +35,15 @@ pub enum NodeData<'a> {
     },
 }
 
+/// A source of tab completions for an argument node.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum Suggestion {
+    /// Completions are requested from the server with
+    /// [`RequestCommandCompletionsC2s`] and answered with
+    /// [`CommandSuggestionsS2c`].
+    ///
+    /// [`RequestCommandCompletionsC2s`]: crate::packets::play::RequestCommandCompletionsC2s
+    /// [`CommandSuggestionsS2c`]: crate::packets::play::CommandSuggestionsS2c
     AskServer,
     AllRecipes,
     AvailableSounds,
@@ -43,7 +51,33 @@ pub enum Suggestion {
     SummonableEntities,
 }
 
-#[derive(Clone, Debug)]
+impl Suggestion {
+    /// Returns the identifier of this suggestion type, as sent in the command
+    /// tree.
+    pub const fn ident(self) -> Ident<&'static str> {
+        match self {
+            Suggestion::AskServer => ident!("ask_server"),
+            Suggestion::AllRecipes => ident!("all_recipes"),
+            Suggestion::AvailableSounds => ident!("available_sounds"),
+            Suggestion::AvailableBiomes => ident!("available_biomes"),
+            Suggestion::SummonableEntities => ident!("summonable_entities"),
+        }
+    }
+
+    /// Returns the suggestion type with the given identifier.
+    pub fn from_ident(ident: Ident<&str>) -> Option<Self> {
+        Some(match ident.as_str() {
+            "minecraft:ask_server" => Suggestion::AskServer,
+            "minecraft:all_recipes" => Suggestion::AllRecipes,
+            "minecraft:available_sounds" => Suggestion::AvailableSounds,
+            "minecraft:available_biomes" => Suggestion::AvailableBiomes,
+            "minecraft:summonable_entities" => Suggestion::SummonableEntities,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
 pub enum Parser<'a> {
     Bool,
     Float { min: Option<f32>, max: Option<f32> },
@@ -102,6 +136,258 @@ pub enum StringArg {
     GreedyPhrase,
 }
 
+impl<'a> CommandTreeS2c<'a> {
+    /// Builds a command tree from a list of top-level commands.
+    ///
+    /// Node indices, including the targets of alias redirects, are assigned
+    /// here, so commands can be added or reordered freely beforehand.
+    pub fn from_commands(commands: impl IntoIterator<Item = CommandNode<'a>>) -> Self {
+        let mut nodes = vec![Node {
+            children: vec![],
+            data: NodeData::Root,
+            executable: false,
+            redirect_node: None,
+        }];
+
+        let mut root_children = vec![];
+
+        for cmd in commands {
+            cmd.flatten(&mut nodes, &mut root_children);
+        }
+
+        nodes[0].children = root_children;
+
+        Self {
+            commands: nodes,
+            root_index: VarInt(0),
+        }
+    }
+
+    /// Checks that the tree is well-formed. Clients disconnect when sent a
+    /// malformed tree, so this is useful for trees built by hand rather than
+    /// with [`from_commands`](Self::from_commands).
+    ///
+    /// A valid tree has exactly one root node, which `root_index` refers to.
+    /// Every child and redirect index must refer to an existing node, the
+    /// child links must not form a cycle, and literal and argument nodes must
+    /// have non-empty names. Redirects may point anywhere in the tree,
+    /// including back to the root.
+    pub fn validate(&self) -> Result<(), CommandTreeError> {
+        let len = self.commands.len();
+        let in_bounds = |idx: VarInt| usize::try_from(idx.0).ok().filter(|&i| i < len);
+
+        let root = in_bounds(self.root_index)
+            .ok_or(CommandTreeError::RootIndexOutOfBounds(self.root_index.0))?;
+
+        let mut found_root = None;
+
+        for (i, node) in self.commands.iter().enumerate() {
+            match node.data {
+                NodeData::Root => {
+                    if let Some(first) = found_root {
+                        return Err(CommandTreeError::MultipleRoots(first, i));
+                    }
+                    found_root = Some(i);
+                }
+                NodeData::Literal { name } | NodeData::Argument { name, .. } => {
+                    if name.is_empty() {
+                        return Err(CommandTreeError::EmptyName(i));
+                    }
+                }
+            }
+
+            for &idx in node.children.iter().chain(&node.redirect_node) {
+                if in_bounds(idx).is_none() {
+                    return Err(CommandTreeError::IndexOutOfBounds {
+                        node: i,
+                        index: idx.0,
+                    });
+                }
+            }
+        }
+
+        if found_root != Some(root) {
+            return Err(CommandTreeError::NotRoot(root));
+        }
+
+        // Depth-first search over child links. A node that is reached again
+        // while it is still on the stack is part of a cycle.
+        #[derive(Copy, Clone, PartialEq)]
+        enum Visit {
+            Unvisited,
+            InProgress,
+            Done,
+        }
+
+        let mut visits = vec![Visit::Unvisited; len];
+
+        for start in 0..len {
+            if visits[start] != Visit::Unvisited {
+                continue;
+            }
+
+            visits[start] = Visit::InProgress;
+            let mut stack = vec![(start, 0)];
+
+            while let Some((node, child)) = stack.last_mut() {
+                let Some(next) = self.commands[*node].children.get(*child) else {
+                    visits[*node] = Visit::Done;
+                    stack.pop();
+                    continue;
+                };
+
+                *child += 1;
+
+                let next = next.0 as usize;
+
+                match visits[next] {
+                    Visit::Unvisited => {
+                        visits[next] = Visit::InProgress;
+                        stack.push((next, 0));
+                    }
+                    Visit::InProgress => return Err(CommandTreeError::Cycle(next)),
+                    Visit::Done => {}
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// An error returned by [`CommandTreeS2c::validate`]. Node indices refer to
+/// [`CommandTreeS2c::commands`].
+#[derive(Clone, PartialEq, Eq, Debug, Error)]
+pub enum CommandTreeError {
+    #[error("root index {0} is out of bounds")]
+    RootIndexOutOfBounds(i32),
+    #[error("root index refers to node {0}, which is not a root node")]
+    NotRoot(usize),
+    #[error("nodes {0} and {1} are both root nodes")]
+    MultipleRoots(usize, usize),
+    #[error("node {node} refers to out of bounds index {index}")]
+    IndexOutOfBounds { node: usize, index: i32 },
+    #[error("node {0} is part of a cycle of child nodes")]
+    Cycle(usize),
+    #[error("node {0} has an empty name")]
+    EmptyName(usize),
+}
+
+/// A node in a command tree that is yet to be built with
+/// [`CommandTreeS2c::from_commands`].
+///
+/// # Examples
+///
+/// ```
+/// use valence_protocol::packets::play::command_tree_s2c::{CommandNode, Parser};
+/// use valence_protocol::packets::play::CommandTreeS2c;
+///
+/// let teleport = CommandNode::literal("teleport").alias("tp").then(
+///     CommandNode::argument(
+///         "target",
+///         Parser::Entity {
+///             single: true,
+///             only_players: false,
+///         },
+///     )
+///     .executable(),
+/// );
+///
+/// let tree = CommandTreeS2c::from_commands([teleport]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CommandNode<'a> {
+    data: NodeData<'a>,
+    executable: bool,
+    children: Vec<CommandNode<'a>>,
+    aliases: Vec<&'a str>,
+}
+
+impl<'a> CommandNode<'a> {
+    /// Creates a literal node matching exactly `name`.
+    pub fn literal(name: &'a str) -> Self {
+        Self::new(NodeData::Literal { name })
+    }
+
+    /// Creates an argument node named `name` parsed with `parser`.
+    pub fn argument(name: &'a str, parser: Parser<'a>) -> Self {
+        Self::new(NodeData::Argument {
+            name,
+            parser,
+            suggestion: None,
+        })
+    }
+
+    fn new(data: NodeData<'a>) -> Self {
+        Self {
+            data,
+            executable: false,
+            children: vec![],
+            aliases: vec![],
+        }
+    }
+
+    /// Marks the command as complete when the input ends at this node.
+    pub fn executable(mut self) -> Self {
+        self.executable = true;
+        self
+    }
+
+    /// Sets the suggestion type of an argument node. Has no effect on literal
+    /// nodes.
+    pub fn suggest(mut self, suggestion: Suggestion) -> Self {
+        if let NodeData::Argument { suggestion: s, .. } = &mut self.data {
+            *s = Some(suggestion);
+        }
+        self
+    }
+
+    /// Adds a child node.
+    pub fn then(mut self, child: CommandNode<'a>) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Adds a literal sibling named `alias` that redirects to this node. The
+    /// client then treats the alias exactly like this node.
+    pub fn alias(mut self, alias: &'a str) -> Self {
+        self.aliases.push(alias);
+        self
+    }
+
+    fn flatten(self, nodes: &mut Vec<Node<'a>>, parent_children: &mut Vec<VarInt>) {
+        let idx = VarInt(nodes.len() as i32);
+
+        nodes.push(Node {
+            children: vec![],
+            data: self.data,
+            executable: self.executable,
+            redirect_node: None,
+        });
+
+        parent_children.push(idx);
+
+        let mut children = vec![];
+
+        for child in self.children {
+            child.flatten(nodes, &mut children);
+        }
+
+        nodes[idx.0 as usize].children = children;
+
+        for alias in self.aliases {
+            parent_children.push(VarInt(nodes.len() as i32));
+
+            nodes.push(Node {
+                children: vec![],
+                data: NodeData::Literal { name: alias },
+                executable: self.executable,
+                redirect_node: Some(idx),
+            });
+        }
+    }
+}
+
 impl Encode for Node<'_> {
     fn encode(&self, mut w: impl Write) -> anyhow::Result<()> {
         let node_type = match &self.data {
@@ -145,14 +431,7 @@ impl Encode for Node<'_> {
                 parser.encode(&mut w)?;
 
                 if let Some(suggestion) = suggestion {
-                    match suggestion {
-                        Suggestion::AskServer => "ask_server",
-                        Suggestion::AllRecipes => "all_recipes",
-                        Suggestion::AvailableSounds => "available_sounds",
-                        Suggestion::AvailableBiomes => "available_biomes",
-                        Suggestion::SummonableEntities => "summonable_entities",
-                    }
-                    .encode(&mut w)?;
+                    suggestion.ident().encode(&mut w)?;
                 }
             }
         }
@@ -182,14 +461,11 @@ impl<'a> Decode<'a> for Node<'a> {
                 name: <&str>::decode(r)?,
                 parser: Parser::decode(r)?,
                 suggestion: if flags & 0x10 != 0 {
-                    Some(match Ident::<Cow<str>>::decode(r)?.as_str() {
-                        "minecraft:ask_server" => Suggestion::AskServer,
-                        "minecraft:all_recipes" => Suggestion::AllRecipes,
-                        "minecraft:available_sounds" => Suggestion::AvailableSounds,
-                        "minecraft:available_biomes" => Suggestion::AvailableBiomes,
-                        "minecraft:summonable_entities" => Suggestion::SummonableEntities,
-                        other => bail!("unknown command suggestion type of \"{other}\""),
-                    })
+                    let ident = Ident::<Cow<str>>::decode(r)?;
+                    match Suggestion::from_ident(ident.as_str_ident()) {
+                        Some(suggestion) => Some(suggestion),
+                        None => bail!("unknown command suggestion type of \"{ident}\""),
+                    }
                 } else {
                     None
                 },
@@ -438,3 +714,224 @@ impl<'a> Decode<'a> for Parser<'a> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Follows `input` through the tree the way the client does, returning
+    /// the index of the last node matched.
+    fn resolve(tree: &CommandTreeS2c, input: &[&str]) -> Option<usize> {
+        let mut idx = tree.root_index.0 as usize;
+
+        for &word in input {
+            let node = &tree.commands[idx];
+            let children = match node.redirect_node {
+                Some(r) => &tree.commands[r.0 as usize].children,
+                None => &node.children,
+            };
+
+            idx = children.iter().map(|c| c.0 as usize).find(
+                |&c| matches!(tree.commands[c].data, NodeData::Literal { name } if name == word),
+            )?;
+        }
+
+        Some(idx)
+    }
+
+    #[test]
+    fn alias_redirects_to_canonical_node() {
+        let teleport = CommandNode::literal("teleport")
+            .alias("tp")
+            .then(CommandNode::literal("here").executable());
+
+        // Commands added around the aliased one shift its index.
+        let tree = CommandTreeS2c::from_commands([
+            CommandNode::literal("help").then(CommandNode::literal("all")),
+            teleport,
+            CommandNode::literal("kill").executable(),
+        ]);
+
+        let canonical = resolve(&tree, &["teleport"]).unwrap();
+        let alias = resolve(&tree, &["tp"]).unwrap();
+
+        assert_ne!(canonical, alias);
+        assert_eq!(
+            tree.commands[alias].redirect_node,
+            Some(VarInt(canonical as i32))
+        );
+        assert!(tree.commands[alias].children.is_empty());
+
+        assert_eq!(
+            resolve(&tree, &["tp", "here"]),
+            resolve(&tree, &["teleport", "here"])
+        );
+        assert!(resolve(&tree, &["tp", "here"]).is_some());
+
+        let mut buf = vec![];
+        tree.encode(&mut buf).unwrap();
+        let decoded = CommandTreeS2c::decode(&mut buf.as_slice()).unwrap();
+
+        assert_eq!(
+            resolve(&decoded, &["tp", "here"]),
+            resolve(&decoded, &["teleport", "here"])
+        );
+    }
+
+    #[test]
+    fn ask_server_sets_suggestion_flag() {
+        let tree = CommandTreeS2c::from_commands([CommandNode::literal("msg").then(
+            CommandNode::argument("name", Parser::String(StringArg::SingleWord))
+                .suggest(Suggestion::AskServer)
+                .executable(),
+        )]);
+
+        let arg = resolve(&tree, &["msg"]).unwrap();
+        let arg = &tree.commands[tree.commands[arg].children[0].0 as usize];
+
+        let mut buf = vec![];
+        arg.encode(&mut buf).unwrap();
+
+        let flags = buf[0];
+        assert_eq!(flags & 0x03, 2);
+        assert_ne!(flags & 0x04, 0);
+        assert_ne!(flags & 0x10, 0);
+
+        let Ok(Node {
+            data: NodeData::Argument { suggestion, .. },
+            ..
+        }) = Node::decode(&mut buf.as_slice())
+        else {
+            panic!("expected an argument node");
+        };
+
+        assert_eq!(suggestion, Some(Suggestion::AskServer));
+    }
+
+    #[test]
+    fn suggestion_idents_round_trip() {
+        for suggestion in [
+            Suggestion::AskServer,
+            Suggestion::AllRecipes,
+            Suggestion::AvailableSounds,
+            Suggestion::AvailableBiomes,
+            Suggestion::SummonableEntities,
+        ] {
+            assert_eq!(Suggestion::from_ident(suggestion.ident()), Some(suggestion));
+        }
+
+        assert_eq!(Suggestion::from_ident(ident!("valence:nothing")), None);
+    }
+
+    #[test]
+    fn validate_tree() {
+        let mut tree = CommandTreeS2c::from_commands([
+            CommandNode::literal("teleport").alias("tp").executable(),
+            CommandNode::literal("execute"),
+        ]);
+
+        assert_eq!(tree.validate(), Ok(()));
+
+        // `/execute run ...` redirects back to the root, which is allowed.
+        let execute = resolve(&tree, &["execute"]).unwrap();
+        tree.commands.push(Node {
+            children: vec![],
+            data: NodeData::Literal { name: "run" },
+            executable: false,
+            redirect_node: Some(tree.root_index),
+        });
+        let run = VarInt(tree.commands.len() as i32 - 1);
+        tree.commands[execute].children.push(run);
+
+        assert_eq!(tree.validate(), Ok(()));
+
+        let mut bad = tree.clone();
+        bad.commands[execute].children.push(VarInt(100));
+        assert_eq!(
+            bad.validate(),
+            Err(CommandTreeError::IndexOutOfBounds {
+                node: execute,
+                index: 100
+            })
+        );
+
+        let mut bad = tree.clone();
+        bad.commands[run.0 as usize]
+            .children
+            .push(VarInt(execute as i32));
+        assert!(matches!(bad.validate(), Err(CommandTreeError::Cycle(_))));
+
+        let mut bad = tree.clone();
+        bad.commands[run.0 as usize].data = NodeData::Root;
+        assert!(matches!(
+            bad.validate(),
+            Err(CommandTreeError::MultipleRoots(..))
+        ));
+
+        let mut bad = tree.clone();
+        bad.commands[run.0 as usize].data = NodeData::Literal { name: "" };
+        assert_eq!(
+            bad.validate(),
+            Err(CommandTreeError::EmptyName(run.0 as usize))
+        );
+
+        let mut bad = tree;
+        bad.root_index = run;
+        assert_eq!(
+            bad.validate(),
+            Err(CommandTreeError::NotRoot(run.0 as usize))
+        );
+    }
+
+    #[test]
+    fn parser_round_trip() {
+        let cases = [
+            (
+                Parser::Double {
+                    min: Some(-1.5),
+                    max: None,
+                },
+                [&[2, 0x01][..], &(-1.5_f64).to_be_bytes()].concat(),
+            ),
+            (
+                Parser::Double {
+                    min: Some(0.0),
+                    max: Some(10.0),
+                },
+                [
+                    &[2, 0x03][..],
+                    &0.0_f64.to_be_bytes(),
+                    &10.0_f64.to_be_bytes(),
+                ]
+                .concat(),
+            ),
+            (Parser::String(StringArg::SingleWord), vec![5, 0]),
+            (Parser::String(StringArg::QuotablePhrase), vec![5, 1]),
+            (Parser::String(StringArg::GreedyPhrase), vec![5, 2]),
+            (
+                Parser::Entity {
+                    single: true,
+                    only_players: false,
+                },
+                vec![6, 0x01],
+            ),
+            (
+                Parser::Entity {
+                    single: false,
+                    only_players: true,
+                },
+                vec![6, 0x02],
+            ),
+        ];
+
+        for (parser, bytes) in cases {
+            let mut buf = vec![];
+            parser.encode(&mut buf).unwrap();
+            assert_eq!(buf, bytes, "{parser:?}");
+
+            let mut r = buf.as_slice();
+            assert_eq!(Parser::decode(&mut r).unwrap(), parser);
+            assert!(r.is_empty());
+        }
+    }
+}
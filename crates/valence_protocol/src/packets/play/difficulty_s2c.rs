@@ -5,3 +5,34 @@ pub struct DifficultyS2c {
     pub difficulty: Difficulty,
     pub locked: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_locked_flag_after_difficulty() {
+        let pkt = DifficultyS2c {
+            difficulty: Difficulty::Hard,
+            locked: true,
+        };
+
+        let mut buf = vec![];
+        pkt.encode(&mut buf).unwrap();
+
+        assert_eq!(buf, [3, 1]);
+
+        let decoded = DifficultyS2c::decode(&mut buf.as_slice()).unwrap();
+        assert_eq!(decoded, pkt);
+
+        let mut buf = vec![];
+        DifficultyS2c {
+            locked: false,
+            ..pkt
+        }
+        .encode(&mut buf)
+        .unwrap();
+
+        assert_eq!(buf, [3, 0]);
+    }
+}
@@ -6,7 +6,10 @@ use crate::{Decode, Encode, Packet, VarInt};
 #[derive(Clone, Debug, Encode, Decode, Packet)]
 pub struct EntityStatusEffectS2c {
     pub entity_id: VarInt,
-    pub effect_id: VarInt, // TODO: effect ID registry.
+    /// The raw ID of the effect. See [`StatusEffect::to_raw`].
+    ///
+    /// [`StatusEffect::to_raw`]: crate::StatusEffect::to_raw
+    pub effect_id: VarInt,
     pub amplifier: u8,
     pub duration: VarInt,
     pub flags: Flags,
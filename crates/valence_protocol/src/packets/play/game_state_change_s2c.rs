@@ -6,6 +6,12 @@ pub struct GameStateChangeS2c {
     pub value: f32,
 }
 
+/// The kind of a [`GameStateChangeS2c`]. The value of each variant is its
+/// protocol ID.
+///
+/// These are the game events of the supported protocol version. Later
+/// versions add more, such as the limited crafting toggle added in 1.20.2,
+/// which clients of this version don't understand.
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Decode)]
 pub enum GameEventKind {
     NoRespawnBlockAvailable,
@@ -38,3 +38,23 @@ pub enum WindowType {
     Cartography,
     Stonecutter,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_type_ids() {
+        let id = |window_type: WindowType| {
+            let mut buf = vec![];
+            window_type.encode(&mut buf).unwrap();
+            VarInt::decode(&mut buf.as_slice()).unwrap().0
+        };
+
+        assert_eq!(id(WindowType::Generic9x1), 0);
+        assert_eq!(id(WindowType::Generic9x6), 5);
+        assert_eq!(id(WindowType::Generic3x3), 6);
+        assert_eq!(id(WindowType::Hopper), 15);
+        assert_eq!(id(WindowType::Stonecutter), 23);
+    }
+}
@@ -439,3 +439,58 @@ impl Encode for Particle {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_packet(particle: Particle) -> Vec<u8> {
+        let pkt = ParticleS2c {
+            particle: Cow::Owned(particle),
+            long_distance: false,
+            position: DVec3::ZERO,
+            offset: Vec3::ZERO,
+            max_speed: 0.0,
+            count: 1,
+        };
+
+        let mut buf = vec![];
+        pkt.encode(&mut buf).unwrap();
+
+        let decoded = ParticleS2c::decode(&mut buf.as_slice()).unwrap();
+        assert_eq!(decoded.particle, pkt.particle);
+
+        buf
+    }
+
+    /// Length of the fields between the particle ID and the particle data.
+    const COMMON_LEN: usize = 1 + 8 * 3 + 4 * 3 + 4 + 4;
+
+    #[test]
+    fn dust_wire_format() {
+        let buf = encode_packet(Particle::Dust {
+            rgb: Vec3::new(1.0, 0.5, 0.0),
+            scale: 2.0,
+        });
+
+        assert_eq!(buf[0], 14);
+
+        let data: Vec<u8> = [1.0_f32, 0.5, 0.0, 2.0]
+            .iter()
+            .flat_map(|f| f.to_be_bytes())
+            .collect();
+        assert_eq!(buf[1 + COMMON_LEN..], data);
+    }
+
+    #[test]
+    fn block_wire_format() {
+        let block = BlockState::STONE;
+        let buf = encode_packet(Particle::Block(block));
+
+        assert_eq!(buf[0], 2);
+
+        let mut data = vec![];
+        VarInt(block.to_raw() as i32).encode(&mut data).unwrap();
+        assert_eq!(buf[1 + COMMON_LEN..], data);
+    }
+}
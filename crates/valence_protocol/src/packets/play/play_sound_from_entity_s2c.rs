@@ -1,12 +1,53 @@
-use crate::sound::SoundCategory;
+use crate::sound::{SoundCategory, SoundId};
 use crate::{Decode, Encode, Packet, VarInt};
 
-#[derive(Copy, Clone, Debug, Encode, Decode, Packet)]
-pub struct PlaySoundFromEntityS2c {
-    pub id: VarInt,
+#[derive(Clone, Debug, Encode, Decode, Packet)]
+pub struct PlaySoundFromEntityS2c<'a> {
+    pub id: SoundId<'a>,
     pub category: SoundCategory,
     pub entity_id: VarInt,
     pub volume: f32,
     pub pitch: f32,
     pub seed: i64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sound;
+
+    #[test]
+    fn encodes_entity_id_and_sound_fields() {
+        let pkt = PlaySoundFromEntityS2c {
+            id: Sound::EntityPlayerLevelup.into(),
+            category: SoundCategory::Neutral,
+            entity_id: VarInt(300),
+            volume: 0.5,
+            pitch: 2.0,
+            seed: 7,
+        };
+
+        let mut buf = vec![];
+        pkt.encode(&mut buf).unwrap();
+
+        let mut expected = vec![];
+        VarInt(Sound::EntityPlayerLevelup.to_raw() as i32 + 1)
+            .encode(&mut expected)
+            .unwrap();
+        SoundCategory::Neutral.encode(&mut expected).unwrap();
+        VarInt(300).encode(&mut expected).unwrap();
+        0.5_f32.encode(&mut expected).unwrap();
+        2.0_f32.encode(&mut expected).unwrap();
+        7_i64.encode(&mut expected).unwrap();
+
+        assert_eq!(buf, expected);
+
+        let decoded = PlaySoundFromEntityS2c::decode(&mut buf.as_slice()).unwrap();
+        assert_eq!(decoded.id, pkt.id);
+        assert_eq!(decoded.category, SoundCategory::Neutral);
+        assert_eq!(decoded.entity_id, VarInt(300));
+        assert_eq!(decoded.volume, 0.5);
+        assert_eq!(decoded.pitch, 2.0);
+        assert_eq!(decoded.seed, 7);
+    }
+}
@@ -10,3 +10,47 @@ pub struct ServerMetadataS2c<'a> {
     pub icon: Option<&'a [u8]>,
     pub enforce_secure_chat: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VarInt;
+
+    #[test]
+    fn icon_present_flag() {
+        let motd = Text::from("A Valence server");
+        let icon = [0x89, b'P', b'N', b'G'];
+
+        let mut motd_bytes = vec![];
+        motd.encode(&mut motd_bytes).unwrap();
+
+        let mut buf = vec![];
+        ServerMetadataS2c {
+            motd: Cow::Borrowed(&motd),
+            icon: Some(&icon),
+            enforce_secure_chat: true,
+        }
+        .encode(&mut buf)
+        .unwrap();
+
+        let mut r = &buf[motd_bytes.len()..];
+        assert!(bool::decode(&mut r).unwrap());
+        assert_eq!(VarInt::decode(&mut r).unwrap().0, icon.len() as i32);
+        assert_eq!(&r[..icon.len()], icon);
+        assert_eq!(&r[icon.len()..], [1]);
+
+        buf.clear();
+        ServerMetadataS2c {
+            motd: Cow::Borrowed(&motd),
+            icon: None,
+            enforce_secure_chat: false,
+        }
+        .encode(&mut buf)
+        .unwrap();
+
+        assert_eq!(&buf[motd_bytes.len()..], [0, 0]);
+
+        let pkt = ServerMetadataS2c::decode(&mut buf.as_slice()).unwrap();
+        assert_eq!(pkt.icon, None);
+    }
+}
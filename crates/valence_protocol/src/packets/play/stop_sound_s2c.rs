@@ -50,3 +50,53 @@ impl<'a> Decode<'a> for StopSoundS2c<'a> {
         Ok(Self { source, sound })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ident, VarInt};
+
+    fn encode(pkt: &StopSoundS2c) -> Vec<u8> {
+        let mut buf = vec![];
+        pkt.encode(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn flags() {
+        let sound = Some(ident!("music.game").into());
+
+        let all = StopSoundS2c {
+            source: None,
+            sound: None,
+        };
+        assert_eq!(encode(&all), [0]);
+
+        let by_category = StopSoundS2c {
+            source: Some(SoundCategory::Music),
+            sound: None,
+        };
+        let mut expected = vec![1];
+        VarInt(SoundCategory::Music as i32)
+            .encode(&mut expected)
+            .unwrap();
+        assert_eq!(encode(&by_category), expected);
+
+        let by_sound = StopSoundS2c {
+            source: None,
+            sound: sound.clone(),
+        };
+        assert_eq!(encode(&by_sound)[0], 2);
+
+        let both = StopSoundS2c {
+            source: Some(SoundCategory::Music),
+            sound,
+        };
+        assert_eq!(encode(&both)[0], 3);
+
+        for pkt in [all, by_category, by_sound, both] {
+            let buf = encode(&pkt);
+            assert_eq!(StopSoundS2c::decode(&mut buf.as_slice()).unwrap(), pkt);
+        }
+    }
+}
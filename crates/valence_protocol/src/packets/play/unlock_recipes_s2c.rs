@@ -57,16 +57,86 @@ impl<'a> Decode<'a> for UnlockRecipesS2c<'a> {
 }
 
 impl Encode for UnlockRecipesS2c<'_> {
-    fn encode(&self, _w: impl Write) -> anyhow::Result<()> {
-        todo!()
+    fn encode(&self, mut w: impl Write) -> anyhow::Result<()> {
+        VarInt(match &self.action {
+            UpdateRecipeBookAction::Init { .. } => 0,
+            UpdateRecipeBookAction::Add => 1,
+            UpdateRecipeBookAction::Remove => 2,
+        })
+        .encode(&mut w)?;
+
+        self.crafting_recipe_book_open.encode(&mut w)?;
+        self.crafting_recipe_book_filter_active.encode(&mut w)?;
+        self.smelting_recipe_book_open.encode(&mut w)?;
+        self.smelting_recipe_book_filter_active.encode(&mut w)?;
+        self.blast_furnace_recipe_book_open.encode(&mut w)?;
+        self.blast_furnace_recipe_book_filter_active
+            .encode(&mut w)?;
+        self.smoker_recipe_book_open.encode(&mut w)?;
+        self.smoker_recipe_book_filter_active.encode(&mut w)?;
+        self.recipe_ids.encode(&mut w)?;
+
+        if let UpdateRecipeBookAction::Init { recipe_ids } = &self.action {
+            recipe_ids.encode(&mut w)?;
+        }
+
+        Ok(())
     }
 }
 
+/// What the client should do with the
+/// [`recipe_ids`](UnlockRecipesS2c::recipe_ids) of an [`UnlockRecipesS2c`].
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum UpdateRecipeBookAction<'a> {
+    /// Tells a client that just joined which recipes are unlocked, without
+    /// showing a toast. The recipes in this variant's `recipe_ids` are also
+    /// highlighted as new in the recipe book.
     Init {
         recipe_ids: Vec<Ident<Cow<'a, str>>>,
     },
+    /// Unlocks the packet's recipes and shows a toast for them.
     Add,
+    /// Locks the packet's recipes.
     Remove,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ident;
+
+    fn round_trip(pkt: &UnlockRecipesS2c) {
+        let mut buf = vec![];
+        pkt.encode(&mut buf).unwrap();
+
+        let mut r = buf.as_slice();
+        assert_eq!(&UnlockRecipesS2c::decode(&mut r).unwrap(), pkt);
+        assert!(r.is_empty());
+    }
+
+    #[test]
+    fn unlock_recipes_round_trip() {
+        let mut pkt = UnlockRecipesS2c {
+            action: UpdateRecipeBookAction::Init {
+                recipe_ids: vec![ident!("stick").into()],
+            },
+            crafting_recipe_book_open: true,
+            crafting_recipe_book_filter_active: false,
+            smelting_recipe_book_open: false,
+            smelting_recipe_book_filter_active: true,
+            blast_furnace_recipe_book_open: false,
+            blast_furnace_recipe_book_filter_active: false,
+            smoker_recipe_book_open: true,
+            smoker_recipe_book_filter_active: true,
+            recipe_ids: vec![ident!("stick").into(), ident!("torch").into()],
+        };
+
+        round_trip(&pkt);
+
+        pkt.action = UpdateRecipeBookAction::Add;
+        round_trip(&pkt);
+
+        pkt.action = UpdateRecipeBookAction::Remove;
+        round_trip(&pkt);
+    }
+}
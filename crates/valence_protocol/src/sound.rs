@@ -18,6 +18,21 @@ pub enum SoundId<'a> {
     },
 }
 
+impl From<Sound> for SoundId<'_> {
+    fn from(sound: Sound) -> Self {
+        SoundId::Reference {
+            id: VarInt(sound.to_raw().into()),
+        }
+    }
+}
+
+/// Custom sounds, such as ones from a resource pack, are sent by identifier.
+impl<'a> From<Ident<Cow<'a, str>>> for SoundId<'a> {
+    fn from(id: Ident<Cow<'a, str>>) -> Self {
+        SoundId::Direct { id, range: None }
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Decode)]
 pub enum SoundCategory {
     Master,
@@ -61,3 +76,46 @@ impl<'a> Decode<'a> for SoundId<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ident;
+
+    #[test]
+    fn sound_reference_encodes_raw_id_plus_one() {
+        assert_eq!(Sound::from_raw(1024), Some(Sound::EntityPlayerLevelup));
+        assert_eq!(
+            Sound::from_ident(ident!("entity.player.levelup").as_str_ident()),
+            Some(Sound::EntityPlayerLevelup)
+        );
+
+        let mut buf = vec![];
+        SoundId::from(Sound::EntityPlayerLevelup)
+            .encode(&mut buf)
+            .unwrap();
+
+        let mut expected = vec![];
+        VarInt(1025).encode(&mut expected).unwrap();
+        assert_eq!(buf, expected);
+
+        let decoded = SoundId::decode(&mut buf.as_slice()).unwrap();
+        assert_eq!(decoded, SoundId::Reference { id: VarInt(1024) });
+    }
+
+    #[test]
+    fn custom_sound_encodes_ident() {
+        let id = SoundId::from(Ident::<Cow<str>>::from(ident!("my_pack:music.boss")));
+
+        let mut buf = vec![];
+        id.encode(&mut buf).unwrap();
+
+        let mut expected = vec![];
+        VarInt(0).encode(&mut expected).unwrap();
+        ident!("my_pack:music.boss").encode(&mut expected).unwrap();
+        false.encode(&mut expected).unwrap();
+        assert_eq!(buf, expected);
+
+        assert_eq!(SoundId::decode(&mut buf.as_slice()).unwrap(), id);
+    }
+}
@@ -151,4 +151,16 @@ mod tests {
             buf.clear();
         }
     }
+
+    #[test]
+    fn varint_too_long() {
+        let mut r: &[u8] = &[0xff; VarInt::MAX_SIZE + 1];
+        assert!(VarInt::decode(&mut r).is_err());
+
+        let mut r: &[u8] = &[0xff; VarInt::MAX_SIZE + 1];
+        assert_eq!(
+            VarInt::decode_partial(&mut r),
+            Err(VarIntDecodeError::TooLarge)
+        );
+    }
 }
@@ -123,7 +123,7 @@ impl Decode<'_> for VarLong {
                 return Ok(VarLong(val));
             }
         }
-        bail!("VarInt is too large")
+        bail!("VarLong is too large")
     }
 }
 
@@ -152,4 +152,10 @@ mod tests {
             buf.clear();
         }
     }
+
+    #[test]
+    fn varlong_too_long() {
+        let mut r: &[u8] = &[0xff; VarLong::MAX_SIZE + 1];
+        assert!(VarLong::decode(&mut r).is_err());
+    }
 }
@@ -0,0 +1,121 @@
+//! Contains damage types and the damage type registry. Minecraft's default
+//! damage types are added to the registry by default.
+//!
+//! ### **NOTE:**
+//! - Modifying the damage type registry after the server has started can
+//!   break invariants within clients! Make sure there are no clients spawned
+//!   before mutating.
+
+use std::ops::{Deref, DerefMut};
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use valence_ident::{ident, Ident};
+use valence_nbt::serde::CompoundSerializer;
+
+use crate::codec::{RegistryCodec, RegistryValue};
+use crate::{Registry, RegistryIdx, RegistrySet};
+
+pub struct DamageTypePlugin;
+
+impl Plugin for DamageTypePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DamageTypeRegistry>()
+            .add_systems(PreStartup, load_default_damage_types)
+            .add_systems(PostUpdate, update_damage_type_registry.before(RegistrySet));
+    }
+}
+
+fn load_default_damage_types(mut reg: ResMut<DamageTypeRegistry>, codec: Res<RegistryCodec>) {
+    let mut helper = move || -> anyhow::Result<()> {
+        for value in codec.registry(DamageTypeRegistry::KEY) {
+            let damage_type = DamageType::deserialize(value.element.clone())?;
+
+            reg.insert(value.name.clone(), damage_type);
+        }
+
+        Ok(())
+    };
+
+    if let Err(e) = helper() {
+        error!("failed to load default damage types from registry codec: {e:#}");
+    }
+}
+
+fn update_damage_type_registry(reg: Res<DamageTypeRegistry>, mut codec: ResMut<RegistryCodec>) {
+    if reg.is_changed() {
+        let damage_types = codec.registry_mut(DamageTypeRegistry::KEY);
+
+        damage_types.clear();
+
+        damage_types.extend(reg.iter().map(|(_, name, damage_type)| {
+            RegistryValue {
+                name: name.into(),
+                element: damage_type
+                    .serialize(CompoundSerializer)
+                    .expect("failed to serialize damage type"),
+            }
+        }));
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct DamageTypeRegistry {
+    reg: Registry<DamageTypeId, DamageType>,
+}
+
+impl DamageTypeRegistry {
+    pub const KEY: Ident<&str> = ident!("damage_type");
+}
+
+impl Deref for DamageTypeRegistry {
+    type Target = Registry<DamageTypeId, DamageType>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.reg
+    }
+}
+
+impl DerefMut for DamageTypeRegistry {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.reg
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug)]
+pub struct DamageTypeId(u32);
+
+impl RegistryIdx for DamageTypeId {
+    const MAX: usize = u32::MAX as _;
+
+    #[inline]
+    fn to_index(self) -> usize {
+        self.0 as _
+    }
+
+    #[inline]
+    fn from_index(idx: usize) -> Self {
+        Self(idx as _)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DamageType {
+    /// The translation key of the death message, after `death.attack.`.
+    pub message_id: String,
+    /// Whether the damage scales with difficulty. One of `never`,
+    /// `when_caused_by_living_non_player` or `always`.
+    pub scaling: String,
+    /// The exhaustion added to the player taking the damage.
+    pub exhaustion: f32,
+    /// The sound played when taking the damage. One of `hurt`, `thorns`,
+    /// `drowning`, `burning`, `poking` or `freezing`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effects: Option<String>,
+    /// How the death message is built. One of `default`,
+    /// `fall_variants` or `intentional_game_design`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub death_message_type: Option<String>,
+}
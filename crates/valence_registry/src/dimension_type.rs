@@ -8,6 +8,7 @@
 
 use std::ops::{Deref, DerefMut};
 
+use anyhow::ensure;
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -135,6 +136,45 @@ pub struct DimensionType {
     pub ultrawarm: bool,
 }
 
+impl DimensionType {
+    /// Checks that the vertical bounds of this dimension type are accepted by
+    /// the client.
+    ///
+    /// `min_y` and `height` must be multiples of 16, `height` must be
+    /// positive, and the dimension must stay within Y levels -2032 and 2031.
+    /// `logical_height` can't exceed `height`.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        ensure!(
+            self.min_y % 16 == 0,
+            "min_y of {} is not a multiple of 16",
+            self.min_y
+        );
+        ensure!(
+            self.height > 0 && self.height % 16 == 0,
+            "height of {} is not a positive multiple of 16",
+            self.height
+        );
+
+        // Widened so that huge heights can't overflow.
+        let end_y = i64::from(self.min_y) + i64::from(self.height);
+
+        ensure!(
+            self.min_y >= -2032 && end_y <= 2032,
+            "dimension spanning Y levels {} to {} is out of bounds",
+            self.min_y,
+            end_y - 1
+        );
+        ensure!(
+            (0..=self.height).contains(&self.logical_height),
+            "logical height of {} is not within the height of {}",
+            self.logical_height,
+            self.height
+        );
+
+        Ok(())
+    }
+}
+
 impl Default for DimensionType {
     fn default() -> Self {
         Self {
@@ -194,3 +234,43 @@ impl From<i32> for MonsterSpawnLightLevel {
         Self::Int(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_dimension_type() {
+        assert!(DimensionType::default().validate().is_ok());
+
+        let void = DimensionType {
+            ambient_light: 1.0,
+            has_skylight: false,
+            effects: DimensionEffects::TheEnd,
+            bed_works: false,
+            min_y: 0,
+            height: 256,
+            logical_height: 256,
+            ..Default::default()
+        };
+        assert!(void.validate().is_ok());
+
+        for (min_y, height) in [
+            (-60, 384),
+            (-64, 0),
+            (-64, 380),
+            (0, 2048),
+            (-2048, 64),
+            (1008, i32::MAX - 15),
+        ] {
+            let dim = DimensionType {
+                min_y,
+                height,
+                logical_height: 0,
+                ..Default::default()
+            };
+
+            assert!(dim.validate().is_err(), "{min_y} {height}");
+        }
+    }
+}
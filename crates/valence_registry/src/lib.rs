@@ -19,6 +19,7 @@
 
 pub mod biome;
 pub mod codec;
+pub mod damage_type;
 pub mod dimension_type;
 pub mod tags;
 
@@ -31,6 +32,7 @@ use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 pub use biome::BiomeRegistry;
 pub use codec::RegistryCodec;
+pub use damage_type::DamageTypeRegistry;
 pub use dimension_type::DimensionTypeRegistry;
 use indexmap::map::Entry;
 use indexmap::IndexMap;
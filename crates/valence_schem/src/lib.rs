@@ -0,0 +1,537 @@
+#![doc = include_str!("../README.md")]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use thiserror::Error;
+use tracing::warn;
+use valence_server::block::{PropName, PropValue};
+use valence_server::layer::chunk::{Block, BlockRef, IntoBlock};
+use valence_server::nbt::{Compound, List, Value};
+use valence_server::protocol::var_int::VarInt;
+use valence_server::protocol::{BlockKind, Encode};
+use valence_server::{BlockPos, BlockState, ChunkLayer};
+
+/// The version of the Sponge schematic format this crate reads and writes.
+pub const SPONGE_VERSION: i32 = 2;
+
+/// The data version of the game version supported by Valence (1.20.1).
+pub const DATA_VERSION: i32 = 3465;
+
+/// The most blocks a schematic file can hold. Every block takes at least one
+/// byte of the `BlockData` byte array, whose length is an `i32`.
+const MAX_VOLUME: u64 = i32::MAX as u64;
+
+/// A cuboid of blocks in the
+/// [Sponge schematic format](https://github.com/SpongePowered/Schematic-Specification).
+///
+/// Blocks are addressed with positions relative to the schematic's minimum
+/// corner, so every component of a position is in `0..width`, `0..height` and
+/// `0..length` respectively.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Schematic {
+    /// Extra data about the schematic, such as its name and author.
+    pub metadata: Option<Compound>,
+    /// The offset of the schematic from the point it was copied at. Not used
+    /// by this crate.
+    pub offset: [i32; 3],
+    width: u16,
+    height: u16,
+    length: u16,
+    blocks: Box<[BlockState]>,
+    block_entities: BTreeMap<BlockPos, Compound>,
+}
+
+/// An error that can occur when loading a [`Schematic`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum LoadSchematicError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Nbt(#[from] valence_server::nbt::binary::Error),
+    #[error("unsupported schematic version {0}")]
+    UnsupportedVersion(i32),
+    #[error("missing or invalid field `{0}`")]
+    InvalidField(&'static str),
+    #[error("invalid block state `{0}` in palette")]
+    InvalidBlockState(String),
+    #[error("block data references palette index {0} which does not exist")]
+    InvalidPaletteIndex(i32),
+    #[error("block data has the wrong length")]
+    BadBlockDataLen,
+    #[error("invalid block entity")]
+    InvalidBlockEntity,
+}
+
+/// An error that can occur when exporting a region into a [`Schematic`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ExportSchematicError {
+    #[error("region is {0} blocks long, but schematics can't be longer than 32767")]
+    RegionTooLarge(i64),
+    #[error("region has {0} blocks, but schematics can't have more than {MAX_VOLUME}")]
+    VolumeTooLarge(u64),
+}
+
+/// An error that can occur when saving a [`Schematic`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SaveSchematicError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Nbt(#[from] valence_server::nbt::binary::Error),
+}
+
+impl Schematic {
+    /// Creates a schematic of the given size filled with air.
+    pub fn new(width: u16, height: u16, length: u16) -> Self {
+        let volume = width as usize * height as usize * length as usize;
+
+        Self {
+            metadata: None,
+            offset: [0; 3],
+            width,
+            height,
+            length,
+            blocks: vec![BlockState::AIR; volume].into(),
+            block_entities: BTreeMap::new(),
+        }
+    }
+
+    /// Copies the blocks and block entities in the cuboid between the corners
+    /// `a` and `b` (inclusive) of `layer` into a new schematic.
+    ///
+    /// Blocks in unloaded chunks or outside the height of the layer are copied
+    /// as air. Block entities whose block can't have one are skipped with a
+    /// warning.
+    ///
+    /// Returns an error if the cuboid is longer than [`i16::MAX`] blocks along
+    /// any axis or has too many blocks to be saved.
+    pub fn export_region(
+        layer: &ChunkLayer,
+        a: impl Into<BlockPos>,
+        b: impl Into<BlockPos>,
+    ) -> Result<Self, ExportSchematicError> {
+        let (a, b) = (a.into(), b.into());
+        let min = BlockPos::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z));
+        let max = BlockPos::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z));
+
+        // Sizes are saved as signed shorts.
+        let size = |min: i32, max: i32| {
+            let len = max as i64 - min as i64 + 1;
+            i16::try_from(len)
+                .map(|len| len as u16)
+                .map_err(|_| ExportSchematicError::RegionTooLarge(len))
+        };
+
+        let (width, height, length) = (
+            size(min.x, max.x)?,
+            size(min.y, max.y)?,
+            size(min.z, max.z)?,
+        );
+
+        let volume = width as u64 * height as u64 * length as u64;
+
+        if volume > MAX_VOLUME {
+            return Err(ExportSchematicError::VolumeTooLarge(volume));
+        }
+
+        let mut schem = Self::new(width, height, length);
+
+        for y in 0..schem.height {
+            for z in 0..schem.length {
+                for x in 0..schem.width {
+                    let offset = BlockPos::new(x.into(), y.into(), z.into());
+
+                    let Some(block) = layer.block(min + offset) else {
+                        continue;
+                    };
+
+                    let idx = schem.index(offset).unwrap();
+                    schem.blocks[idx] = block.state;
+
+                    if let Some(nbt) = block.nbt {
+                        if block.state.block_entity_kind().is_some() {
+                            schem.block_entities.insert(offset, nbt.clone());
+                        } else {
+                            warn!(
+                                "skipping block entity at {} because {} can't have one",
+                                min + offset,
+                                block.state
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(schem)
+    }
+
+    /// Sets the blocks of `layer` in the cuboid starting at `origin` to the
+    /// blocks of this schematic, including air. Blocks that fall outside the
+    /// loaded chunks of the layer are left out.
+    pub fn paste(&self, layer: &mut ChunkLayer, origin: impl Into<BlockPos>) {
+        let origin = origin.into();
+
+        for y in 0..self.height {
+            for z in 0..self.length {
+                for x in 0..self.width {
+                    let offset = BlockPos::new(x.into(), y.into(), z.into());
+                    let block = self.block(offset).unwrap();
+
+                    layer.set_block(origin + offset, Block::new(block.state, block.nbt.cloned()));
+                }
+            }
+        }
+    }
+
+    /// The size of the schematic along the X axis.
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// The size of the schematic along the Y axis.
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// The size of the schematic along the Z axis.
+    pub fn length(&self) -> u16 {
+        self.length
+    }
+
+    /// Gets the block at the given position relative to the minimum corner of
+    /// the schematic. Returns `None` if the position is out of bounds.
+    pub fn block(&self, pos: impl Into<BlockPos>) -> Option<BlockRef<'_>> {
+        let pos = pos.into();
+        let idx = self.index(pos)?;
+
+        Some(BlockRef::new(
+            self.blocks[idx],
+            self.block_entities.get(&pos),
+        ))
+    }
+
+    /// Sets the block at the given position relative to the minimum corner of
+    /// the schematic, returning the previous block. Returns `None` if the
+    /// position is out of bounds.
+    pub fn set_block(&mut self, pos: impl Into<BlockPos>, block: impl IntoBlock) -> Option<Block> {
+        let pos = pos.into();
+        let idx = self.index(pos)?;
+        let block = block.into_block();
+
+        let state = std::mem::replace(&mut self.blocks[idx], block.state);
+        let nbt = match block.nbt {
+            Some(nbt) => self.block_entities.insert(pos, nbt),
+            None => self.block_entities.remove(&pos),
+        };
+
+        Some(Block::new(state, nbt))
+    }
+
+    fn index(&self, pos: BlockPos) -> Option<usize> {
+        let in_bounds = |v: i32, len: u16| (0..len as i32).contains(&v);
+
+        if in_bounds(pos.x, self.width)
+            && in_bounds(pos.y, self.height)
+            && in_bounds(pos.z, self.length)
+        {
+            let (width, length) = (self.width as usize, self.length as usize);
+
+            Some(pos.x as usize + pos.z as usize * width + pos.y as usize * width * length)
+        } else {
+            None
+        }
+    }
+
+    /// Loads a gzip compressed schematic file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LoadSchematicError> {
+        let mut buf = vec![];
+        GzDecoder::new(BufReader::new(File::open(path)?)).read_to_end(&mut buf)?;
+
+        let (nbt, _) = Compound::from_binary(&mut buf.as_slice())?;
+
+        Self::from_nbt(nbt)
+    }
+
+    /// Saves this schematic to a gzip compressed file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SaveSchematicError> {
+        let mut encoder = GzEncoder::new(BufWriter::new(File::create(path)?), Compression::best());
+
+        self.to_nbt().to_binary(&mut encoder, "Schematic")?;
+        encoder.finish()?.flush()?;
+
+        Ok(())
+    }
+
+    /// Reads a schematic from the root compound of a schematic file.
+    pub fn from_nbt(mut nbt: Compound) -> Result<Self, LoadSchematicError> {
+        let Some(Value::Int(version)) = nbt.remove("Version") else {
+            return Err(LoadSchematicError::InvalidField("Version"));
+        };
+
+        if version != SPONGE_VERSION {
+            return Err(LoadSchematicError::UnsupportedVersion(version));
+        }
+
+        let mut size = |name| match nbt.remove(name) {
+            Some(Value::Short(v)) if v >= 0 => Ok(v as u16),
+            _ => Err(LoadSchematicError::InvalidField(name)),
+        };
+
+        let width = size("Width")?;
+        let height = size("Height")?;
+        let length = size("Length")?;
+
+        let metadata = match nbt.remove("Metadata") {
+            Some(Value::Compound(metadata)) => Some(metadata),
+            None => None,
+            Some(_) => return Err(LoadSchematicError::InvalidField("Metadata")),
+        };
+
+        let offset = match nbt.remove("Offset") {
+            Some(Value::IntArray(offset)) => offset
+                .try_into()
+                .map_err(|_| LoadSchematicError::InvalidField("Offset"))?,
+            None => [0; 3],
+            Some(_) => return Err(LoadSchematicError::InvalidField("Offset")),
+        };
+
+        let Some(Value::Compound(palette)) = nbt.remove("Palette") else {
+            return Err(LoadSchematicError::InvalidField("Palette"));
+        };
+
+        let mut states = BTreeMap::new();
+
+        for (name, idx) in palette {
+            let Value::Int(idx) = idx else {
+                return Err(LoadSchematicError::InvalidField("Palette"));
+            };
+
+            let Some(state) = parse_block_state(&name) else {
+                return Err(LoadSchematicError::InvalidBlockState(name));
+            };
+
+            states.insert(idx, state);
+        }
+
+        let Some(Value::ByteArray(data)) = nbt.remove("BlockData") else {
+            return Err(LoadSchematicError::InvalidField("BlockData"));
+        };
+
+        let data: Vec<u8> = data.into_iter().map(|b| b as u8).collect();
+        let mut data = data.as_slice();
+
+        // Decode the block data before allocating anything for the size read from
+        // the file, since the block data is at least as large as the schematic.
+        let mut blocks = vec![];
+
+        while !data.is_empty() {
+            let idx = VarInt::decode_partial(&mut data)
+                .map_err(|_| LoadSchematicError::BadBlockDataLen)?;
+
+            blocks.push(
+                *states
+                    .get(&idx)
+                    .ok_or(LoadSchematicError::InvalidPaletteIndex(idx))?,
+            );
+        }
+
+        if blocks.len() as u64 != width as u64 * height as u64 * length as u64 {
+            return Err(LoadSchematicError::BadBlockDataLen);
+        }
+
+        let mut schem = Self {
+            metadata,
+            offset,
+            width,
+            height,
+            length,
+            blocks: blocks.into(),
+            block_entities: BTreeMap::new(),
+        };
+
+        match nbt.remove("BlockEntities") {
+            Some(Value::List(List::Compound(block_entities))) => {
+                for mut block_entity in block_entities {
+                    let Some(Value::IntArray(pos)) = block_entity.remove("Pos") else {
+                        return Err(LoadSchematicError::InvalidBlockEntity);
+                    };
+
+                    let Ok([x, y, z]) = <[i32; 3]>::try_from(pos) else {
+                        return Err(LoadSchematicError::InvalidBlockEntity);
+                    };
+
+                    let pos = BlockPos::new(x, y, z);
+
+                    if schem.index(pos).is_none() {
+                        return Err(LoadSchematicError::InvalidBlockEntity);
+                    }
+
+                    block_entity.remove("Id");
+
+                    schem.block_entities.insert(pos, block_entity);
+                }
+            }
+            Some(Value::List(List::End)) | None => {}
+            Some(_) => return Err(LoadSchematicError::InvalidField("BlockEntities")),
+        }
+
+        Ok(schem)
+    }
+
+    /// Writes this schematic to the root compound of a schematic file.
+    pub fn to_nbt(&self) -> Compound {
+        let mut palette = Compound::new();
+        let mut indices = BTreeMap::new();
+        let mut data = vec![];
+
+        for &state in self.blocks.iter() {
+            let next_idx = indices.len() as i32;
+
+            let idx = *indices.entry(state).or_insert_with(|| {
+                palette.insert(block_state_name(state), next_idx);
+                next_idx
+            });
+
+            VarInt(idx).encode(&mut data).unwrap();
+        }
+
+        let mut block_entities = vec![];
+
+        for (pos, nbt) in &self.block_entities {
+            let state = self.blocks[self.index(*pos).unwrap()];
+
+            let Some(kind) = state.block_entity_kind() else {
+                warn!("skipping block entity at {pos} because {state} can't have one");
+                continue;
+            };
+
+            let mut block_entity = nbt.clone();
+            block_entity.insert("Pos", vec![pos.x, pos.y, pos.z]);
+            block_entity.insert("Id", kind.ident().to_string());
+
+            block_entities.push(block_entity);
+        }
+
+        let mut nbt = Compound::new();
+
+        nbt.insert("Version", SPONGE_VERSION);
+        nbt.insert("DataVersion", DATA_VERSION);
+        if let Some(metadata) = &self.metadata {
+            nbt.insert("Metadata", metadata.clone());
+        }
+        nbt.insert("Width", self.width as i16);
+        nbt.insert("Height", self.height as i16);
+        nbt.insert("Length", self.length as i16);
+        nbt.insert("Offset", self.offset.to_vec());
+        nbt.insert("PaletteMax", palette.len() as i32);
+        nbt.insert("Palette", palette);
+        nbt.insert(
+            "BlockData",
+            data.into_iter().map(|b| b as i8).collect::<Vec<_>>(),
+        );
+        nbt.insert("BlockEntities", List::Compound(block_entities));
+
+        nbt
+    }
+}
+
+/// Formats a block state like `minecraft:oak_stairs[facing=east,half=top]`.
+fn block_state_name(state: BlockState) -> String {
+    let kind = state.to_kind();
+    let mut name = format!("minecraft:{}", kind.to_str());
+
+    let props = kind.props();
+
+    if !props.is_empty() {
+        name.push('[');
+
+        for (i, &prop) in props.iter().enumerate() {
+            if i != 0 {
+                name.push(',');
+            }
+
+            name.push_str(prop.to_str());
+            name.push('=');
+            name.push_str(state.get(prop).unwrap().to_str());
+        }
+
+        name.push(']');
+    }
+
+    name
+}
+
+/// Parses a block state formatted like `minecraft:oak_stairs[facing=east]`.
+/// Properties that aren't given keep their default value.
+fn parse_block_state(name: &str) -> Option<BlockState> {
+    let (kind, props) = match name.split_once('[') {
+        Some((kind, props)) => (kind, Some(props.strip_suffix(']')?)),
+        None => (name, None),
+    };
+
+    let kind = kind.strip_prefix("minecraft:").unwrap_or(kind);
+    let mut state = BlockKind::from_str(kind)?.to_state();
+
+    for prop in props.into_iter().flat_map(|props| props.split(',')) {
+        let (key, value) = prop.split_once('=')?;
+
+        let key = PropName::from_str(key.trim())?;
+        let value = PropValue::from_str(value.trim())?;
+
+        // Reject properties the block doesn't have.
+        state.get(key)?;
+        state = state.set(key, value);
+    }
+
+    Some(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_state_names_round_trip() {
+        for state in [
+            BlockState::AIR,
+            BlockState::STONE,
+            BlockState::OAK_STAIRS
+                .set(PropName::Facing, PropValue::East)
+                .set(PropName::Half, PropValue::Top),
+            BlockState::CHEST.set(PropName::Waterlogged, PropValue::True),
+        ] {
+            assert_eq!(parse_block_state(&block_state_name(state)), Some(state));
+        }
+
+        assert_eq!(parse_block_state("stone"), Some(BlockState::STONE));
+        assert_eq!(parse_block_state("minecraft:stone[facing=east]"), None);
+        assert_eq!(parse_block_state("minecraft:not_a_block"), None);
+    }
+}
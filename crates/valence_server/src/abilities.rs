@@ -33,13 +33,15 @@ impl Default for FovModifier {
     }
 }
 
-/// Send if the client sends [`UpdatePlayerAbilitiesC2s::StartFlying`]
+/// Send if the client sends [`UpdatePlayerAbilitiesC2s::StartFlying`], which
+/// happens when the player toggles flight on with the jump key.
 #[derive(Event)]
 pub struct PlayerStartFlyingEvent {
     pub client: Entity,
 }
 
-/// Send if the client sends [`UpdatePlayerAbilitiesC2s::StopFlying`]
+/// Send if the client sends [`UpdatePlayerAbilitiesC2s::StopFlying`], which
+/// happens when the player toggles flight off or lands.
 #[derive(Event)]
 pub struct PlayerStopFlyingEvent {
     pub client: Entity,
@@ -51,15 +53,18 @@ pub struct PlayerStopFlyingEvent {
 ///
 /// 2. `update_client_player_abilities`: Watch [`PlayerAbilitiesFlags`],
 /// [`FlyingSpeed`] and [`FovModifier`] changes => Send [`PlayerAbilitiesS2c`]
-/// to update the client's abilities
+/// to update the client's abilities. Clients ignore the `flying` flag without
+/// the `allow_flying` flag, so `allow_flying` is also sent to flying clients.
+/// The [`PlayerAbilitiesFlags`] component itself is left unchanged.
 ///
 /// 3. `update_player_abilities`: Watch
 /// [`GameMode`] changes => Update [`PlayerAbilitiesFlags`] according to the
-/// [`GameMode`]
+/// [`GameMode`], which are then sent by `update_client_player_abilities`
 ///
 /// 4. `update_server_player_abilities`: Watch
 /// [`UpdatePlayerAbilitiesC2s`] packets => Update [`PlayerAbilitiesFlags`]
-/// according to the packet
+/// according to the packet. Clients that start flying without being allowed
+/// to fly are sent their abilities again instead.
 pub struct AbilitiesPlugin;
 
 impl Plugin for AbilitiesPlugin {
@@ -95,23 +100,29 @@ fn update_client_player_abilities(
     >,
 ) {
     for (mut client, flags, flying_speed, fov_modifier) in clients_query.iter_mut() {
+        let mut flags = *flags;
+
+        if flags.flying() {
+            flags.set_allow_flying(true);
+        }
+
         client.write_packet(&PlayerAbilitiesS2c {
-            flags: *flags,
+            flags,
             flying_speed: flying_speed.0,
             fov_modifier: fov_modifier.0,
         })
     }
 }
 
-/// /!\ This system does not trigger change detection on
-/// [`PlayerAbilitiesFlags`]
+/// Triggers change detection on [`PlayerAbilitiesFlags`] so that the client
+/// is sent the abilities of its new [`GameMode`], such as losing the ability
+/// to fly when switching from spectator to survival.
 fn update_player_abilities(
     mut player_start_flying_event_writer: EventWriter<PlayerStartFlyingEvent>,
     mut player_stop_flying_event_writer: EventWriter<PlayerStopFlyingEvent>,
     mut client_query: Query<(Entity, &mut PlayerAbilitiesFlags, &GameMode), Changed<GameMode>>,
 ) {
-    for (entity, mut mut_flags, gamemode) in client_query.iter_mut() {
-        let flags = mut_flags.bypass_change_detection();
+    for (entity, mut flags, gamemode) in client_query.iter_mut() {
         match gamemode {
             GameMode::Creative => {
                 flags.set_invulnerable(true);
@@ -156,6 +167,10 @@ fn update_server_player_abilities(
             if let Ok(mut mut_flags) = client_query.get_mut(packets.client) {
                 let flags = mut_flags.bypass_change_detection();
                 match pkt {
+                    UpdatePlayerAbilitiesC2s::StartFlying if !flags.allow_flying() => {
+                        // Make the client fall back down.
+                        mut_flags.set_changed();
+                    }
                     UpdatePlayerAbilitiesC2s::StartFlying => {
                         flags.set_flying(true);
                         player_start_flying_event_writer.send(PlayerStartFlyingEvent {
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_server_common::Server;
+
+pub struct AutosavePlugin;
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AutosaveSettings>()
+            .add_event::<AutosaveEvent>()
+            .add_systems(PreUpdate, send_autosave_events);
+    }
+}
+
+/// Controls how often an [`AutosaveEvent`] is sent.
+#[derive(Resource, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct AutosaveSettings {
+    /// The time between two autosaves, or `None` to disable autosaving. The
+    /// period is counted in ticks at the server's tick rate, so a slow server
+    /// saves less often in real time. Five minutes by default.
+    pub period: Option<Duration>,
+}
+
+impl Default for AutosaveSettings {
+    fn default() -> Self {
+        Self {
+            period: Some(Duration::from_secs(5 * 60)),
+        }
+    }
+}
+
+impl AutosaveSettings {
+    /// Returns the number of ticks between two autosaves at the given tick
+    /// rate, or `None` if autosaving is disabled. A period shorter than a tick
+    /// saves every tick.
+    pub fn period_ticks(&self, tick_rate: u32) -> Option<i64> {
+        self.period
+            .map(|period| ((period.as_secs_f64() * tick_rate as f64).round() as i64).max(1))
+    }
+}
+
+/// Sent in [`PreUpdate`] whenever the [`AutosaveSettings::period`] has passed.
+///
+/// Valence doesn't save anything by itself. Systems reading this event should
+/// save whatever state they keep. The chunks of a layer that were modified
+/// since they were last saved are found with [`LoadedChunk::is_modified`],
+/// and [`LoadedChunk::mark_saved`] resets them once saved. Chunks removed
+/// from a layer are not tracked, so save them when removing them if needed.
+///
+/// The event is sent in the middle of the tick, so the save itself should not
+/// block for long. Copy the state that needs saving and write it out on
+/// another thread, the way `valence_anvil` loads chunks on its own thread.
+///
+/// [`LoadedChunk::is_modified`]: crate::layer::chunk::LoadedChunk::is_modified
+/// [`LoadedChunk::mark_saved`]: crate::layer::chunk::LoadedChunk::mark_saved
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct AutosaveEvent {
+    /// The tick in which the autosave happened.
+    pub tick: i64,
+}
+
+fn send_autosave_events(
+    server: Res<Server>,
+    settings: Res<AutosaveSettings>,
+    mut last_autosave: Local<Option<i64>>,
+    mut events: EventWriter<AutosaveEvent>,
+) {
+    let tick = server.current_tick();
+
+    let Some(period) = settings.period_ticks(server.tick_rate().get()) else {
+        *last_autosave = None;
+        return;
+    };
+
+    // The period starts when autosaving is enabled.
+    let last = *last_autosave.get_or_insert(tick);
+
+    if tick - last >= period {
+        *last_autosave = Some(tick);
+        events.send(AutosaveEvent { tick });
+    }
+}
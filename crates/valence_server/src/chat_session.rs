@@ -0,0 +1,303 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use rsa::pkcs1v15::{Signature, VerifyingKey};
+use rsa::pkcs8::DecodePublicKey;
+use rsa::signature::Verifier;
+use rsa::RsaPublicKey;
+use sha2::Sha256;
+use tracing::warn;
+use uuid::Uuid;
+use valence_protocol::packets::play::PlayerSessionC2s;
+
+use crate::client::DisconnectClient;
+use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
+
+pub struct ChatSessionPlugin;
+
+impl Plugin for ChatSessionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChatSessionSettings>()
+            .add_event::<ChatSessionEvent>()
+            .add_systems(EventLoopPreUpdate, handle_player_session);
+    }
+}
+
+/// Global configuration for chat sessions sent by clients.
+#[derive(Resource, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct ChatSessionSettings {
+    /// What to do when a client sends a session whose public key has already
+    /// expired.
+    pub expired_key: ExpiredKeyPolicy,
+}
+
+/// How to handle a chat session with an expired public key.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum ExpiredKeyPolicy {
+    /// Discard the session. The client keeps playing without a
+    /// [`ChatSession`].
+    #[default]
+    Ignore,
+    /// Disconnect the client.
+    Disconnect,
+}
+
+/// The chat session of a client, holding the public key it signs chat
+/// messages with.
+///
+/// Clients send their session once they join, and again whenever their key
+/// is refreshed. Only sessions with an unexpired key are stored.
+///
+/// Chat messages are verified against the public key, see
+/// [`ChatMessageEvent::signed`]. The key signature is kept as-is and is not
+/// verified against Mojang's public keys. Read [`ChatSessionEvent`]s to verify
+/// it yourself.
+///
+/// [`ChatMessageEvent::signed`]: crate::message::ChatMessageEvent::signed
+#[derive(Component, Clone, PartialEq, Eq, Debug)]
+pub struct ChatSession {
+    pub session_id: Uuid,
+    /// When the public key expires, in milliseconds since the Unix epoch.
+    pub expires_at: i64,
+    /// The public key in X.509 DER encoding.
+    pub public_key: Box<[u8]>,
+    /// Mojang's signature of the public key.
+    pub key_signature: Box<[u8]>,
+    /// The index of the next message in the session's chain of signed
+    /// messages.
+    message_index: u32,
+}
+
+impl ChatSession {
+    /// Returns the number of signed messages received in this session. The
+    /// index of each message is part of its signature.
+    pub fn message_index(&self) -> u32 {
+        self.message_index
+    }
+
+    /// Returns whether `signature` is a valid signature of a chat message
+    /// made with this session's key.
+    ///
+    /// `sender` is the UUID of the client and `index` is the number of
+    /// messages signed in this session before this one. Returns `false` if the
+    /// key has expired or can't be parsed.
+    pub fn verify_message(
+        &self,
+        sender: Uuid,
+        index: u32,
+        body: &MessageBody,
+        signature: &[u8],
+    ) -> bool {
+        if self.is_expired_at(SystemTime::now()) {
+            return false;
+        }
+
+        let Ok(key) = RsaPublicKey::from_public_key_der(&self.public_key) else {
+            return false;
+        };
+
+        let Ok(signature) = Signature::try_from(signature) else {
+            return false;
+        };
+
+        let mut signed = vec![];
+
+        // Version of the signed data.
+        signed.extend(1_i32.to_be_bytes());
+        // Link to the previous messages of the session.
+        signed.extend(sender.as_bytes());
+        signed.extend(self.session_id.as_bytes());
+        signed.extend(index.to_be_bytes());
+        // Message body.
+        signed.extend(body.salt.to_be_bytes());
+        signed.extend((body.timestamp / 1000).to_be_bytes());
+        signed.extend((body.message.len() as i32).to_be_bytes());
+        signed.extend(body.message.as_bytes());
+        signed.extend((body.last_seen.len() as i32).to_be_bytes());
+        for last_seen in body.last_seen {
+            signed.extend(last_seen);
+        }
+
+        VerifyingKey::<Sha256>::new(key)
+            .verify(&signed, &signature)
+            .is_ok()
+    }
+
+    /// Returns the index of the next signed message and advances it. The
+    /// client advances its own index for every message it signs, so this must
+    /// be called for every signed message, valid or not.
+    pub(crate) fn next_message_index(&mut self) -> u32 {
+        let index = self.message_index;
+        self.message_index = self.message_index.wrapping_add(1);
+        index
+    }
+
+    /// Returns `true` if the public key has expired at `now`.
+    pub fn is_expired_at(&self, now: SystemTime) -> bool {
+        let now = now
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis().min(i64::MAX as u128) as i64);
+
+        self.expires_at <= now
+    }
+}
+
+/// The signed contents of a chat message, see [`ChatSession::verify_message`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct MessageBody<'a> {
+    pub message: &'a str,
+    /// When the message was sent, in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub salt: u64,
+    /// The signatures of the signed chat messages the sender acknowledged
+    /// having seen, oldest first.
+    pub last_seen: &'a [[u8; 256]],
+}
+
+/// Sent when a client sends a chat session with an unexpired key. The
+/// session is inserted on the client entity at the same time.
+#[derive(Event, Clone, PartialEq, Eq, Debug)]
+pub struct ChatSessionEvent {
+    pub client: Entity,
+    pub session: ChatSession,
+}
+
+fn handle_player_session(
+    mut packets: EventReader<PacketEvent>,
+    mut events: EventWriter<ChatSessionEvent>,
+    settings: Res<ChatSessionSettings>,
+    mut commands: Commands,
+) {
+    let now = SystemTime::now();
+
+    for packet in packets.iter() {
+        if let Some(pkt) = packet.decode::<PlayerSessionC2s>() {
+            let session = ChatSession {
+                session_id: pkt.session_id,
+                expires_at: pkt.expires_at,
+                public_key: pkt.public_key_data.0.into(),
+                key_signature: pkt.key_signature.0.into(),
+                message_index: 0,
+            };
+
+            if session.is_expired_at(now) {
+                match settings.expired_key {
+                    ExpiredKeyPolicy::Ignore => {
+                        warn!(
+                            "ignoring expired chat session from client {:?}",
+                            packet.client
+                        );
+                    }
+                    ExpiredKeyPolicy::Disconnect => commands.add(DisconnectClient {
+                        client: packet.client,
+                        reason: "Expired profile public key".into(),
+                    }),
+                }
+
+                continue;
+            }
+
+            commands.entity(packet.client).insert(session.clone());
+
+            events.send(ChatSessionEvent {
+                client: packet.client,
+                session,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rsa::pkcs1v15::SigningKey;
+    use rsa::pkcs8::EncodePublicKey;
+    use rsa::signature::{SignatureEncoding, Signer};
+    use rsa::RsaPrivateKey;
+
+    use super::*;
+
+    #[test]
+    fn verify_chat_message() {
+        let private_key = RsaPrivateKey::new(&mut rand::thread_rng(), 1024).unwrap();
+        let public_key = private_key.to_public_key().to_public_key_der().unwrap();
+
+        let sender = Uuid::from_u128(1);
+        let mut session = ChatSession {
+            session_id: Uuid::from_u128(2),
+            expires_at: i64::MAX,
+            public_key: public_key.as_bytes().into(),
+            key_signature: [].into(),
+            message_index: 0,
+        };
+
+        let seen = [[7; 256]];
+
+        let sign = |index: u32, message: &str, last_seen: &[[u8; 256]]| {
+            let mut signed = vec![];
+            signed.extend(1_i32.to_be_bytes());
+            signed.extend(sender.as_bytes());
+            signed.extend(session.session_id.as_bytes());
+            signed.extend(index.to_be_bytes());
+            signed.extend(42_u64.to_be_bytes());
+            signed.extend(1_700_000_000_u64.to_be_bytes());
+            signed.extend((message.len() as i32).to_be_bytes());
+            signed.extend(message.as_bytes());
+            signed.extend((last_seen.len() as i32).to_be_bytes());
+            for sig in last_seen {
+                signed.extend(sig);
+            }
+
+            SigningKey::<Sha256>::new(private_key.clone())
+                .sign(&signed)
+                .to_vec()
+        };
+
+        let first = sign(0, "hello", &[]);
+        let second = sign(1, "world", &seen);
+
+        let body = |message| MessageBody {
+            message,
+            timestamp: 1_700_000_000_123,
+            salt: 42,
+            last_seen: &[],
+        };
+
+        assert!(session.verify_message(sender, 0, &body("hello"), &first));
+        assert!(session.verify_message(
+            sender,
+            1,
+            &MessageBody {
+                last_seen: &seen,
+                ..body("world")
+            },
+            &second
+        ));
+
+        // The index and the acknowledged messages are part of the signature.
+        assert!(!session.verify_message(sender, 1, &body("hello"), &first));
+        assert!(!session.verify_message(sender, 1, &body("world"), &second));
+
+        // Any change to the message breaks the signature.
+        assert!(!session.verify_message(sender, 0, &body("hellp"), &first));
+        assert!(!session.verify_message(
+            sender,
+            0,
+            &MessageBody {
+                salt: 43,
+                ..body("hello")
+            },
+            &first
+        ));
+        assert!(!session.verify_message(Uuid::from_u128(3), 0, &body("hello"), &first));
+
+        // The index advances with every message.
+        assert_eq!(session.next_message_index(), 0);
+        assert_eq!(session.next_message_index(), 1);
+        assert_eq!(session.message_index(), 2);
+
+        // Expired keys don't verify anything.
+        session.expires_at = 0;
+        assert!(!session.verify_message(sender, 0, &body("hello"), &first));
+    }
+}
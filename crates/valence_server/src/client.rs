@@ -14,10 +14,11 @@ use derive_more::{Deref, DerefMut, From, Into};
 use tracing::warn;
 use uuid::Uuid;
 use valence_entity::player::PlayerEntityBundle;
-use valence_entity::query::EntityInitQuery;
+use valence_entity::query::{EntityInitQuery, EntityInitQueryItem};
 use valence_entity::tracked_data::TrackedData;
 use valence_entity::{
-    ClearEntityChangesSet, EntityId, EntityStatus, OldPosition, Position, Velocity,
+    ClearEntityChangesSet, EntityId, EntityLayerId, EntityStatus, OldEntityLayerId, OldPosition,
+    Passengers, Position, Velocity,
 };
 use valence_math::{DVec3, Vec3};
 use valence_protocol::encode::{PacketEncoder, WritePacket};
@@ -26,15 +27,17 @@ use valence_protocol::packets::play::game_state_change_s2c::GameEventKind;
 use valence_protocol::packets::play::particle_s2c::Particle;
 use valence_protocol::packets::play::{
     ChunkBiomeDataS2c, ChunkLoadDistanceS2c, ChunkRenderDistanceCenterS2c, DeathMessageS2c,
-    DisconnectS2c, EntitiesDestroyS2c, EntityStatusS2c, EntityTrackerUpdateS2c,
-    EntityVelocityUpdateS2c, GameStateChangeS2c, ParticleS2c, PlaySoundS2c, UnloadChunkS2c,
+    DisconnectS2c, EntitiesDestroyS2c, EntityDamageS2c, EntityStatusS2c, EntityTrackerUpdateS2c,
+    EntityVelocityUpdateS2c, GameStateChangeS2c, ParticleS2c, PlaySoundFromEntityS2c, PlaySoundS2c,
+    ServerMetadataS2c, StopSoundS2c, UnloadChunkS2c,
 };
 use valence_protocol::profile::Property;
 use valence_protocol::sound::{Sound, SoundCategory, SoundId};
 use valence_protocol::text::{IntoText, Text};
 use valence_protocol::var_int::VarInt;
-use valence_protocol::{BlockPos, ChunkPos, Encode, GameMode, Packet};
-use valence_registry::RegistrySet;
+use valence_protocol::{BlockPos, ChunkPos, Encode, GameMode, Ident, Packet};
+use valence_registry::damage_type::DamageTypeId;
+use valence_registry::{RegistryIdx, RegistrySet};
 use valence_server_common::{Despawned, UniqueId};
 
 use crate::layer::{ChunkLayer, EntityLayer, UpdateLayersPostClientSet, UpdateLayersPreClientSet};
@@ -65,12 +68,19 @@ impl Plugin for ClientPlugin {
             PostUpdate,
             (
                 (
-                    crate::spawn::initial_join.after(RegistrySet),
+                    crate::spawn::apply_layer_reduced_debug_info,
+                    crate::spawn::initial_join
+                        .after(RegistrySet)
+                        .after(crate::spawn::apply_layer_reduced_debug_info),
+                    crate::spawn::update_reduced_debug_info.after(crate::spawn::initial_join),
+                    crate::spawn::update_has_respawn_screen.after(crate::spawn::initial_join),
+                    crate::spawn::update_difficulty.after(crate::spawn::initial_join),
                     update_chunk_load_dist,
                     handle_layer_messages.after(update_chunk_load_dist),
+                    update_hidden_entities.after(handle_layer_messages),
                     update_view_and_layers
                         .after(crate::spawn::initial_join)
-                        .after(handle_layer_messages),
+                        .after(update_hidden_entities),
                     cleanup_chunks_after_client_despawn.after(update_view_and_layers),
                     crate::spawn::update_respawn_position.after(update_view_and_layers),
                     crate::spawn::respawn.after(crate::spawn::update_respawn_position),
@@ -83,6 +93,11 @@ impl Plugin for ClientPlugin {
                 flush_packets.in_set(FlushPacketsSet),
             ),
         )
+        .add_systems(
+            PreUpdate,
+            crate::spawn::apply_default_game_mode.after(SpawnClientsSet),
+        )
+        .init_resource::<crate::spawn::DifficultySettings>()
         .configure_set(PreUpdate, SpawnClientsSet)
         .configure_sets(
             PostUpdate,
@@ -109,18 +124,26 @@ pub struct ClientBundle {
     pub username: Username,
     pub ip: Ip,
     pub properties: Properties,
+    pub transferred: Transferred,
     pub respawn_pos: crate::spawn::RespawnPosition,
     pub op_level: crate::op_level::OpLevel,
     pub action_sequence: crate::action::ActionSequence,
+    pub interact_cooldowns: crate::interact_cooldown::InteractCooldowns,
+    pub command_suggestions_rate_limit: crate::command_suggestions::CommandSuggestionsRateLimit,
+    pub message_acknowledgments: crate::message::MessageAcknowledgments,
+    pub recipe_book: crate::recipe_book::RecipeBook,
     pub view_distance: ViewDistance,
     pub old_view_distance: OldViewDistance,
     pub visible_chunk_layer: VisibleChunkLayer,
     pub old_visible_chunk_layer: OldVisibleChunkLayer,
     pub visible_entity_layers: VisibleEntityLayers,
     pub old_visible_entity_layers: OldVisibleEntityLayers,
+    pub hidden_entities: HiddenEntities,
+    pub old_hidden_entities: OldHiddenEntities,
     pub keepalive_state: crate::keepalive::KeepaliveState,
     pub ping: crate::keepalive::Ping,
     pub teleport_state: crate::teleport::TeleportState,
+    pub awaiting_initial_teleport: crate::teleport::AwaitingInitialTeleport,
     pub game_mode: GameMode,
     pub prev_game_mode: crate::spawn::PrevGameMode,
     pub death_location: crate::spawn::DeathLocation,
@@ -150,18 +173,26 @@ impl ClientBundle {
             username: Username(args.username),
             ip: Ip(args.ip),
             properties: Properties(args.properties),
+            transferred: Transferred(args.transferred),
             respawn_pos: Default::default(),
             op_level: Default::default(),
             action_sequence: Default::default(),
+            interact_cooldowns: Default::default(),
+            command_suggestions_rate_limit: Default::default(),
+            message_acknowledgments: Default::default(),
+            recipe_book: Default::default(),
             view_distance: Default::default(),
             old_view_distance: OldViewDistance(2),
             visible_chunk_layer: Default::default(),
             old_visible_chunk_layer: OldVisibleChunkLayer(Entity::PLACEHOLDER),
             visible_entity_layers: Default::default(),
             old_visible_entity_layers: OldVisibleEntityLayers(BTreeSet::new()),
+            hidden_entities: Default::default(),
+            old_hidden_entities: Default::default(),
             keepalive_state: crate::keepalive::KeepaliveState::new(),
             ping: Default::default(),
             teleport_state: crate::teleport::TeleportState::new(),
+            awaiting_initial_teleport: Default::default(),
             game_mode: GameMode::default(),
             prev_game_mode: Default::default(),
             death_location: Default::default(),
@@ -193,6 +224,8 @@ pub struct ClientBundleArgs {
     pub ip: IpAddr,
     /// Properties of this client from the game profile.
     pub properties: Vec<Property>,
+    /// Whether the client was transferred from another server.
+    pub transferred: bool,
     /// The abstract socket connection.
     pub conn: Box<dyn ClientConnection>,
     /// The packet encoder to use. This should be in sync with [`Self::conn`].
@@ -280,6 +313,11 @@ impl Client {
     /// is dropped. Unless you're in a hurry, there's usually no reason to
     /// call this method yourself.
     ///
+    /// Packets written during a tick are buffered and handed to the connection
+    /// all at once, so a tick's worth of packets usually ends up in a single
+    /// write to the socket. Calling this method mid-tick sends the packets
+    /// buffered so far right away, at the cost of an extra write.
+    ///
     /// Returns an error if flushing was unsuccessful.
     pub fn flush_packets(&mut self) -> anyhow::Result<()> {
         let bytes = self.enc.take();
@@ -290,8 +328,16 @@ impl Client {
         }
     }
 
-    /// Kills the client and shows `message` on the death screen. If an entity
-    /// killed the player, you should supply it as `killer`.
+    /// Kills the client and shows `message` on the death screen.
+    ///
+    /// When the client clicks the respawn button, a
+    /// [`RequestRespawnEvent`](crate::status::RequestRespawnEvent) is sent. If
+    /// the client's [`HasRespawnScreen`] is `false`, it skips the death screen
+    /// and the event is sent right away. To respawn the client, modify its
+    /// [`VisibleChunkLayer`], marking it as changed if it stays in the same
+    /// layer, and move it to its respawn position.
+    ///
+    /// [`HasRespawnScreen`]: crate::spawn::HasRespawnScreen
     pub fn kill<'a>(&mut self, message: impl IntoText<'a>) {
         self.write_packet(&DeathMessageS2c {
             player_id: VarInt(0),
@@ -308,6 +354,15 @@ impl Client {
     }
 
     /// Puts a particle effect at the given position, only for this client.
+    ///
+    /// `count` particles are spawned at random positions around `position`,
+    /// spread out by `offset`. If `count` is zero, a single particle is spawned
+    /// and `offset` multiplied by `max_speed` is used as its velocity instead.
+    /// A negative `count` is treated as zero.
+    ///
+    /// Clients don't show particles more than 32 blocks away from the camera.
+    /// If `long_distance` is `true`, this limit and the client's particle
+    /// setting are ignored.
     pub fn play_particle(
         &mut self,
         particle: &Particle,
@@ -323,7 +378,7 @@ impl Client {
             position: position.into(),
             offset: offset.into(),
             max_speed,
-            count,
+            count: count.max(0),
         })
     }
 
@@ -336,18 +391,77 @@ impl Client {
         volume: f32,
         pitch: f32,
     ) {
-        let position = position.into();
-
-        self.write_packet(&PlaySoundS2c {
-            id: SoundId::Direct {
+        self.play_sound_id(
+            SoundId::Direct {
                 id: sound.to_ident().into(),
                 range: None,
             },
             category,
+            position,
+            volume,
+            pitch,
+            rand::random(),
+        );
+    }
+
+    /// Plays any sound effect at the given position, only for this client.
+    /// Unlike [`Self::play_sound`], the sound can be a custom one from a
+    /// resource pack by using [`SoundId::Direct`] with its identifier.
+    ///
+    /// `seed` chooses between the variants of the sound, so playing a sound
+    /// with the same seed always plays the same variant.
+    pub fn play_sound_id(
+        &mut self,
+        id: SoundId,
+        category: SoundCategory,
+        position: impl Into<DVec3>,
+        volume: f32,
+        pitch: f32,
+        seed: i64,
+    ) {
+        let position = position.into();
+
+        self.write_packet(&PlaySoundS2c {
+            id,
+            category,
             position: (position * 8.0).as_ivec3(),
             volume,
             pitch,
-            seed: rand::random(),
+            seed,
+        });
+    }
+
+    /// Like [`Self::play_sound_id`], but the sound is attached to the entity
+    /// with the given [`EntityId`] and follows it as it moves.
+    pub fn play_sound_id_from_entity(
+        &mut self,
+        entity_id: EntityId,
+        id: SoundId,
+        category: SoundCategory,
+        volume: f32,
+        pitch: f32,
+        seed: i64,
+    ) {
+        self.write_packet(&PlaySoundFromEntityS2c {
+            id,
+            category,
+            entity_id: VarInt(entity_id.get()),
+            volume,
+            pitch,
+            seed,
+        });
+    }
+
+    /// Stops sounds that are playing for this client.
+    ///
+    /// If `category` is `Some`, only sounds in that category are stopped. If
+    /// `sound` is `Some`, only the sound with that identifier is stopped, which
+    /// can be a [`Sound`] from [`Sound::to_ident`] or a custom sound from a
+    /// resource pack. Passing `None` for both stops all sounds.
+    pub fn stop_sound(&mut self, category: Option<SoundCategory>, sound: Option<Ident<&str>>) {
+        self.write_packet(&StopSoundS2c {
+            source: category,
+            sound: sound.map(Into::into),
         });
     }
 
@@ -359,6 +473,73 @@ impl Client {
         });
     }
 
+    /// Launches the client with the given velocity in m/s. Useful for jump
+    /// pads, knockback or double jumps.
+    ///
+    /// Unlike [`Self::set_velocity`], each component is clamped to the range
+    /// the client accepts (±3.9 blocks per tick) and rounded to the nearest
+    /// packet unit instead of being truncated. Components smaller than the
+    /// client's movement threshold of 0.003 blocks per tick would be discarded
+    /// by the client anyway and are sent as zero.
+    pub fn launch(&mut self, velocity: impl Into<Vec3>) {
+        /// Maximum velocity in m/s the client accepts.
+        const MAX: f32 = 3.9 * 20.0;
+        /// Minimum velocity in m/s the client doesn't discard.
+        const MIN: f32 = 0.003 * 20.0;
+
+        let velocity = velocity.into().to_array().map(|v| {
+            if v.abs() < MIN {
+                0
+            } else {
+                // Packet units are 1/8000 of a block per tick.
+                (v.clamp(-MAX, MAX) * 8000.0 / 20.0).round() as i16
+            }
+        });
+
+        self.write_packet(&EntityVelocityUpdateS2c {
+            entity_id: VarInt(0),
+            velocity: valence_protocol::Velocity(velocity),
+        });
+    }
+
+    /// Knocks the client back away from `source`, such as the position of an
+    /// attacking player, the way vanilla does. `position` is the client's own
+    /// [`Position`].
+    ///
+    /// Vanilla attacks use a `strength` of `0.4`. If `sprint_hit` is `true`,
+    /// the extra knockback of a sprinting attack is added. The client is
+    /// assumed to be standing still on the ground, since the server doesn't
+    /// know its exact velocity.
+    pub fn apply_knockback(
+        &mut self,
+        position: impl Into<DVec3>,
+        source: impl Into<DVec3>,
+        strength: f32,
+        sprint_hit: bool,
+    ) {
+        /// The knockback strength added by sprinting attacks.
+        const SPRINT_BONUS: f32 = 0.5;
+
+        let dir = (source.into() - position.into()).as_vec3();
+        let dir = Vec3::new(dir.x, 0.0, dir.z).normalize_or_zero();
+
+        // In blocks per tick, as computed by the vanilla server. Each knockback
+        // halves the horizontal velocity from the previous one.
+        let mut velocity = Vec3::ZERO;
+
+        for strength in [strength, if sprint_hit { SPRINT_BONUS } else { 0.0 }] {
+            if strength > 0.0 {
+                velocity = Vec3::new(
+                    velocity.x / 2.0 - dir.x * strength,
+                    (velocity.y / 2.0 + strength).min(0.4),
+                    velocity.z / 2.0 - dir.z * strength,
+                );
+            }
+        }
+
+        self.launch(velocity * 20.0);
+    }
+
     /// Triggers an [`EntityStatus`].
     ///
     /// The status is only visible to this client.
@@ -368,6 +549,46 @@ impl Client {
             entity_status: status as u8,
         });
     }
+
+    /// Plays the hurt animation and sound of the given damage type for the
+    /// client. `source_pos` is the position the damage came from, if any.
+    /// This does not change the client's health.
+    ///
+    /// The direction the screen tilts in is not affected by `source_pos`.
+    /// Send a [`DamageTiltS2c`] for that.
+    ///
+    /// Damage type IDs are looked up by name in the
+    /// [`DamageTypeRegistry`](valence_registry::DamageTypeRegistry).
+    ///
+    /// [`DamageTiltS2c`]: valence_protocol::packets::play::DamageTiltS2c
+    pub fn take_damage(&mut self, source_pos: Option<DVec3>, damage_type: DamageTypeId) {
+        self.write_packet(&EntityDamageS2c {
+            entity_id: VarInt(0),
+            source_type_id: VarInt(damage_type.to_index() as i32),
+            source_cause_id: VarInt(0),
+            source_direct_id: VarInt(0),
+            source_pos,
+        });
+    }
+
+    /// Sets the server information shown in the pause menu and the social
+    /// interactions screen.
+    ///
+    /// `icon` is the raw bytes of a 64x64 PNG image, not base64 encoded as in
+    /// the server list. `enforces_secure_chat` should match
+    /// [`ChatSettings`](crate::message::ChatSettings).
+    pub fn set_server_data<'a>(
+        &mut self,
+        motd: impl IntoText<'a>,
+        icon: Option<&[u8]>,
+        enforces_secure_chat: bool,
+    ) {
+        self.write_packet(&ServerMetadataS2c {
+            motd: motd.into_cow_text(),
+            icon,
+            enforce_secure_chat: enforces_secure_chat,
+        });
+    }
 }
 
 /// A [`Command`] to disconnect a [`Client`] with a displayed reason.
@@ -455,6 +676,12 @@ pub struct PropertyValue {
 #[derive(Component, Clone, PartialEq, Eq, Debug, Deref)]
 pub struct Ip(pub IpAddr);
 
+/// Whether the client was transferred to this server by another one, as
+/// opposed to connecting on its own. Transferred players may want to skip
+/// things like an introduction they've already seen.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug, Deref)]
+pub struct Transferred(pub bool);
+
 #[derive(Component, Clone, PartialEq, Eq, Debug, Deref)]
 pub struct ViewDistance(u8);
 
@@ -521,6 +748,13 @@ impl OldViewItem<'_> {
 ///
 /// A client can only see one chunk layer at a time. Mutating this component
 /// will cause the client to respawn in the new chunk layer.
+///
+/// This is also how clients change dimensions: the client is respawned in the
+/// dimension type of the new layer, the chunks of the old layer are unloaded
+/// and those of the new layer are sent, all without reconnecting. Switching to
+/// a layer of the same dimension type works the same way. The client's
+/// [`EntityLayerId`] and [`VisibleEntityLayers`] are not changed, so they
+/// should usually be updated along with this component.
 #[derive(Component, Copy, Clone, PartialEq, Eq, Debug, Deref, DerefMut)]
 pub struct VisibleChunkLayer(pub Entity);
 
@@ -560,6 +794,29 @@ impl OldVisibleEntityLayers {
     }
 }
 
+/// A [`Component`] containing the set of Minecraft entities hidden from a
+/// client, such as vanished moderators.
+///
+/// Hidden entities are not spawned for the client and none of their packets
+/// are sent to it, even while they are in one of the client's
+/// [`VisibleEntityLayers`] and in view. Other clients are unaffected.
+///
+/// This set can be mutated at any time. Entities that become hidden are
+/// despawned for the client, and entities that are shown again are spawned
+/// at their current position if they would otherwise be visible.
+#[derive(Component, Default, Debug)]
+pub struct HiddenEntities(pub BTreeSet<Entity>);
+
+/// The value of [`HiddenEntities`] from the end of the previous tick.
+#[derive(Component, Default, Debug, Deref)]
+pub struct OldHiddenEntities(BTreeSet<Entity>);
+
+impl OldHiddenEntities {
+    pub fn get(&self) -> &BTreeSet<Entity> {
+        &self.0
+    }
+}
+
 /// A system for adding [`Despawned`] components to disconnected clients. This
 /// works by listening for removed [`Client`] components.
 pub fn despawn_disconnected_clients(
@@ -601,10 +858,13 @@ fn handle_layer_messages(
         &OldVisibleChunkLayer,
         &mut VisibleEntityLayers,
         &OldVisibleEntityLayers,
+        &HiddenEntities,
+        &OldHiddenEntities,
     )>,
     chunk_layers: Query<&ChunkLayer>,
     entity_layers: Query<&EntityLayer>,
     entities: Query<(EntityInitQuery, &OldPosition)>,
+    vehicles: Query<(&EntityId, &Passengers)>,
 ) {
     clients.par_iter_mut().for_each_mut(
         |(
@@ -616,10 +876,19 @@ fn handle_layer_messages(
             old_visible_chunk_layer,
             mut visible_entity_layers,
             old_visible_entity_layers,
+            hidden_entities,
+            old_hidden_entities,
         )| {
             let block_pos = BlockPos::from_pos(old_view.old_pos.get());
             let old_view = old_view.get();
 
+            // Entities hidden now or at the end of the last tick are neither spawned nor
+            // updated. `update_hidden_entities` takes care of the entities whose visibility
+            // changed this tick.
+            let is_hidden = |entity| {
+                hidden_entities.0.contains(&entity) || old_hidden_entities.0.contains(&entity)
+            };
+
             fn in_radius(p0: BlockPos, p1: BlockPos, radius_squared: u32) -> bool {
                 let dist_squared =
                     (p1.x - p0.x).pow(2) + (p1.y - p0.y).pow(2) + (p1.z - p0.z).pow(2);
@@ -739,8 +1008,14 @@ fn handle_layer_messages(
 
                     // Local messages
                     messages.query_local(old_view, |msg, range| match msg {
-                        crate::layer::entity::LocalMsg::DespawnEntity { pos: _, dest_layer } => {
-                            if !old_visible_entity_layers.0.contains(&dest_layer) {
+                        crate::layer::entity::LocalMsg::DespawnEntity {
+                            pos: _,
+                            dest_layer,
+                            dest_pos,
+                        } => {
+                            if !old_visible_entity_layers.0.contains(&dest_layer)
+                                || !old_view.contains(dest_pos)
+                            {
                                 let mut bytes = &bytes[range];
 
                                 while let Ok(id) = bytes.read_i32::<NativeEndian>() {
@@ -764,21 +1039,33 @@ fn handle_layer_messages(
                                 }
                             }
                         }
-                        crate::layer::entity::LocalMsg::SpawnEntity { pos: _, src_layer } => {
-                            if !old_visible_entity_layers.0.contains(&src_layer) {
+                        crate::layer::entity::LocalMsg::SpawnEntity {
+                            pos: _,
+                            src_layer,
+                            src_pos,
+                        } => {
+                            if !old_visible_entity_layers.0.contains(&src_layer)
+                                || !old_view.contains(src_pos)
+                            {
                                 let mut bytes = &bytes[range];
 
                                 while let Ok(u64) = bytes.read_u64::<NativeEndian>() {
                                     let entity = Entity::from_bits(u64);
 
-                                    if self_entity != entity {
+                                    if self_entity != entity && !is_hidden(entity) {
                                         if let Ok((init, old_pos)) = entities.get(entity) {
                                             remove_buf.send_and_clear(&mut *client);
 
                                             // Spawn at the entity's old position since we may get a
                                             // relative movement packet for this entity in a later
                                             // iteration of the loop.
-                                            init.write_init_packets(old_pos.get(), &mut *client);
+                                            write_entity_init_packets(
+                                                &init,
+                                                old_pos.get(),
+                                                *self_entity_id,
+                                                &vehicles,
+                                                &mut client,
+                                            );
                                         }
                                     }
                                 }
@@ -794,14 +1081,20 @@ fn handle_layer_messages(
                                 while let Ok(u64) = bytes.read_u64::<NativeEndian>() {
                                     let entity = Entity::from_bits(u64);
 
-                                    if self_entity != entity {
+                                    if self_entity != entity && !is_hidden(entity) {
                                         if let Ok((init, old_pos)) = entities.get(entity) {
                                             remove_buf.send_and_clear(&mut *client);
 
                                             // Spawn at the entity's old position since we may get a
                                             // relative movement packet for this entity in a later
                                             // iteration of the loop.
-                                            init.write_init_packets(old_pos.get(), &mut *client);
+                                            write_entity_init_packets(
+                                                &init,
+                                                old_pos.get(),
+                                                *self_entity_id,
+                                                &vehicles,
+                                                &mut client,
+                                            );
                                         }
                                     }
                                 }
@@ -815,6 +1108,24 @@ fn handle_layer_messages(
                                 client.write_packet_bytes(&bytes[range]);
                             }
                         }
+                        crate::layer::entity::LocalMsg::EntityPacketAt { pos: _, entity } => {
+                            if self_entity != entity && !is_hidden(entity) {
+                                client.write_packet_bytes(&bytes[range]);
+                            }
+                        }
+                        crate::layer::entity::LocalMsg::PassengersAt { pos: _, vehicle } => {
+                            if !is_hidden(vehicle) {
+                                if let Ok((vehicle, _)) = entities.get(vehicle) {
+                                    if let Some(passengers) = vehicle.passengers {
+                                        passengers.write_packet(
+                                            *vehicle.entity_id,
+                                            *self_entity_id,
+                                            &mut *client,
+                                        );
+                                    }
+                                }
+                            }
+                        }
                         crate::layer::entity::LocalMsg::RadiusAt {
                             center,
                             radius_squared,
@@ -842,16 +1153,35 @@ fn handle_layer_messages(
     );
 }
 
+/// Writes the packets to initialize an entity for the client whose own entity
+/// has the ID `viewer`, along with the passengers of the vehicle it rides.
+fn write_entity_init_packets(
+    init: &EntityInitQueryItem,
+    pos: DVec3,
+    viewer: EntityId,
+    vehicles: &Query<(&EntityId, &Passengers)>,
+    client: &mut Client,
+) {
+    let vehicle = init
+        .vehicle
+        .and_then(|vehicle| vehicles.get(vehicle.get()).ok())
+        .map(|(id, passengers)| (*id, passengers));
+
+    init.write_init_packets(pos, viewer, vehicle, client);
+}
+
 pub(crate) fn update_view_and_layers(
     mut clients: Query<
         (
             Entity,
+            &EntityId,
             &mut Client,
             &mut EntityRemoveBuf,
             &VisibleChunkLayer,
             &mut OldVisibleChunkLayer,
             Ref<VisibleEntityLayers>,
             &mut OldVisibleEntityLayers,
+            &HiddenEntities,
             &Position,
             &OldPosition,
             &ViewDistance,
@@ -868,16 +1198,19 @@ pub(crate) fn update_view_and_layers(
     entity_layers: Query<&EntityLayer>,
     entity_ids: Query<&EntityId>,
     entity_init: Query<(EntityInitQuery, &Position)>,
+    vehicles: Query<(&EntityId, &Passengers)>,
 ) {
     clients.par_iter_mut().for_each_mut(
         |(
             self_entity,
+            self_entity_id,
             mut client,
             mut remove_buf,
             chunk_layer,
             mut old_chunk_layer,
             visible_entity_layers,
             mut old_visible_entity_layers,
+            hidden_entities,
             pos,
             old_pos,
             view_dist,
@@ -886,6 +1219,11 @@ pub(crate) fn update_view_and_layers(
             let view = ChunkView::new(ChunkPos::from_pos(pos.0), view_dist.0);
             let old_view = ChunkView::new(ChunkPos::from_pos(old_pos.get()), old_view_dist.0);
 
+            // Whether `entity` is spawned for the client while it is in view.
+            // `update_hidden_entities` has already brought the old view up to date with
+            // the hidden entities.
+            let can_see = |entity| self_entity != entity && !hidden_entities.0.contains(&entity);
+
             // Make sure the center chunk is set before loading chunks! Otherwise the client
             // may ignore the chunk.
             if old_view.pos != view.pos {
@@ -924,7 +1262,7 @@ pub(crate) fn update_view_and_layers(
                     if let Ok(layer) = entity_layers.get(layer) {
                         for pos in old_view.iter() {
                             for entity in layer.entities_at(pos) {
-                                if self_entity != entity {
+                                if can_see(entity) {
                                     if let Ok(id) = entity_ids.get(entity) {
                                         remove_buf.push(id.get());
                                     }
@@ -941,9 +1279,15 @@ pub(crate) fn update_view_and_layers(
                     if let Ok(layer) = entity_layers.get(layer) {
                         for pos in view.iter() {
                             for entity in layer.entities_at(pos) {
-                                if self_entity != entity {
+                                if can_see(entity) {
                                     if let Ok((init, pos)) = entity_init.get(entity) {
-                                        init.write_init_packets(pos.get(), &mut *client);
+                                        write_entity_init_packets(
+                                            &init,
+                                            pos.get(),
+                                            *self_entity_id,
+                                            &vehicles,
+                                            &mut client,
+                                        );
                                     }
                                 }
                             }
@@ -961,7 +1305,7 @@ pub(crate) fn update_view_and_layers(
                         if let Ok(layer) = entity_layers.get(layer) {
                             for pos in old_view.iter() {
                                 for entity in layer.entities_at(pos) {
-                                    if self_entity != entity {
+                                    if can_see(entity) {
                                         if let Ok(id) = entity_ids.get(entity) {
                                             remove_buf.push(id.get());
                                         }
@@ -981,9 +1325,15 @@ pub(crate) fn update_view_and_layers(
                         if let Ok(layer) = entity_layers.get(layer) {
                             for pos in old_view.iter() {
                                 for entity in layer.entities_at(pos) {
-                                    if self_entity != entity {
+                                    if can_see(entity) {
                                         if let Ok((init, pos)) = entity_init.get(entity) {
-                                            init.write_init_packets(pos.get(), &mut *client);
+                                            write_entity_init_packets(
+                                                &init,
+                                                pos.get(),
+                                                *self_entity_id,
+                                                &vehicles,
+                                                &mut client,
+                                            );
                                         }
                                     }
                                 }
@@ -1023,7 +1373,7 @@ pub(crate) fn update_view_and_layers(
                         if let Ok(layer) = entity_layers.get(layer) {
                             for pos in old_view.diff(view) {
                                 for entity in layer.entities_at(pos) {
-                                    if self_entity != entity {
+                                    if can_see(entity) {
                                         if let Ok(id) = entity_ids.get(entity) {
                                             remove_buf.push(id.get());
                                         }
@@ -1038,9 +1388,15 @@ pub(crate) fn update_view_and_layers(
                         if let Ok(layer) = entity_layers.get(layer) {
                             for pos in view.diff(old_view) {
                                 for entity in layer.entities_at(pos) {
-                                    if self_entity != entity {
+                                    if can_see(entity) {
                                         if let Ok((init, pos)) = entity_init.get(entity) {
-                                            init.write_init_packets(pos.get(), &mut *client);
+                                            write_entity_init_packets(
+                                                &init,
+                                                pos.get(),
+                                                *self_entity_id,
+                                                &vehicles,
+                                                &mut client,
+                                            );
                                         }
                                     }
                                 }
@@ -1063,6 +1419,94 @@ pub(crate) fn update_view_and_layers(
     );
 }
 
+/// Despawns entities that were hidden from clients this tick and spawns the
+/// ones that were shown again.
+fn update_hidden_entities(
+    mut clients: Query<
+        (
+            Entity,
+            &EntityId,
+            &mut Client,
+            &mut EntityRemoveBuf,
+            &HiddenEntities,
+            &mut OldHiddenEntities,
+            &OldVisibleEntityLayers,
+            OldView,
+        ),
+        Changed<HiddenEntities>,
+    >,
+    entities: Query<(
+        EntityInitQuery,
+        &Position,
+        &OldPosition,
+        &EntityLayerId,
+        &OldEntityLayerId,
+    )>,
+    vehicles: Query<(&EntityId, &Passengers)>,
+) {
+    for (
+        self_entity,
+        self_entity_id,
+        mut client,
+        mut remove_buf,
+        hidden,
+        mut old_hidden,
+        old_visible_layers,
+        old_view,
+    ) in &mut clients
+    {
+        // This runs after the layer messages and before the view is updated, so the
+        // client's old view is the one its entities were spawned in.
+        let view = old_view.get();
+
+        let in_view = |layer: Entity, pos: DVec3| {
+            old_visible_layers.0.contains(&layer) && view.contains(ChunkPos::from_pos(pos))
+        };
+
+        // Hidden entities were never spawned for clients that just joined.
+        let newly_hidden = if client.is_added() {
+            Default::default()
+        } else {
+            hidden
+                .0
+                .difference(&old_hidden.0)
+                .copied()
+                .collect::<Vec<_>>()
+        };
+
+        for entity in newly_hidden {
+            if let Ok((init, pos, old_pos, layer_id, old_layer_id)) = entities.get(entity) {
+                // The entity is only spawned if the client was sent it at its old position
+                // and the layer messages did not despawn it at its new one.
+                if self_entity != entity
+                    && in_view(old_layer_id.get(), old_pos.get())
+                    && in_view(layer_id.0, pos.0)
+                {
+                    remove_buf.push(init.entity_id.get());
+                }
+            }
+        }
+
+        remove_buf.send_and_clear(&mut *client);
+
+        for &entity in old_hidden.0.difference(&hidden.0) {
+            if let Ok((init, pos, _, layer_id, _)) = entities.get(entity) {
+                if self_entity != entity && in_view(layer_id.0, pos.0) {
+                    write_entity_init_packets(
+                        &init,
+                        pos.get(),
+                        *self_entity_id,
+                        &vehicles,
+                        &mut client,
+                    );
+                }
+            }
+        }
+
+        old_hidden.0.clone_from(&hidden.0);
+    }
+}
+
 pub(crate) fn update_game_mode(mut clients: Query<(&mut Client, &GameMode), Changed<GameMode>>) {
     for (mut client, game_mode) in &mut clients {
         if client.is_added() {
@@ -15,6 +15,7 @@ impl Plugin for ClientCommandPlugin {
             .add_event::<SneakEvent>()
             .add_event::<JumpWithHorseEvent>()
             .add_event::<LeaveBedEvent>()
+            .add_event::<OpenHorseInventoryEvent>()
             .add_systems(EventLoopPreUpdate, handle_client_command);
     }
 }
@@ -63,6 +64,13 @@ pub struct LeaveBedEvent {
     pub client: Entity,
 }
 
+/// Sent when a client riding a horse, donkey, mule or llama presses the
+/// inventory key, requesting to open the inventory of its mount.
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct OpenHorseInventoryEvent {
+    pub client: Entity,
+}
+
 fn handle_client_command(
     mut packets: EventReader<PacketEvent>,
     mut clients: Query<(&mut entity::Pose, &mut Flags)>,
@@ -70,6 +78,7 @@ fn handle_client_command(
     mut sneaking_events: EventWriter<SneakEvent>,
     mut jump_with_horse_events: EventWriter<JumpWithHorseEvent>,
     mut leave_bed_events: EventWriter<LeaveBedEvent>,
+    mut open_horse_inventory_events: EventWriter<OpenHorseInventoryEvent>,
 ) {
     for packet in packets.iter() {
         if let Some(pkt) = packet.decode::<ClientCommandC2s>() {
@@ -123,7 +132,7 @@ fn handle_client_command(
                     jump_with_horse_events.send(JumpWithHorseEvent {
                         client: packet.client,
                         state: JumpWithHorseState::Start {
-                            power: pkt.jump_boost.0 as u8,
+                            power: pkt.jump_boost.0.clamp(0, 100) as u8,
                         },
                     })
                 }
@@ -133,7 +142,11 @@ fn handle_client_command(
                         state: JumpWithHorseState::Stop,
                     })
                 }
-                ClientCommand::OpenHorseInventory => {} // TODO
+                ClientCommand::OpenHorseInventory => {
+                    open_horse_inventory_events.send(OpenHorseInventoryEvent {
+                        client: packet.client,
+                    })
+                }
                 ClientCommand::StartFlyingWithElytra => {
                     if let Ok((mut pose, _)) = clients.get_mut(packet.client) {
                         pose.0 = Pose::FallFlying;
@@ -0,0 +1,93 @@
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_protocol::packets::play::command_tree_s2c::CommandNode;
+use valence_protocol::packets::play::CommandTreeS2c;
+use valence_protocol::WritePacket;
+
+use crate::client::{Client, UpdateClientsSet};
+use crate::op_level::OpLevel;
+
+pub struct CommandGraphPlugin;
+
+impl Plugin for CommandGraphPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CommandGraph>()
+            .add_systems(PostUpdate, send_command_tree.in_set(UpdateClientsSet));
+    }
+}
+
+/// The commands known to clients, used to build the command tree each client
+/// receives for syntax highlighting and tab completion.
+///
+/// Every command has a minimum [`OpLevel`]. A client is only sent the
+/// commands its op level allows, so players never see commands they are not
+/// allowed to use. The tree is sent again whenever the graph or a client's
+/// op level changes, so commands added at runtime show up in tab completion
+/// without reconnecting.
+///
+/// Nothing is sent until a command is added to the graph, so servers that
+/// send their own trees with [`Client::set_commands`] are left alone.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct CommandGraph {
+    commands: Vec<(u8, CommandNode<'static>)>,
+}
+
+impl CommandGraph {
+    /// Adds a command available to every client.
+    pub fn add(&mut self, cmd: CommandNode<'static>) -> &mut Self {
+        self.add_with_op_level(0, cmd)
+    }
+
+    /// Adds a command only available to clients with an op level of at least
+    /// `op_level`.
+    pub fn add_with_op_level(&mut self, op_level: u8, cmd: CommandNode<'static>) -> &mut Self {
+        self.commands.push((op_level, cmd));
+        self
+    }
+
+    /// Removes all commands.
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    /// Builds the command tree for a client with the given op level.
+    pub fn tree_for(&self, op_level: u8) -> CommandTreeS2c<'static> {
+        CommandTreeS2c::from_commands(
+            self.commands
+                .iter()
+                .filter(|(lvl, _)| *lvl <= op_level)
+                .map(|(_, cmd)| cmd.clone()),
+        )
+    }
+}
+
+impl Client {
+    /// Immediately sends the client a command tree built from `commands`,
+    /// replacing the one it currently has.
+    ///
+    /// This bypasses the [`CommandGraph`], which sends its own tree again
+    /// the next time it or the client's [`OpLevel`] changes.
+    pub fn set_commands<'a>(&mut self, commands: impl IntoIterator<Item = CommandNode<'a>>) {
+        self.write_packet(&CommandTreeS2c::from_commands(commands));
+    }
+}
+
+fn send_command_tree(
+    graph: Res<CommandGraph>,
+    mut clients: Query<(&mut Client, Ref<OpLevel>)>,
+    mut in_use: Local<bool>,
+) {
+    // Once used, the graph keeps sending trees even if it is cleared, so that
+    // clients forget the removed commands.
+    *in_use |= !graph.commands.is_empty();
+
+    if !*in_use {
+        return;
+    }
+
+    for (mut client, op_level) in &mut clients {
+        if graph.is_changed() || op_level.is_changed() {
+            client.write_packet(&graph.tree_for(op_level.get()));
+        }
+    }
+}
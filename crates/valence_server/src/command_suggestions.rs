@@ -0,0 +1,138 @@
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_protocol::packets::play::command_suggestions_s2c::CommandSuggestionsMatch;
+use valence_protocol::packets::play::{CommandSuggestionsS2c, RequestCommandCompletionsC2s};
+use valence_protocol::{VarInt, WritePacket};
+use valence_server_common::Server;
+
+use crate::client::Client;
+use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
+
+pub struct CommandSuggestionsPlugin;
+
+impl Plugin for CommandSuggestionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CommandSuggestionsSettings>()
+            .add_event::<CommandSuggestionsRequestEvent>()
+            .add_systems(EventLoopPreUpdate, handle_command_completion_request);
+    }
+}
+
+/// Sent when a client asks for completions of an argument whose suggestion
+/// type is
+/// [`Suggestion::AskServer`](valence_protocol::packets::play::command_tree_s2c::Suggestion::AskServer).
+///
+/// Answer with [`Client::send_command_suggestions`] using the same
+/// `transaction_id`.
+#[derive(Event, Clone, PartialEq, Eq, Debug)]
+pub struct CommandSuggestionsRequestEvent {
+    pub client: Entity,
+    pub transaction_id: i32,
+    /// The command typed so far, including the leading `/`.
+    pub text: String,
+}
+
+/// Global configuration for rate limiting [`CommandSuggestionsRequestEvent`]s.
+#[derive(Resource, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct CommandSuggestionsSettings {
+    /// The maximum number of completion requests accepted from a single
+    /// client per second. Requests beyond this limit are silently dropped and
+    /// no event is emitted for them. A value of `0` disables the limit.
+    ///
+    /// Clients send a request for every keystroke, so this should leave room
+    /// for fast typing.
+    ///
+    /// # Default Value
+    ///
+    /// `20`
+    pub max_requests_per_second: u32,
+}
+
+impl Default for CommandSuggestionsSettings {
+    fn default() -> Self {
+        Self {
+            max_requests_per_second: 20,
+        }
+    }
+}
+
+/// Per-client state used to enforce
+/// [`CommandSuggestionsSettings::max_requests_per_second`].
+#[derive(Component, Clone, Default, Debug)]
+pub struct CommandSuggestionsRateLimit {
+    /// The tick at which the current one second window started.
+    window_start: i64,
+    /// The number of requests accepted in the current window.
+    accepted: u32,
+}
+
+impl CommandSuggestionsRateLimit {
+    /// Records a request at tick `now` unless the limit for the current window
+    /// has been reached. Returns whether the request was accepted.
+    fn try_accept(&mut self, now: i64, window_len: i64, max_requests: u32) -> bool {
+        if max_requests == 0 {
+            return true;
+        }
+
+        if now - self.window_start >= window_len {
+            self.window_start = now;
+            self.accepted = 0;
+        }
+
+        if self.accepted >= max_requests {
+            return false;
+        }
+
+        self.accepted += 1;
+
+        true
+    }
+}
+
+impl Client {
+    /// Answers a [`CommandSuggestionsRequestEvent`]. `start` and `length`
+    /// describe the range of the typed text, in characters, that the
+    /// suggestions replace.
+    pub fn send_command_suggestions(
+        &mut self,
+        transaction_id: i32,
+        start: i32,
+        length: i32,
+        matches: &[CommandSuggestionsMatch],
+    ) {
+        self.write_packet(&CommandSuggestionsS2c {
+            id: VarInt(transaction_id),
+            start: VarInt(start),
+            length: VarInt(length),
+            matches: matches.into(),
+        });
+    }
+}
+
+fn handle_command_completion_request(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<&mut CommandSuggestionsRateLimit>,
+    mut events: EventWriter<CommandSuggestionsRequestEvent>,
+    settings: Res<CommandSuggestionsSettings>,
+    server: Res<Server>,
+) {
+    for packet in packets.iter() {
+        if let Some(pkt) = packet.decode::<RequestCommandCompletionsC2s>() {
+            if let Ok(mut limit) = clients.get_mut(packet.client) {
+                if !limit.try_accept(
+                    server.current_tick(),
+                    server.tick_rate().get().into(),
+                    settings.max_requests_per_second,
+                ) {
+                    continue;
+                }
+            }
+
+            events.send(CommandSuggestionsRequestEvent {
+                client: packet.client,
+                transaction_id: pkt.transaction_id.0,
+                text: pkt.text.0.into(),
+            });
+        }
+    }
+}
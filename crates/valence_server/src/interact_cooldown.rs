@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+use valence_protocol::{Hand, ItemKind};
+
+/// Global configuration for rate limiting client interactions.
+///
+/// Each value is the minimum number of ticks that must pass between two
+/// accepted interactions of the same kind from a single client. Interactions
+/// arriving inside the cooldown window are silently dropped and no event is
+/// emitted for them. A value of `0` disables the cooldown.
+#[derive(Resource, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct InteractCooldownSettings {
+    /// Cooldown for
+    /// [`InteractItemEvent`](crate::interact_item::InteractItemEvent).
+    /// Tracked separately for each kind of item, so switching to another
+    /// item doesn't wait for the cooldown of the previous one.
+    pub use_item: i64,
+    /// Cooldown for
+    /// [`InteractEntityEvent`](crate::interact_entity::InteractEntityEvent).
+    /// Tracked separately for each target entity.
+    pub interact_entity: i64,
+}
+
+/// The kind of interaction a cooldown applies to. Interactions of different
+/// kinds do not share a cooldown.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum InteractKind {
+    UseItem(ItemKind),
+    InteractEntity(Entity),
+}
+
+/// The kinds of the items a client is holding, which the use item cooldown is
+/// keyed by. Kept up to date by `valence_inventory`. Clients without this
+/// component share a single use item cooldown for every item.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct HeldItemKinds {
+    pub main_hand: ItemKind,
+    pub off_hand: ItemKind,
+}
+
+impl Default for HeldItemKinds {
+    fn default() -> Self {
+        Self {
+            main_hand: ItemKind::Air,
+            off_hand: ItemKind::Air,
+        }
+    }
+}
+
+impl HeldItemKinds {
+    /// Returns the kind of the item held in the given hand.
+    pub fn get(&self, hand: Hand) -> ItemKind {
+        match hand {
+            Hand::Main => self.main_hand,
+            Hand::Off => self.off_hand,
+        }
+    }
+}
+
+/// Per-client record of the tick at which each kind of interaction was last
+/// accepted.
+#[derive(Component, Clone, Default, Debug)]
+pub struct InteractCooldowns {
+    last_accepted: HashMap<InteractKind, i64>,
+}
+
+impl InteractCooldowns {
+    /// Returns the tick at which an interaction of the given kind was last
+    /// accepted, if any.
+    pub fn last_accepted(&self, kind: InteractKind) -> Option<i64> {
+        self.last_accepted.get(&kind).copied()
+    }
+
+    /// Returns `true` if an interaction of the given kind is still cooling
+    /// down at tick `now`.
+    pub fn is_cooling_down(&self, kind: InteractKind, now: i64, cooldown: i64) -> bool {
+        self.last_accepted(kind)
+            .is_some_and(|last| now - last < cooldown)
+    }
+
+    /// Forgets all recorded interactions, ending every cooldown immediately.
+    pub fn clear(&mut self) {
+        self.last_accepted.clear();
+    }
+
+    /// Records an interaction at tick `now` unless it is still cooling down.
+    /// Returns whether the interaction was accepted.
+    pub(crate) fn try_accept(&mut self, kind: InteractKind, now: i64, cooldown: i64) -> bool {
+        if cooldown <= 0 {
+            return true;
+        }
+
+        if self.is_cooling_down(kind, now, cooldown) {
+            return false;
+        }
+
+        // Drop expired entity entries so that interactions with many different
+        // entities don't grow the map forever.
+        if let InteractKind::InteractEntity(_) = kind {
+            self.last_accepted.retain(|k, last| {
+                !matches!(k, InteractKind::InteractEntity(_)) || now - *last < cooldown
+            });
+        }
+
+        self.last_accepted.insert(kind, now);
+
+        true
+    }
+}
@@ -3,18 +3,29 @@ use bevy_ecs::prelude::*;
 use valence_entity::EntityManager;
 pub use valence_protocol::packets::play::player_interact_entity_c2s::EntityInteraction;
 use valence_protocol::packets::play::PlayerInteractEntityC2s;
+use valence_server_common::Server;
 
 use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
+use crate::interact_cooldown::{InteractCooldownSettings, InteractCooldowns, InteractKind};
 
 pub struct InteractEntityPlugin;
 
 impl Plugin for InteractEntityPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<InteractEntityEvent>()
+        app.init_resource::<InteractCooldownSettings>()
+            .add_event::<InteractEntityEvent>()
             .add_systems(EventLoopPreUpdate, handle_interact_entity);
     }
 }
 
+/// Sent when a client attacks or interacts with an entity.
+///
+/// Clicks on [`interaction`](valence_entity::interaction) entities are
+/// reported here like any other. Interaction entities have no visible model,
+/// so together with their configurable width and height they make invisible
+/// clickable hitboxes, e.g. for buttons on a hologram. If
+/// [`Response`](valence_entity::interaction::Response) is `true`, the client
+/// swings its arm when interacting with one.
 #[derive(Event, Copy, Clone, Debug)]
 pub struct InteractEntityEvent {
     pub client: Entity,
@@ -29,7 +40,10 @@ pub struct InteractEntityEvent {
 fn handle_interact_entity(
     mut packets: EventReader<PacketEvent>,
     entities: Res<EntityManager>,
+    mut clients: Query<&mut InteractCooldowns>,
     mut events: EventWriter<InteractEntityEvent>,
+    settings: Res<InteractCooldownSettings>,
+    server: Res<Server>,
 ) {
     for packet in packets.iter() {
         if let Some(pkt) = packet.decode::<PlayerInteractEntityC2s>() {
@@ -38,6 +52,16 @@ fn handle_interact_entity(
             // within some configurable tolerance level.
 
             if let Some(entity) = entities.get_by_id(pkt.entity_id.0) {
+                if let Ok(mut cooldowns) = clients.get_mut(packet.client) {
+                    if !cooldowns.try_accept(
+                        InteractKind::InteractEntity(entity),
+                        server.current_tick(),
+                        settings.interact_entity,
+                    ) {
+                        continue;
+                    }
+                }
+
                 events.send(InteractEntityEvent {
                     client: packet.client,
                     entity,
@@ -1,16 +1,21 @@
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use valence_protocol::packets::play::PlayerInteractItemC2s;
-use valence_protocol::Hand;
+use valence_protocol::{Hand, ItemKind};
+use valence_server_common::Server;
 
 use crate::action::ActionSequence;
 use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
+use crate::interact_cooldown::{
+    HeldItemKinds, InteractCooldownSettings, InteractCooldowns, InteractKind,
+};
 
 pub struct InteractItemPlugin;
 
 impl Plugin for InteractItemPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<InteractItemEvent>()
+        app.init_resource::<InteractCooldownSettings>()
+            .add_event::<InteractItemEvent>()
             .add_systems(EventLoopPreUpdate, handle_player_interact_item);
     }
 }
@@ -24,13 +29,29 @@ pub struct InteractItemEvent {
 
 fn handle_player_interact_item(
     mut packets: EventReader<PacketEvent>,
-    mut clients: Query<&mut ActionSequence>,
+    mut clients: Query<(
+        &mut ActionSequence,
+        Option<&mut InteractCooldowns>,
+        Option<&HeldItemKinds>,
+    )>,
     mut events: EventWriter<InteractItemEvent>,
+    settings: Res<InteractCooldownSettings>,
+    server: Res<Server>,
 ) {
     for packet in packets.iter() {
         if let Some(pkt) = packet.decode::<PlayerInteractItemC2s>() {
-            if let Ok(mut action_seq) = clients.get_mut(packet.client) {
+            if let Ok((mut action_seq, cooldowns, held)) = clients.get_mut(packet.client) {
                 action_seq.update(pkt.sequence.0);
+
+                if let Some(mut cooldowns) = cooldowns {
+                    if !cooldowns.try_accept(
+                        InteractKind::UseItem(held.map_or(ItemKind::Air, |h| h.get(pkt.hand))),
+                        server.current_tick(),
+                        settings.use_item,
+                    ) {
+                        continue;
+                    }
+                }
             }
 
             events.send(InteractItemEvent {
@@ -2,6 +2,8 @@
 mod chunk;
 pub mod loaded;
 mod paletted_container;
+mod sign;
+mod sky_light;
 pub mod unloaded;
 
 use std::borrow::Cow;
@@ -13,14 +15,16 @@ use bevy_ecs::prelude::*;
 pub use chunk::{MAX_HEIGHT, *};
 pub use loaded::LoadedChunk;
 use rustc_hash::FxHashMap;
+pub use sign::DyeColor;
 pub use unloaded::UnloadedChunk;
+use valence_entity::EntityId;
 use valence_math::{DVec3, Vec3};
 use valence_nbt::Compound;
 use valence_protocol::encode::{PacketWriter, WritePacket};
 use valence_protocol::packets::play::particle_s2c::Particle;
-use valence_protocol::packets::play::{ParticleS2c, PlaySoundS2c};
+use valence_protocol::packets::play::{BlockBreakingProgressS2c, ParticleS2c, PlaySoundS2c};
 use valence_protocol::sound::{Sound, SoundCategory, SoundId};
-use valence_protocol::{BlockPos, ChunkPos, CompressionThreshold, Encode, Ident, Packet};
+use valence_protocol::{BlockPos, ChunkPos, CompressionThreshold, Encode, Ident, Packet, VarInt};
 use valence_registry::biome::{BiomeId, BiomeRegistry};
 use valence_registry::DimensionTypeRegistry;
 use valence_server_common::Server;
@@ -125,6 +129,13 @@ impl ChunkLayer {
     pub(crate) const OVERWRITE: u8 = 2;
 
     /// Creates a new chunk layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension type doesn't exist or is invalid according to
+    /// [`DimensionType::validate`].
+    ///
+    /// [`DimensionType::validate`]: valence_registry::dimension_type::DimensionType::validate
     #[track_caller]
     pub fn new(
         dimension_type_name: impl Into<Ident<String>>,
@@ -136,11 +147,9 @@ impl ChunkLayer {
 
         let dim = &dimensions[dimension_type_name.as_str_ident()];
 
-        assert!(
-            (0..MAX_HEIGHT as i32).contains(&dim.height),
-            "invalid dimension height of {}",
-            dim.height
-        );
+        if let Err(e) = dim.validate() {
+            panic!("invalid dimension type \"{dimension_type_name}\": {e:#}");
+        }
 
         Self {
             messages: Messages::new(),
@@ -277,6 +286,58 @@ impl ChunkLayer {
         Some(chunk.set_block(x, y, z, block))
     }
 
+    /// Sets many blocks at once, such as the blocks of a procedurally
+    /// generated tree or rock, and returns the number of blocks that were
+    /// set.
+    ///
+    /// Each chunk is only looked up once for a run of blocks in the same
+    /// chunk, so blocks should be grouped by chunk where possible. As with
+    /// [`Self::set_block`], the changes to each chunk section are sent to
+    /// viewers together at the end of the tick.
+    ///
+    /// Blocks outside the height of the layer are skipped. Blocks in chunks
+    /// that aren't loaded are handled according to `missing`.
+    pub fn set_blocks<I, B>(&mut self, blocks: I, missing: MissingChunks) -> usize
+    where
+        I: IntoIterator<Item = (BlockPos, B)>,
+        B: IntoBlock,
+    {
+        let (min_y, height) = (self.info.min_y, self.info.height);
+
+        let mut count = 0;
+        let mut current: Option<(ChunkPos, &mut LoadedChunk)> = None;
+
+        for (pos, block) in blocks {
+            let Some(y) = pos
+                .y
+                .checked_sub(min_y)
+                .and_then(|y| u32::try_from(y).ok())
+                .filter(|&y| y < height)
+            else {
+                continue;
+            };
+
+            let chunk_pos = ChunkPos::from_block_pos(pos);
+
+            if !matches!(&current, Some((p, _)) if *p == chunk_pos) {
+                let chunk = match missing {
+                    MissingChunks::Skip => self.chunks.get_mut(&chunk_pos),
+                    MissingChunks::Insert => Some(self.chunk_entry(chunk_pos).or_default()),
+                };
+
+                current = chunk.map(|chunk| (chunk_pos, chunk));
+            }
+
+            if let Some((_, chunk)) = &mut current {
+                let (x, z) = (pos.x.rem_euclid(16) as u32, pos.z.rem_euclid(16) as u32);
+                chunk.set_block(x, y, z, block);
+                count += 1;
+            }
+        }
+
+        count
+    }
+
     pub fn block_entity_mut(&mut self, pos: impl Into<BlockPos>) -> Option<&mut Compound> {
         let (chunk, x, y, z) = self.chunk_and_offsets_mut(pos.into())?;
         chunk.block_entity_mut(x, y, z)
@@ -287,11 +348,61 @@ impl ChunkLayer {
         Some(chunk.biome(x / 4, y / 4, z / 4))
     }
 
+    /// Sets the biome at the given position and returns the previous one, or
+    /// `None` if the chunk isn't loaded.
+    ///
+    /// Viewers of the chunk are sent only its new biomes at the end of the
+    /// tick, and the chunk itself is not resent. All changes to the chunk's
+    /// biomes in the same tick are sent together. Clients re-render the
+    /// affected area with the new biome colors.
     pub fn set_biome(&mut self, pos: impl Into<BlockPos>, biome: BiomeId) -> Option<BiomeId> {
         let (chunk, x, y, z) = self.chunk_and_offsets_mut(pos.into())?;
         Some(chunk.set_biome(x / 4, y / 4, z / 4, biome))
     }
 
+    /// Returns the position of the highest block in the column at `x` and `z`
+    /// that has a collision shape, or `None` if the chunk isn't loaded or the
+    /// column has no such block.
+    ///
+    /// Blocks without collision, such as air, grass, flowers and liquids, are
+    /// skipped.
+    pub fn highest_solid_block(&self, x: i32, z: i32) -> Option<BlockPos> {
+        let chunk = self.chunk(ChunkPos::from_block_pos(BlockPos::new(x, 0, z)))?;
+
+        let (offset_x, offset_z) = (x.rem_euclid(16) as u32, z.rem_euclid(16) as u32);
+
+        (0..self.info.height).rev().find_map(|y| {
+            let state = chunk.block_state(offset_x, y, offset_z);
+
+            (state.collision_shapes().len() > 0)
+                .then(|| BlockPos::new(x, self.info.min_y + y as i32, z))
+        })
+    }
+
+    /// Returns the position a player standing on top of the column at `x` and
+    /// `z` would have, centered on the block. This is on top of the
+    /// [highest solid block](Self::highest_solid_block), or `None` if there
+    /// is none.
+    ///
+    /// The height of the block's collision shape is taken into account, so a
+    /// player standing on a bottom slab at Y 70 is placed at Y 70.5.
+    pub fn spawn_on_ground(&self, x: i32, z: i32) -> Option<DVec3> {
+        let pos = self.highest_solid_block(x, z)?;
+
+        let top = self
+            .block(pos)?
+            .state
+            .collision_shapes()
+            .map(|shape| shape.max().y)
+            .fold(0.0, f64::max);
+
+        Some(DVec3::new(
+            f64::from(x) + 0.5,
+            f64::from(pos.y) + top,
+            f64::from(z) + 0.5,
+        ))
+    }
+
     #[inline]
     fn chunk_and_offsets(&self, pos: BlockPos) -> Option<(&LoadedChunk, u32, u32, u32)> {
         let Some(y) = pos
@@ -355,6 +466,15 @@ impl ChunkLayer {
     /// Puts a particle effect at the given position in the world. The particle
     /// effect is visible to all players in the instance with the
     /// appropriate chunk in view.
+    ///
+    /// `count` particles are spawned at random positions around `position`,
+    /// spread out by `offset`. If `count` is zero, a single particle is spawned
+    /// and `offset` multiplied by `max_speed` is used as its velocity instead.
+    /// A negative `count` is treated as zero.
+    ///
+    /// Clients don't show particles more than 32 blocks away from the camera.
+    /// If `long_distance` is `true`, this limit and the client's particle
+    /// setting are ignored.
     pub fn play_particle(
         &mut self,
         particle: &Particle,
@@ -373,7 +493,33 @@ impl ChunkLayer {
                 position,
                 offset: offset.into(),
                 max_speed,
-                count,
+                count: count.max(0),
+            });
+    }
+
+    /// Shows the cracking overlay on the block at `pos` to all clients with
+    /// the block's chunk in view.
+    ///
+    /// `stage` goes from `0` (barely cracked) to `9` (almost broken). Any
+    /// greater value removes the overlay.
+    ///
+    /// Each breaker entity has at most one animation at a time, so calling
+    /// this again with the same `breaker` replaces its previous animation,
+    /// even if `pos` is different. Use distinct breakers to show several
+    /// animations at once.
+    pub fn set_block_break_progress(
+        &mut self,
+        breaker: EntityId,
+        pos: impl Into<BlockPos>,
+        stage: u8,
+    ) {
+        let position = pos.into();
+
+        self.view_writer(position)
+            .write_packet(&BlockBreakingProgressS2c {
+                entity_id: VarInt(breaker.get()),
+                position,
+                destroy_stage: if stage <= 9 { stage } else { u8::MAX },
             });
     }
 
@@ -636,6 +782,17 @@ impl WritePacket for RadiusExceptWriter<'_> {
     }
 }
 
+/// What [`ChunkLayer::set_blocks`] does with blocks in chunks that aren't
+/// loaded.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum MissingChunks {
+    /// Leave the blocks out.
+    #[default]
+    Skip,
+    /// Insert an empty chunk to put the blocks in.
+    Insert,
+}
+
 #[derive(Debug)]
 pub enum ChunkEntry<'a> {
     Occupied(OccupiedChunkEntry<'a>),
@@ -29,14 +29,23 @@ pub trait Chunk {
     /// are in the range `0..16` while `y` is in the range `0..height`. The
     /// previous block at the position is returned.
     ///
+    /// If the new block state has no [block entity
+    /// kind](BlockState::block_entity_kind), the block's NBT is discarded and
+    /// any block entity at the position is removed.
+    ///
     /// # Panics
     ///
     /// May panic if the position is out of bounds.
     #[track_caller]
     fn set_block(&mut self, x: u32, y: u32, z: u32, block: impl IntoBlock) -> Block {
         let block = block.into_block();
+        let nbt = block
+            .nbt
+            .filter(|_| block.state.block_entity_kind().is_some());
+        // The block entity goes first because `set_block_state` removes it if the
+        // new state can't have one.
+        let nbt = self.set_block_entity(x, y, z, nbt);
         let state = self.set_block_state(x, y, z, block.state);
-        let nbt = self.set_block_entity(x, y, z, block.nbt);
 
         Block { state, nbt }
     }
@@ -47,7 +56,7 @@ pub trait Chunk {
 
         self.fill_block_states(block.state);
 
-        if block.nbt.is_some() {
+        if block.nbt.is_some() && block.state.block_entity_kind().is_some() {
             for x in 0..16 {
                 for z in 0..16 {
                     for y in 0..self.height() {
@@ -73,9 +82,11 @@ pub trait Chunk {
     /// are in the range `0..16` while `y` is in the range `0..height`. The
     /// previous block state at the position is returned.
     ///
-    /// **NOTE:** This is a low-level function which may break expected
-    /// invariants for block entities. Prefer [`Self::set_block`] if performance
-    /// is not a concern.
+    /// If the new block state has no [block entity
+    /// kind](BlockState::block_entity_kind), any block entity at the position
+    /// is removed. Otherwise, the block entity is left as is, even if it
+    /// belonged to a different kind of block. Prefer [`Self::set_block`] to
+    /// replace both at once.
     ///
     /// # Panics
     ///
@@ -195,6 +206,95 @@ pub trait Chunk {
     #[track_caller]
     fn fill_biome_section(&mut self, sect_y: u32, biome: BiomeId);
 
+    /// Gets the sky light level at the provided position in this chunk. `x`
+    /// and `z` are in the range `0..16` while `y` is in the range
+    /// `0..height`. Positions that were never given a sky light level have a
+    /// level of zero.
+    ///
+    /// # Panics
+    ///
+    /// May panic if the position is out of bounds.
+    #[track_caller]
+    fn sky_light(&self, x: u32, y: u32, z: u32) -> u8;
+
+    /// Sets the sky light level at the provided position in this chunk. `x`
+    /// and `z` are in the range `0..16` while `y` is in the range
+    /// `0..height`. Levels above 15 are clamped to 15. The previous level is
+    /// returned.
+    ///
+    /// Valence doesn't compute light itself. Chunks without any sky light set
+    /// are sent to clients without light data.
+    ///
+    /// # Panics
+    ///
+    /// May panic if the position is out of bounds.
+    #[track_caller]
+    fn set_sky_light(&mut self, x: u32, y: u32, z: u32, level: u8) -> u8;
+
+    /// Sets the sky light level of every block in this chunk. Levels above 15
+    /// are clamped to 15.
+    ///
+    /// Filling a chunk with a level of 15 makes it fully bright, which is
+    /// useful for worlds without any real lighting.
+    fn fill_sky_light(&mut self, level: u8) {
+        for sect_y in 0..self.height() / 16 {
+            self.fill_sky_light_section(sect_y, level);
+        }
+    }
+
+    /// Sets the sky light level of every block in a section. Levels above 15
+    /// are clamped to 15.
+    ///
+    /// # Panics
+    ///
+    /// May panic if the section offset is out of bounds.
+    #[track_caller]
+    fn fill_sky_light_section(&mut self, sect_y: u32, level: u8);
+
+    /// Sets the sky light of the entire chunk for a flat surface, without
+    /// running a light engine. Blocks at `surface_y` and above get full sky
+    /// light. Below it, the light level drops by one for every block, so
+    /// blocks 15 or more blocks below the surface are dark.
+    ///
+    /// `surface_y` is in the range `0..=height` and is usually the height of
+    /// the first air block above the ground.
+    fn fill_sky_light_above(&mut self, surface_y: u32) {
+        for y in 0..self.height() {
+            let level = 15_u32.saturating_sub(surface_y.saturating_sub(y)) as u8;
+
+            for z in 0..16 {
+                for x in 0..16 {
+                    self.set_sky_light(x, y, z, level);
+                }
+            }
+        }
+    }
+
+    /// Gets the block light level at the provided position in this chunk. `x`
+    /// and `z` are in the range `0..16` while `y` is in the range
+    /// `0..height`. Positions that were never given a block light level have
+    /// a level of zero.
+    ///
+    /// # Panics
+    ///
+    /// May panic if the position is out of bounds.
+    #[track_caller]
+    fn block_light(&self, x: u32, y: u32, z: u32) -> u8;
+
+    /// Sets the block light level at the provided position in this chunk. `x`
+    /// and `z` are in the range `0..16` while `y` is in the range
+    /// `0..height`. Levels above 15 are clamped to 15. The previous level is
+    /// returned.
+    ///
+    /// Like sky light, block light is not computed from light sources such as
+    /// torches.
+    ///
+    /// # Panics
+    ///
+    /// May panic if the position is out of bounds.
+    #[track_caller]
+    fn set_block_light(&mut self, x: u32, y: u32, z: u32, level: u8) -> u8;
+
     /// Sets all blocks and biomes in this chunk to the default values. The
     /// height of the chunk is not modified.
     fn clear(&mut self) {
@@ -281,6 +381,84 @@ pub(super) type BlockStateContainer =
 pub(super) type BiomeContainer =
     PalettedContainer<BiomeId, SECTION_BIOME_COUNT, { SECTION_BIOME_COUNT / 2 }>;
 
+/// The light levels of a section, packed two per byte as in the protocol.
+/// Sections where every block has the same level don't allocate.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(super) enum LightContainer {
+    Single(u8),
+    Direct(Box<[u8; SECTION_BLOCK_COUNT / 2]>),
+}
+
+impl Default for LightContainer {
+    fn default() -> Self {
+        Self::Single(0)
+    }
+}
+
+impl LightContainer {
+    pub(super) fn get(&self, idx: usize) -> u8 {
+        match self {
+            Self::Single(level) => *level,
+            Self::Direct(arr) => arr[idx / 2] >> (idx % 2 * 4) & 0xf,
+        }
+    }
+
+    pub(super) fn set(&mut self, idx: usize, level: u8) -> u8 {
+        let level = level.min(15);
+
+        if let Self::Single(old) = *self {
+            if old == level {
+                return old;
+            }
+
+            *self = Self::Direct(Box::new([old << 4 | old; SECTION_BLOCK_COUNT / 2]));
+        }
+
+        let Self::Direct(arr) = self else {
+            unreachable!()
+        };
+
+        let shift = idx % 2 * 4;
+        let old = arr[idx / 2] >> shift & 0xf;
+        arr[idx / 2] = arr[idx / 2] & !(0xf << shift) | level << shift;
+
+        old
+    }
+
+    /// Sets every level in the section, returning whether anything changed.
+    pub(super) fn fill(&mut self, level: u8) -> bool {
+        let level = level.min(15);
+        let changed = *self != Self::Single(level);
+
+        *self = Self::Single(level);
+
+        changed
+    }
+
+    /// Returns the packed levels as sent in the protocol, or `None` if every
+    /// level is zero.
+    pub(super) fn to_array(&self) -> Option<[u8; SECTION_BLOCK_COUNT / 2]> {
+        match self {
+            Self::Single(0) => None,
+            Self::Single(level) => Some([*level << 4 | *level; SECTION_BLOCK_COUNT / 2]),
+            Self::Direct(arr) if arr.iter().all(|&b| b == 0) => None,
+            Self::Direct(arr) => Some(**arr),
+        }
+    }
+
+    /// Collapses the container back to a single level if every level is the
+    /// same.
+    pub(super) fn shrink_to_fit(&mut self) {
+        if let Self::Direct(arr) = self {
+            let first = arr[0];
+
+            if first >> 4 == first & 0xf && arr.iter().all(|&b| b == first) {
+                *self = Self::Single(first & 0xf);
+            }
+        }
+    }
+}
+
 #[inline]
 #[track_caller]
 pub(super) fn check_block_oob(chunk: &impl Chunk, x: u32, y: u32, z: u32) {
@@ -315,6 +493,8 @@ pub(super) const fn bit_width(n: usize) -> usize {
 
 #[cfg(test)]
 mod tests {
+    use valence_nbt::compound;
+
     use super::*;
     use crate::layer::chunk::{LoadedChunk, UnloadedChunk};
 
@@ -332,6 +512,17 @@ mod tests {
 
             assert_eq!(chunk.set_block_entity(1, 2, 3, Some(Compound::new())), None);
             assert_eq!(chunk.set_block_entity(1, 2, 3, None), Some(Compound::new()));
+
+            assert_eq!(chunk.set_sky_light(1, 2, 3, 7), 0);
+            assert_eq!(chunk.set_sky_light(0, 2, 3, 20), 0);
+            assert_eq!(chunk.sky_light(1, 2, 3), 7);
+            assert_eq!(chunk.sky_light(0, 2, 3), 15);
+
+            assert_eq!(chunk.set_block_light(1, 2, 3, 9), 0);
+            assert_eq!(chunk.set_block_light(0, 2, 3, 16), 0);
+            assert_eq!(chunk.block_light(1, 2, 3), 9);
+            assert_eq!(chunk.block_light(0, 2, 3), 15);
+            assert_eq!(chunk.sky_light(1, 2, 3), 7);
         }
 
         let unloaded = UnloadedChunk::with_height(512);
@@ -341,6 +532,107 @@ mod tests {
         check(loaded);
     }
 
+    #[test]
+    fn chunk_set_block_removes_unsupported_block_entity() {
+        fn check(mut chunk: impl Chunk) {
+            let nbt = compound! { "Text1" => "hello" };
+
+            chunk.set_block(4, 5, 6, Block::new(BlockState::OAK_SIGN, Some(nbt.clone())));
+            assert_eq!(chunk.block_entity(4, 5, 6), Some(&nbt));
+
+            // Stone can't have a block entity, so the sign's is removed.
+            let old = chunk.set_block(4, 5, 6, BlockState::STONE);
+            assert_eq!(old, Block::new(BlockState::OAK_SIGN, Some(nbt.clone())));
+            assert_eq!(chunk.block_entity(4, 5, 6), None);
+
+            chunk.set_block(4, 5, 6, Block::new(BlockState::DIRT, Some(nbt.clone())));
+            assert_eq!(chunk.block_entity(4, 5, 6), None);
+
+            // Setting only the block state removes stale block entities too.
+            chunk.set_block(4, 5, 6, Block::new(BlockState::OAK_SIGN, Some(nbt.clone())));
+            assert_eq!(
+                chunk.set_block_state(4, 5, 6, BlockState::OAK_WALL_SIGN),
+                BlockState::OAK_SIGN
+            );
+            assert_eq!(chunk.block_entity(4, 5, 6), Some(&nbt));
+
+            chunk.set_block_state(4, 5, 6, BlockState::AIR);
+            assert_eq!(chunk.block_entity(4, 5, 6), None);
+        }
+
+        check(UnloadedChunk::with_height(32));
+        check(LoadedChunk::new(32));
+    }
+
+    #[test]
+    fn chunk_fill_sky_light_above() {
+        fn check(mut chunk: impl Chunk) {
+            // The surface isn't aligned to a section, so the light fades out
+            // across the section boundary at y = 32.
+            chunk.fill_sky_light_above(40);
+
+            for (x, z) in [(0, 0), (7, 9), (15, 15)] {
+                assert_eq!(chunk.sky_light(x, 63, z), 15);
+                assert_eq!(chunk.sky_light(x, 40, z), 15);
+                assert_eq!(chunk.sky_light(x, 39, z), 14);
+                assert_eq!(chunk.sky_light(x, 32, z), 7);
+                assert_eq!(chunk.sky_light(x, 31, z), 6);
+                assert_eq!(chunk.sky_light(x, 26, z), 1);
+                assert_eq!(chunk.sky_light(x, 25, z), 0);
+                assert_eq!(chunk.sky_light(x, 0, z), 0);
+            }
+        }
+
+        check(UnloadedChunk::with_height(64));
+        check(LoadedChunk::new(64));
+    }
+
+    #[test]
+    fn chunk_fill_sky_light() {
+        fn check(mut chunk: impl Chunk) {
+            chunk.fill_sky_light(20);
+
+            assert_eq!(chunk.sky_light(0, 0, 0), 15);
+            assert_eq!(chunk.sky_light(15, 63, 15), 15);
+            assert_eq!(chunk.block_light(15, 63, 15), 0);
+
+            assert_eq!(chunk.set_sky_light(4, 20, 4, 3), 15);
+            assert_eq!(chunk.sky_light(4, 20, 4), 3);
+            assert_eq!(chunk.sky_light(5, 20, 4), 15);
+        }
+
+        check(UnloadedChunk::with_height(64));
+        check(LoadedChunk::new(64));
+    }
+
+    #[test]
+    fn light_container_single_level() {
+        let mut light = LightContainer::default();
+
+        assert!(light.fill(15));
+        assert!(!light.fill(15));
+        assert_eq!(light, LightContainer::Single(15));
+        assert_eq!(light.to_array(), Some([0xff; SECTION_BLOCK_COUNT / 2]));
+
+        // Setting the level every block already has doesn't allocate.
+        assert_eq!(light.set(100, 15), 15);
+        assert_eq!(light, LightContainer::Single(15));
+
+        assert_eq!(light.set(100, 4), 15);
+        assert_eq!(light.get(100), 4);
+        assert_eq!(light.get(101), 15);
+
+        // Once every block has the same level again, the array can be
+        // dropped.
+        light.set(100, 15);
+        assert!(matches!(light, LightContainer::Direct(_)));
+        light.shrink_to_fit();
+        assert_eq!(light, LightContainer::Single(15));
+
+        assert!(light.fill(0));
+        assert_eq!(light.to_array(), None);
+    }
+
     #[cfg(debug_assertions)]
     #[test]
     #[should_panic]
@@ -8,15 +8,15 @@ use valence_nbt::{compound, Compound};
 use valence_protocol::encode::{PacketWriter, WritePacket};
 use valence_protocol::packets::play::chunk_data_s2c::ChunkDataBlockEntity;
 use valence_protocol::packets::play::{
-    BlockEntityUpdateS2c, BlockUpdateS2c, ChunkDataS2c, ChunkDeltaUpdateS2c,
+    BlockEntityUpdateS2c, BlockUpdateS2c, ChunkDataS2c, ChunkDeltaUpdateS2c, LightUpdateS2c,
 };
-use valence_protocol::{BlockPos, BlockState, ChunkPos, Encode, VarLong};
+use valence_protocol::{BlockPos, BlockState, ChunkPos, Encode, FixedArray, VarInt, VarLong};
 use valence_registry::biome::BiomeId;
 use valence_registry::RegistryIdx;
 
 use super::chunk::{
     bit_width, check_biome_oob, check_block_oob, check_section_oob, BiomeContainer,
-    BlockStateContainer, Chunk, SECTION_BLOCK_COUNT,
+    BlockStateContainer, Chunk, LightContainer, SECTION_BLOCK_COUNT,
 };
 use super::paletted_container::PalettedContainer;
 use super::unloaded::{self, UnloadedChunk};
@@ -36,16 +36,68 @@ pub struct LoadedChunk {
     changed_block_entities: BTreeSet<u32>,
     /// If any biomes in this chunk have been modified this tick.
     changed_biomes: bool,
-    /// Cached bytes of the chunk initialization packet. The cache is considered
-    /// invalidated if empty. This should be cleared whenever the chunk is
-    /// modified in an observable way, even if the chunk is not viewed.
-    cached_init_packets: Mutex<Vec<u8>>,
+    /// If the sky or block light in this chunk has been modified this tick.
+    changed_light: bool,
+    /// If anything in this chunk has been modified since it was inserted or
+    /// last marked as saved.
+    modified: bool,
+    /// Cached bytes of the chunk initialization packet and of the sections in
+    /// it. This should be invalidated whenever the chunk is modified in an
+    /// observable way, even if the chunk is not viewed.
+    cached_init_packets: Mutex<InitPacketCache>,
+}
+
+#[derive(Default, Debug)]
+struct InitPacketCache {
+    /// The chunk initialization packet. Considered invalidated if empty.
+    packets: Vec<u8>,
+    /// The encoded blocks and biomes of each section, as they appear in the
+    /// packet. A section is dirty if its bytes are empty, and only dirty
+    /// sections are encoded again when the packet is rebuilt.
+    ///
+    /// This keeps a second copy of the encoded sections next to `packets`, so
+    /// a cached chunk uses roughly twice the memory it used to.
+    sections: Vec<Vec<u8>>,
+}
+
+impl InitPacketCache {
+    /// Invalidates the packet for a change that doesn't affect the blocks or
+    /// biomes of any section, such as a change to block entities or light.
+    fn invalidate(&mut self) {
+        self.packets.clear();
+    }
+
+    /// Invalidates the packet and marks the section as dirty.
+    fn invalidate_section(&mut self, sect_y: usize) {
+        self.packets.clear();
+
+        if let Some(sect) = self.sections.get_mut(sect_y) {
+            sect.clear();
+        }
+    }
+
+    fn invalidate_all(&mut self) {
+        self.packets.clear();
+        self.sections.clear();
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.packets.shrink_to_fit();
+
+        for sect in &mut self.sections {
+            sect.shrink_to_fit();
+        }
+
+        self.sections.shrink_to_fit();
+    }
 }
 
 #[derive(Clone, Default, Debug)]
 struct Section {
     block_states: BlockStateContainer,
     biomes: BiomeContainer,
+    sky_light: LightContainer,
+    block_light: LightContainer,
     /// Contains modifications for the update section packet. (Or the regular
     /// block update packet if len == 1).
     section_updates: Vec<VarLong>,
@@ -89,7 +141,9 @@ impl LoadedChunk {
             block_entities: BTreeMap::new(),
             changed_block_entities: BTreeSet::new(),
             changed_biomes: false,
-            cached_init_packets: Mutex::new(vec![]),
+            changed_light: false,
+            modified: false,
+            cached_init_packets: Mutex::new(InitPacketCache::default()),
         }
     }
 
@@ -113,13 +167,17 @@ impl LoadedChunk {
                 unloaded::Section {
                     block_states: mem::replace(&mut sect.block_states, other_sect.block_states),
                     biomes: mem::replace(&mut sect.biomes, other_sect.biomes),
+                    sky_light: mem::replace(&mut sect.sky_light, other_sect.sky_light),
+                    block_light: mem::replace(&mut sect.block_light, other_sect.block_light),
                 }
             })
             .collect();
         let old_block_entities = mem::replace(&mut self.block_entities, chunk.block_entities);
         self.changed_block_entities.clear();
         self.changed_biomes = false;
-        self.cached_init_packets.get_mut().clear();
+        self.changed_light = false;
+        self.modified = false;
+        self.cached_init_packets.get_mut().invalidate_all();
 
         self.assert_no_changes();
 
@@ -139,13 +197,17 @@ impl LoadedChunk {
                 unloaded::Section {
                     block_states: mem::take(&mut sect.block_states),
                     biomes: mem::take(&mut sect.biomes),
+                    sky_light: mem::take(&mut sect.sky_light),
+                    block_light: mem::take(&mut sect.block_light),
                 }
             })
             .collect();
         let old_block_entities = mem::take(&mut self.block_entities);
         self.changed_block_entities.clear();
         self.changed_biomes = false;
-        self.cached_init_packets.get_mut().clear();
+        self.changed_light = false;
+        self.modified = false;
+        self.cached_init_packets.get_mut().invalidate_all();
 
         self.assert_no_changes();
 
@@ -165,6 +227,22 @@ impl LoadedChunk {
         *self.viewer_count.get_mut()
     }
 
+    /// Returns whether anything in this chunk was modified since it was
+    /// inserted into the layer or since the last call to
+    /// [`Self::mark_saved`]. Useful for saving only the chunks that changed
+    /// when an [`AutosaveEvent`] is sent.
+    ///
+    /// [`AutosaveEvent`]: crate::autosave::AutosaveEvent
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    /// Marks this chunk as saved, so that [`Self::is_modified`] returns
+    /// `false` until the chunk is modified again.
+    pub fn mark_saved(&mut self) {
+        self.modified = false;
+    }
+
     /// Increments the viewer count.
     pub(crate) fn inc_viewer_count(&self) {
         self.viewer_count.fetch_add(1, Ordering::Relaxed);
@@ -291,10 +369,92 @@ impl LoadedChunk {
             });
         }
 
+        // Light
+        if self.changed_light {
+            self.changed_light = false;
+
+            let (sky_light_mask, empty_sky_light_mask, sky_light_arrays) =
+                self.light_data(true, true);
+            let (block_light_mask, empty_block_light_mask, block_light_arrays) =
+                self.light_data(false, true);
+
+            messages.send_local_infallible(LocalMsg::PacketAt { pos }, |buf| {
+                let mut writer = PacketWriter::new(buf, info.threshold);
+
+                writer.write_packet(&LightUpdateS2c {
+                    chunk_x: VarInt(pos.x),
+                    chunk_z: VarInt(pos.z),
+                    sky_light_mask: Cow::Owned(sky_light_mask),
+                    block_light_mask: Cow::Owned(block_light_mask),
+                    empty_sky_light_mask: Cow::Owned(empty_sky_light_mask),
+                    empty_block_light_mask: Cow::Owned(empty_block_light_mask),
+                    sky_light_arrays: Cow::Owned(sky_light_arrays),
+                    block_light_arrays: Cow::Owned(block_light_arrays),
+                });
+            });
+        }
+
         // All changes should be cleared.
         self.assert_no_changes();
     }
 
+    /// Returns the light mask, the empty light mask and the light arrays of
+    /// this chunk for either sky light or block light, as sent in the
+    /// protocol. Everything is empty if no light of that kind was set, unless
+    /// `update` is true. Light updates must mark dark sections as empty,
+    /// otherwise the client keeps the light it had.
+    ///
+    /// The protocol has an extra section below and above the chunk. For sky
+    /// light, the one below is dark and the one above has full light. Both
+    /// are dark for block light.
+    fn light_data(
+        &self,
+        sky: bool,
+        update: bool,
+    ) -> (Vec<u64>, Vec<u64>, Vec<FixedArray<u8, 2048>>) {
+        let light = |sect: &Section| {
+            if sky {
+                sect.sky_light.to_array()
+            } else {
+                sect.block_light.to_array()
+            }
+        };
+
+        if !update && self.sections.iter().all(|sect| light(sect).is_none()) {
+            return (vec![], vec![], vec![]);
+        }
+
+        let sect_count = self.sections.len() + 2;
+        let mut mask = vec![0_u64; sect_count.div_ceil(64)];
+        let mut empty_mask = mask.clone();
+        let mut arrays = vec![];
+
+        empty_mask[0] |= 1;
+
+        for (i, sect) in self.sections.iter().enumerate() {
+            let bit = i + 1;
+
+            match light(sect) {
+                Some(arr) => {
+                    mask[bit / 64] |= 1 << (bit % 64);
+                    arrays.push(FixedArray(arr));
+                }
+                None => empty_mask[bit / 64] |= 1 << (bit % 64),
+            }
+        }
+
+        let top = sect_count - 1;
+
+        if sky {
+            mask[top / 64] |= 1 << (top % 64);
+            arrays.push(FixedArray([0xff; 2048]));
+        } else {
+            empty_mask[top / 64] |= 1 << (top % 64);
+        }
+
+        (mask, empty_mask, arrays)
+    }
+
     /// Writes the packet data needed to initialize this chunk.
     pub(crate) fn write_init_packets(
         &self,
@@ -302,39 +462,44 @@ impl LoadedChunk {
         pos: ChunkPos,
         info: &ChunkLayerInfo,
     ) {
-        let mut init_packets = self.cached_init_packets.lock();
+        let mut cache = self.cached_init_packets.lock();
+        let cache = &mut *cache;
 
-        if init_packets.is_empty() {
+        if cache.packets.is_empty() {
             let heightmaps = compound! {
                 // TODO: MOTION_BLOCKING and WORLD_SURFACE heightmaps.
             };
 
             let mut blocks_and_biomes: Vec<u8> = vec![];
 
-            for sect in self.sections.iter() {
-                sect.count_non_air_blocks()
-                    .encode(&mut blocks_and_biomes)
-                    .unwrap();
-
-                sect.block_states
-                    .encode_mc_format(
-                        &mut blocks_and_biomes,
-                        |b| b.to_raw().into(),
-                        4,
-                        8,
-                        bit_width(BlockState::max_raw().into()),
-                    )
-                    .expect("paletted container encode should always succeed");
-
-                sect.biomes
-                    .encode_mc_format(
-                        &mut blocks_and_biomes,
-                        |b| b.to_index() as _,
-                        0,
-                        3,
-                        bit_width(info.biome_registry_len - 1),
-                    )
-                    .expect("paletted container encode should always succeed");
+            cache.sections.resize_with(self.sections.len(), Vec::new);
+
+            for (sect, encoded) in self.sections.iter().zip(&mut cache.sections) {
+                if encoded.is_empty() {
+                    sect.count_non_air_blocks().encode(&mut *encoded).unwrap();
+
+                    sect.block_states
+                        .encode_mc_format(
+                            &mut *encoded,
+                            |b| b.to_raw().into(),
+                            4,
+                            8,
+                            bit_width(BlockState::max_raw().into()),
+                        )
+                        .expect("paletted container encode should always succeed");
+
+                    sect.biomes
+                        .encode_mc_format(
+                            &mut *encoded,
+                            |b| b.to_index() as _,
+                            0,
+                            3,
+                            bit_width(info.biome_registry_len - 1),
+                        )
+                        .expect("paletted container encode should always succeed");
+                }
+
+                blocks_and_biomes.extend_from_slice(encoded);
             }
 
             let block_entities: Vec<_> = self
@@ -359,21 +524,26 @@ impl LoadedChunk {
                 })
                 .collect();
 
-            PacketWriter::new(&mut init_packets, info.threshold).write_packet(&ChunkDataS2c {
+            let (sky_light_mask, empty_sky_light_mask, sky_light_arrays) =
+                self.light_data(true, false);
+            let (block_light_mask, empty_block_light_mask, block_light_arrays) =
+                self.light_data(false, false);
+
+            PacketWriter::new(&mut cache.packets, info.threshold).write_packet(&ChunkDataS2c {
                 pos,
                 heightmaps: Cow::Owned(heightmaps),
                 blocks_and_biomes: &blocks_and_biomes,
                 block_entities: Cow::Owned(block_entities),
-                sky_light_mask: Cow::Borrowed(&[]),
-                block_light_mask: Cow::Borrowed(&[]),
-                empty_sky_light_mask: Cow::Borrowed(&[]),
-                empty_block_light_mask: Cow::Borrowed(&[]),
-                sky_light_arrays: Cow::Borrowed(&[]),
-                block_light_arrays: Cow::Borrowed(&[]),
+                sky_light_mask: Cow::Owned(sky_light_mask),
+                block_light_mask: Cow::Owned(block_light_mask),
+                empty_sky_light_mask: Cow::Owned(empty_sky_light_mask),
+                empty_block_light_mask: Cow::Owned(empty_block_light_mask),
+                sky_light_arrays: Cow::Owned(sky_light_arrays),
+                block_light_arrays: Cow::Owned(block_light_arrays),
             })
         }
 
-        writer.write_packet_bytes(&init_packets);
+        writer.write_packet_bytes(&cache.packets);
     }
 
     /// Asserts that no changes to this chunk are currently recorded.
@@ -382,6 +552,7 @@ impl LoadedChunk {
         #[cfg(debug_assertions)]
         {
             assert!(!self.changed_biomes);
+            assert!(!self.changed_light);
             assert!(self.changed_block_entities.is_empty());
 
             for sect in self.sections.iter() {
@@ -415,7 +586,10 @@ impl Chunk for LoadedChunk {
         let old_block = sect.block_states.set(idx as usize, block);
 
         if block != old_block {
-            self.cached_init_packets.get_mut().clear();
+            self.modified = true;
+            self.cached_init_packets
+                .get_mut()
+                .invalidate_section(sect_y as usize);
 
             if *self.viewer_count.get_mut() > 0 {
                 let compact = (block.to_raw() as i64) << 12 | (x << 8 | z << 4 | (y % 16)) as i64;
@@ -423,6 +597,10 @@ impl Chunk for LoadedChunk {
             }
         }
 
+        if block.block_entity_kind().is_none() {
+            self.set_block_entity(x, y, z, None);
+        }
+
         old_block
     }
 
@@ -433,7 +611,10 @@ impl Chunk for LoadedChunk {
 
         if let PalettedContainer::Single(b) = &sect.block_states {
             if *b != block {
-                self.cached_init_packets.get_mut().clear();
+                self.modified = true;
+                self.cached_init_packets
+                    .get_mut()
+                    .invalidate_section(sect_y as usize);
 
                 if *self.viewer_count.get_mut() > 0 {
                     // The whole section is being modified, so any previous modifications would
@@ -443,25 +624,32 @@ impl Chunk for LoadedChunk {
                     // Push section updates for all the blocks in the section.
                     sect.section_updates.reserve_exact(SECTION_BLOCK_COUNT);
                     let block_bits = (block.to_raw() as i64) << 12;
-                    for z in 0..16 {
-                        for x in 0..16 {
-                            let packed = block_bits | (x << 8 | z << 4 | sect_y as i64);
-                            sect.section_updates.push(VarLong(packed));
+                    for y in 0..16 {
+                        for z in 0..16 {
+                            for x in 0..16 {
+                                let packed = block_bits | (x << 8 | z << 4 | y);
+                                sect.section_updates.push(VarLong(packed));
+                            }
                         }
                     }
                 }
             }
         } else {
             let block_bits = (block.to_raw() as i64) << 12;
-            for z in 0..16 {
-                for x in 0..16 {
-                    let idx = x + z * 16 + sect_y * (16 * 16);
-                    if block != sect.block_states.get(idx as usize) {
-                        self.cached_init_packets.get_mut().clear();
-
-                        if *self.viewer_count.get_mut() > 0 {
-                            let packed = block_bits | (x << 8 | z << 4 | sect_y) as i64;
-                            sect.section_updates.push(VarLong(packed));
+            for y in 0..16 {
+                for z in 0..16 {
+                    for x in 0..16 {
+                        let idx = x + z * 16 + y * (16 * 16);
+                        if block != sect.block_states.get(idx as usize) {
+                            self.modified = true;
+                            self.cached_init_packets
+                                .get_mut()
+                                .invalidate_section(sect_y as usize);
+
+                            if *self.viewer_count.get_mut() > 0 {
+                                let packed = block_bits | (x << 8 | z << 4 | y) as i64;
+                                sect.section_updates.push(VarLong(packed));
+                            }
                         }
                     }
                 }
@@ -487,7 +675,8 @@ impl Chunk for LoadedChunk {
             if *self.viewer_count.get_mut() > 0 {
                 self.changed_block_entities.insert(idx);
             }
-            self.cached_init_packets.get_mut().clear();
+            self.modified = true;
+            self.cached_init_packets.get_mut().invalidate();
 
             Some(be)
         } else {
@@ -511,7 +700,8 @@ impl Chunk for LoadedChunk {
                 if *self.viewer_count.get_mut() > 0 {
                     self.changed_block_entities.insert(idx);
                 }
-                self.cached_init_packets.get_mut().clear();
+                self.modified = true;
+                self.cached_init_packets.get_mut().invalidate();
 
                 self.block_entities.insert(idx, nbt)
             }
@@ -519,7 +709,8 @@ impl Chunk for LoadedChunk {
                 let res = self.block_entities.remove(&idx);
 
                 if res.is_some() {
-                    self.cached_init_packets.get_mut().clear();
+                    self.modified = true;
+                    self.cached_init_packets.get_mut().invalidate();
                 }
 
                 res
@@ -532,7 +723,8 @@ impl Chunk for LoadedChunk {
             return;
         }
 
-        self.cached_init_packets.get_mut().clear();
+        self.modified = true;
+        self.cached_init_packets.get_mut().invalidate();
 
         if *self.viewer_count.get_mut() > 0 {
             self.changed_block_entities
@@ -558,7 +750,10 @@ impl Chunk for LoadedChunk {
             .set(idx as usize, biome);
 
         if biome != old_biome {
-            self.cached_init_packets.get_mut().clear();
+            self.modified = true;
+            self.cached_init_packets
+                .get_mut()
+                .invalidate_section(y as usize / 4);
 
             if *self.viewer_count.get_mut() > 0 {
                 self.changed_biomes = true;
@@ -575,23 +770,98 @@ impl Chunk for LoadedChunk {
 
         if let PalettedContainer::Single(b) = &sect.biomes {
             if *b != biome {
-                self.cached_init_packets.get_mut().clear();
+                self.modified = true;
+                self.cached_init_packets
+                    .get_mut()
+                    .invalidate_section(sect_y as usize);
                 self.changed_biomes = *self.viewer_count.get_mut() > 0;
             }
         } else {
-            self.cached_init_packets.get_mut().clear();
+            self.modified = true;
+            self.cached_init_packets
+                .get_mut()
+                .invalidate_section(sect_y as usize);
             self.changed_biomes = *self.viewer_count.get_mut() > 0;
         }
 
         sect.biomes.fill(biome);
     }
 
+    fn sky_light(&self, x: u32, y: u32, z: u32) -> u8 {
+        check_block_oob(self, x, y, z);
+
+        let idx = x + z * 16 + y % 16 * 16 * 16;
+        self.sections[y as usize / 16].sky_light.get(idx as usize)
+    }
+
+    fn set_sky_light(&mut self, x: u32, y: u32, z: u32, level: u8) -> u8 {
+        check_block_oob(self, x, y, z);
+
+        let idx = x + z * 16 + y % 16 * 16 * 16;
+        let old_level = self.sections[y as usize / 16]
+            .sky_light
+            .set(idx as usize, level);
+
+        if level.min(15) != old_level {
+            self.modified = true;
+            self.cached_init_packets.get_mut().invalidate();
+
+            if *self.viewer_count.get_mut() > 0 {
+                self.changed_light = true;
+            }
+        }
+
+        old_level
+    }
+
+    fn fill_sky_light_section(&mut self, sect_y: u32, level: u8) {
+        check_section_oob(self, sect_y);
+
+        if self.sections[sect_y as usize].sky_light.fill(level) {
+            self.modified = true;
+            self.cached_init_packets.get_mut().invalidate();
+
+            if *self.viewer_count.get_mut() > 0 {
+                self.changed_light = true;
+            }
+        }
+    }
+
+    fn block_light(&self, x: u32, y: u32, z: u32) -> u8 {
+        check_block_oob(self, x, y, z);
+
+        let idx = x + z * 16 + y % 16 * 16 * 16;
+        self.sections[y as usize / 16].block_light.get(idx as usize)
+    }
+
+    fn set_block_light(&mut self, x: u32, y: u32, z: u32, level: u8) -> u8 {
+        check_block_oob(self, x, y, z);
+
+        let idx = x + z * 16 + y % 16 * 16 * 16;
+        let old_level = self.sections[y as usize / 16]
+            .block_light
+            .set(idx as usize, level);
+
+        if level.min(15) != old_level {
+            self.modified = true;
+            self.cached_init_packets.get_mut().invalidate();
+
+            if *self.viewer_count.get_mut() > 0 {
+                self.changed_light = true;
+            }
+        }
+
+        old_level
+    }
+
     fn shrink_to_fit(&mut self) {
         self.cached_init_packets.get_mut().shrink_to_fit();
 
         for sect in self.sections.iter_mut() {
             sect.block_states.shrink_to_fit();
             sect.biomes.shrink_to_fit();
+            sect.sky_light.shrink_to_fit();
+            sect.block_light.shrink_to_fit();
             sect.section_updates.shrink_to_fit();
         }
     }
@@ -618,6 +888,15 @@ mod tests {
 
         chunk.fill_biomes(BiomeId::from_index(42));
         chunk.assert_no_changes();
+
+        chunk.fill_sky_light_above(100);
+        chunk.assert_no_changes();
+
+        chunk.fill_sky_light(15);
+        chunk.assert_no_changes();
+
+        chunk.set_block_light(0, 10, 0, 14);
+        chunk.assert_no_changes();
     }
 
     #[test]
@@ -639,15 +918,15 @@ mod tests {
             chunk.write_init_packets(&mut writer, ChunkPos::new(3, 4), &info);
 
             // Check that the cache is built.
-            assert!(!chunk.cached_init_packets.get_mut().is_empty());
+            assert!(!chunk.cached_init_packets.get_mut().packets.is_empty());
 
             // Making a change should clear the cache.
             change(chunk);
-            assert!(chunk.cached_init_packets.get_mut().is_empty());
+            assert!(chunk.cached_init_packets.get_mut().packets.is_empty());
 
             // Rebuild cache again.
             chunk.write_init_packets(&mut writer, ChunkPos::new(3, 4), &info);
-            assert!(!chunk.cached_init_packets.get_mut().is_empty());
+            assert!(!chunk.cached_init_packets.get_mut().packets.is_empty());
         }
 
         let mut chunk = LoadedChunk::new(512);
@@ -665,6 +944,9 @@ mod tests {
             c.block_entity_mut(3, 40, 5).unwrap();
         });
         check(&mut chunk, |c| c.set_block_entity(3, 40, 5, None));
+        check(&mut chunk, |c| c.set_sky_light(3, 40, 5, 15));
+        check(&mut chunk, |c| c.fill_sky_light(15));
+        check(&mut chunk, |c| c.set_block_light(3, 40, 5, 12));
 
         // Old block state is the same as new block state, so the cache should still be
         // intact.
@@ -673,6 +955,89 @@ mod tests {
             BlockState::WET_SPONGE
         );
 
-        assert!(!chunk.cached_init_packets.get_mut().is_empty());
+        assert!(!chunk.cached_init_packets.get_mut().packets.is_empty());
+    }
+
+    #[test]
+    fn loaded_chunk_changes_dirty_only_their_section() {
+        let info = ChunkLayerInfo {
+            dimension_type_name: ident!("whatever").into(),
+            height: 512,
+            min_y: -16,
+            biome_registry_len: 200,
+            threshold: CompressionThreshold(-1),
+        };
+
+        #[track_caller]
+        fn dirty_sections(chunk: &mut LoadedChunk) -> Vec<usize> {
+            let cache = chunk.cached_init_packets.get_mut();
+            assert_eq!(cache.sections.len(), 32);
+
+            (0..cache.sections.len())
+                .filter(|&i| cache.sections[i].is_empty())
+                .collect()
+        }
+
+        let init_packets = |chunk: &LoadedChunk| {
+            let mut buf = vec![];
+            let writer = PacketWriter::new(&mut buf, CompressionThreshold(-1));
+            chunk.write_init_packets(writer, ChunkPos::new(3, 4), &info);
+            buf
+        };
+
+        let mut chunk = LoadedChunk::new(512);
+
+        init_packets(&chunk);
+        assert!(dirty_sections(&mut chunk).is_empty());
+
+        chunk.set_block_state(5, 40, 5, BlockState::STONE);
+        assert_eq!(dirty_sections(&mut chunk), [2]);
+
+        // Rebuilding the packet only encodes the dirty section again.
+        let rebuilt = init_packets(&chunk);
+        assert!(dirty_sections(&mut chunk).is_empty());
+
+        let mut fresh = LoadedChunk::new(512);
+        fresh.set_block_state(5, 40, 5, BlockState::STONE);
+        assert_eq!(rebuilt, init_packets(&fresh));
+
+        // Biomes are part of the section data.
+        chunk.set_biome(0, 20, 0, BiomeId::from_index(3));
+        assert_eq!(dirty_sections(&mut chunk), [5]);
+
+        init_packets(&chunk);
+
+        // Light isn't.
+        chunk.set_sky_light(0, 100, 0, 7);
+        assert!(chunk.cached_init_packets.get_mut().packets.is_empty());
+        assert!(dirty_sections(&mut chunk).is_empty());
+    }
+
+    #[test]
+    fn loaded_chunk_fill_section_updates_every_block() {
+        let mut chunk = LoadedChunk::new(512);
+        chunk.inc_viewer_count();
+
+        chunk.fill_block_state_section(3, BlockState::STONE);
+
+        let updates = &chunk.sections[3].section_updates;
+        assert_eq!(updates.len(), SECTION_BLOCK_COUNT);
+
+        let positions: BTreeSet<_> = updates.iter().map(|v| v.0 & 0xfff).collect();
+        assert_eq!(positions.len(), SECTION_BLOCK_COUNT);
+
+        // Only the blocks that differ are updated when filling again.
+        chunk.sections[3].section_updates.clear();
+        chunk.set_block_state(1, 3 * 16 + 15, 2, BlockState::DIRT);
+        chunk.sections[3].section_updates.clear();
+
+        chunk.fill_block_state_section(3, BlockState::STONE);
+
+        let updates = &chunk.sections[3].section_updates;
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].0 & 0xfff, 1 << 8 | 2 << 4 | 15);
+
+        chunk.sections[3].section_updates.clear();
+        chunk.dec_viewer_count();
     }
 }
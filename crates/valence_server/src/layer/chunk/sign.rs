@@ -0,0 +1,108 @@
+use valence_nbt::{compound, Compound, List};
+use valence_protocol::block::{PropName, PropValue};
+use valence_protocol::{BlockPos, BlockState, Direction, Text};
+
+use super::{Block, ChunkLayer};
+
+/// The sixteen dye colors, used for the color of sign text among other things.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default, Debug)]
+pub enum DyeColor {
+    White,
+    Orange,
+    Magenta,
+    LightBlue,
+    Yellow,
+    Lime,
+    Pink,
+    Gray,
+    LightGray,
+    Cyan,
+    Purple,
+    Blue,
+    Brown,
+    Green,
+    Red,
+    #[default]
+    Black,
+}
+
+impl DyeColor {
+    /// Returns the name of the color as used in NBT, such as `"light_blue"`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            DyeColor::White => "white",
+            DyeColor::Orange => "orange",
+            DyeColor::Magenta => "magenta",
+            DyeColor::LightBlue => "light_blue",
+            DyeColor::Yellow => "yellow",
+            DyeColor::Lime => "lime",
+            DyeColor::Pink => "pink",
+            DyeColor::Gray => "gray",
+            DyeColor::LightGray => "light_gray",
+            DyeColor::Cyan => "cyan",
+            DyeColor::Purple => "purple",
+            DyeColor::Blue => "blue",
+            DyeColor::Brown => "brown",
+            DyeColor::Green => "green",
+            DyeColor::Red => "red",
+            DyeColor::Black => "black",
+        }
+    }
+}
+
+impl ChunkLayer {
+    /// Places an oak sign with text on its front at the given position and
+    /// returns the previous block, or `None` if the chunk isn't loaded.
+    ///
+    /// `facing` is the direction the front of the sign faces. A wall sign is
+    /// attached to the block behind it, while a standing sign is placed on
+    /// top of the block below it. The back of the sign is left blank.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `facing` is [`Direction::Up`] or [`Direction::Down`], since
+    /// signs can only face horizontally.
+    #[track_caller]
+    pub fn place_sign(
+        &mut self,
+        pos: impl Into<BlockPos>,
+        facing: Direction,
+        on_wall: bool,
+        lines: [Text; 4],
+        color: DyeColor,
+        glowing: bool,
+    ) -> Option<Block> {
+        let (facing, rotation) = match facing {
+            Direction::South => (PropValue::South, PropValue::_0),
+            Direction::West => (PropValue::West, PropValue::_4),
+            Direction::North => (PropValue::North, PropValue::_8),
+            Direction::East => (PropValue::East, PropValue::_12),
+            Direction::Up | Direction::Down => {
+                panic!("signs must face horizontally, but the facing is {facing:?}")
+            }
+        };
+
+        let state = if on_wall {
+            BlockState::OAK_WALL_SIGN.set(PropName::Facing, facing)
+        } else {
+            BlockState::OAK_SIGN.set(PropName::Rotation, rotation)
+        };
+
+        let nbt = compound! {
+            "front_text" => sign_text(lines, color, glowing),
+            "back_text" => sign_text(Default::default(), DyeColor::Black, false),
+            "is_waxed" => false,
+        };
+
+        self.set_block(pos, Block::new(state, Some(nbt)))
+    }
+}
+
+fn sign_text(lines: [Text; 4], color: DyeColor, glowing: bool) -> Compound {
+    compound! {
+        // All 4 lines are required, otherwise no text is displayed.
+        "messages" => List::String(lines.map(String::from).into()),
+        "color" => color.name(),
+        "has_glowing_text" => glowing,
+    }
+}
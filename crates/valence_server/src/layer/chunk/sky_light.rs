@@ -0,0 +1,149 @@
+use std::collections::VecDeque;
+
+use rustc_hash::FxHashMap;
+use valence_protocol::ChunkPos;
+
+use super::{Chunk, ChunkLayer};
+
+impl ChunkLayer {
+    /// Computes the sky light of every loaded chunk in this layer from its
+    /// blocks, replacing any sky light that was set before.
+    ///
+    /// Light from the sky travels straight down without losing any strength
+    /// until it hits an opaque block. From there, it spreads into the
+    /// surrounding non-opaque blocks, dropping by one level for every block
+    /// travelled. Light spreads between neighboring loaded chunks, but never
+    /// enters unloaded ones.
+    ///
+    /// The extracted block data only says whether a block is opaque, so
+    /// partially transparent blocks such as leaves and water don't dim the
+    /// light like they do in vanilla.
+    ///
+    /// This is an expensive operation that is never run automatically. Call
+    /// it after generating or editing terrain.
+    pub fn recompute_sky_light(&mut self) {
+        let height = self.info.height;
+
+        // The height of the first block above the highest opaque block of
+        // every column.
+        let mut heightmaps = FxHashMap::<ChunkPos, Box<[u32; 16 * 16]>>::default();
+
+        for (&pos, chunk) in &mut self.chunks {
+            let mut heightmap = Box::new([0; 16 * 16]);
+
+            for z in 0..16 {
+                for x in 0..16 {
+                    let surface = (0..height)
+                        .rev()
+                        .find(|&y| chunk.block_state(x, y, z).is_opaque())
+                        .map_or(0, |y| y + 1);
+
+                    heightmap[(x + z * 16) as usize] = surface;
+                }
+            }
+
+            let lowest = heightmap.iter().copied().min().unwrap_or(0);
+            let highest = heightmap.iter().copied().max().unwrap_or(0);
+
+            for sect_y in 0..height / 16 {
+                let (bottom, top) = (sect_y * 16, sect_y * 16 + 16);
+
+                if bottom >= highest {
+                    chunk.fill_sky_light_section(sect_y, 15);
+                } else if top <= lowest {
+                    chunk.fill_sky_light_section(sect_y, 0);
+                } else {
+                    for z in 0..16 {
+                        for x in 0..16 {
+                            let surface = heightmap[(x + z * 16) as usize];
+
+                            for y in bottom..top {
+                                chunk.set_sky_light(x, y, z, if y >= surface { 15 } else { 0 });
+                            }
+                        }
+                    }
+                }
+            }
+
+            heightmaps.insert(pos, heightmap);
+        }
+
+        // Light spreads sideways from the parts of a column that are lower
+        // than a neighboring column.
+        let surface_at = |x: i32, z: i32| {
+            let (pos, offset_x, offset_z) = column(x, z);
+            heightmaps
+                .get(&pos)
+                .map(|heightmap| heightmap[(offset_x + offset_z * 16) as usize])
+        };
+
+        let mut queue = VecDeque::new();
+
+        for (&pos, heightmap) in &heightmaps {
+            for offset_z in 0..16_u32 {
+                for offset_x in 0..16 {
+                    let surface = heightmap[(offset_x + offset_z * 16) as usize];
+                    let x = pos.x * 16 + offset_x as i32;
+                    let z = pos.z * 16 + offset_z as i32;
+
+                    let neighbor_surface = [(x - 1, z), (x + 1, z), (x, z - 1), (x, z + 1)]
+                        .into_iter()
+                        .filter_map(|(x, z)| surface_at(x, z))
+                        .max()
+                        .unwrap_or(0);
+
+                    for y in surface..neighbor_surface {
+                        queue.push_back((x, y, z, 15_u8));
+                    }
+                }
+            }
+        }
+
+        while let Some((x, y, z, level)) = queue.pop_front() {
+            let spread = level - 1;
+
+            let neighbors = [
+                (x - 1, y, z),
+                (x + 1, y, z),
+                (x, y.wrapping_sub(1), z),
+                (x, y + 1, z),
+                (x, y, z - 1),
+                (x, y, z + 1),
+            ];
+
+            for (x, y, z) in neighbors {
+                if y >= height {
+                    continue;
+                }
+
+                let (pos, offset_x, offset_z) = column(x, z);
+
+                let Some(chunk) = self.chunks.get_mut(&pos) else {
+                    continue;
+                };
+
+                if chunk.block_state(offset_x, y, offset_z).is_opaque()
+                    || chunk.sky_light(offset_x, y, offset_z) >= spread
+                {
+                    continue;
+                }
+
+                chunk.set_sky_light(offset_x, y, offset_z, spread);
+
+                if spread > 1 {
+                    queue.push_back((x, y, z, spread));
+                }
+            }
+        }
+    }
+}
+
+/// Returns the chunk containing the column at block `x` and `z`, and the
+/// offsets of the column within the chunk.
+fn column(x: i32, z: i32) -> (ChunkPos, u32, u32) {
+    (
+        ChunkPos::new(x.div_euclid(16), z.div_euclid(16)),
+        x.rem_euclid(16) as u32,
+        z.rem_euclid(16) as u32,
+    )
+}
@@ -7,7 +7,7 @@ use valence_registry::biome::BiomeId;
 
 use super::chunk::{
     check_biome_oob, check_block_oob, check_section_oob, BiomeContainer, BlockStateContainer,
-    Chunk, MAX_HEIGHT, SECTION_BLOCK_COUNT,
+    Chunk, LightContainer, MAX_HEIGHT, SECTION_BLOCK_COUNT,
 };
 
 #[derive(Clone, Default, Debug)]
@@ -20,6 +20,8 @@ pub struct UnloadedChunk {
 pub(super) struct Section {
     pub(super) block_states: BlockStateContainer,
     pub(super) biomes: BiomeContainer,
+    pub(super) sky_light: LightContainer,
+    pub(super) block_light: LightContainer,
 }
 
 impl UnloadedChunk {
@@ -81,9 +83,15 @@ impl Chunk for UnloadedChunk {
         check_block_oob(self, x, y, z);
 
         let idx = x + z * 16 + y % 16 * 16 * 16;
-        self.sections[y as usize / 16]
+        let old_block = self.sections[y as usize / 16]
             .block_states
-            .set(idx as usize, block)
+            .set(idx as usize, block);
+
+        if block.block_entity_kind().is_none() {
+            self.set_block_entity(x, y, z, None);
+        }
+
+        old_block
     }
 
     fn fill_block_state_section(&mut self, sect_y: u32, block: BlockState) {
@@ -149,10 +157,50 @@ impl Chunk for UnloadedChunk {
         self.sections[sect_y as usize].biomes.fill(biome);
     }
 
+    fn sky_light(&self, x: u32, y: u32, z: u32) -> u8 {
+        check_block_oob(self, x, y, z);
+
+        let idx = x + z * 16 + y % 16 * 16 * 16;
+        self.sections[y as usize / 16].sky_light.get(idx as usize)
+    }
+
+    fn set_sky_light(&mut self, x: u32, y: u32, z: u32, level: u8) -> u8 {
+        check_block_oob(self, x, y, z);
+
+        let idx = x + z * 16 + y % 16 * 16 * 16;
+        self.sections[y as usize / 16]
+            .sky_light
+            .set(idx as usize, level)
+    }
+
+    fn fill_sky_light_section(&mut self, sect_y: u32, level: u8) {
+        check_section_oob(self, sect_y);
+
+        self.sections[sect_y as usize].sky_light.fill(level);
+    }
+
+    fn block_light(&self, x: u32, y: u32, z: u32) -> u8 {
+        check_block_oob(self, x, y, z);
+
+        let idx = x + z * 16 + y % 16 * 16 * 16;
+        self.sections[y as usize / 16].block_light.get(idx as usize)
+    }
+
+    fn set_block_light(&mut self, x: u32, y: u32, z: u32, level: u8) -> u8 {
+        check_block_oob(self, x, y, z);
+
+        let idx = x + z * 16 + y % 16 * 16 * 16;
+        self.sections[y as usize / 16]
+            .block_light
+            .set(idx as usize, level)
+    }
+
     fn shrink_to_fit(&mut self) {
         for sect in &mut self.sections {
             sect.block_states.shrink_to_fit();
             sect.biomes.shrink_to_fit();
+            sect.sky_light.shrink_to_fit();
+            sect.block_light.shrink_to_fit();
         }
     }
 }
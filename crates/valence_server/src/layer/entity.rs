@@ -1,27 +1,94 @@
 use std::collections::hash_map::Entry;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use bevy_ecs::query::Has;
-use rustc_hash::FxHashMap;
+use bevy_ecs::system::SystemParam;
+use rustc_hash::{FxHashMap, FxHashSet};
 use valence_entity::query::UpdateEntityQuery;
-use valence_entity::{EntityId, EntityLayerId, OldEntityLayerId, OldPosition, Position};
+use valence_entity::{
+    EntityId, EntityKind, EntityLayerId, OldEntityLayerId, OldPosition, Position,
+};
 use valence_protocol::encode::{PacketWriter, WritePacket};
-use valence_protocol::{BlockPos, ChunkPos, CompressionThreshold, Encode, Packet};
+use valence_protocol::packets::play::PlaySoundFromEntityS2c;
+use valence_protocol::sound::{SoundCategory, SoundId};
+use valence_protocol::{BlockPos, ChunkPos, CompressionThreshold, Encode, Packet, VarInt};
 use valence_server_common::{Despawned, Server};
 
 use super::bvh::GetChunkPos;
 use super::message::Messages;
 use super::{Layer, UpdateLayersPostClientSet, UpdateLayersPreClientSet};
-use crate::client::Client;
+use crate::client::{
+    Client, HiddenEntities, OldHiddenEntities, View, ViewDistance, VisibleEntityLayers,
+};
+use crate::ChunkView;
 
 /// A [`Component`] containing Minecraft entities.
+///
+/// Packets written to the layer with [`WritePacket`] reach viewers before the
+/// entity spawns and updates of the same tick. For instance, a team created
+/// alongside a glowing entity colors the entity's outline as soon as it
+/// appears, and adding the entity to another team later recolors it without
+/// resending the entity.
 #[derive(Component, Debug)]
 pub struct EntityLayer {
     messages: EntityLayerMessages,
     entities: FxHashMap<ChunkPos, BTreeSet<Entity>>,
+    /// The number of entities in [`Self::entities`].
+    entity_count: usize,
+    /// The non-player entities in this layer ordered from oldest to newest,
+    /// along with the chunk they are in. Used to find entities to evict.
+    non_players: BTreeMap<(EntityId, Entity), ChunkPos>,
     threshold: CompressionThreshold,
+    frozen: bool,
+    /// Whether viewers need to be resynchronized with the positions of
+    /// entities after the layer was unfrozen.
+    needs_resync: bool,
+    entity_limit: Option<usize>,
+    entity_limit_policy: EntityLimitPolicy,
+    /// The clients viewing each chunk of this layer. Used by
+    /// [`EntityViewers`].
+    viewers: FxHashMap<ChunkPos, Vec<Entity>>,
+    /// Sounds to play from entities once their position is known. See
+    /// [`EntityLayer::play_sound_from_entity`].
+    entity_sounds: Vec<EntitySound>,
+}
+
+#[derive(Debug)]
+struct EntitySound {
+    entity: Entity,
+    id: SoundId<'static>,
+    category: SoundCategory,
+    volume: f32,
+    pitch: f32,
+    seed: i64,
+}
+
+/// Sent when an entity is despawned because it was added to an
+/// [`EntityLayer`] that reached its entity limit. See
+/// [`EntityLayer::set_entity_limit`].
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct EntityRejectedEvent {
+    /// The rejected entity. It has the [`Despawned`] component.
+    pub entity: Entity,
+    /// The layer that the entity was added to.
+    pub layer: Entity,
+}
+
+/// Determines what happens when a non-player entity is added to an
+/// [`EntityLayer`] that has reached its entity limit. See
+/// [`EntityLayer::set_entity_limit`].
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum EntityLimitPolicy {
+    /// The entity being added is despawned instead, and an
+    /// [`EntityRejectedEvent`] is sent. An entity that was moved from another
+    /// layer is despawned from that layer too.
+    #[default]
+    Reject,
+    /// The oldest non-player entity in the layer is despawned to make room.
+    /// The oldest entity is the one with the lowest [`EntityId`].
+    EvictOldest,
 }
 
 type EntityLayerMessages = Messages<GlobalMsg, LocalMsg>;
@@ -42,15 +109,23 @@ pub(crate) enum GlobalMsg {
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
 // NOTE: Variant order is significant. Despawns should be ordered before spawns.
 pub(crate) enum LocalMsg {
-    /// Despawn entities if the client is not already viewing `dest_layer`.
-    /// Message data is the serialized form of `EntityId`.
-    DespawnEntity { pos: ChunkPos, dest_layer: Entity },
+    /// Despawn entities unless the client is already viewing `dest_layer` with
+    /// `dest_pos` in view. Message data is the serialized form of `EntityId`.
+    DespawnEntity {
+        pos: ChunkPos,
+        dest_layer: Entity,
+        dest_pos: ChunkPos,
+    },
     /// Despawn entities if the client is not in view of `dest_pos`. Message
     /// data is the serialized form of `EntityId`.
     DespawnEntityTransition { pos: ChunkPos, dest_pos: ChunkPos },
-    /// Spawn entities if the client is not already viewing `src_layer`. Message
-    /// data is the serialized form of [`Entity`].
-    SpawnEntity { pos: ChunkPos, src_layer: Entity },
+    /// Spawn entities unless the client is already viewing `src_layer` with
+    /// `src_pos` in view. Message data is the serialized form of [`Entity`].
+    SpawnEntity {
+        pos: ChunkPos,
+        src_layer: Entity,
+        src_pos: ChunkPos,
+    },
     /// Spawn entities if the client is not in view of `src_pos`. Message data
     /// is the serialized form of [`Entity`].
     SpawnEntityTransition { pos: ChunkPos, src_pos: ChunkPos },
@@ -61,6 +136,15 @@ pub(crate) enum LocalMsg {
     /// except the client identified by `except`. Message data is serialized
     /// packet data.
     PacketAtExcept { pos: ChunkPos, except: Entity },
+    /// Send the packet data of `entity` to all clients viewing the layer in
+    /// view of `pos`, except `entity` itself and the clients it is hidden
+    /// from. Message data is serialized packet data.
+    EntityPacketAt { pos: ChunkPos, entity: Entity },
+    /// Send the [`Passengers`](valence_entity::Passengers) of `vehicle` to all
+    /// clients viewing the layer in view of `pos`, except the clients it is
+    /// hidden from. Each client writes the packet itself since riders know
+    /// their own entity by the ID 0. Message data is empty.
+    PassengersAt { pos: ChunkPos, vehicle: Entity },
     /// Send packet data to all clients in a sphere.
     RadiusAt {
         center: BlockPos,
@@ -79,6 +163,8 @@ impl GetChunkPos for LocalMsg {
         match *self {
             LocalMsg::PacketAt { pos } => pos,
             LocalMsg::PacketAtExcept { pos, .. } => pos,
+            LocalMsg::EntityPacketAt { pos, .. } => pos,
+            LocalMsg::PassengersAt { pos, .. } => pos,
             LocalMsg::RadiusAt { center, .. } => center.to_chunk_pos(),
             LocalMsg::RadiusAtExcept { center, .. } => center.to_chunk_pos(),
             LocalMsg::SpawnEntity { pos, .. } => pos,
@@ -95,8 +181,151 @@ impl EntityLayer {
         Self {
             messages: Messages::new(),
             entities: Default::default(),
+            entity_count: 0,
+            non_players: BTreeMap::new(),
             threshold: server.compression_threshold(),
+            frozen: false,
+            needs_resync: false,
+            entity_limit: None,
+            entity_limit_policy: EntityLimitPolicy::Reject,
+            viewers: Default::default(),
+            entity_sounds: vec![],
+        }
+    }
+
+    /// Returns whether this layer is frozen. See [`Self::set_frozen`].
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Freezes or unfreezes this layer.
+    ///
+    /// While a layer is frozen, changes to the position, look, velocity and
+    /// head yaw of its entities are not sent to viewers. Tracked data,
+    /// statuses and animations are still sent, as are spawn and despawn
+    /// packets. Clients viewing the layer keep receiving everything else as
+    /// usual.
+    ///
+    /// When the layer is unfrozen, viewers receive a single absolute position
+    /// update for each entity instead of the movement that happened while the
+    /// layer was frozen.
+    pub fn set_frozen(&mut self, frozen: bool) {
+        if self.frozen && !frozen {
+            self.needs_resync = true;
+        }
+
+        self.frozen = frozen;
+    }
+
+    /// Returns the number of entities in this layer.
+    pub fn entity_count(&self) -> usize {
+        self.entity_count
+    }
+
+    /// Returns the maximum number of entities in this layer and what happens
+    /// when it is reached, if there is a limit. See [`Self::set_entity_limit`].
+    pub fn entity_limit(&self) -> Option<(usize, EntityLimitPolicy)> {
+        self.entity_limit
+            .map(|limit| (limit, self.entity_limit_policy))
+    }
+
+    /// Limits the number of entities in this layer. Use `None` to remove the
+    /// limit, which is the default.
+    ///
+    /// The limit is checked when entities are added to the layer. Entities that
+    /// are added while the layer is full are handled according to `policy`.
+    /// Player entities are never rejected or evicted, but they do count
+    /// towards the limit. Entities already in the layer are kept if the limit
+    /// is lowered.
+    pub fn set_entity_limit(&mut self, limit: Option<usize>, policy: EntityLimitPolicy) {
+        self.entity_limit = limit;
+        self.entity_limit_policy = policy;
+    }
+
+    /// Makes room for a new entity according to the entity limit. Returns the
+    /// entity that was removed from the layer to make room, or `Err` if the new
+    /// entity must be rejected. Nothing is changed when the entity is rejected.
+    fn make_room(&mut self, is_player: bool) -> Result<Option<Entity>, ()> {
+        let Some(limit) = self.entity_limit else {
+            return Ok(None);
+        };
+
+        if is_player || self.entity_count < limit {
+            return Ok(None);
+        }
+
+        if self.entity_limit_policy == EntityLimitPolicy::Reject {
+            return Err(());
+        }
+
+        // Only players remain otherwise.
+        let Some((&(id, entity), &pos)) = self.non_players.first_key_value() else {
+            return Err(());
+        };
+
+        self.remove_entity(pos, entity, id, false);
+
+        self.messages.send_local_infallible(
+            LocalMsg::DespawnEntity {
+                pos,
+                dest_layer: Entity::PLACEHOLDER,
+                dest_pos: pos,
+            },
+            |b| b.extend_from_slice(&id.get().to_ne_bytes()),
+        );
+
+        Ok(Some(entity))
+    }
+
+    /// Adds an entity to the cell at `pos`. Returns whether it wasn't already
+    /// there.
+    fn insert_entity(
+        &mut self,
+        pos: ChunkPos,
+        entity: Entity,
+        id: EntityId,
+        is_player: bool,
+    ) -> bool {
+        if !self.entities.entry(pos).or_default().insert(entity) {
+            return false;
+        }
+
+        self.entity_count += 1;
+
+        if !is_player {
+            self.non_players.insert((id, entity), pos);
         }
+
+        true
+    }
+
+    /// Removes an entity from the cell at `pos`. Returns whether it was there.
+    fn remove_entity(
+        &mut self,
+        pos: ChunkPos,
+        entity: Entity,
+        id: EntityId,
+        is_player: bool,
+    ) -> bool {
+        let Entry::Occupied(mut cell) = self.entities.entry(pos) else {
+            return false;
+        };
+
+        if !cell.get_mut().remove(&entity) {
+            return false;
+        }
+
+        if cell.get().is_empty() {
+            cell.remove();
+        }
+
+        self.entity_count -= 1;
+
+        if !is_player {
+            self.non_players.remove(&(id, entity));
+        }
+
+        true
     }
 
     /// Returns an iterator over all entities contained within the given chunk
@@ -111,6 +340,57 @@ impl EntityLayer {
             .flat_map(|entities| entities.iter().copied())
     }
 
+    /// Returns the clients viewing the given chunk position in this layer.
+    fn viewers_at(&self, pos: ChunkPos) -> impl Iterator<Item = Entity> + '_ {
+        self.viewers
+            .get(&pos)
+            .into_iter()
+            .flat_map(|viewers| viewers.iter().copied())
+    }
+
+    fn add_viewer(&mut self, pos: ChunkPos, client: Entity) {
+        self.viewers.entry(pos).or_default().push(client);
+    }
+
+    fn remove_viewer(&mut self, pos: ChunkPos, client: Entity) {
+        if let Entry::Occupied(mut viewers) = self.viewers.entry(pos) {
+            viewers.get_mut().retain(|&e| e != client);
+
+            if viewers.get().is_empty() {
+                viewers.remove();
+            }
+        }
+    }
+
+    /// Plays a sound effect attached to `entity`, which should be in this
+    /// layer. Unlike [`ChunkLayer::play_sound`], the sound follows the entity
+    /// as it moves. `sound` can also be a custom sound from a resource pack.
+    ///
+    /// The sound is played for the clients that can see the entity's chunk in
+    /// this layer when the layer is updated. Clients the entity is hidden from
+    /// with [`HiddenEntities`] don't hear it, and neither does the entity
+    /// itself if it is a client, since clients play their own sounds. Nothing
+    /// is played if the entity is despawned by then.
+    ///
+    /// [`ChunkLayer::play_sound`]: super::ChunkLayer::play_sound
+    pub fn play_sound_from_entity(
+        &mut self,
+        entity: Entity,
+        sound: impl Into<SoundId<'static>>,
+        category: SoundCategory,
+        volume: f32,
+        pitch: f32,
+    ) {
+        self.entity_sounds.push(EntitySound {
+            entity,
+            id: sound.into(),
+            category,
+            volume,
+            pitch,
+            seed: rand::random(),
+        });
+    }
+
     pub(crate) fn messages(&self) -> &EntityLayerMessages {
         &self.messages
     }
@@ -346,21 +626,180 @@ impl WritePacket for RadiusExceptWriter<'_> {
     }
 }
 
-pub(super) fn build(app: &mut App) {
-    app.add_systems(
-        PostUpdate,
+/// A [`SystemParam`] for finding the clients that can see a Minecraft entity.
+///
+/// A client sees an entity if the entity's [`EntityLayerId`] is one of the
+/// client's [`VisibleEntityLayers`], the entity's chunk is within the client's
+/// view and the entity is not one of the client's [`HiddenEntities`]. Clients
+/// never see themselves, and markers are not visible to anyone.
+///
+/// The clients viewing each chunk are looked up in an index kept by the
+/// [`EntityLayer`]. The index is updated in [`UpdateLayersPreClientSet`], so
+/// changes to a client's view or visible layers made earlier in the tick are
+/// not reflected until then. Every call made within one system run agrees on
+/// the result.
+#[derive(SystemParam)]
+pub struct EntityViewers<'w, 's> {
+    clients: Query<'w, 's, &'static HiddenEntities, With<Client>>,
+    entities: Query<
+        'w,
+        's,
         (
-            (
-                change_entity_positions,
-                send_entity_update_messages,
-                send_layer_despawn_messages,
-                ready_entity_layers,
-            )
-                .chain()
-                .in_set(UpdateLayersPreClientSet),
-            unready_entity_layers.in_set(UpdateLayersPostClientSet),
+            &'static EntityLayerId,
+            &'static Position,
+            &'static EntityKind,
         ),
-    );
+    >,
+    layers: Query<'w, 's, &'static EntityLayer>,
+}
+
+impl EntityViewers<'_, '_> {
+    /// Returns an iterator over the client entities that can see `entity`. The
+    /// iterator is empty if `entity` is not a Minecraft entity.
+    pub fn iter(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.target(entity)
+            .into_iter()
+            .flat_map(|(layer, pos)| layer.viewers_at(pos))
+            .filter(move |&client| self.can_see(entity, client))
+    }
+
+    /// Returns whether `client` can see `entity`.
+    pub fn contains(&self, entity: Entity, client: Entity) -> bool {
+        self.target(entity).is_some_and(|(layer, pos)| {
+            layer.viewers_at(pos).any(|e| e == client) && self.can_see(entity, client)
+        })
+    }
+
+    fn target(&self, entity: Entity) -> Option<(&EntityLayer, ChunkPos)> {
+        let (layer, pos, kind) = self.entities.get(entity).ok()?;
+
+        if *kind == EntityKind::MARKER {
+            return None;
+        }
+
+        Some((self.layers.get(layer.0).ok()?, pos.to_chunk_pos()))
+    }
+
+    fn can_see(&self, entity: Entity, client: Entity) -> bool {
+        client != entity
+            && self
+                .clients
+                .get(client)
+                .is_ok_and(|hidden| !hidden.0.contains(&entity))
+    }
+}
+
+/// Keeps the per-chunk viewer index of entity layers in sync with the view and
+/// [`VisibleEntityLayers`] of clients. The indexed state of each client is kept
+/// in `indexed` so that clients can be removed after they are despawned.
+#[allow(clippy::type_complexity)]
+fn update_entity_layer_viewers(
+    clients: Query<
+        (Entity, &VisibleEntityLayers, View),
+        (
+            With<Client>,
+            Or<(
+                Changed<VisibleEntityLayers>,
+                Changed<Position>,
+                Changed<ViewDistance>,
+            )>,
+        ),
+    >,
+    mut removed_clients: RemovedComponents<Client>,
+    mut layers: Query<&mut EntityLayer>,
+    mut indexed: Local<FxHashMap<Entity, (BTreeSet<Entity>, ChunkView)>>,
+) {
+    let mut update = |client: Entity, new: Option<(&BTreeSet<Entity>, ChunkView)>| {
+        let old = indexed.get(&client);
+
+        if old.map(|(l, v)| (l, *v)) == new {
+            return;
+        }
+
+        let empty = BTreeSet::new();
+        let (old_layers, old_view) = old.map_or((&empty, None), |(l, v)| (l, Some(*v)));
+        let (new_layers, new_view) = new.map_or((&empty, None), |(l, v)| (l, Some(v)));
+
+        for &layer_id in old_layers.union(new_layers) {
+            let Ok(mut layer) = layers.get_mut(layer_id) else {
+                continue;
+            };
+
+            let old_view = old_view.filter(|_| old_layers.contains(&layer_id));
+            let new_view = new_view.filter(|_| new_layers.contains(&layer_id));
+
+            match (old_view, new_view) {
+                (Some(old_view), Some(new_view)) => {
+                    for pos in old_view.diff(new_view) {
+                        layer.remove_viewer(pos, client);
+                    }
+
+                    for pos in new_view.diff(old_view) {
+                        layer.add_viewer(pos, client);
+                    }
+                }
+                (Some(old_view), None) => {
+                    for pos in old_view.iter() {
+                        layer.remove_viewer(pos, client);
+                    }
+                }
+                (None, Some(new_view)) => {
+                    for pos in new_view.iter() {
+                        layer.add_viewer(pos, client);
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+
+        match new {
+            Some((layers, view)) => {
+                indexed.insert(client, (layers.clone(), view));
+            }
+            None => {
+                indexed.remove(&client);
+            }
+        }
+    };
+
+    for client in removed_clients.iter() {
+        update(client, None);
+    }
+
+    for (client, visible_layers, view) in &clients {
+        update(client, Some((&visible_layers.0, view.get())));
+    }
+}
+
+/// Global settings for how entity updates are sent to viewers.
+#[derive(Resource, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct EntityUpdateSettings {
+    /// Send every change to an entity's position or look as an absolute
+    /// teleport instead of relative movement. This is meant for debugging
+    /// desyncs. If an entity drifts out of place with this enabled, the problem
+    /// isn't the accumulation of relative movement on the client. Off by
+    /// default.
+    pub always_teleport: bool,
+}
+
+pub(super) fn build(app: &mut App) {
+    app.init_resource::<EntityUpdateSettings>()
+        .add_event::<EntityRejectedEvent>()
+        .add_systems(
+            PostUpdate,
+            (
+                (
+                    change_entity_positions,
+                    send_entity_update_messages,
+                    send_layer_despawn_messages,
+                    ready_entity_layers,
+                )
+                    .chain()
+                    .in_set(UpdateLayersPreClientSet),
+                update_entity_layer_viewers.in_set(UpdateLayersPreClientSet),
+                unready_entity_layers.in_set(UpdateLayersPostClientSet),
+            ),
+        );
 }
 
 fn change_entity_positions(
@@ -372,68 +811,105 @@ fn change_entity_positions(
             &OldPosition,
             &EntityLayerId,
             &OldEntityLayerId,
+            &EntityKind,
             Has<Despawned>,
         ),
         Or<(Changed<Position>, Changed<EntityLayerId>, With<Despawned>)>,
     >,
     mut layers: Query<&mut EntityLayer>,
+    mut rejected_events: EventWriter<EntityRejectedEvent>,
+    mut commands: Commands,
 ) {
-    for (entity, entity_id, pos, old_pos, layer_id, old_layer_id, despawned) in &entities {
+    // Entities removed by entity limits during this run.
+    let mut removed = FxHashSet::default();
+
+    for (entity, entity_id, pos, old_pos, layer_id, old_layer_id, kind, despawned) in &entities {
+        if removed.contains(&entity) {
+            continue;
+        }
+
         let chunk_pos = pos.to_chunk_pos();
         let old_chunk_pos = old_pos.chunk_pos();
+        let is_player = *kind == EntityKind::PLAYER;
 
         if despawned {
             // Entity was deleted. Remove it from the layer.
 
-            if let Ok(old_layer) = layers.get_mut(layer_id.0) {
-                let old_layer = old_layer.into_inner();
+            if let Ok(mut old_layer) = layers.get_mut(layer_id.0) {
+                if old_layer.remove_entity(old_chunk_pos, entity, *entity_id, is_player) {
+                    old_layer.messages.send_local_infallible(
+                        LocalMsg::DespawnEntity {
+                            pos: old_chunk_pos,
+                            dest_layer: Entity::PLACEHOLDER,
+                            dest_pos: old_chunk_pos,
+                        },
+                        |b| b.extend_from_slice(&entity_id.get().to_ne_bytes()),
+                    );
+                }
+            }
+        } else if old_layer_id != layer_id {
+            // Entity changed their layer. Make room in the new layer, then remove it from
+            // the old layer and insert it in the new layer.
 
-                if let Entry::Occupied(mut old_cell) = old_layer.entities.entry(old_chunk_pos) {
-                    if old_cell.get_mut().remove(&entity) {
-                        old_layer.messages.send_local_infallible(
-                            LocalMsg::DespawnEntity {
-                                pos: old_chunk_pos,
-                                dest_layer: Entity::PLACEHOLDER,
-                            },
-                            |b| b.extend_from_slice(&entity_id.get().to_ne_bytes()),
-                        );
+            let mut accepted = true;
 
-                        if old_cell.get().is_empty() {
-                            old_cell.remove();
-                        }
+            if let Ok(mut layer) = layers.get_mut(layer_id.0) {
+                if layer
+                    .entities
+                    .get(&chunk_pos)
+                    .is_some_and(|cell| cell.contains(&entity))
+                {
+                    continue;
+                }
+
+                match layer.make_room(is_player) {
+                    Ok(None) => {}
+                    Ok(Some(evicted)) => {
+                        commands.entity(evicted).insert(Despawned);
+                        removed.insert(evicted);
+                    }
+                    Err(()) => {
+                        accepted = false;
+                        commands.entity(entity).insert(Despawned);
+                        rejected_events.send(EntityRejectedEvent {
+                            entity,
+                            layer: layer_id.0,
+                        });
                     }
                 }
             }
-        } else if old_layer_id != layer_id {
-            // Entity changed their layer. Remove it from old layer and insert it in the new
-            // layer.
-
-            if let Ok(old_layer) = layers.get_mut(old_layer_id.get()) {
-                let old_layer = old_layer.into_inner();
-
-                if let Entry::Occupied(mut old_cell) = old_layer.entities.entry(old_chunk_pos) {
-                    if old_cell.get_mut().remove(&entity) {
-                        old_layer.messages.send_local_infallible(
-                            LocalMsg::DespawnEntity {
-                                pos: old_chunk_pos,
-                                dest_layer: layer_id.0,
-                            },
-                            |b| b.extend_from_slice(&entity_id.get().to_ne_bytes()),
-                        );
 
-                        if old_cell.get().is_empty() {
-                            old_cell.remove();
-                        }
-                    }
+            if let Ok(mut old_layer) = layers.get_mut(old_layer_id.get()) {
+                if old_layer.remove_entity(old_chunk_pos, entity, *entity_id, is_player) {
+                    // Rejected entities are despawned for everyone.
+                    let (dest_layer, dest_pos) = if accepted {
+                        (layer_id.0, chunk_pos)
+                    } else {
+                        (Entity::PLACEHOLDER, old_chunk_pos)
+                    };
+
+                    old_layer.messages.send_local_infallible(
+                        LocalMsg::DespawnEntity {
+                            pos: old_chunk_pos,
+                            dest_layer,
+                            dest_pos,
+                        },
+                        |b| b.extend_from_slice(&entity_id.get().to_ne_bytes()),
+                    );
                 }
             }
 
+            if !accepted {
+                continue;
+            }
+
             if let Ok(mut layer) = layers.get_mut(layer_id.0) {
-                if layer.entities.entry(chunk_pos).or_default().insert(entity) {
+                if layer.insert_entity(chunk_pos, entity, *entity_id, is_player) {
                     layer.messages.send_local_infallible(
                         LocalMsg::SpawnEntity {
                             pos: chunk_pos,
                             src_layer: old_layer_id.get(),
+                            src_pos: old_chunk_pos,
                         },
                         |b| b.extend_from_slice(&entity.to_bits().to_ne_bytes()),
                     );
@@ -444,19 +920,17 @@ fn change_entity_positions(
             // old cell and insert it in the new cell.
 
             if let Ok(mut layer) = layers.get_mut(layer_id.0) {
-                if let Entry::Occupied(mut old_cell) = layer.entities.entry(old_chunk_pos) {
-                    if old_cell.get_mut().remove(&entity) {
-                        layer.messages.send_local_infallible(
-                            LocalMsg::DespawnEntityTransition {
-                                pos: old_chunk_pos,
-                                dest_pos: chunk_pos,
-                            },
-                            |b| b.extend_from_slice(&entity_id.get().to_ne_bytes()),
-                        );
-                    }
+                if layer.remove_entity(old_chunk_pos, entity, *entity_id, is_player) {
+                    layer.messages.send_local_infallible(
+                        LocalMsg::DespawnEntityTransition {
+                            pos: old_chunk_pos,
+                            dest_pos: chunk_pos,
+                        },
+                        |b| b.extend_from_slice(&entity_id.get().to_ne_bytes()),
+                    );
                 }
 
-                if layer.entities.entry(chunk_pos).or_default().insert(entity) {
+                if layer.insert_entity(chunk_pos, entity, *entity_id, is_player) {
                     layer.messages.send_local_infallible(
                         LocalMsg::SpawnEntityTransition {
                             pos: chunk_pos,
@@ -473,7 +947,16 @@ fn change_entity_positions(
 fn send_entity_update_messages(
     entities: Query<(Entity, UpdateEntityQuery, Has<Client>), Without<Despawned>>,
     mut layers: Query<&mut EntityLayer>,
+    hidden_entities: Query<(&HiddenEntities, &OldHiddenEntities)>,
+    settings: Res<EntityUpdateSettings>,
 ) {
+    // Entities that are hidden from at least one client, or were until this tick.
+    let hidden: FxHashSet<Entity> = hidden_entities
+        .iter()
+        .flat_map(|(hidden, old_hidden)| hidden.0.iter().chain(old_hidden.get()))
+        .copied()
+        .collect();
+
     for layer in layers.iter_mut() {
         let layer = layer.into_inner();
 
@@ -484,8 +967,14 @@ fn send_entity_update_messages(
 
                     // Send the update packets to all viewers. If the entity being updated is a
                     // client, then we need to be careful to exclude the client itself from
-                    // receiving the update packets.
-                    let msg = if is_client {
+                    // receiving the update packets. Hidden entities get their own messages so
+                    // that clients can skip them.
+                    let msg = if hidden.contains(&entity) {
+                        LocalMsg::EntityPacketAt {
+                            pos: chunk_pos,
+                            entity,
+                        }
+                    } else if is_client {
                         LocalMsg::PacketAtExcept {
                             pos: chunk_pos,
                             except: entity,
@@ -495,8 +984,30 @@ fn send_entity_update_messages(
                     };
 
                     layer.messages.send_local_infallible(msg, |b| {
-                        update.write_update_packets(PacketWriter::new(b, layer.threshold))
+                        let mut writer = PacketWriter::new(b, layer.threshold);
+
+                        if !layer.frozen {
+                            if layer.needs_resync {
+                                update.write_resync_packets(&mut writer);
+                            } else if settings.always_teleport {
+                                update.write_absolute_movement_packets(&mut writer);
+                            } else {
+                                update.write_movement_packets(&mut writer);
+                            }
+                        }
+
+                        update.write_state_packets(&mut writer);
                     });
+
+                    if update.passengers.as_ref().is_some_and(|p| p.is_changed()) {
+                        layer.messages.send_local_infallible(
+                            LocalMsg::PassengersAt {
+                                pos: chunk_pos,
+                                vehicle: entity,
+                            },
+                            |_| {},
+                        );
+                    }
                 } else {
                     panic!(
                         "Entity {entity:?} was not properly removed from entity layer. Did you \
@@ -505,6 +1016,32 @@ fn send_entity_update_messages(
                 }
             }
         }
+
+        for sound in layer.entity_sounds.drain(..) {
+            let Ok((entity, update, _)) = entities.get(sound.entity) else {
+                continue;
+            };
+
+            let msg = LocalMsg::EntityPacketAt {
+                pos: update.pos.to_chunk_pos(),
+                entity,
+            };
+
+            layer.messages.send_local_infallible(msg, |b| {
+                PacketWriter::new(b, layer.threshold).write_packet(&PlaySoundFromEntityS2c {
+                    id: sound.id,
+                    category: sound.category,
+                    entity_id: VarInt(update.id.get()),
+                    volume: sound.volume,
+                    pitch: sound.pitch,
+                    seed: sound.seed,
+                });
+            });
+        }
+
+        if !layer.frozen {
+            layer.needs_resync = false;
+        }
     }
 }
 
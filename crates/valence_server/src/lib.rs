@@ -20,14 +20,19 @@
 
 pub mod abilities;
 pub mod action;
+pub mod autosave;
+pub mod chat_session;
 mod chunk_view;
 pub mod client;
 pub mod client_command;
 pub mod client_settings;
+pub mod command_graph;
+pub mod command_suggestions;
 pub mod custom_payload;
 pub mod event_loop;
 pub mod hand_swing;
 pub mod interact_block;
+pub mod interact_cooldown;
 pub mod interact_entity;
 pub mod interact_item;
 pub mod keepalive;
@@ -35,11 +40,15 @@ pub mod layer;
 pub mod message;
 pub mod movement;
 pub mod op_level;
+pub mod recipe_book;
 pub mod resource_pack;
 pub mod spawn;
 pub mod status;
+pub mod team_visibility;
 pub mod teleport;
+pub mod tick_budget;
 pub mod title;
+pub mod world_time;
 
 pub use chunk_view::ChunkView;
 pub use event_loop::{EventLoopPostUpdate, EventLoopPreUpdate, EventLoopUpdate};
@@ -1,22 +1,119 @@
 // TODO: delete this module in favor of valence_chat.
 
+use std::collections::{BTreeMap, VecDeque};
+
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use valence_protocol::encode::WritePacket;
-use valence_protocol::packets::play::{ChatMessageC2s, GameMessageS2c};
+use valence_protocol::packets::play::{ChatMessageC2s, GameMessageS2c, MessageAcknowledgmentC2s};
 use valence_protocol::text::IntoText;
+use valence_protocol::{FixedBitSet, Text, VarInt};
+use valence_server_common::UniqueId;
 
+use crate::chat_session::{ChatSession, MessageBody};
+use crate::client::DisconnectClient;
 use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
 
 pub struct MessagePlugin;
 
 impl Plugin for MessagePlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<ChatMessageEvent>()
+        app.init_resource::<ChatSettings>()
+            .add_event::<ChatMessageEvent>()
             .add_systems(EventLoopPreUpdate, handle_chat_message);
     }
 }
 
+/// Global chat configuration.
+#[derive(Resource, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct ChatSettings {
+    /// Whether clients must sign their chat messages.
+    ///
+    /// When `false`, unsigned messages are accepted and the server list
+    /// advertises that secure chat is not enforced. Clients sending a message
+    /// without a valid signature from their [`ChatSession`] while this is
+    /// `true` are disconnected.
+    ///
+    /// The server list only picks up the value present when `NetworkPlugin`
+    /// is built. Pass the same value to
+    /// [`Client::set_server_data`](crate::client::Client::set_server_data) to
+    /// keep the pause menu consistent with it.
+    ///
+    /// # Default Value
+    ///
+    /// `false`
+    pub enforce_secure_chat: bool,
+}
+
+/// A message with variants for different client locales.
+///
+/// Pick the variant for a client with [`LocalizedText::get`] and the locale
+/// from its [`ClientSettings`]. Messages that only use vanilla translation
+/// keys don't need this, since the client localizes
+/// [`Text::translate`](valence_protocol::Text::translate) components itself.
+///
+/// # Examples
+///
+/// ```
+/// use valence_server::message::LocalizedText;
+/// use valence_server::Text;
+///
+/// let welcome = LocalizedText::new("Welcome!").with("de_de", "Willkommen!");
+///
+/// assert_eq!(welcome.get("de_de"), &Text::from("Willkommen!"));
+/// assert_eq!(welcome.get("de_at"), &Text::from("Willkommen!"));
+/// assert_eq!(welcome.get("fr_fr"), &Text::from("Welcome!"));
+/// ```
+///
+/// [`ClientSettings`]: crate::client_settings::ClientSettings
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct LocalizedText {
+    default: Text,
+    variants: BTreeMap<Box<str>, Text>,
+}
+
+impl LocalizedText {
+    /// Creates a message that shows `default` for every locale without a
+    /// variant.
+    pub fn new<'a>(default: impl IntoText<'a>) -> Self {
+        Self {
+            default: default.into_text(),
+            variants: BTreeMap::new(),
+        }
+    }
+
+    /// Adds the variant shown to clients with the locale `locale`, such as
+    /// `"de_de"`.
+    pub fn with<'a>(mut self, locale: &str, text: impl IntoText<'a>) -> Self {
+        self.variants
+            .insert(locale.to_ascii_lowercase().into(), text.into_text());
+        self
+    }
+
+    /// Returns the variant for `locale`.
+    ///
+    /// If there is no variant for the exact locale, a variant for another
+    /// locale of the same language is used, e.g. `de_de` for `de_at`. Failing
+    /// that, the default is returned.
+    pub fn get(&self, locale: &str) -> &Text {
+        let locale = locale.to_ascii_lowercase();
+
+        if let Some(text) = self.variants.get(locale.as_str()) {
+            return text;
+        }
+
+        let language = match locale.split_once('_') {
+            Some((language, _)) => language,
+            None => &locale,
+        };
+
+        self.variants
+            .iter()
+            .find(|(l, _)| l.split('_').next() == Some(language))
+            .map_or(&self.default, |(_, text)| text)
+    }
+}
+
 pub trait SendMessage {
     /// Sends a system message visible in the chat.
     fn send_chat_message<'a>(&mut self, msg: impl IntoText<'a>);
@@ -40,24 +137,214 @@ impl<T: WritePacket> SendMessage for T {
     }
 }
 
+/// The number of signed chat messages a client reports as seen or not in each
+/// of its acknowledgments.
+const LAST_SEEN_COUNT: usize = 20;
+
+/// Tracks the chat messages a client has acknowledged.
+///
+/// Clients report the number of chat messages they have seen since their
+/// previous report, either along with a chat message of their own or in a
+/// separate [`MessageAcknowledgmentC2s`]. The signatures of the signed chat
+/// messages a client has seen are part of the signatures of its own messages,
+/// so they are kept here to verify them.
+///
+/// Everything sent through [`SendMessage`] is a system message, which clients
+/// don't acknowledge. Call [`Self::track_signed_message`] for every signed
+/// player chat message written to the client directly.
+#[derive(Component, Clone, PartialEq, Eq, Debug)]
+pub struct MessageAcknowledgments {
+    acknowledged: u64,
+    /// The signed chat messages written to the client, oldest first. The
+    /// client reports on the first [`LAST_SEEN_COUNT`] of them, and `None`
+    /// marks the ones it has not seen.
+    tracked: VecDeque<Option<TrackedMessage>>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct TrackedMessage {
+    signature: Box<[u8; 256]>,
+    /// Whether the client has not reported on the message yet.
+    pending: bool,
+}
+
+impl Default for MessageAcknowledgments {
+    fn default() -> Self {
+        Self {
+            acknowledged: 0,
+            tracked: vec![None; LAST_SEEN_COUNT].into(),
+        }
+    }
+}
+
+impl MessageAcknowledgments {
+    /// Returns the total number of messages the client has acknowledged.
+    pub fn acknowledged(&self) -> u64 {
+        self.acknowledged
+    }
+
+    /// Tracks a signed chat message with the given signature that was written
+    /// to the client.
+    pub fn track_signed_message(&mut self, signature: &[u8; 256]) {
+        self.tracked.push_back(Some(TrackedMessage {
+            signature: Box::new(*signature),
+            pending: true,
+        }));
+    }
+
+    /// Records that the client has seen `message_count` more messages. Returns
+    /// `false` if the count doesn't match the tracked messages.
+    fn record(&mut self, message_count: VarInt) -> bool {
+        // Negative counts are invalid and ignored.
+        let Ok(count) = usize::try_from(message_count.0) else {
+            return false;
+        };
+
+        self.acknowledged += count as u64;
+
+        if count > self.tracked.len() - LAST_SEEN_COUNT {
+            return false;
+        }
+
+        self.tracked.drain(..count);
+
+        true
+    }
+
+    /// Returns the signatures of the messages the client reports as seen in
+    /// `acknowledgement`, oldest first. Returns `None` if the report doesn't
+    /// match the tracked messages.
+    fn last_seen(&mut self, acknowledgement: &FixedBitSet<20, 3>) -> Option<Vec<[u8; 256]>> {
+        let mut last_seen = vec![];
+
+        for (i, entry) in self.tracked.iter_mut().take(LAST_SEEN_COUNT).enumerate() {
+            if acknowledgement.bit(i) {
+                let msg = entry.as_mut()?;
+                msg.pending = false;
+                last_seen.push(*msg.signature);
+            } else if entry.as_ref().is_some_and(|msg| !msg.pending) {
+                // A message can't be unseen once it was reported as seen.
+                return None;
+            } else {
+                *entry = None;
+            }
+        }
+
+        Some(last_seen)
+    }
+}
+
 #[derive(Event, Clone, Debug)]
 pub struct ChatMessageEvent {
     pub client: Entity,
     pub message: Box<str>,
     pub timestamp: u64,
+    /// Whether the message was signed with the key of the client's
+    /// [`ChatSession`]. See [`ChatSession::verify_message`].
+    pub signed: bool,
 }
 
 pub fn handle_chat_message(
     mut packets: EventReader<PacketEvent>,
+    mut clients: Query<(
+        &mut MessageAcknowledgments,
+        &UniqueId,
+        Option<&mut ChatSession>,
+    )>,
     mut events: EventWriter<ChatMessageEvent>,
+    settings: Res<ChatSettings>,
+    mut commands: Commands,
 ) {
     for packet in packets.iter() {
-        if let Some(pkt) = packet.decode::<ChatMessageC2s>() {
+        if let Some(pkt) = packet.decode::<MessageAcknowledgmentC2s>() {
+            if let Ok((mut acks, _, _)) = clients.get_mut(packet.client) {
+                acks.record(pkt.message_count);
+            }
+        } else if let Some(pkt) = packet.decode::<ChatMessageC2s>() {
+            let Ok((mut acks, uuid, session)) = clients.get_mut(packet.client) else {
+                continue;
+            };
+
+            let last_seen = if acks.record(pkt.message_count) {
+                acks.last_seen(&pkt.acknowledgement)
+            } else {
+                None
+            };
+
+            let signed = match (pkt.signature, session) {
+                (Some(signature), Some(mut session)) => {
+                    let index = session.next_message_index();
+
+                    last_seen.is_some_and(|last_seen| {
+                        session.verify_message(
+                            uuid.0,
+                            index,
+                            &MessageBody {
+                                message: pkt.message.0,
+                                timestamp: pkt.timestamp,
+                                salt: pkt.salt,
+                                last_seen: &last_seen,
+                            },
+                            signature,
+                        )
+                    })
+                }
+                _ => false,
+            };
+
+            if settings.enforce_secure_chat && !signed {
+                commands.add(DisconnectClient {
+                    client: packet.client,
+                    reason: "Received chat packet with missing or invalid signature.".into(),
+                });
+                continue;
+            }
+
             events.send(ChatMessageEvent {
                 client: packet.client,
                 message: pkt.message.0.into(),
                 timestamp: pkt.timestamp,
+                signed,
             });
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_seen_signed_messages() {
+        let mut acks = MessageAcknowledgments::default();
+
+        let seen = |bits: &[usize]| {
+            let mut set = FixedBitSet::default();
+            for &bit in bits {
+                set.set_bit(bit, true);
+            }
+            set
+        };
+
+        // Nothing was tracked yet.
+        assert_eq!(acks.last_seen(&seen(&[])), Some(vec![]));
+        assert_eq!(acks.last_seen(&seen(&[0])), None);
+
+        acks.track_signed_message(&[1; 256]);
+        acks.track_signed_message(&[2; 256]);
+
+        // New messages enter the client's window at the end.
+        assert!(acks.record(VarInt(2)));
+        assert_eq!(
+            acks.last_seen(&seen(&[18, 19])),
+            Some(vec![[1; 256], [2; 256]])
+        );
+
+        // Messages can't be unseen once they were reported as seen.
+        assert_eq!(acks.clone().last_seen(&seen(&[19])), None);
+
+        // Clients can't acknowledge more messages than they were sent.
+        assert!(!acks.record(VarInt(1)));
+        assert_eq!(acks.acknowledged(), 3);
+    }
+}
@@ -1,12 +1,18 @@
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
-use valence_entity::{HeadYaw, Look, OnGround, Position};
-use valence_math::DVec3;
+use valence_entity::{HeadYaw, Look, OnGround, Position, Vehicle};
+use valence_math::{Aabb, DVec3};
+use valence_protocol::block::{PropName, PropValue};
 use valence_protocol::packets::play::{
-    FullC2s, LookAndOnGroundC2s, OnGroundOnlyC2s, PositionAndOnGroundC2s, VehicleMoveC2s,
+    FullC2s, LookAndOnGroundC2s, OnGroundOnlyC2s, PlayerInputC2s, PositionAndOnGroundC2s,
+    VehicleMoveC2s, VehicleMoveS2c,
 };
+use valence_protocol::WritePacket;
+use valence_protocol::BlockPos;
 
+use crate::client::Client;
 use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
+use crate::layer::ChunkLayer;
 use crate::teleport::TeleportState;
 
 pub struct MovementPlugin;
@@ -15,7 +21,15 @@ impl Plugin for MovementPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<MovementSettings>()
             .add_event::<MovementEvent>()
-            .add_systems(EventLoopPreUpdate, handle_client_movement);
+            .add_event::<SteerVehicleEvent>()
+            .add_systems(
+                EventLoopPreUpdate,
+                (
+                    handle_client_movement,
+                    handle_steer_vehicle,
+                    correct_locked_vehicle_moves,
+                ),
+            );
     }
 }
 
@@ -33,10 +47,120 @@ pub struct MovementEvent {
     pub old_position: DVec3,
     pub look: Look,
     pub old_look: Look,
+    /// The on-ground flag reported by the client. This is advisory only, see
+    /// [`is_supported_by_blocks`].
     pub on_ground: bool,
     pub old_on_ground: bool,
 }
 
+/// Event sent when a client riding a vehicle presses the movement, jump or
+/// sneak keys.
+#[derive(Event, Copy, Clone, PartialEq, Debug)]
+pub struct SteerVehicleEvent {
+    pub client: Entity,
+    /// Positive to the left.
+    pub sideways: f32,
+    /// Positive forward.
+    pub forward: f32,
+    pub jump: bool,
+    /// Whether the client wants to dismount. The client stays seated until
+    /// it is removed from the vehicle's
+    /// [`Passengers`](valence_entity::Passengers).
+    pub unmount: bool,
+}
+
+/// A [`Component`] for vehicles that are driven by the server alone, such as
+/// the cart of a scripted tour.
+///
+/// Riders of a locked vehicle keep their camera attached to it, but their
+/// steering input is ignored: no [`SteerVehicleEvent`]s are sent for them
+/// and vehicle movement they report does not update their [`Position`].
+/// Instead, they are sent the vehicle's position back so that their copy of
+/// the vehicle doesn't drift away from the server's.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct LockedSteering {
+    /// Whether riders may still ask to dismount. If `true`, pressing sneak
+    /// sends a [`SteerVehicleEvent`] with only
+    /// [`unmount`](SteerVehicleEvent::unmount) set.
+    pub allow_dismount: bool,
+}
+
+/// Returns the [`LockedSteering`] of the vehicle `client` is riding, if that
+/// vehicle is locked.
+fn locked_vehicle(
+    riders: &Query<&Vehicle>,
+    vehicles: &Query<&LockedSteering>,
+    client: Entity,
+) -> Option<LockedSteering> {
+    let vehicle = riders.get(client).ok()?;
+    vehicles.get(vehicle.get()).ok().copied()
+}
+
+/// Returns `true` if the block containing `pos` holds a fluid. This includes
+/// water and lava as well as waterlogged blocks.
+///
+/// Returns `false` if the position is outside of a loaded chunk.
+pub fn is_in_fluid(layer: &ChunkLayer, pos: DVec3) -> bool {
+    layer.block(BlockPos::from_pos(pos)).is_some_and(|block| {
+        block.state.is_liquid() || block.state.get(PropName::Waterlogged) == Some(PropValue::True)
+    })
+}
+
+/// Returns `true` if the bottom of `hitbox` is resting on the collision shape
+/// of at least one block in `layer`.
+///
+/// The [`OnGround`] component of a client is set from the flag sent in its
+/// movement packets and can be trivially spoofed. Use this check instead when
+/// the result matters for gameplay, e.g. to decide if a double jump is allowed.
+pub fn is_supported_by_blocks(layer: &ChunkLayer, hitbox: Aabb) -> bool {
+    const EPSILON: f64 = 1e-4;
+
+    let min = hitbox.min();
+    let max = hitbox.max();
+
+    // A thin slice just below the hitbox. It is shrunk horizontally so that
+    // blocks merely touching the sides of the hitbox don't count as support.
+    let feet = Aabb::new(
+        DVec3::new(
+            (min.x + EPSILON).min(max.x),
+            min.y - EPSILON,
+            (min.z + EPSILON).min(max.z),
+        ),
+        DVec3::new(
+            (max.x - EPSILON).max(min.x),
+            min.y - EPSILON / 2.0,
+            (max.z - EPSILON).max(min.z),
+        ),
+    );
+
+    let lo = BlockPos::from_pos(feet.min());
+    let hi = BlockPos::from_pos(feet.max());
+
+    // Fences and walls are 1.5 blocks tall, so the blocks below the slice are
+    // checked as well.
+    for y in lo.y - 1..=hi.y {
+        for z in lo.z..=hi.z {
+            for x in lo.x..=hi.x {
+                let Some(block) = layer.block([x, y, z]) else {
+                    continue;
+                };
+
+                let offset = DVec3::new(x as f64, y as f64, z as f64);
+
+                if block
+                    .state
+                    .collision_shapes()
+                    .any(|shape| (shape + offset).intersects(feet))
+                {
+                    return true;
+                }
+            }
+        }
+    }
+
+    false
+}
+
 fn handle_client_movement(
     mut packets: EventReader<PacketEvent>,
     mut clients: Query<(
@@ -46,6 +170,8 @@ fn handle_client_movement(
         &mut OnGround,
         &mut TeleportState,
     )>,
+    riders: Query<&Vehicle>,
+    vehicles: Query<&LockedSteering>,
     mut movement_events: EventWriter<MovementEvent>,
 ) {
     for packet in packets.iter() {
@@ -152,6 +278,10 @@ fn handle_client_movement(
                 );
             }
         } else if let Some(pkt) = packet.decode::<VehicleMoveC2s>() {
+            if locked_vehicle(&riders, &vehicles, packet.client).is_some() {
+                continue;
+            }
+
             if let Ok((pos, look, head_yaw, on_ground, teleport_state)) =
                 clients.get_mut(packet.client)
             {
@@ -182,6 +312,37 @@ fn handle_client_movement(
     }
 }
 
+fn handle_steer_vehicle(
+    mut packets: EventReader<PacketEvent>,
+    riders: Query<&Vehicle>,
+    vehicles: Query<&LockedSteering>,
+    mut events: EventWriter<SteerVehicleEvent>,
+) {
+    for packet in packets.iter() {
+        if let Some(pkt) = packet.decode::<PlayerInputC2s>() {
+            let mut event = SteerVehicleEvent {
+                client: packet.client,
+                sideways: pkt.sideways,
+                forward: pkt.forward,
+                jump: pkt.flags.jump(),
+                unmount: pkt.flags.unmount(),
+            };
+
+            if let Some(lock) = locked_vehicle(&riders, &vehicles, packet.client) {
+                if !(lock.allow_dismount && event.unmount) {
+                    continue;
+                }
+
+                event.sideways = 0.0;
+                event.forward = 0.0;
+                event.jump = false;
+            }
+
+            events.send(event);
+        }
+    }
+}
+
 fn handle(
     mov: MovementEvent,
     mut pos: Mut<Position>,
@@ -207,3 +368,32 @@ fn handle(
 
     movement_events.send(mov);
 }
+
+/// Sends riders of a [`LockedSteering`] vehicle the position of the vehicle
+/// when they try to move it.
+fn correct_locked_vehicle_moves(
+    mut packets: EventReader<PacketEvent>,
+    riders: Query<&Vehicle>,
+    vehicles: Query<(&Position, &Look), With<LockedSteering>>,
+    mut clients: Query<&mut Client>,
+) {
+    for packet in packets.iter() {
+        if packet.decode::<VehicleMoveC2s>().is_none() {
+            continue;
+        }
+
+        let Ok(vehicle) = riders.get(packet.client) else {
+            continue;
+        };
+
+        if let Ok((pos, look)) = vehicles.get(vehicle.get()) {
+            if let Ok(mut client) = clients.get_mut(packet.client) {
+                client.write_packet(&VehicleMoveS2c {
+                    position: pos.0,
+                    yaw: look.yaw,
+                    pitch: look.pitch,
+                });
+            }
+        }
+    }
+}
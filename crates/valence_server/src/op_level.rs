@@ -14,6 +14,12 @@ impl Plugin for OpLevelPlugin {
     }
 }
 
+/// The permission level of a client, from `0` (a regular player) to `4` (full
+/// operator).
+///
+/// The level is reported to the client with an entity status of `24 + level`
+/// on its own entity, which controls client-side features such as the command
+/// block screen and the game mode switcher.
 #[derive(Component, Clone, PartialEq, Eq, Default, Debug, Deref)]
 pub struct OpLevel(u8);
 
@@ -22,9 +28,9 @@ impl OpLevel {
         self.0
     }
 
-    /// Sets the op level. Value is clamped to `0..=3`.
+    /// Sets the op level. Value is clamped to `0..=4`.
     pub fn set(&mut self, lvl: u8) {
-        self.0 = lvl.min(3);
+        self.0 = lvl.min(4);
     }
 }
 
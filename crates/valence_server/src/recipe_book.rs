@@ -0,0 +1,168 @@
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+pub use valence_protocol::packets::play::recipe_category_options_c2s::RecipeBookId;
+use valence_protocol::packets::play::unlock_recipes_s2c::UpdateRecipeBookAction;
+use valence_protocol::packets::play::{RecipeCategoryOptionsC2s, UnlockRecipesS2c};
+use valence_protocol::{Ident, WritePacket};
+
+use crate::client::{Client, UpdateClientsSet};
+use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
+
+pub struct RecipeBookPlugin;
+
+impl Plugin for RecipeBookPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(EventLoopPreUpdate, handle_recipe_category_options)
+            .add_systems(PostUpdate, update_recipe_book.in_set(UpdateClientsSet));
+    }
+}
+
+/// The state of one of the books in a client's recipe book screen.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct RecipeBookOptions {
+    /// Whether the book is shown next to the crafting, furnace, blast furnace
+    /// or smoker screen.
+    pub open: bool,
+    /// Whether the book only shows the recipes the client can craft.
+    pub filter_active: bool,
+}
+
+/// The recipes a client has unlocked in its recipe book, along with the
+/// options of each book.
+///
+/// Unlocking recipes shows a toast on the client. Changes made while the
+/// client is joining are sent without a toast. The options are updated when
+/// the client changes them itself.
+#[derive(Component, Default, Debug)]
+pub struct RecipeBook {
+    unlocked: BTreeSet<Ident<String>>,
+    options: [RecipeBookOptions; 4],
+    added: Vec<Ident<String>>,
+    removed: Vec<Ident<String>>,
+    options_changed: bool,
+}
+
+impl RecipeBook {
+    pub fn is_unlocked(&self, recipe: Ident<&str>) -> bool {
+        self.unlocked.contains(recipe.as_str())
+    }
+
+    pub fn unlocked(&self) -> impl Iterator<Item = Ident<&str>> + '_ {
+        self.unlocked.iter().map(|id| id.as_str_ident())
+    }
+
+    /// Unlocks the given recipes. Recipes that are already unlocked are
+    /// ignored.
+    pub fn unlock_recipes<I: Into<Ident<String>>>(&mut self, recipes: impl IntoIterator<Item = I>) {
+        for recipe in recipes {
+            let recipe = recipe.into();
+
+            if self.unlocked.insert(recipe.clone()) {
+                self.removed.retain(|r| *r != recipe);
+                self.added.push(recipe);
+            }
+        }
+    }
+
+    /// Locks the given recipes. Recipes that aren't unlocked are ignored.
+    pub fn lock_recipes<I: Into<Ident<String>>>(&mut self, recipes: impl IntoIterator<Item = I>) {
+        for recipe in recipes {
+            let recipe = recipe.into();
+
+            if self.unlocked.remove(recipe.as_str()) {
+                self.added.retain(|r| *r != recipe);
+                self.removed.push(recipe);
+            }
+        }
+    }
+
+    pub fn options(&self, book: RecipeBookId) -> RecipeBookOptions {
+        self.options[book as usize]
+    }
+
+    pub fn set_options(&mut self, book: RecipeBookId, options: RecipeBookOptions) {
+        if self.options[book as usize] != options {
+            self.options[book as usize] = options;
+            self.options_changed = true;
+        }
+    }
+
+    fn packet<'a>(
+        &self,
+        action: UpdateRecipeBookAction<'a>,
+        recipe_ids: Vec<Ident<Cow<'a, str>>>,
+    ) -> UnlockRecipesS2c<'a> {
+        let [crafting, smelting, blast_furnace, smoker] = self.options;
+
+        UnlockRecipesS2c {
+            action,
+            crafting_recipe_book_open: crafting.open,
+            crafting_recipe_book_filter_active: crafting.filter_active,
+            smelting_recipe_book_open: smelting.open,
+            smelting_recipe_book_filter_active: smelting.filter_active,
+            blast_furnace_recipe_book_open: blast_furnace.open,
+            blast_furnace_recipe_book_filter_active: blast_furnace.filter_active,
+            smoker_recipe_book_open: smoker.open,
+            smoker_recipe_book_filter_active: smoker.filter_active,
+            recipe_ids,
+        }
+    }
+}
+
+fn handle_recipe_category_options(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<&mut RecipeBook>,
+) {
+    for packet in packets.iter() {
+        if let Some(pkt) = packet.decode::<RecipeCategoryOptionsC2s>() {
+            if let Ok(mut book) = clients.get_mut(packet.client) {
+                // The client already knows about the change, so don't send it back.
+                book.bypass_change_detection().options[pkt.book_id as usize] = RecipeBookOptions {
+                    open: pkt.book_open,
+                    filter_active: pkt.filter_active,
+                };
+            }
+        }
+    }
+}
+
+fn update_recipe_book(mut clients: Query<(&mut Client, &mut RecipeBook), Changed<RecipeBook>>) {
+    for (mut client, mut book) in &mut clients {
+        let book = book.bypass_change_detection();
+
+        if client.is_added() {
+            if !book.unlocked.is_empty() || book.options_changed {
+                let recipe_ids = book.unlocked.iter().map(|id| id.as_str_ident().into());
+
+                client.write_packet(&book.packet(
+                    UpdateRecipeBookAction::Init { recipe_ids: vec![] },
+                    recipe_ids.collect(),
+                ));
+            }
+        } else {
+            if !book.removed.is_empty() {
+                let recipe_ids = book.removed.iter().map(|id| id.as_str_ident().into());
+
+                client.write_packet(
+                    &book.packet(UpdateRecipeBookAction::Remove, recipe_ids.collect()),
+                );
+            }
+
+            // The options are sent with every action, so an empty `Add` is enough to
+            // update them.
+            if !book.added.is_empty() || (book.options_changed && book.removed.is_empty()) {
+                let recipe_ids = book.added.iter().map(|id| id.as_str_ident().into());
+
+                client
+                    .write_packet(&book.packet(UpdateRecipeBookAction::Add, recipe_ids.collect()));
+            }
+        }
+
+        book.added.clear();
+        book.removed.clear();
+        book.options_changed = false;
+    }
+}
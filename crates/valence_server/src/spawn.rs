@@ -6,13 +6,16 @@ use std::collections::BTreeSet;
 use bevy_ecs::prelude::*;
 use bevy_ecs::query::WorldQuery;
 use derive_more::{Deref, DerefMut};
-use valence_entity::EntityLayerId;
-use valence_protocol::packets::play::{GameJoinS2c, PlayerRespawnS2c, PlayerSpawnPositionS2c};
-use valence_protocol::{BlockPos, GameMode, GlobalPos, Ident, VarInt, WritePacket};
+use valence_protocol::packets::play::game_state_change_s2c::GameEventKind;
+use valence_protocol::packets::play::{
+    DifficultyS2c, EntityStatusS2c, GameJoinS2c, GameStateChangeS2c, PlayerRespawnS2c,
+    PlayerSpawnPositionS2c,
+};
+use valence_protocol::{BlockPos, Difficulty, GameMode, GlobalPos, Ident, VarInt, WritePacket};
 use valence_registry::tags::TagsRegistry;
 use valence_registry::{BiomeRegistry, RegistryCodec};
 
-use crate::client::{Client, ViewDistance, VisibleChunkLayer};
+use crate::client::{Client, OldVisibleChunkLayer, ViewDistance, VisibleChunkLayer};
 use crate::layer::ChunkLayer;
 
 // Components for the join game and respawn packet.
@@ -27,9 +30,22 @@ pub struct IsHardcore(pub bool);
 #[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug, Deref, DerefMut)]
 pub struct HashedSeed(pub u64);
 
+/// Hides coordinates and other details from the client's F3 debug screen.
+///
+/// Changing this on a client after it has joined takes effect immediately.
+/// This component can also be inserted on a [`ChunkLayer`] entity to make it
+/// the setting of the layer's world: whenever a client's
+/// [`VisibleChunkLayer`] changes to a layer with this component, the client's
+/// own `ReducedDebugInfo` is set to the layer's value. Clients leaving such a
+/// layer for one without the component are reset to the default of `false`.
 #[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug, Deref, DerefMut)]
 pub struct ReducedDebugInfo(pub bool);
 
+/// Whether the client is shown the death screen when killed, as opposed to
+/// respawning immediately. This is the inverse of the `doImmediateRespawn`
+/// game rule.
+///
+/// Changing this on a client after it has joined takes effect immediately.
 #[derive(Component, Copy, Clone, PartialEq, Eq, Debug, Deref, DerefMut)]
 pub struct HasRespawnScreen(pub bool);
 
@@ -48,6 +64,40 @@ pub struct PortalCooldown(pub i32);
 #[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug, Deref, DerefMut)]
 pub struct PrevGameMode(pub Option<GameMode>);
 
+/// An optional resource with the game mode clients are given when they join.
+///
+/// It is applied right after clients are spawned, so systems in [`Update`]
+/// can still give individual clients a different game mode before they join.
+/// Without this resource, clients keep the game mode of their
+/// [`ClientBundle`](crate::client::ClientBundle).
+///
+/// [`Update`]: bevy_app::Update
+#[derive(Resource, Copy, Clone, PartialEq, Eq, Default, Debug, Deref, DerefMut)]
+pub struct DefaultGameMode(pub GameMode);
+
+/// The difficulty of the server, as shown to all clients.
+///
+/// It is sent to clients when they join and again whenever this resource is
+/// modified. Valence doesn't simulate hunger or mobs itself, so it is up to
+/// the server to disable them when the difficulty is
+/// [`Peaceful`](Difficulty::Peaceful).
+#[derive(Resource, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct DifficultySettings {
+    pub difficulty: Difficulty,
+    /// Whether clients are prevented from changing the difficulty in their
+    /// options menu.
+    pub locked: bool,
+}
+
+impl Default for DifficultySettings {
+    fn default() -> Self {
+        Self {
+            difficulty: Difficulty::Normal,
+            locked: true,
+        }
+    }
+}
+
 impl Default for HasRespawnScreen {
     fn default() -> Self {
         Self(true)
@@ -83,6 +133,19 @@ pub struct ClientSpawnQuery {
     pub portal_cooldown: &'static mut PortalCooldown,
 }
 
+pub(super) fn apply_default_game_mode(
+    default_game_mode: Option<Res<DefaultGameMode>>,
+    mut clients: Query<&mut GameMode, Added<Client>>,
+) {
+    let Some(default_game_mode) = default_game_mode else {
+        return;
+    };
+
+    for mut game_mode in &mut clients {
+        *game_mode = default_game_mode.0;
+    }
+}
+
 pub(super) fn initial_join(
     codec: Res<RegistryCodec>,
     tags: Res<TagsRegistry>,
@@ -141,37 +204,46 @@ pub(super) fn initial_join(
     }
 }
 
+/// Sends the difficulty to clients that just joined, or to everyone if it
+/// was changed.
+pub(super) fn update_difficulty(
+    settings: Res<DifficultySettings>,
+    mut clients: Query<&mut Client>,
+) {
+    let pkt = DifficultyS2c {
+        difficulty: settings.difficulty,
+        locked: settings.locked,
+    };
+
+    for mut client in &mut clients {
+        if settings.is_changed() || client.is_added() {
+            client.write_packet(&pkt);
+        }
+    }
+}
+
 pub(super) fn respawn(
     mut clients: Query<
-        (
-            &mut Client,
-            &EntityLayerId,
-            &DeathLocation,
-            &HashedSeed,
-            &GameMode,
-            &PrevGameMode,
-            &IsDebug,
-            &IsFlat,
-        ),
+        (&mut Client, &VisibleChunkLayer, ClientSpawnQueryReadOnly),
         Changed<VisibleChunkLayer>,
     >,
     chunk_layers: Query<&ChunkLayer>,
 ) {
-    for (mut client, loc, death_loc, hashed_seed, game_mode, prev_game_mode, is_debug, is_flat) in
-        &mut clients
-    {
+    for (mut client, visible_chunk_layer, spawn) in &mut clients {
         if client.is_added() {
             // No need to respawn since we are sending the game join packet this tick.
             continue;
         }
 
-        let Ok(chunk_layer) = chunk_layers.get(loc.0) else {
+        // The client is moved to the dimension of its new chunk layer. Its entity
+        // layer is left alone.
+        let Ok(chunk_layer) = chunk_layers.get(visible_chunk_layer.0) else {
             continue;
         };
 
         let dimension_name = chunk_layer.dimension_type_name();
 
-        let last_death_location = death_loc.0.as_ref().map(|(id, pos)| GlobalPos {
+        let last_death_location = spawn.death_loc.0.as_ref().map(|(id, pos)| GlobalPos {
             dimension_name: id.as_str_ident().into(),
             position: *pos,
         });
@@ -179,14 +251,68 @@ pub(super) fn respawn(
         client.write_packet(&PlayerRespawnS2c {
             dimension_type_name: dimension_name.into(),
             dimension_name: dimension_name.into(),
-            hashed_seed: hashed_seed.0,
-            game_mode: *game_mode,
-            previous_game_mode: prev_game_mode.0.into(),
-            is_debug: is_debug.0,
-            is_flat: is_flat.0,
+            hashed_seed: spawn.hashed_seed.0,
+            game_mode: *spawn.game_mode,
+            previous_game_mode: spawn.prev_game_mode.0.into(),
+            is_debug: spawn.is_debug.0,
+            is_flat: spawn.is_flat.0,
             copy_metadata: true,
             last_death_location,
-            portal_cooldown: VarInt(0), // TODO
+            portal_cooldown: VarInt(spawn.portal_cooldown.0),
+        });
+    }
+}
+
+/// Copies the [`ReducedDebugInfo`] of a client's new chunk layer to the client.
+pub(super) fn apply_layer_reduced_debug_info(
+    mut clients: Query<
+        (
+            &VisibleChunkLayer,
+            &OldVisibleChunkLayer,
+            &mut ReducedDebugInfo,
+        ),
+        (With<Client>, Changed<VisibleChunkLayer>),
+    >,
+    layers: Query<&ReducedDebugInfo, (With<ChunkLayer>, Without<Client>)>,
+) {
+    for (visible_chunk_layer, old_visible_chunk_layer, mut reduced_debug_info) in &mut clients {
+        if let Ok(layer_reduced_debug_info) = layers.get(visible_chunk_layer.0) {
+            reduced_debug_info.set_if_neq(*layer_reduced_debug_info);
+        } else if layers.contains(old_visible_chunk_layer.get()) {
+            // Don't carry the setting of the old layer over to the new one.
+            reduced_debug_info.set_if_neq(ReducedDebugInfo::default());
+        }
+    }
+}
+
+pub(super) fn update_reduced_debug_info(
+    mut clients: Query<(&mut Client, &ReducedDebugInfo), Changed<ReducedDebugInfo>>,
+) {
+    for (mut client, reduced_debug_info) in &mut clients {
+        if client.is_added() {
+            // Game join packet includes the initial setting.
+            continue;
+        }
+
+        client.write_packet(&EntityStatusS2c {
+            entity_id: 0,
+            entity_status: if reduced_debug_info.0 { 22 } else { 23 },
+        });
+    }
+}
+
+pub(super) fn update_has_respawn_screen(
+    mut clients: Query<(&mut Client, &HasRespawnScreen), Changed<HasRespawnScreen>>,
+) {
+    for (mut client, has_respawn_screen) in &mut clients {
+        if client.is_added() {
+            // Game join packet includes the initial setting.
+            continue;
+        }
+
+        client.write_packet(&GameStateChangeS2c {
+            kind: GameEventKind::EnableRespawnScreen,
+            value: if has_respawn_screen.0 { 0.0 } else { 1.0 },
         });
     }
 }
@@ -0,0 +1,89 @@
+use std::collections::BTreeSet;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use rustc_hash::FxHashMap;
+
+use crate::client::{Client, HiddenEntities};
+use crate::layer::UpdateLayersPreClientSet;
+
+pub struct TeamVisibilityPlugin;
+
+impl Plugin for TeamVisibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            update_team_visibility.before(UpdateLayersPreClientSet),
+        );
+    }
+}
+
+/// A [`Component`] naming the team a client or entity belongs to.
+///
+/// This only affects visibility through [`HiddenFromTeams`]. Teams shown to
+/// clients, such as for name tag colors, are still created by sending the
+/// team packet.
+#[derive(Component, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Team(pub String);
+
+/// A [`Component`] that hides an entity from every client whose [`Team`] is in
+/// this set, such as a predator that its prey can't see.
+///
+/// The hidden entity is added to the [`HiddenEntities`] of those clients.
+/// Whenever the set or a client's team changes, the clients the entity is
+/// hidden from are updated accordingly. Entities the client already had in
+/// its [`HiddenEntities`] are left alone, so they stay hidden after the
+/// entity would be shown to the team again.
+#[derive(Component, Clone, PartialEq, Eq, Default, Debug)]
+pub struct HiddenFromTeams(pub BTreeSet<String>);
+
+fn update_team_visibility(
+    mut clients: Query<(Entity, Option<&Team>, &mut HiddenEntities), With<Client>>,
+    hidden_from_teams: Query<(Entity, &HiddenFromTeams)>,
+    changed_teams: Query<(), Or<(Changed<Team>, Added<Client>)>>,
+    changed_hidden_from_teams: Query<(), Changed<HiddenFromTeams>>,
+    mut removed_teams: RemovedComponents<Team>,
+    mut removed_hidden_from_teams: RemovedComponents<HiddenFromTeams>,
+    // The entities hidden from each client because of its team.
+    mut hidden_by_team: Local<FxHashMap<Entity, BTreeSet<Entity>>>,
+) {
+    let removed = removed_teams.iter().count() + removed_hidden_from_teams.iter().count() > 0;
+
+    if !removed && changed_teams.is_empty() && changed_hidden_from_teams.is_empty() {
+        return;
+    }
+
+    hidden_by_team.retain(|&client, _| clients.contains(client));
+
+    for (client, team, mut hidden) in &mut clients {
+        let should_hide: BTreeSet<Entity> = match team {
+            Some(team) => hidden_from_teams
+                .iter()
+                .filter(|(entity, teams)| *entity != client && teams.0.contains(&team.0))
+                .map(|(entity, _)| entity)
+                .collect(),
+            None => BTreeSet::new(),
+        };
+
+        let hidden_before = hidden_by_team.entry(client).or_default();
+
+        if *hidden_before == should_hide {
+            continue;
+        }
+
+        for entity in hidden_before.difference(&should_hide) {
+            hidden.0.remove(entity);
+        }
+
+        let mut now_hidden = BTreeSet::new();
+
+        for &entity in &should_hide {
+            // Entities that were hidden by other means don't belong to the team.
+            if hidden_before.contains(&entity) || hidden.0.insert(entity) {
+                now_hidden.insert(entity);
+            }
+        }
+
+        *hidden_before = now_hidden;
+    }
+}
@@ -1,31 +1,94 @@
+use std::ops::Add;
+
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
+use bevy_ecs::system::Command;
 use tracing::warn;
 use valence_entity::{Look, Position};
 use valence_math::DVec3;
 use valence_protocol::packets::play::player_position_look_s2c::PlayerPositionLookFlags;
 use valence_protocol::packets::play::{PlayerPositionLookS2c, TeleportConfirmC2s};
-use valence_protocol::WritePacket;
+use valence_protocol::{ChunkPos, WritePacket};
 
-use crate::client::{update_view_and_layers, Client, UpdateClientsSet};
+use crate::client::{
+    update_view_and_layers, Client, UpdateClientsSet, ViewDistance, VisibleChunkLayer,
+};
 use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
+use crate::layer::ChunkLayer;
 use crate::spawn::update_respawn_position;
 
 pub struct TeleportPlugin;
 
 impl Plugin for TeleportPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            PostUpdate,
-            teleport
-                .after(update_view_and_layers)
-                .before(update_respawn_position)
-                .in_set(UpdateClientsSet),
-        )
-        .add_systems(EventLoopPreUpdate, handle_teleport_confirmations);
+        app.init_resource::<InitialTeleportSettings>()
+            .add_event::<MissingSpawnChunksEvent>()
+            .add_event::<PlayerLoadedEvent>()
+            .add_systems(
+                PostUpdate,
+                teleport
+                    .after(update_view_and_layers)
+                    .before(update_respawn_position)
+                    .in_set(UpdateClientsSet),
+            )
+            .add_systems(EventLoopPreUpdate, handle_teleport_confirmations);
+    }
+}
+
+/// Controls when the initial position of a joining client is sent.
+///
+/// Until the client receives its first position, it stays on the "Loading
+/// terrain" screen. Holding the position back until the chunks around the
+/// client are sent prevents it from falling through the floor.
+#[derive(Resource, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct InitialTeleportSettings {
+    /// The initial position is withheld until every chunk in the square of
+    /// this radius (in chunks) around the client's position is loaded in its
+    /// [`VisibleChunkLayer`]. The radius is capped by the client's
+    /// [`ViewDistance`]. A radius of `0` sends the position immediately.
+    pub min_chunk_radius: u8,
+    /// The maximum number of ticks to wait for the chunks to load. Once this
+    /// many ticks have passed, the position is sent regardless.
+    pub timeout_ticks: u32,
+}
+
+impl Default for InitialTeleportSettings {
+    fn default() -> Self {
+        Self {
+            min_chunk_radius: 0,
+            timeout_ticks: 100,
+        }
     }
 }
 
+/// Sent when the initial position of a joining client is withheld because
+/// chunks around it are missing. See [`InitialTeleportSettings`].
+///
+/// The event is sent once per client. Worlds that generate or load chunks on
+/// demand can respond by inserting the chunks, after which the client is
+/// spawned. If they are never inserted, the client spawns anyway once the
+/// timeout is reached.
+#[derive(Event, Clone, PartialEq, Eq, Debug)]
+pub struct MissingSpawnChunksEvent {
+    pub client: Entity,
+    /// The [`ChunkLayer`] the client is viewing.
+    pub chunk_layer: Entity,
+    /// The positions of the chunks that are not loaded.
+    pub chunks: Vec<ChunkPos>,
+}
+
+/// Sent once per client when it has finished spawning and is in the world.
+///
+/// This is a good time to give items or start an intro sequence, since the
+/// client has left the "Loading terrain" screen by then. Newer versions of the
+/// game send a dedicated packet for this. Here it is approximated by the
+/// client confirming its initial teleport, which is withheld until the spawn
+/// chunks are sent (see [`InitialTeleportSettings`]).
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PlayerLoadedEvent {
+    pub client: Entity,
+}
+
 #[derive(Component, Debug)]
 pub struct TeleportState {
     /// Counts up as teleports are made.
@@ -36,6 +99,8 @@ pub struct TeleportState {
     pending_teleports: u32,
     pub(super) synced_pos: DVec3,
     pub(super) synced_look: Look,
+    /// Whether the client has confirmed the initial teleport.
+    loaded: bool,
 }
 
 impl TeleportState {
@@ -50,6 +115,7 @@ impl TeleportState {
                 yaw: f32::NAN,
                 pitch: f32::NAN,
             },
+            loaded: false,
         }
     }
 
@@ -60,20 +126,184 @@ impl TeleportState {
     pub fn pending_teleports(&self) -> u32 {
         self.pending_teleports
     }
+
+    /// Returns whether the client has finished spawning. See
+    /// [`PlayerLoadedEvent`].
+    pub fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+}
+
+/// Present on clients whose initial position hasn't been sent yet. Removed
+/// once it is sent. See [`InitialTeleportSettings`].
+#[derive(Component, Default, Debug)]
+pub struct AwaitingInitialTeleport {
+    /// The number of ticks the initial teleport has been waiting on chunks.
+    wait_ticks: u32,
+}
+
+/// A [`Command`] to teleport a [`Client`] with some components of the new
+/// position and look given relative to the current ones.
+///
+/// Each field of `flags` that is `true` makes the matching component relative.
+/// For instance, a yaw of `90.0` with only [`y_rot`] set turns the client by
+/// 90° while `position` and `pitch` are taken as absolute values. The client's
+/// [`Position`] and [`Look`] are updated to match.
+///
+/// If the client hasn't received its initial position yet, only [`Position`]
+/// and [`Look`] are changed.
+///
+/// [`y_rot`]: PlayerPositionLookFlags::y_rot
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct RelativeTeleport {
+    pub client: Entity,
+    pub position: DVec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub flags: PlayerPositionLookFlags,
+}
+
+impl Command for RelativeTeleport {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity) = world.get_entity_mut(self.client) else {
+            return;
+        };
+
+        if !entity.contains::<Client>() {
+            return;
+        }
+
+        let (Some(&pos), Some(&look)) = (entity.get::<Position>(), entity.get::<Look>()) else {
+            return;
+        };
+
+        let spawned = !entity.contains::<AwaitingInitialTeleport>();
+
+        let Some(mut state) = entity.get_mut::<TeleportState>() else {
+            return;
+        };
+
+        // Relative components are applied to what the client last received.
+        let (base_pos, base_look) = if spawned {
+            (state.synced_pos, state.synced_look)
+        } else {
+            (pos.0, look)
+        };
+
+        fn rel<T: Add<Output = T>>(relative: bool, base: T, value: T) -> T {
+            if relative {
+                base + value
+            } else {
+                value
+            }
+        }
+
+        let flags = self.flags;
+
+        let new_pos = DVec3::new(
+            rel(flags.x(), base_pos.x, self.position.x),
+            rel(flags.y(), base_pos.y, self.position.y),
+            rel(flags.z(), base_pos.z, self.position.z),
+        );
+        let new_look = Look {
+            yaw: rel(flags.y_rot(), base_look.yaw, self.yaw),
+            pitch: rel(flags.x_rot(), base_look.pitch, self.pitch),
+        };
+
+        let teleport_id = state.teleport_id_counter;
+
+        if spawned {
+            state.synced_pos = new_pos;
+            state.synced_look = new_look;
+            state.pending_teleports = state.pending_teleports.wrapping_add(1);
+            state.teleport_id_counter = state.teleport_id_counter.wrapping_add(1);
+        }
+
+        entity.get_mut::<Position>().unwrap().0 = new_pos;
+        *entity.get_mut::<Look>().unwrap() = new_look;
+
+        if spawned {
+            entity
+                .get_mut::<Client>()
+                .unwrap()
+                .write_packet(&PlayerPositionLookS2c {
+                    position: self.position,
+                    yaw: self.yaw,
+                    pitch: self.pitch,
+                    flags,
+                    teleport_id: (teleport_id as i32).into(),
+                });
+        }
+    }
 }
 
 /// Syncs the client's position and look with the server.
 ///
 /// This should happen after chunks are loaded so the client doesn't fall though
-/// the floor.
+/// the floor. Only clients that moved or haven't received their initial
+/// position are visited.
 #[allow(clippy::type_complexity)]
 fn teleport(
     mut clients: Query<
-        (&mut Client, &mut TeleportState, &Position, &Look),
-        Or<(Changed<Position>, Changed<Look>)>,
+        (
+            Entity,
+            &mut Client,
+            &mut TeleportState,
+            &Position,
+            &Look,
+            &ViewDistance,
+            &VisibleChunkLayer,
+            Option<&mut AwaitingInitialTeleport>,
+        ),
+        Or<(
+            Changed<Position>,
+            Changed<Look>,
+            With<AwaitingInitialTeleport>,
+        )>,
     >,
+    chunk_layers: Query<&ChunkLayer>,
+    settings: Res<InitialTeleportSettings>,
+    mut missing_chunk_events: EventWriter<MissingSpawnChunksEvent>,
+    mut commands: Commands,
 ) {
-    for (mut client, mut state, pos, look) in &mut clients {
+    for (entity, mut client, mut state, pos, look, view_dist, chunk_layer, awaiting) in &mut clients
+    {
+        if let Some(mut awaiting) = awaiting {
+            let wait_ticks = awaiting.wait_ticks;
+            let radius = settings.min_chunk_radius.min(view_dist.get()) as i32;
+            let center = pos.to_chunk_pos();
+            let layer = chunk_layers.get(chunk_layer.0).ok();
+
+            let missing: Vec<_> = if radius == 0 {
+                vec![]
+            } else {
+                (center.z - radius..=center.z + radius)
+                    .flat_map(|z| {
+                        (center.x - radius..=center.x + radius).map(move |x| ChunkPos::new(x, z))
+                    })
+                    .filter(|&pos| match layer {
+                        Some(layer) => layer.chunk(pos).is_none(),
+                        None => true,
+                    })
+                    .collect()
+            };
+
+            if !missing.is_empty() && wait_ticks < settings.timeout_ticks {
+                if wait_ticks == 0 {
+                    missing_chunk_events.send(MissingSpawnChunksEvent {
+                        client: entity,
+                        chunk_layer: chunk_layer.0,
+                        chunks: missing,
+                    });
+                }
+
+                awaiting.wait_ticks += 1;
+                continue;
+            }
+
+            commands.entity(entity).remove::<AwaitingInitialTeleport>();
+        }
+
         let changed_pos = pos.0 != state.synced_pos;
         let changed_yaw = look.yaw != state.synced_look.yaw;
         let changed_pitch = look.pitch != state.synced_look.pitch;
@@ -106,6 +336,7 @@ fn teleport(
 fn handle_teleport_confirmations(
     mut packets: EventReader<PacketEvent>,
     mut clients: Query<&mut TeleportState>,
+    mut loaded_events: EventWriter<PlayerLoadedEvent>,
     mut commands: Commands,
 ) {
     for packet in packets.iter() {
@@ -126,6 +357,13 @@ fn handle_teleport_confirmations(
 
                 if got == expected {
                     state.pending_teleports -= 1;
+
+                    if !state.loaded {
+                        state.loaded = true;
+                        loaded_events.send(PlayerLoadedEvent {
+                            client: packet.client,
+                        });
+                    }
                 } else {
                     warn!(
                         "unexpected teleport ID for client {:?} (expected {expected}, got {got}",
@@ -0,0 +1,159 @@
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::Has;
+use valence_entity::{EntityLayerId, Position};
+use valence_server_common::Server;
+
+use crate::layer::ChunkLayer;
+
+pub struct TickBudgetPlugin;
+
+impl Plugin for TickBudgetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TickBudget>()
+            .add_systems(PreUpdate, schedule_budgeted_ticks);
+    }
+}
+
+/// Limits how many entities with a [`BudgetedTick`] are scheduled to run
+/// their expensive logic (such as AI) each tick.
+///
+/// Every tick, the entities that have waited the longest since they were last
+/// scheduled are chosen, so all entities take turns in a round-robin fashion.
+/// Systems in [`Update`] should skip entities whose
+/// [`BudgetedTick::is_scheduled`] is `false`.
+///
+/// With `n` budgeted entities, each entity is scheduled at least once every
+/// `ceil(n / per_tick)` ticks.
+///
+/// If [`skip_unviewed`](Self::skip_unviewed) is enabled, entities in chunks
+/// that no client is viewing are not scheduled at all and don't count towards
+/// `n`.
+#[derive(Resource, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct TickBudget {
+    /// The number of entities scheduled each tick.
+    pub per_tick: usize,
+    /// If nonzero, the maximum number of ticks an entity may go without being
+    /// scheduled. When there are too many entities to meet this with
+    /// [`per_tick`](Self::per_tick), more entities are scheduled each tick
+    /// instead.
+    pub max_interval: usize,
+    /// Whether to skip entities in chunks that no client is viewing, unless
+    /// they are [`AlwaysSimulated`]. A chunk is viewed if it is loaded in the
+    /// [`ChunkLayer`] of the entity's [`EntityLayerId`] and has viewers.
+    /// Entities whose layer has no chunk layer are always scheduled. Disabled
+    /// by default.
+    pub skip_unviewed: bool,
+}
+
+impl TickBudget {
+    /// Returns the number of entities that will be scheduled in a tick, given
+    /// the total number of budgeted entities.
+    pub fn scheduled_count(&self, total: usize) -> usize {
+        let mut count = self.per_tick;
+
+        if self.max_interval > 0 {
+            count = count.max(total.div_ceil(self.max_interval));
+        }
+
+        count.min(total)
+    }
+}
+
+impl Default for TickBudget {
+    fn default() -> Self {
+        Self {
+            per_tick: 100,
+            max_interval: 0,
+            skip_unviewed: false,
+        }
+    }
+}
+
+/// Keeps an entity with a [`BudgetedTick`] scheduled while its chunk has no
+/// viewers or isn't loaded when [`TickBudget::skip_unviewed`] is enabled, such
+/// as a boss waiting in an arena no player has reached yet.
+///
+/// This only affects scheduling. Clients that move out of range of the entity
+/// still stop seeing it as usual.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct AlwaysSimulated;
+
+/// Opts an entity into the [`TickBudget`]. Tells whether the entity has been
+/// scheduled to run its logic in the current tick.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct BudgetedTick {
+    scheduled: bool,
+    last_scheduled: Option<i64>,
+}
+
+impl BudgetedTick {
+    /// Whether the entity should run its logic this tick.
+    pub fn is_scheduled(&self) -> bool {
+        self.scheduled
+    }
+
+    /// The tick in which the entity was last scheduled, or `None` if it never
+    /// was.
+    pub fn last_scheduled(&self) -> Option<i64> {
+        self.last_scheduled
+    }
+}
+
+fn schedule_budgeted_ticks(
+    server: Res<Server>,
+    budget: Res<TickBudget>,
+    mut entities: Query<(
+        Entity,
+        &mut BudgetedTick,
+        Option<&Position>,
+        Option<&EntityLayerId>,
+        Has<AlwaysSimulated>,
+    )>,
+    layers: Query<&ChunkLayer>,
+    mut queue: Local<Vec<(Option<i64>, Entity)>>,
+) {
+    let tick = server.current_tick();
+
+    queue.clear();
+
+    for (entity, mut budgeted, pos, layer_id, always_simulated) in &mut entities {
+        budgeted.scheduled = false;
+
+        if budget.skip_unviewed && !always_simulated {
+            if let (Some(pos), Some(layer_id)) = (pos, layer_id) {
+                if let Ok(layer) = layers.get(layer_id.0) {
+                    let viewed = layer
+                        .chunk(pos.to_chunk_pos())
+                        .is_some_and(|chunk| chunk.viewer_count() > 0);
+
+                    if !viewed {
+                        continue;
+                    }
+                }
+            }
+        }
+
+        queue.push((budgeted.last_scheduled, entity));
+    }
+
+    let count = budget.scheduled_count(queue.len());
+
+    if count == 0 {
+        return;
+    }
+
+    // Entities that were never scheduled come first, followed by those that
+    // have waited the longest. Ties are broken by entity to stay
+    // deterministic.
+    if count < queue.len() {
+        queue.select_nth_unstable(count - 1);
+    }
+
+    for &(_, entity) in &queue[..count] {
+        if let Ok((_, mut budgeted, ..)) = entities.get_mut(entity) {
+            budgeted.scheduled = true;
+            budgeted.last_scheduled = Some(tick);
+        }
+    }
+}
@@ -1,8 +1,27 @@
+use std::borrow::Cow;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
 use valence_protocol::encode::WritePacket;
 use valence_protocol::packets::play::{
     ClearTitleS2c, OverlayMessageS2c, SubtitleS2c, TitleFadeS2c, TitleS2c,
 };
-use valence_protocol::text::IntoText;
+use valence_protocol::text::{IntoText, Text};
+use valence_server_common::Server;
+
+use crate::client::{Client, UpdateClientsSet};
+
+pub struct TitlePlugin;
+
+impl Plugin for TitlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, update_action_bar.in_set(UpdateClientsSet));
+    }
+}
+
+/// The number of ticks between two sends of the same [`ActionBar`] text. The
+/// client starts fading the action bar out 40 ticks after receiving it.
+const ACTION_BAR_RESEND_TICKS: i64 = 40;
 
 pub trait SetTitle {
     /// Displays a title to a client.
@@ -17,6 +36,30 @@ pub trait SetTitle {
 
     fn set_action_bar<'a>(&mut self, text: impl IntoText<'a>);
 
+    /// Displays a title and subtitle to a client with the given timing, all
+    /// in one call.
+    ///
+    /// The timing is sent first so that it applies to the new title. A
+    /// negative value keeps the client's current value for that field, and
+    /// the timing is not sent at all if every field is negative. See
+    /// [`set_title_times`](Self::set_title_times) for the meaning of each
+    /// field.
+    fn show_title<'a, 'b>(
+        &mut self,
+        title: impl IntoText<'a>,
+        subtitle: impl IntoText<'b>,
+        fade_in: i32,
+        stay: i32,
+        fade_out: i32,
+    ) {
+        if fade_in >= 0 || stay >= 0 || fade_out >= 0 {
+            self.set_title_times(fade_in, stay, fade_out);
+        }
+
+        self.set_subtitle(subtitle);
+        self.set_title(title);
+    }
+
     /// - `fade_in`: Ticks to spend fading in.
     /// - `stay`: Ticks to keep the title displayed.
     /// - `fade_out`: Ticks to spend fading out.
@@ -62,3 +105,76 @@ impl<T: WritePacket> SetTitle for T {
         self.write_packet(&ClearTitleS2c { reset: true });
     }
 }
+
+/// An optional component holding the text to keep in a client's action bar,
+/// above the hotbar.
+///
+/// The client fades the action bar out a few seconds after it is sent, so
+/// the text is sent again periodically for as long as it is set. For one-off
+/// messages use [`SetTitle::set_action_bar`] instead.
+///
+/// Setting the text that is already shown does nothing, so it is fine to call
+/// [`set`](Self::set) every tick.
+#[derive(Component, Clone, PartialEq, Default, Debug)]
+pub struct ActionBar {
+    text: Option<Text>,
+    /// The tick at which the text was last sent, or `None` if it still needs to
+    /// be sent.
+    sent_tick: Option<i64>,
+}
+
+impl ActionBar {
+    pub fn new<'a>(text: impl IntoText<'a>) -> Self {
+        Self {
+            text: Some(text.into_text()),
+            sent_tick: None,
+        }
+    }
+
+    pub fn text(&self) -> Option<&Text> {
+        self.text.as_ref()
+    }
+
+    /// Sets the text of the action bar. The text is only copied if it differs
+    /// from the current one.
+    pub fn set<'a>(&mut self, text: impl IntoText<'a>) {
+        let text = text.into_cow_text();
+
+        if self.text.as_ref() != Some(&*text) {
+            self.text = Some(text.into_owned());
+            self.sent_tick = None;
+        }
+    }
+
+    /// Removes the text from the action bar immediately.
+    pub fn clear(&mut self) {
+        if self.text.take().is_some() {
+            self.sent_tick = None;
+        }
+    }
+}
+
+fn update_action_bar(server: Res<Server>, mut clients: Query<(&mut Client, &mut ActionBar)>) {
+    let tick = server.current_tick();
+
+    for (mut client, mut action_bar) in &mut clients {
+        let action_bar = action_bar.bypass_change_detection();
+
+        let due = match (&action_bar.text, action_bar.sent_tick) {
+            (_, None) => true,
+            (Some(_), Some(sent)) => tick - sent >= ACTION_BAR_RESEND_TICKS,
+            (None, Some(_)) => false,
+        };
+
+        if due {
+            client.write_packet(&OverlayMessageS2c {
+                action_bar_text: match &action_bar.text {
+                    Some(text) => Cow::Borrowed(text),
+                    None => Cow::Owned(Text::default()),
+                },
+            });
+
+            action_bar.sent_tick = Some(tick);
+        }
+    }
+}
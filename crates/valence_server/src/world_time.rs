@@ -0,0 +1,120 @@
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_protocol::packets::play::WorldTimeUpdateS2c;
+use valence_protocol::WritePacket;
+use valence_server_common::Server;
+
+use crate::client::{Client, UpdateClientsSet, VisibleChunkLayer};
+use crate::layer::{ChunkLayer, UpdateLayersPreClientSet};
+
+pub struct WorldTimePlugin;
+
+impl Plugin for WorldTimePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, advance_world_time).add_systems(
+            PostUpdate,
+            (
+                broadcast_world_time.before(UpdateLayersPreClientSet),
+                update_client_world_time
+                    .in_set(UpdateClientsSet)
+                    .after(crate::spawn::initial_join)
+                    .after(crate::spawn::respawn),
+            ),
+        );
+    }
+}
+
+/// The number of ticks between two broadcasts of an unchanged [`WorldTime`].
+/// Clients advance the time on their own in between.
+const BROADCAST_PERIOD: i64 = 20;
+
+/// An optional component on a [`ChunkLayer`] holding the time of that world.
+///
+/// Each layer keeps its own time, so a lobby can be frozen at noon while a
+/// survival world cycles through day and night. Clients are sent the time of
+/// the layer they are viewing, immediately after switching layers and
+/// periodically after that.
+///
+/// The time advances once per tick. Modifying the component sends the new
+/// time to clients right away.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct WorldTime {
+    /// The number of ticks the world has existed for. Always advances.
+    pub world_age: i64,
+    /// The time of day in ticks. `0` is sunrise, `6000` is noon, `12000` is
+    /// sunset and `18000` is midnight. The value may grow past `24000`, in
+    /// which case only the remainder matters.
+    pub time_of_day: i64,
+    /// Whether [`time_of_day`](Self::time_of_day) advances with each tick.
+    pub daylight_cycle: bool,
+}
+
+impl WorldTime {
+    /// Creates a time frozen at the given time of day.
+    pub const fn fixed(time_of_day: i64) -> Self {
+        Self {
+            world_age: 0,
+            time_of_day,
+            daylight_cycle: false,
+        }
+    }
+
+    fn packet(&self) -> WorldTimeUpdateS2c {
+        WorldTimeUpdateS2c {
+            world_age: self.world_age,
+            // A negative time tells the client not to advance the time of day
+            // itself. Zero can't be negated, so it becomes -1.
+            time_of_day: match (self.daylight_cycle, self.time_of_day) {
+                (true, t) => t,
+                (false, 0) => -1,
+                (false, t) => -t.abs(),
+            },
+        }
+    }
+}
+
+impl Default for WorldTime {
+    fn default() -> Self {
+        Self {
+            world_age: 0,
+            time_of_day: 0,
+            daylight_cycle: true,
+        }
+    }
+}
+
+fn advance_world_time(mut layers: Query<&mut WorldTime>) {
+    for mut time in &mut layers {
+        // Advancing on its own doesn't need to be sent right away.
+        let time = time.bypass_change_detection();
+
+        time.world_age += 1;
+
+        if time.daylight_cycle {
+            time.time_of_day += 1;
+        }
+    }
+}
+
+fn broadcast_world_time(server: Res<Server>, mut layers: Query<(&mut ChunkLayer, Ref<WorldTime>)>) {
+    let periodic = server.current_tick() % BROADCAST_PERIOD == 0;
+
+    for (mut layer, time) in &mut layers {
+        if periodic || time.is_changed() {
+            layer.write_packet(&time.packet());
+        }
+    }
+}
+
+/// Sends the time of their new layer to clients that switched layers, or just
+/// joined.
+fn update_client_world_time(
+    mut clients: Query<(&mut Client, &VisibleChunkLayer), Changed<VisibleChunkLayer>>,
+    layers: Query<&WorldTime, With<ChunkLayer>>,
+) {
+    for (mut client, visible_chunk_layer) in &mut clients {
+        if let Ok(time) = layers.get(visible_chunk_layer.0) {
+            client.write_packet(&time.packet());
+        }
+    }
+}
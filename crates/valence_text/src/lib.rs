@@ -365,6 +365,17 @@ impl Text {
         }))
     }
 
+    /// Creates a two-line message of the day, suitable for the description of
+    /// a server list ping response.
+    ///
+    /// The lines are joined by a text component containing a literal `\n`,
+    /// which the client renders as a line break. Both lines are siblings of an
+    /// empty root component, so the styling of the first line does not carry
+    /// over to the second.
+    pub fn motd(line1: impl IntoText<'static>, line2: impl IntoText<'static>) -> Self {
+        Text::text("") + line1 + "\n" + line2
+    }
+
     /// Returns `true` if the text contains no characters. Returns `false`
     /// otherwise.
     pub fn is_empty(&self) -> bool {
@@ -134,3 +134,14 @@ fn text_to_legacy_lossy() {
          formatted blue text"
     );
 }
+
+#[test]
+fn motd() {
+    let txt = Text::motd("A Valence Server".color(Color::GOLD), "Now with two lines!");
+
+    assert_eq!(txt.extra.len(), 3);
+    assert_eq!(txt.extra[0], "A Valence Server".color(Color::GOLD));
+    assert_eq!(txt.extra[1], "\n".into_text());
+    assert_eq!(txt.extra[2], "Now with two lines!".into_text());
+    assert!(txt.to_string().contains(r#""text":"\n""#));
+}
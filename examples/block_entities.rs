@@ -47,22 +47,18 @@ fn setup(
         BlockState::CHEST.set(PropName::Facing, PropValue::West),
     );
 
-    layer.chunk.set_block(
+    layer.chunk.place_sign(
         SIGN_POS,
-        Block {
-            state: BlockState::OAK_SIGN.set(PropName::Rotation, PropValue::_4),
-            nbt: Some(compound! {
-                "front_text" => compound! {
-                    "messages" => List::String(vec![
-                        // All 4 lines are required, otherwise no text is displayed.
-                        "Type in chat:".color(Color::RED).into(),
-                        "".into_text().into(),
-                        "".into_text().into(),
-                        "".into_text().into(),
-                    ]),
-                }
-            }),
-        },
+        Direction::West,
+        false,
+        [
+            "Type in chat:".color(Color::RED),
+            "".into_text(),
+            "".into_text(),
+            "".into_text(),
+        ],
+        DyeColor::Black,
+        false,
     );
 
     layer.chunk.set_block(
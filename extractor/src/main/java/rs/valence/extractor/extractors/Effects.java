@@ -0,0 +1,33 @@
+package rs.valence.extractor.extractors;
+
+import com.google.gson.JsonArray;
+import com.google.gson.JsonElement;
+import com.google.gson.JsonObject;
+import net.minecraft.registry.Registries;
+import rs.valence.extractor.Main;
+
+public class Effects implements Main.Extractor {
+    public Effects() {
+    }
+
+    @Override
+    public String fileName() {
+        return "effects.json";
+    }
+
+    @Override
+    public JsonElement extract() throws Exception {
+        var effectsJson = new JsonArray();
+
+        for (var effect : Registries.STATUS_EFFECT) {
+            var effectJson = new JsonObject();
+            effectJson.addProperty("id", Registries.STATUS_EFFECT.getRawId(effect));
+            effectJson.addProperty("name", Registries.STATUS_EFFECT.getId(effect).getPath());
+            effectJson.addProperty("translation_key", effect.getTranslationKey());
+            effectJson.addProperty("category", effect.getCategory().name().toLowerCase());
+            effectsJson.add(effectJson);
+        }
+
+        return effectsJson;
+    }
+}
@@ -36,6 +36,7 @@ mod tests;
 #[cfg(feature = "log")]
 pub use bevy_log as log;
 use registry::biome::BiomePlugin;
+use registry::damage_type::DamageTypePlugin;
 use registry::dimension_type::DimensionTypePlugin;
 #[cfg(feature = "advancement")]
 pub use valence_advancement as advancement;
@@ -51,13 +52,19 @@ pub use valence_network as network;
 #[cfg(feature = "player_list")]
 pub use valence_player_list as player_list;
 use valence_registry::RegistryPlugin;
+#[cfg(feature = "schem")]
+pub use valence_schem as schem;
 #[cfg(feature = "scoreboard")]
 pub use valence_scoreboard as scoreboard;
 use valence_server::abilities::AbilitiesPlugin;
 use valence_server::action::ActionPlugin;
+use valence_server::autosave::AutosavePlugin;
+use valence_server::chat_session::ChatSessionPlugin;
 use valence_server::client::ClientPlugin;
 use valence_server::client_command::ClientCommandPlugin;
 use valence_server::client_settings::ClientSettingsPlugin;
+use valence_server::command_graph::CommandGraphPlugin;
+use valence_server::command_suggestions::CommandSuggestionsPlugin;
 use valence_server::custom_payload::CustomPayloadPlugin;
 use valence_server::entity::hitbox::HitboxPlugin;
 use valence_server::entity::EntityPlugin;
@@ -71,9 +78,14 @@ use valence_server::layer::LayerPlugin;
 use valence_server::message::MessagePlugin;
 use valence_server::movement::MovementPlugin;
 use valence_server::op_level::OpLevelPlugin;
+use valence_server::recipe_book::RecipeBookPlugin;
 use valence_server::resource_pack::ResourcePackPlugin;
 use valence_server::status::StatusPlugin;
+use valence_server::team_visibility::TeamVisibilityPlugin;
 use valence_server::teleport::TeleportPlugin;
+use valence_server::tick_budget::TickBudgetPlugin;
+use valence_server::title::TitlePlugin;
+use valence_server::world_time::WorldTimePlugin;
 pub use valence_server::*;
 #[cfg(feature = "weather")]
 pub use valence_weather as weather;
@@ -113,12 +125,15 @@ pub mod prelude {
     #[cfg(feature = "player_list")]
     pub use valence_player_list::{PlayerList, PlayerListEntry};
     pub use valence_registry::biome::{Biome, BiomeId, BiomeRegistry};
+    pub use valence_registry::damage_type::{DamageType, DamageTypeId, DamageTypeRegistry};
     pub use valence_registry::dimension_type::{DimensionType, DimensionTypeRegistry};
     pub use valence_server::action::{DiggingEvent, DiggingState};
+    pub use valence_server::autosave::{AutosaveEvent, AutosaveSettings};
     pub use valence_server::block::{BlockKind, BlockState, PropName, PropValue};
     pub use valence_server::client::{
-        despawn_disconnected_clients, Client, Ip, OldView, OldViewDistance, Properties, Username,
-        View, ViewDistance, VisibleChunkLayer, VisibleEntityLayers,
+        despawn_disconnected_clients, Client, HiddenEntities, Ip, OldView, OldViewDistance,
+        Properties, Transferred, Username, View, ViewDistance, VisibleChunkLayer,
+        VisibleEntityLayers,
     };
     pub use valence_server::client_command::{
         ClientCommand, JumpWithHorseEvent, JumpWithHorseState, LeaveBedEvent, SneakEvent,
@@ -126,8 +141,9 @@ pub mod prelude {
     };
     pub use valence_server::entity::hitbox::{Hitbox, HitboxShape};
     pub use valence_server::entity::{
-        EntityAnimation, EntityKind, EntityLayerId, EntityManager, EntityStatus, HeadYaw, Look,
-        OldEntityLayerId, OldPosition, Position,
+        EntityAnimation, EntityKind, EntityLayerId, EntityManager, EntityStatus, Equipment,
+        EquipmentSlot, FaceMovement, FireTicks, HeadYaw, Look, OldEntityLayerId, OldPosition,
+        Position,
     };
     pub use valence_server::event_loop::{
         EventLoopPostUpdate, EventLoopPreUpdate, EventLoopUpdate,
@@ -135,7 +151,7 @@ pub mod prelude {
     pub use valence_server::ident::Ident;
     pub use valence_server::interact_entity::{EntityInteraction, InteractEntityEvent};
     pub use valence_server::layer::chunk::{
-        Block, BlockRef, Chunk, ChunkLayer, LoadedChunk, UnloadedChunk,
+        Block, BlockRef, Chunk, ChunkLayer, DyeColor, LoadedChunk, UnloadedChunk,
     };
     pub use valence_server::layer::{EntityLayer, LayerBundle};
     pub use valence_server::math::{DVec2, DVec3, Vec2, Vec3};
@@ -143,7 +159,12 @@ pub mod prelude {
     pub use valence_server::nbt::Compound;
     pub use valence_server::protocol::packets::play::particle_s2c::Particle;
     pub use valence_server::protocol::text::{Color, IntoText, Text};
-    pub use valence_server::spawn::{ClientSpawnQuery, ClientSpawnQueryReadOnly, RespawnPosition};
+    pub use valence_server::spawn::{
+        ClientSpawnQuery, ClientSpawnQueryReadOnly, DefaultGameMode, DifficultySettings,
+        RespawnPosition,
+    };
+    pub use valence_server::team_visibility::{HiddenFromTeams, Team};
+    pub use valence_server::tick_budget::{AlwaysSimulated, BudgetedTick, TickBudget};
     pub use valence_server::title::SetTitle as _;
     pub use valence_server::{
         ident, BlockPos, ChunkPos, ChunkView, Despawned, Direction, GameMode, Hand, ItemKind,
@@ -168,6 +189,7 @@ impl PluginGroup for DefaultPlugins {
             .add(ServerPlugin)
             .add(RegistryPlugin)
             .add(BiomePlugin)
+            .add(DamageTypePlugin)
             .add(DimensionTypePlugin)
             .add(EntityPlugin)
             .add(HitboxPlugin)
@@ -182,14 +204,23 @@ impl PluginGroup for DefaultPlugins {
             .add(ActionPlugin)
             .add(TeleportPlugin)
             .add(MessagePlugin)
+            .add(ChatSessionPlugin)
             .add(CustomPayloadPlugin)
+            .add(CommandSuggestionsPlugin)
+            .add(CommandGraphPlugin)
             .add(HandSwingPlugin)
             .add(InteractBlockPlugin)
             .add(InteractItemPlugin)
             .add(OpLevelPlugin)
+            .add(RecipeBookPlugin)
             .add(ResourcePackPlugin)
             .add(StatusPlugin)
-            .add(AbilitiesPlugin);
+            .add(TitlePlugin)
+            .add(WorldTimePlugin)
+            .add(TickBudgetPlugin)
+            .add(TeamVisibilityPlugin)
+            .add(AbilitiesPlugin)
+            .add(AutosavePlugin);
 
         #[cfg(feature = "log")]
         {
@@ -91,6 +91,7 @@ pub fn create_mock_client(name: impl Into<String>) -> (ClientBundle, MockClientH
         uuid: Uuid::from_bytes(rand::random()),
         ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
         properties: Default::default(),
+        transferred: false,
         conn: Box::new(conn.clone()),
         enc: PacketEncoder::new(),
     });
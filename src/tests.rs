@@ -1,9 +1,12 @@
+mod autosave;
 mod boss_bar;
 mod client;
 mod example;
 mod inventory;
 mod layer;
 mod player_list;
+mod schem;
 mod scoreboard;
+mod tick_budget;
 mod weather;
 mod world_border;
@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use bevy_app::Update;
+use bevy_ecs::prelude::*;
+
+use crate::autosave::{AutosaveEvent, AutosaveSettings};
+use crate::layer::chunk::{Chunk, UnloadedChunk};
+use crate::layer::ChunkLayer;
+use crate::testing::ScenarioSingleClient;
+use crate::{BlockState, ChunkPos, Server};
+
+#[derive(Resource, Default)]
+struct SavedTicks(Vec<i64>);
+
+fn save_game(mut events: EventReader<AutosaveEvent>, mut saved: ResMut<SavedTicks>) {
+    for event in events.iter() {
+        saved.0.push(event.tick);
+    }
+}
+
+#[test]
+fn autosave_at_interval() {
+    let ScenarioSingleClient { mut app, .. } = ScenarioSingleClient::new();
+
+    app.insert_resource(AutosaveSettings {
+        period: Some(Duration::from_secs(1)),
+    })
+    .init_resource::<SavedTicks>()
+    .add_systems(Update, save_game);
+
+    let tick_rate = app.world.resource::<Server>().tick_rate().get() as i64;
+
+    for _ in 0..tick_rate * 3 {
+        app.update();
+    }
+
+    let saved = &app.world.resource::<SavedTicks>().0;
+    assert_eq!(saved.len(), 3);
+    assert!(saved.windows(2).all(|w| w[1] - w[0] == tick_rate));
+
+    // Nothing is saved while autosaving is disabled.
+    app.insert_resource(AutosaveSettings { period: None });
+
+    for _ in 0..tick_rate * 3 {
+        app.update();
+    }
+
+    assert_eq!(app.world.resource::<SavedTicks>().0.len(), 3);
+}
+
+#[derive(Resource, Default)]
+struct SavedChunks(Vec<ChunkPos>);
+
+fn save_chunks(
+    mut events: EventReader<AutosaveEvent>,
+    mut layers: Query<&mut ChunkLayer>,
+    mut saved: ResMut<SavedChunks>,
+) {
+    for _ in events.iter() {
+        for mut layer in &mut layers {
+            for (pos, chunk) in layer.chunks_mut() {
+                if chunk.is_modified() {
+                    saved.0.push(pos);
+                    chunk.mark_saved();
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn autosave_modified_chunks() {
+    let ScenarioSingleClient {
+        mut app,
+        layer: layer_ent,
+        ..
+    } = ScenarioSingleClient::new();
+
+    app.insert_resource(AutosaveSettings {
+        period: Some(Duration::from_secs(1)),
+    })
+    .init_resource::<SavedChunks>()
+    .add_systems(Update, save_chunks);
+
+    let tick_rate = app.world.resource::<Server>().tick_rate().get();
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+
+    // Inserted chunks are not modified.
+    for pos in [[0, 0], [1, 0], [2, 0]] {
+        layer.insert_chunk(pos, UnloadedChunk::new());
+    }
+
+    layer.set_block([20, 10, 0], BlockState::STONE);
+    layer
+        .chunk_mut([2, 0])
+        .unwrap()
+        .set_block_light(0, 10, 0, 4);
+
+    for _ in 0..tick_rate {
+        app.update();
+    }
+
+    let saved = &mut app.world.resource_mut::<SavedChunks>().0;
+    saved.sort_by_key(|pos| pos.x);
+    assert_eq!(*saved, [ChunkPos::new(1, 0), ChunkPos::new(2, 0)]);
+    saved.clear();
+
+    // Saved chunks are only saved again once they change.
+    app.world
+        .get_mut::<ChunkLayer>(layer_ent)
+        .unwrap()
+        .set_block([20, 11, 0], BlockState::STONE);
+
+    for _ in 0..tick_rate {
+        app.update();
+    }
+
+    assert_eq!(app.world.resource::<SavedChunks>().0, [ChunkPos::new(1, 0)]);
+}
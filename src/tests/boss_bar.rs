@@ -1,12 +1,12 @@
 use valence_boss_bar::{
-    BossBarBundle, BossBarColor, BossBarDivision, BossBarFlags, BossBarHealth, BossBarStyle,
-    BossBarTitle,
+    BossBarAction, BossBarBundle, BossBarColor, BossBarDivision, BossBarFlags, BossBarHealth,
+    BossBarStyle, BossBarTitle, BossBarViewers,
 };
 use valence_server::client::VisibleEntityLayers;
 use valence_server::entity::EntityLayerId;
 use valence_server::protocol::packets::play::BossBarS2c;
 use valence_server::text::IntoText;
-use valence_server::Despawned;
+use valence_server::{Despawned, UniqueId};
 
 use crate::testing::ScenarioSingleClient;
 use crate::Text;
@@ -170,6 +170,133 @@ fn test_client_layer_change() {
     frames.assert_count::<BossBarS2c>(1);
 }
 
+#[test]
+fn test_explicit_viewers() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    // The boss bar isn't in any layer the client can see.
+    let boss_bar = app
+        .world
+        .spawn((
+            BossBarBundle {
+                title: BossBarTitle("Dragon".into_text()),
+                health: BossBarHealth(0.25),
+                ..Default::default()
+            },
+            BossBarViewers::new([client]),
+        ))
+        .id();
+
+    app.update();
+
+    let id = app.world.get::<UniqueId>(boss_bar).unwrap().0;
+
+    let frames = helper.collect_received();
+    frames.assert_count::<BossBarS2c>(1);
+
+    let pkt = frames.first::<BossBarS2c>();
+    assert_eq!(pkt.id, id);
+    assert!(matches!(pkt.action, BossBarAction::Add { health, .. } if health == 0.25));
+
+    app.world.get_mut::<BossBarHealth>(boss_bar).unwrap().0 = 0.5;
+
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<BossBarS2c>(1);
+    assert_eq!(
+        frames.first::<BossBarS2c>().action,
+        BossBarAction::UpdateHealth(0.5)
+    );
+
+    // Hide the boss bar from the client.
+    assert!(app
+        .world
+        .get_mut::<BossBarViewers>(boss_bar)
+        .unwrap()
+        .remove(client));
+
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<BossBarS2c>(1);
+    assert_eq!(frames.first::<BossBarS2c>().action, BossBarAction::Remove);
+
+    app.world.get_mut::<BossBarHealth>(boss_bar).unwrap().0 = 1.0;
+
+    app.update();
+
+    helper.collect_received().assert_count::<BossBarS2c>(0);
+}
+
+#[test]
+fn test_explicit_viewers_replace_layer_viewers() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer,
+    } = prepare();
+
+    // The client sees the boss bar through the layer until it gets explicit
+    // viewers that don't include the client.
+    app.world
+        .entity_mut(layer)
+        .insert(BossBarViewers::default());
+
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<BossBarS2c>(1);
+    assert_eq!(frames.first::<BossBarS2c>().action, BossBarAction::Remove);
+
+    app.world
+        .get_mut::<BossBarViewers>(layer)
+        .unwrap()
+        .insert(client);
+
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<BossBarS2c>(1);
+    assert!(matches!(
+        frames.first::<BossBarS2c>().action,
+        BossBarAction::Add { .. }
+    ));
+}
+
+#[test]
+fn test_explicit_viewers_forget_despawned_clients() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::new();
+
+    app.update();
+
+    let boss_bar = app
+        .world
+        .spawn((BossBarBundle::default(), BossBarViewers::new([client])))
+        .id();
+
+    app.update();
+
+    app.world.entity_mut(client).insert(Despawned);
+
+    app.update();
+
+    let viewers = app.world.get::<BossBarViewers>(boss_bar).unwrap();
+    assert!(!viewers.contains(client));
+    assert_eq!(viewers.iter().len(), 0);
+}
+
 fn prepare() -> ScenarioSingleClient {
     let mut s = ScenarioSingleClient::new();
 
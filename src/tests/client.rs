@@ -1,12 +1,74 @@
-use crate::abilities::PlayerAbilitiesFlags;
-use crate::layer::chunk::UnloadedChunk;
-use crate::layer::ChunkLayer;
-use crate::math::DVec3;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy_app::{App, Update};
+use bevy_ecs::change_detection::DetectChangesMut;
+use bevy_ecs::event::{EventReader, Events};
+use bevy_ecs::system::{Command, Query};
+
+use crate::abilities::{
+    FlyingSpeed, PlayerAbilitiesFlags, PlayerStartFlyingEvent, PlayerStopFlyingEvent,
+};
+use crate::chat_session::{ChatSession, ChatSessionSettings, ExpiredKeyPolicy};
+use crate::client::{Client, VisibleChunkLayer};
+use crate::client_command::{
+    JumpWithHorseEvent, JumpWithHorseState, OpenHorseInventoryEvent, SneakEvent, SneakState,
+};
+use crate::client_settings::ClientSettings;
+use crate::command_graph::CommandGraph;
+use crate::command_suggestions::{CommandSuggestionsRequestEvent, CommandSuggestionsSettings};
+use crate::entity::entity::{Flags, Pose};
+use crate::entity::interaction::{self, InteractionEntityBundle};
+use crate::entity::minecart::MinecartEntityBundle;
+use crate::entity::{EntityId, EntityLayerId, Look, OnGround, Passengers, Position};
+use crate::interact_cooldown::InteractCooldownSettings;
+use crate::interact_entity::{EntityInteraction, InteractEntityEvent};
+use crate::interact_item::InteractItemEvent;
+use crate::inventory::{HeldItem, Inventory};
+use crate::layer::chunk::{Chunk, UnloadedChunk};
+use crate::layer::{ChunkLayer, EntityLayer};
+use crate::math::{Aabb, DVec3};
+use crate::message::{
+    ChatMessageEvent, ChatSettings, LocalizedText, MessageAcknowledgments, SendMessage,
+};
+use crate::movement::{is_in_fluid, is_supported_by_blocks, LockedSteering, SteerVehicleEvent};
+use crate::op_level::OpLevel;
+use crate::protocol::packets::play::client_command_c2s::ClientCommand;
+use crate::protocol::packets::play::client_settings_c2s::{ChatMode, DisplayedSkinParts, MainArm};
+use crate::protocol::packets::play::command_suggestions_s2c::CommandSuggestionsMatch;
+use crate::protocol::packets::play::command_tree_s2c::{CommandNode, NodeData};
+use crate::protocol::packets::play::game_state_change_s2c::GameEventKind;
+use crate::protocol::packets::play::particle_s2c::Particle;
+use crate::protocol::packets::play::player_input_c2s::PlayerInputFlags;
+use crate::protocol::packets::play::player_position_look_s2c::PlayerPositionLookFlags;
+use crate::protocol::packets::play::unlock_recipes_s2c::UpdateRecipeBookAction;
 use crate::protocol::packets::play::{
-    FullC2s, MoveRelativeS2c, PlayerPositionLookS2c, TeleportConfirmC2s,
+    ChatMessageC2s, ChunkDataS2c, ClientCommandC2s, ClientSettingsC2s, ClientStatusC2s,
+    CommandSuggestionsS2c, CommandTreeS2c, DeathMessageS2c, DifficultyS2c, EntityDamageS2c,
+    EntityPassengersSetS2c, EntityStatusS2c, EntityTrackerUpdateS2c, EntityVelocityUpdateS2c,
+    FullC2s, GameJoinS2c, GameMessageS2c, GameStateChangeS2c, MessageAcknowledgmentC2s,
+    MoveRelativeS2c, OverlayMessageS2c, ParticleS2c, PlaySoundFromEntityS2c, PlaySoundS2c,
+    PlayerAbilitiesS2c, PlayerInputC2s, PlayerInteractEntityC2s, PlayerInteractItemC2s,
+    PlayerListS2c, PlayerPositionLookS2c, PlayerRespawnS2c, PlayerSessionC2s,
+    PositionAndOnGroundC2s, RequestCommandCompletionsC2s, StopSoundS2c, SubtitleS2c,
+    TeleportConfirmC2s, TitleFadeS2c, TitleS2c, UnlockRecipesS2c, UpdatePlayerAbilitiesC2s,
+    VehicleMoveC2s, VehicleMoveS2c, WorldTimeUpdateS2c,
+};
+use crate::protocol::sound::{Sound, SoundCategory, SoundId};
+use crate::protocol::{Bounded, Packet, VarInt};
+use crate::recipe_book::RecipeBook;
+use crate::registry::{BiomeRegistry, DamageTypeRegistry, DimensionTypeRegistry, RegistryIdx};
+use crate::spawn::{DefaultGameMode, DifficultySettings, HasRespawnScreen, ReducedDebugInfo};
+use crate::status::RequestRespawnEvent;
+use crate::teleport::{
+    InitialTeleportSettings, MissingSpawnChunksEvent, PlayerLoadedEvent, RelativeTeleport,
 };
 use crate::testing::{create_mock_client, ScenarioSingleClient};
-use crate::{ChunkPos, GameMode};
+use crate::title::{ActionBar, SetTitle};
+use crate::uuid::Uuid;
+use crate::world_time::WorldTime;
+use crate::{
+    ident, BlockState, ChunkPos, Difficulty, GameMode, Hand, ItemKind, ItemStack, Server, Text,
+};
 
 #[test]
 fn client_teleport_and_move() {
@@ -101,3 +163,1990 @@ fn client_gamemode_changed_ability() {
     assert!(!abilities.instant_break());
     assert!(!abilities.invulnerable());
 }
+
+#[test]
+fn client_gamemode_change_sends_packets() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    *app.world.get_mut::<GameMode>(client).unwrap() = GameMode::Spectator;
+
+    app.update();
+
+    let frames = helper.collect_received();
+
+    frames.assert_count::<GameStateChangeS2c>(1);
+    let pkt = frames.first::<GameStateChangeS2c>();
+    assert_eq!(pkt.kind, GameEventKind::ChangeGameMode);
+    assert_eq!(pkt.value, GameMode::Spectator as i32 as f32);
+
+    frames.assert_count::<PlayerAbilitiesS2c>(1);
+    let pkt = frames.first::<PlayerAbilitiesS2c>();
+    assert!(pkt.flags.allow_flying());
+    assert!(pkt.flags.flying());
+
+    // The client's player list entry is updated too.
+    frames.assert_count::<PlayerListS2c>(1);
+    let pkt = frames.first::<PlayerListS2c>();
+    assert!(pkt.actions.update_game_mode());
+    assert_eq!(pkt.entries[0].game_mode, GameMode::Spectator);
+
+    *app.world.get_mut::<GameMode>(client).unwrap() = GameMode::Survival;
+
+    app.update();
+
+    let frames = helper.collect_received();
+
+    frames.assert_count::<GameStateChangeS2c>(1);
+    frames.assert_count::<PlayerAbilitiesS2c>(1);
+    let pkt = frames.first::<PlayerAbilitiesS2c>();
+    assert!(!pkt.flags.allow_flying());
+    assert!(!pkt.flags.flying());
+}
+
+#[test]
+fn client_flying_implies_allow_flying() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    let mut flags = app.world.get_mut::<PlayerAbilitiesFlags>(client).unwrap();
+    flags.set_flying(true);
+    flags.set_instant_break(true);
+    app.world.get_mut::<FlyingSpeed>(client).unwrap().0 = 0.1;
+
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<PlayerAbilitiesS2c>(1);
+
+    let pkt = frames.first::<PlayerAbilitiesS2c>();
+    assert!(pkt.flags.flying());
+    assert!(pkt.flags.allow_flying());
+    assert!(pkt.flags.instant_break());
+    assert_eq!(pkt.flying_speed, 0.1);
+    // Only the packet is changed.
+    assert!(!app
+        .world
+        .get::<PlayerAbilitiesFlags>(client)
+        .unwrap()
+        .allow_flying());
+
+    // Pressing the fly key is reported back.
+    helper.send(&UpdatePlayerAbilitiesC2s::StopFlying);
+
+    app.update();
+
+    let events = app.world.resource::<Events<PlayerStopFlyingEvent>>();
+    assert_eq!(events.iter_current_update_events().count(), 1);
+    assert!(!app
+        .world
+        .get::<PlayerAbilitiesFlags>(client)
+        .unwrap()
+        .flying());
+}
+
+#[test]
+fn client_start_flying_without_permission() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    helper.send(&UpdatePlayerAbilitiesC2s::StartFlying);
+
+    app.update();
+
+    let flags = *app.world.get::<PlayerAbilitiesFlags>(client).unwrap();
+    assert!(!flags.flying());
+    assert!(!flags.allow_flying());
+
+    let events = app.world.resource::<Events<PlayerStartFlyingEvent>>();
+    assert_eq!(events.iter_current_update_events().count(), 0);
+
+    // The client is told that it can't fly.
+    let frames = helper.collect_received();
+    frames.assert_count::<PlayerAbilitiesS2c>(1);
+    assert!(!frames.first::<PlayerAbilitiesS2c>().flags.flying());
+
+    // Later changes don't grant flight either.
+    app.world.get_mut::<FlyingSpeed>(client).unwrap().0 = 0.1;
+
+    app.update();
+
+    let frames = helper.collect_received();
+    assert!(!frames.first::<PlayerAbilitiesS2c>().flags.allow_flying());
+}
+
+#[test]
+fn client_interact_item_cooldown() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.insert_resource(InteractCooldownSettings {
+        use_item: 10,
+        ..Default::default()
+    });
+
+    app.update();
+
+    let mut inv = app.world.get_mut::<Inventory>(client).unwrap();
+    inv.set_slot(36, ItemStack::new(ItemKind::EnderPearl, 16, None));
+    inv.set_slot(37, ItemStack::new(ItemKind::Snowball, 16, None));
+    inv.set_slot(45, ItemStack::new(ItemKind::Egg, 16, None));
+
+    app.update();
+
+    let interact_count = |app: &App| {
+        app.world
+            .resource::<Events<InteractItemEvent>>()
+            .iter_current_update_events()
+            .count()
+    };
+
+    // The second main hand interaction is within the cooldown, but the item in
+    // the off hand has its own cooldown.
+    helper.send(&PlayerInteractItemC2s {
+        hand: Hand::Main,
+        sequence: 0.into(),
+    });
+    helper.send(&PlayerInteractItemC2s {
+        hand: Hand::Main,
+        sequence: 1.into(),
+    });
+    helper.send(&PlayerInteractItemC2s {
+        hand: Hand::Off,
+        sequence: 2.into(),
+    });
+
+    app.update();
+
+    assert_eq!(interact_count(&app), 2);
+
+    helper.send(&PlayerInteractItemC2s {
+        hand: Hand::Main,
+        sequence: 3.into(),
+    });
+
+    app.update();
+
+    assert_eq!(interact_count(&app), 0);
+
+    // Switching to another item doesn't share the cooldown.
+    app.world
+        .get_mut::<HeldItem>(client)
+        .unwrap()
+        .set_hotbar_slot(1);
+
+    app.update();
+
+    helper.send(&PlayerInteractItemC2s {
+        hand: Hand::Main,
+        sequence: 4.into(),
+    });
+
+    app.update();
+
+    assert_eq!(interact_count(&app), 1);
+
+    // Switching back to the first item waits for its cooldown.
+    app.world
+        .get_mut::<HeldItem>(client)
+        .unwrap()
+        .set_hotbar_slot(0);
+
+    app.update();
+
+    helper.send(&PlayerInteractItemC2s {
+        hand: Hand::Main,
+        sequence: 5.into(),
+    });
+
+    app.update();
+
+    assert_eq!(interact_count(&app), 0);
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    helper.send(&PlayerInteractItemC2s {
+        hand: Hand::Main,
+        sequence: 6.into(),
+    });
+
+    app.update();
+
+    assert_eq!(interact_count(&app), 1);
+}
+
+#[test]
+fn client_on_ground_from_movement() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+
+    helper.confirm_initial_pending_teleports();
+
+    helper.send(&PositionAndOnGroundC2s {
+        position: DVec3::new(0.0, 64.0, 0.0),
+        on_ground: true,
+    });
+
+    app.update();
+
+    assert!(app.world.get::<OnGround>(client).unwrap().0);
+
+    helper.send(&PositionAndOnGroundC2s {
+        position: DVec3::new(0.0, 65.0, 0.0),
+        on_ground: false,
+    });
+
+    app.update();
+
+    assert!(!app.world.get::<OnGround>(client).unwrap().0);
+}
+
+#[test]
+fn server_side_ground_and_fluid_checks() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        helper: _,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+
+    layer.insert_chunk(ChunkPos::new(0, 0), UnloadedChunk::new());
+    layer.set_block([0, 63, 0], BlockState::STONE);
+    layer.set_block([1, 64, 0], BlockState::STONE);
+    layer.set_block([4, 63, 0], BlockState::OAK_FENCE);
+    layer.set_block([0, 70, 0], BlockState::WATER);
+
+    let player_box =
+        |x, y, z| Aabb::from_bottom_size(DVec3::new(x, y, z), DVec3::new(0.6, 1.8, 0.6));
+
+    // Standing on top of the stone.
+    assert!(is_supported_by_blocks(&layer, player_box(0.5, 64.0, 0.5)));
+    // Half a block above the stone.
+    assert!(!is_supported_by_blocks(&layer, player_box(0.5, 64.5, 0.5)));
+    // Over air, but touching the side of the stone at (1, 64, 0).
+    assert!(!is_supported_by_blocks(&layer, player_box(2.3, 64.0, 0.5)));
+    // Standing on top of the fence, which extends into the block above it.
+    assert!(is_supported_by_blocks(&layer, player_box(4.5, 64.5, 0.5)));
+    // On the same level as the top of the fence, but next to it.
+    assert!(!is_supported_by_blocks(&layer, player_box(5.5, 64.5, 0.5)));
+
+    assert!(is_in_fluid(&layer, DVec3::new(0.5, 70.2, 0.5)));
+    assert!(!is_in_fluid(&layer, DVec3::new(0.5, 71.2, 0.5)));
+}
+
+#[test]
+fn client_launch_sends_velocity() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    let mut client_comp = app.world.get_mut::<Client>(client).unwrap();
+    client_comp.launch([0.01, 10.0, 1000.0]);
+    client_comp.flush_packets().unwrap();
+
+    let recvd = helper.collect_received();
+    recvd.assert_count::<EntityVelocityUpdateS2c>(1);
+
+    let pkt = recvd.first::<EntityVelocityUpdateS2c>();
+
+    assert_eq!(pkt.entity_id.0, 0);
+    // Too small for the client to notice.
+    assert_eq!(pkt.velocity.0[0], 0);
+    assert_eq!(pkt.velocity.0[1], 4000);
+    // Clamped to 3.9 blocks per tick.
+    assert_eq!(pkt.velocity.0[2], 31200);
+}
+
+#[test]
+fn client_knockback_pushes_away_from_source() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    let mut client_comp = app.world.get_mut::<Client>(client).unwrap();
+    // The source is directly west of the client.
+    client_comp.apply_knockback([0.0, 64.0, 0.0], [-2.0, 64.5, 0.0], 0.4, false);
+    client_comp.apply_knockback([0.0, 64.0, 0.0], [-2.0, 64.5, 0.0], 0.4, true);
+    client_comp.flush_packets().unwrap();
+
+    let recvd = helper.collect_received();
+    recvd.assert_count::<EntityVelocityUpdateS2c>(2);
+
+    let velocities: Vec<_> = recvd
+        .0
+        .iter()
+        .filter(|f| f.id == EntityVelocityUpdateS2c::ID)
+        .map(|f| f.decode::<EntityVelocityUpdateS2c>().unwrap().velocity.0)
+        .collect();
+
+    // 0.4 blocks per tick east and up.
+    assert_eq!(velocities[0], [3200, 3200, 0]);
+    // 0.4 / 2 + 0.5 blocks per tick east with the sprint bonus.
+    assert_eq!(velocities[1], [5600, 3200, 0]);
+}
+
+#[test]
+fn client_death_and_respawn() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    app.world
+        .get_mut::<Client>(client)
+        .unwrap()
+        .kill("Fell out of the world");
+    app.update();
+
+    helper.collect_received().assert_count::<DeathMessageS2c>(1);
+
+    helper.send(&ClientStatusC2s::PerformRespawn);
+    app.update();
+
+    let events = app.world.resource::<Events<RequestRespawnEvent>>();
+    assert_eq!(
+        events.iter_current_update_events().collect::<Vec<_>>(),
+        [&RequestRespawnEvent { client }]
+    );
+
+    // Respawning in the same layer.
+    app.world
+        .get_mut::<VisibleChunkLayer>(client)
+        .unwrap()
+        .set_changed();
+    app.update();
+
+    helper
+        .collect_received()
+        .assert_count::<PlayerRespawnS2c>(1);
+
+    app.world.get_mut::<HasRespawnScreen>(client).unwrap().0 = false;
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<GameStateChangeS2c>(1);
+    let pkt = frames.first::<GameStateChangeS2c>();
+    assert_eq!(pkt.kind, GameEventKind::EnableRespawnScreen);
+    assert_eq!(pkt.value, 1.0);
+}
+
+#[test]
+fn initial_teleport_waits_for_chunks() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    app.insert_resource(InitialTeleportSettings {
+        min_chunk_radius: 1,
+        timeout_ticks: 100,
+    });
+
+    app.update();
+
+    // No chunks around the client yet.
+    helper
+        .collect_received()
+        .assert_count::<PlayerPositionLookS2c>(0);
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+
+    for z in -1..=1 {
+        for x in -1..=1 {
+            layer.insert_chunk([x, z], UnloadedChunk::new());
+        }
+    }
+
+    app.update();
+
+    {
+        let recvd = helper.collect_received();
+
+        recvd.assert_count::<PlayerPositionLookS2c>(1);
+        // The chunks are sent before the position.
+        recvd.assert_order::<(ChunkDataS2c, PlayerPositionLookS2c)>();
+    }
+}
+
+#[test]
+fn initial_teleport_wait_times_out() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.insert_resource(InitialTeleportSettings {
+        min_chunk_radius: 1,
+        timeout_ticks: 3,
+    });
+
+    for _ in 0..3 {
+        app.update();
+
+        helper
+            .collect_received()
+            .assert_count::<PlayerPositionLookS2c>(0);
+    }
+
+    // The world is empty, but the client still spawns.
+    app.update();
+
+    helper
+        .collect_received()
+        .assert_count::<PlayerPositionLookS2c>(1);
+}
+
+#[test]
+fn missing_spawn_chunks_are_requested() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    app.insert_resource(InitialTeleportSettings {
+        min_chunk_radius: 1,
+        timeout_ticks: 100,
+    });
+
+    // Generates the requested chunks on demand with a floor to stand on.
+    fn generate_chunks(
+        mut events: EventReader<MissingSpawnChunksEvent>,
+        mut layers: Query<&mut ChunkLayer>,
+    ) {
+        for event in events.iter() {
+            let mut layer = layers.get_mut(event.chunk_layer).unwrap();
+
+            for &pos in &event.chunks {
+                let mut chunk = UnloadedChunk::with_height(layer.height());
+
+                for z in 0..16 {
+                    for x in 0..16 {
+                        chunk.set_block_state(x, (63 - layer.min_y()) as u32, z, BlockState::STONE);
+                    }
+                }
+
+                layer.insert_chunk(pos, chunk);
+            }
+        }
+    }
+
+    app.add_systems(Update, generate_chunks);
+
+    app.update();
+
+    {
+        let events = app.world.resource::<Events<MissingSpawnChunksEvent>>();
+        let event = events.iter_current_update_events().next().unwrap();
+
+        assert_eq!(event.client, client);
+        assert_eq!(event.chunks.len(), 9);
+    }
+
+    helper
+        .collect_received()
+        .assert_count::<PlayerPositionLookS2c>(0);
+
+    app.update();
+
+    helper
+        .collect_received()
+        .assert_count::<PlayerPositionLookS2c>(1);
+
+    let layer = app.world.get::<ChunkLayer>(layer_ent).unwrap();
+
+    assert_eq!(layer.block([0, 63, 0]).unwrap().state, BlockState::STONE);
+}
+
+#[test]
+fn client_command_suggestions() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.add_systems(
+        Update,
+        |mut events: EventReader<CommandSuggestionsRequestEvent>,
+         mut clients: Query<&mut Client>| {
+            for event in events.iter() {
+                let mut client = clients.get_mut(event.client).unwrap();
+
+                let start = event.text.rfind(' ').map_or(0, |i| i + 1) as i32;
+                let length = event.text.len() as i32 - start;
+
+                client.send_command_suggestions(
+                    event.transaction_id,
+                    start,
+                    length,
+                    &[CommandSuggestionsMatch {
+                        suggested_match: "Alice",
+                        tooltip: None,
+                    }],
+                );
+            }
+        },
+    );
+
+    app.update();
+    helper.clear_received();
+
+    helper.send(&RequestCommandCompletionsC2s {
+        transaction_id: 7.into(),
+        text: Bounded("/msg Al"),
+    });
+
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<CommandSuggestionsS2c>(1);
+
+    let pkt = frames.first::<CommandSuggestionsS2c>();
+    assert_eq!(pkt.id.0, 7);
+    assert_eq!(pkt.start.0, 5);
+    assert_eq!(pkt.length.0, 2);
+    assert_eq!(pkt.matches[0].suggested_match, "Alice");
+}
+
+#[test]
+fn client_command_suggestions_rate_limit() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.insert_resource(CommandSuggestionsSettings {
+        max_requests_per_second: 3,
+    });
+
+    app.update();
+
+    let request_count = |app: &App| {
+        app.world
+            .resource::<Events<CommandSuggestionsRequestEvent>>()
+            .iter_current_update_events()
+            .count()
+    };
+
+    for i in 0..5 {
+        helper.send(&RequestCommandCompletionsC2s {
+            transaction_id: i.into(),
+            text: Bounded("/msg "),
+        });
+    }
+
+    app.update();
+
+    assert_eq!(request_count(&app), 3);
+
+    // Requests are accepted again once the one second window has passed.
+    let tick_rate = app.world.resource::<Server>().tick_rate().get();
+
+    for _ in 0..tick_rate {
+        app.update();
+    }
+
+    helper.send(&RequestCommandCompletionsC2s {
+        transaction_id: 5.into(),
+        text: Bounded("/msg "),
+    });
+
+    app.update();
+
+    assert_eq!(request_count(&app), 1);
+}
+
+#[test]
+fn client_command_tree_filtered_by_op_level() {
+    let ScenarioSingleClient {
+        mut app,
+        client: player,
+        helper: mut player_helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    app.world
+        .resource_mut::<CommandGraph>()
+        .add(CommandNode::literal("help").executable())
+        .add_with_op_level(3, CommandNode::literal("ban").executable());
+
+    let (mut bundle, mut admin_helper) = create_mock_client("admin");
+
+    bundle.player.layer.0 = layer_ent;
+    bundle.visible_chunk_layer.0 = layer_ent;
+    bundle.visible_entity_layers.0.insert(layer_ent);
+    bundle.op_level.set(3);
+
+    app.world.spawn(bundle);
+
+    app.update();
+
+    fn literals<'a>(tree: &CommandTreeS2c<'a>) -> Vec<&'a str> {
+        let mut names = vec![];
+
+        for child in &tree.commands[tree.root_index.0 as usize].children {
+            if let NodeData::Literal { name } = tree.commands[child.0 as usize].data {
+                names.push(name);
+            }
+        }
+
+        names
+    }
+
+    let frames = player_helper.collect_received();
+    frames.assert_count::<CommandTreeS2c>(1);
+    assert_eq!(literals(&frames.first::<CommandTreeS2c>()), ["help"]);
+
+    let frames = admin_helper.collect_received();
+    frames.assert_count::<CommandTreeS2c>(1);
+    assert_eq!(literals(&frames.first::<CommandTreeS2c>()), ["help", "ban"]);
+
+    // Nothing changed, so the tree is not sent again.
+    app.update();
+
+    player_helper
+        .collect_received()
+        .assert_count::<CommandTreeS2c>(0);
+
+    // Promoting the player re-sends their tree.
+    app.world.get_mut::<OpLevel>(player).unwrap().set(3);
+
+    app.update();
+
+    let frames = player_helper.collect_received();
+    frames.assert_count::<CommandTreeS2c>(1);
+    assert_eq!(literals(&frames.first::<CommandTreeS2c>()), ["help", "ban"]);
+
+    admin_helper
+        .collect_received()
+        .assert_count::<CommandTreeS2c>(0);
+}
+
+#[test]
+fn client_set_commands() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+
+    // The command graph is empty, so it doesn't send a tree of its own.
+    helper.collect_received().assert_count::<CommandTreeS2c>(0);
+
+    let warp = CommandNode::literal("warp")
+        .then(CommandNode::literal("spawn").executable())
+        .then(CommandNode::literal("arena").executable());
+
+    app.world
+        .get_mut::<Client>(client)
+        .unwrap()
+        .set_commands([warp, CommandNode::literal("home").executable()]);
+
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<CommandTreeS2c>(1);
+
+    let tree = frames.first::<CommandTreeS2c>();
+    let root = &tree.commands[tree.root_index.0 as usize];
+    assert!(matches!(root.data, NodeData::Root));
+
+    let names = |children: &[VarInt]| -> Vec<&str> {
+        children
+            .iter()
+            .map(|c| match tree.commands[c.0 as usize].data {
+                NodeData::Literal { name } => name,
+                _ => panic!("expected a literal node"),
+            })
+            .collect()
+    };
+
+    assert_eq!(names(&root.children), ["warp", "home"]);
+    assert_eq!(
+        names(&tree.commands[root.children[0].0 as usize].children),
+        ["spawn", "arena"]
+    );
+
+    // Changing the op level doesn't replace the tree with an empty one.
+    app.world.get_mut::<OpLevel>(client).unwrap().set(2);
+
+    app.update();
+
+    helper.collect_received().assert_count::<CommandTreeS2c>(0);
+}
+
+#[test]
+fn client_op_level_entity_status() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    let mut op_level = app.world.get_mut::<OpLevel>(client).unwrap();
+    op_level.set(4);
+    assert_eq!(op_level.get(), 4);
+    op_level.set(u8::MAX);
+    assert_eq!(op_level.get(), 4);
+
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<EntityStatusS2c>(1);
+
+    let pkt = frames.first::<EntityStatusS2c>();
+    assert_eq!(pkt.entity_id, 0);
+    assert_eq!(pkt.entity_status, 28);
+}
+
+#[test]
+fn client_chat_session() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64;
+
+    let session_id = Uuid::from_u128(42);
+    let public_key = [0x30, 0x82, 0x01, 0x22];
+    let key_signature = [7; 512];
+
+    helper.send(&PlayerSessionC2s {
+        session_id,
+        expires_at: now + 60_000,
+        public_key_data: Bounded(&public_key),
+        key_signature: Bounded(&key_signature),
+    });
+
+    app.update();
+
+    let session = app.world.get::<ChatSession>(client).unwrap();
+    assert_eq!(session.session_id, session_id);
+    assert_eq!(&*session.public_key, public_key);
+    assert_eq!(&*session.key_signature, key_signature);
+
+    // Expired sessions disconnect the client when configured to.
+    app.insert_resource(ChatSessionSettings {
+        expired_key: ExpiredKeyPolicy::Disconnect,
+    });
+
+    helper.send(&PlayerSessionC2s {
+        session_id,
+        expires_at: now - 60_000,
+        public_key_data: Bounded(&public_key),
+        key_signature: Bounded(&key_signature),
+    });
+
+    app.update();
+
+    assert!(app.world.get::<Client>(client).is_none());
+}
+
+#[test]
+fn client_unsigned_chat() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+
+    let unsigned = ChatMessageC2s {
+        message: Bounded("hello"),
+        timestamp: 0,
+        salt: 0,
+        signature: None,
+        message_count: 0.into(),
+        acknowledgement: Default::default(),
+    };
+
+    // Secure chat is not enforced by default.
+    helper.send(&unsigned);
+
+    app.update();
+
+    let events = app.world.resource::<Events<ChatMessageEvent>>();
+    let messages: Vec<_> = events
+        .iter_current_update_events()
+        .map(|e| (e.client, &*e.message, e.signed))
+        .collect();
+    assert_eq!(messages, [(client, "hello", false)]);
+
+    // Signatures are only valid with the key of the client's chat session.
+    let bad_signature = ChatMessageC2s {
+        signature: Some(&[7; 256]),
+        ..unsigned.clone()
+    };
+
+    helper.send(&bad_signature);
+
+    app.update();
+
+    let events = app.world.resource::<Events<ChatMessageEvent>>();
+    let signed: Vec<_> = events
+        .iter_current_update_events()
+        .map(|e| e.signed)
+        .collect();
+    assert_eq!(signed, [false]);
+
+    app.insert_resource(ChatSettings {
+        enforce_secure_chat: true,
+    });
+
+    helper.send(&bad_signature);
+
+    app.update();
+
+    assert_eq!(
+        app.world
+            .resource::<Events<ChatMessageEvent>>()
+            .iter_current_update_events()
+            .count(),
+        0
+    );
+    assert!(app.world.get::<Client>(client).is_none());
+}
+
+#[test]
+fn client_chat_message_acknowledgments() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+
+    for i in 0..10 {
+        helper.send(&ChatMessageC2s {
+            message: Bounded("spam"),
+            timestamp: i,
+            salt: 0,
+            signature: None,
+            message_count: 1.into(),
+            acknowledgement: Default::default(),
+        });
+    }
+
+    helper.send(&MessageAcknowledgmentC2s {
+        message_count: 5.into(),
+    });
+
+    app.update();
+
+    assert_eq!(
+        app.world
+            .resource::<Events<ChatMessageEvent>>()
+            .iter_current_update_events()
+            .count(),
+        10
+    );
+    assert_eq!(
+        app.world
+            .get::<MessageAcknowledgments>(client)
+            .unwrap()
+            .acknowledged(),
+        15
+    );
+    assert!(app.world.get::<Client>(client).is_some());
+}
+
+#[test]
+fn client_interaction_entity() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let button = app
+        .world
+        .spawn(InteractionEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            interaction_width: interaction::Width(2.0),
+            interaction_height: interaction::Height(0.5),
+            interaction_response: interaction::Response(true),
+            ..Default::default()
+        })
+        .id();
+
+    app.update();
+
+    let button_id = app.world.get::<EntityId>(button).unwrap().get();
+
+    /// Splits tracked data into `(index, value)` pairs. Only handles the
+    /// float and boolean types used by interaction entities.
+    fn tracked_values(mut data: &[u8]) -> Vec<(u8, Vec<u8>)> {
+        let mut values = vec![];
+
+        while let [index, type_id, rest @ ..] = data {
+            let len = match type_id {
+                3 => 4,
+                8 => 1,
+                _ => panic!("unexpected tracked data type {type_id}"),
+            };
+
+            values.push((*index, rest[..len].to_vec()));
+            data = &rest[len..];
+        }
+
+        assert_eq!(data, [0xff]);
+
+        values
+    }
+
+    let frames = helper.collect_received();
+    let pkt = frames.first::<EntityTrackerUpdateS2c>();
+    assert_eq!(pkt.entity_id.0, button_id);
+
+    let mut values = tracked_values(pkt.tracked_values.0);
+    values.sort();
+    assert_eq!(
+        values,
+        [
+            (8, 2.0_f32.to_be_bytes().to_vec()),
+            (9, 0.5_f32.to_be_bytes().to_vec()),
+            (10, vec![1]),
+        ]
+    );
+
+    app.world.get_mut::<interaction::Width>(button).unwrap().0 = 3.0;
+
+    app.update();
+
+    let frames = helper.collect_received();
+    let pkt = frames.first::<EntityTrackerUpdateS2c>();
+    assert_eq!(
+        tracked_values(pkt.tracked_values.0),
+        [(8, 3.0_f32.to_be_bytes().to_vec())]
+    );
+
+    helper.send(&PlayerInteractEntityC2s {
+        entity_id: button_id.into(),
+        interact: EntityInteraction::Interact(Hand::Main),
+        sneaking: false,
+    });
+
+    app.update();
+
+    let events: Vec<_> = app
+        .world
+        .resource::<Events<InteractEntityEvent>>()
+        .iter_current_update_events()
+        .map(|e| (e.client, e.entity, e.interact))
+        .collect();
+    assert_eq!(
+        events,
+        [(client, button, EntityInteraction::Interact(Hand::Main))]
+    );
+}
+
+#[test]
+fn client_locked_vehicle_steering() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+
+    for z in -10..10 {
+        for x in -10..10 {
+            layer.insert_chunk(ChunkPos::new(x, z), UnloadedChunk::new());
+        }
+    }
+
+    let cart = app
+        .world
+        .spawn(MinecartEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            ..Default::default()
+        })
+        .insert((Passengers::new([client]), LockedSteering::default()))
+        .id();
+
+    app.update();
+
+    // The rider knows its own entity by the ID 0.
+    let frames = helper.collect_received();
+    let pkt = frames.first::<EntityPassengersSetS2c>();
+    assert_eq!(
+        pkt.entity_id.0,
+        app.world.get::<EntityId>(cart).unwrap().get()
+    );
+    assert_eq!(pkt.passengers, [VarInt(0)].as_slice());
+
+    helper.send(&TeleportConfirmC2s {
+        teleport_id: 0.into(),
+    });
+
+    let steer = |flags| PlayerInputC2s {
+        sideways: 0.0,
+        forward: 0.98,
+        flags,
+    };
+
+    let steer_events = |app: &App| -> Vec<SteerVehicleEvent> {
+        app.world
+            .resource::<Events<SteerVehicleEvent>>()
+            .iter_current_update_events()
+            .copied()
+            .collect()
+    };
+
+    let start = app.world.get::<Position>(client).unwrap().0;
+    let vehicle_move = VehicleMoveC2s {
+        position: start + DVec3::new(5.0, 0.0, 0.0),
+        yaw: 0.0,
+        pitch: 0.0,
+    };
+
+    // Steering a locked vehicle does nothing.
+    helper.send(&steer(PlayerInputFlags::new()));
+    helper.send(&vehicle_move);
+    helper.send(&steer(PlayerInputFlags::new().with_unmount(true)));
+
+    app.update();
+
+    assert!(steer_events(&app).is_empty());
+    assert_eq!(app.world.get::<Position>(client).unwrap().0, start);
+
+    // The rider is sent the vehicle's position back.
+    let frames = helper.collect_received();
+    let pkt = frames.first::<VehicleMoveS2c>();
+    assert_eq!(pkt.position, app.world.get::<Position>(cart).unwrap().0);
+
+    // Dismounting can be allowed separately from steering.
+    app.world
+        .get_mut::<LockedSteering>(cart)
+        .unwrap()
+        .allow_dismount = true;
+
+    helper.send(&steer(PlayerInputFlags::new().with_unmount(true)));
+
+    app.update();
+
+    assert_eq!(
+        steer_events(&app),
+        [SteerVehicleEvent {
+            client,
+            sideways: 0.0,
+            forward: 0.0,
+            jump: false,
+            unmount: true,
+        }]
+    );
+
+    // Unlocked vehicles are steered by their riders again.
+    app.world.entity_mut(cart).remove::<LockedSteering>();
+
+    helper.send(&steer(PlayerInputFlags::new()));
+    helper.send(&vehicle_move);
+
+    app.update();
+
+    assert_eq!(steer_events(&app).len(), 1);
+    assert_eq!(
+        app.world.get::<Position>(client).unwrap().0,
+        vehicle_move.position
+    );
+}
+
+#[test]
+fn client_localized_message() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    helper.send(&ClientSettingsC2s {
+        locale: "de_de",
+        view_distance: 8,
+        chat_mode: ChatMode::Enabled,
+        chat_colors: true,
+        displayed_skin_parts: DisplayedSkinParts::new(),
+        main_arm: MainArm::Right,
+        enable_text_filtering: false,
+        allow_server_listings: true,
+    });
+
+    app.update();
+    helper.clear_received();
+
+    let welcome = LocalizedText::new("Welcome!").with("de_DE", "Willkommen!");
+
+    let locale = app
+        .world
+        .get::<ClientSettings>(client)
+        .unwrap()
+        .locale
+        .clone();
+    let mut client = app.world.get_mut::<Client>(client).unwrap();
+    client.send_chat_message(welcome.get(&locale).clone());
+
+    app.update();
+
+    let frames = helper.collect_received();
+    let pkt = frames.first::<GameMessageS2c>();
+    assert_eq!(*pkt.chat, Text::from("Willkommen!"));
+
+    // Unknown locales fall back to the default variant.
+    assert_eq!(welcome.get("ja_jp"), &Text::from("Welcome!"));
+}
+
+#[test]
+fn client_take_damage() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    let player_attack = app
+        .world
+        .resource::<DamageTypeRegistry>()
+        .index_of(ident!("player_attack"))
+        .unwrap();
+
+    assert_eq!(
+        app.world.resource::<DamageTypeRegistry>()[player_attack].message_id,
+        "player"
+    );
+
+    let attacker = DVec3::new(3.0, 64.0, -2.0);
+
+    let mut client_mut = app.world.get_mut::<Client>(client).unwrap();
+    client_mut.take_damage(Some(attacker), player_attack);
+    client_mut.take_damage(None, player_attack);
+
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<EntityDamageS2c>(2);
+
+    let mut damage = frames
+        .0
+        .iter()
+        .filter(|f| f.id == EntityDamageS2c::ID)
+        .map(|f| f.decode::<EntityDamageS2c>().unwrap());
+
+    let with_source = damage.next().unwrap();
+    assert_eq!(with_source.entity_id.0, 0);
+    assert_eq!(
+        with_source.source_type_id.0 as usize,
+        player_attack.to_index()
+    );
+    assert_eq!(with_source.source_pos, Some(attacker));
+
+    let without_source = damage.next().unwrap();
+    assert_eq!(without_source.source_pos, None);
+}
+
+#[test]
+fn client_play_particle() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    let dust = Particle::Dust {
+        rgb: [1.0, 0.0, 0.0].into(),
+        scale: 1.5,
+    };
+
+    app.world.get_mut::<Client>(client).unwrap().play_particle(
+        &dust,
+        false,
+        [0.0, 70.0, 0.0],
+        [0.0; 3],
+        0.0,
+        -5,
+    );
+
+    app.update();
+
+    // Negative counts are clamped to zero.
+    let frames = helper.collect_received();
+    frames.assert_count::<ParticleS2c>(1);
+
+    let pkt = frames.first::<ParticleS2c>();
+    assert_eq!(*pkt.particle, dust);
+    assert!(!pkt.long_distance);
+    assert_eq!(pkt.count, 0);
+}
+
+#[test]
+fn client_show_title() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    let mut client_mut = app.world.get_mut::<Client>(client).unwrap();
+    client_mut.show_title("Round 1", "Fight!", 10, 40, -1);
+    client_mut.show_title("Round 2", "", -1, -1, -1);
+
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<TitleFadeS2c>(1);
+    frames.assert_count::<SubtitleS2c>(2);
+    frames.assert_count::<TitleS2c>(2);
+
+    let ids: Vec<_> = frames.0.iter().map(|f| f.id).collect();
+    assert_eq!(
+        ids,
+        [
+            TitleFadeS2c::ID,
+            SubtitleS2c::ID,
+            TitleS2c::ID,
+            SubtitleS2c::ID,
+            TitleS2c::ID
+        ]
+    );
+
+    let fade = frames.first::<TitleFadeS2c>();
+    assert_eq!((fade.fade_in, fade.stay, fade.fade_out), (10, 40, -1));
+    assert_eq!(
+        frames.first::<TitleS2c>().title_text.as_ref(),
+        &Text::from("Round 1")
+    );
+}
+
+#[test]
+fn client_action_bar() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    app.world
+        .entity_mut(client)
+        .insert(ActionBar::new("Score: 1"));
+
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<OverlayMessageS2c>(1);
+    assert_eq!(
+        frames.first::<OverlayMessageS2c>().action_bar_text.as_ref(),
+        &Text::from("Score: 1")
+    );
+
+    // Setting the same text doesn't send it again right away.
+    for _ in 0..10 {
+        let text = Text::from("Score: 1");
+        app.world.get_mut::<ActionBar>(client).unwrap().set(&text);
+        app.update();
+    }
+
+    helper
+        .collect_received()
+        .assert_count::<OverlayMessageS2c>(0);
+
+    // Until it's about to fade out.
+    for _ in 0..30 {
+        app.update();
+    }
+
+    helper
+        .collect_received()
+        .assert_count::<OverlayMessageS2c>(1);
+
+    app.world.get_mut::<ActionBar>(client).unwrap().clear();
+
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<OverlayMessageS2c>(1);
+    assert_eq!(
+        frames.first::<OverlayMessageS2c>().action_bar_text.as_ref(),
+        &Text::default()
+    );
+}
+
+#[test]
+fn client_unlock_recipes() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    let mut book = app.world.get_mut::<RecipeBook>(client).unwrap();
+    book.unlock_recipes([ident!("furnace")]);
+
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<UnlockRecipesS2c>(1);
+
+    let pkt = frames.first::<UnlockRecipesS2c>();
+    assert_eq!(pkt.action, UpdateRecipeBookAction::Add);
+    assert_eq!(pkt.recipe_ids, [ident!("furnace")]);
+    assert!(!pkt.crafting_recipe_book_open);
+
+    // Unlocking again does nothing, and locking sends the removal.
+    let mut book = app.world.get_mut::<RecipeBook>(client).unwrap();
+    book.unlock_recipes([ident!("furnace")]);
+    book.lock_recipes([ident!("furnace"), ident!("chest")]);
+
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<UnlockRecipesS2c>(1);
+
+    let pkt = frames.first::<UnlockRecipesS2c>();
+    assert_eq!(pkt.action, UpdateRecipeBookAction::Remove);
+    assert_eq!(pkt.recipe_ids, [ident!("furnace")]);
+
+    let book = app.world.get::<RecipeBook>(client).unwrap();
+    assert!(!book.is_unlocked(ident!("furnace")));
+}
+
+#[test]
+fn client_stop_sound() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    let mut client_mut = app.world.get_mut::<Client>(client).unwrap();
+    client_mut.stop_sound(Some(SoundCategory::Music), None);
+    client_mut.stop_sound(None, Some(Sound::MusicGame.to_ident()));
+    client_mut.stop_sound(None, None);
+    client_mut.stop_sound(
+        Some(SoundCategory::Record),
+        Some(ident!("my_pack:music.boss")),
+    );
+
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<StopSoundS2c>(4);
+
+    let mut stops = frames
+        .0
+        .iter()
+        .filter(|f| f.id == StopSoundS2c::ID)
+        .map(|f| f.decode::<StopSoundS2c>().unwrap());
+
+    let by_category = stops.next().unwrap();
+    assert_eq!(by_category.source, Some(SoundCategory::Music));
+    assert_eq!(by_category.sound, None);
+
+    let by_sound = stops.next().unwrap();
+    assert_eq!(by_sound.source, None);
+    assert_eq!(by_sound.sound.unwrap().as_str(), "minecraft:music.game");
+
+    let all = stops.next().unwrap();
+    assert_eq!(all.source, None);
+    assert_eq!(all.sound, None);
+
+    let custom = stops.next().unwrap();
+    assert_eq!(custom.source, Some(SoundCategory::Record));
+    assert_eq!(custom.sound.unwrap().as_str(), "my_pack:music.boss");
+}
+
+#[test]
+fn client_reduced_debug_info_per_layer() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: mystery,
+    } = ScenarioSingleClient::new();
+
+    app.world.entity_mut(mystery).insert(ReducedDebugInfo(true));
+
+    let lobby = ChunkLayer::new(
+        ident!("overworld"),
+        app.world.resource::<DimensionTypeRegistry>(),
+        app.world.resource::<BiomeRegistry>(),
+        app.world.resource::<Server>(),
+    );
+    let lobby_entities = EntityLayer::new(app.world.resource::<Server>());
+    let lobby = app
+        .world
+        .spawn((lobby, lobby_entities, ReducedDebugInfo(false)))
+        .id();
+
+    let arena = ChunkLayer::new(
+        ident!("overworld"),
+        app.world.resource::<DimensionTypeRegistry>(),
+        app.world.resource::<BiomeRegistry>(),
+        app.world.resource::<Server>(),
+    );
+    let arena_entities = EntityLayer::new(app.world.resource::<Server>());
+    let arena = app.world.spawn((arena, arena_entities)).id();
+
+    app.update();
+
+    // Spawning into the mystery world hides debug info from the start.
+    {
+        let frames = helper.collect_received();
+        assert!(frames.first::<GameJoinS2c>().reduced_debug_info);
+
+        let sent_status = frames
+            .0
+            .iter()
+            .filter(|f| f.id == EntityStatusS2c::ID)
+            .any(|f| {
+                let status = f.decode::<EntityStatusS2c>().unwrap().entity_status;
+                status == 22 || status == 23
+            });
+        assert!(!sent_status);
+    }
+
+    let mut switch_to = |app: &mut App, layer| {
+        let mut client_mut = app.world.entity_mut(client);
+        client_mut.get_mut::<VisibleChunkLayer>().unwrap().0 = layer;
+        client_mut.get_mut::<EntityLayerId>().unwrap().0 = layer;
+
+        app.update();
+
+        let frames = helper.collect_received();
+        frames.assert_count::<EntityStatusS2c>(1);
+
+        let pkt = frames.first::<EntityStatusS2c>();
+        assert_eq!(pkt.entity_id, 0);
+        pkt.entity_status
+    };
+
+    // Enable and disable reduced debug info.
+    assert_eq!(switch_to(&mut app, lobby), 23);
+    assert!(!app.world.get::<ReducedDebugInfo>(client).unwrap().0);
+
+    assert_eq!(switch_to(&mut app, mystery), 22);
+    assert!(app.world.get::<ReducedDebugInfo>(client).unwrap().0);
+
+    // Leaving for a layer without the setting goes back to the default.
+    assert_eq!(switch_to(&mut app, arena), 23);
+    assert!(!app.world.get::<ReducedDebugInfo>(client).unwrap().0);
+}
+
+#[test]
+fn client_change_dimension() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: overworld,
+    } = ScenarioSingleClient::new();
+
+    let mut nether = ChunkLayer::new(
+        ident!("the_nether"),
+        app.world.resource::<DimensionTypeRegistry>(),
+        app.world.resource::<BiomeRegistry>(),
+        app.world.resource::<Server>(),
+    );
+
+    for z in -3..3 {
+        for x in -3..3 {
+            nether.insert_chunk(ChunkPos::new(x, z), UnloadedChunk::new());
+        }
+    }
+
+    let nether_entities = EntityLayer::new(app.world.resource::<Server>());
+    let nether = app.world.spawn((nether, nether_entities)).id();
+
+    app.update();
+    helper.clear_received();
+
+    // Only the chunk layer is switched. The client must still be sent to the
+    // dimension of that layer.
+    app.world.get_mut::<VisibleChunkLayer>(client).unwrap().0 = nether;
+
+    app.update();
+
+    {
+        let frames = helper.collect_received();
+        frames.assert_count::<PlayerRespawnS2c>(1);
+
+        let pkt = frames.first::<PlayerRespawnS2c>();
+        assert_eq!(pkt.dimension_type_name, ident!("the_nether"));
+        assert_eq!(pkt.dimension_name, ident!("the_nether"));
+        assert!(frames.0.iter().any(|f| f.id == ChunkDataS2c::ID));
+    }
+
+    // Going back works the same way, without reconnecting.
+    app.world.get_mut::<VisibleChunkLayer>(client).unwrap().0 = overworld;
+
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<GameJoinS2c>(0);
+    frames.assert_count::<PlayerRespawnS2c>(1);
+    assert_eq!(
+        frames.first::<PlayerRespawnS2c>().dimension_type_name,
+        ident!("overworld")
+    );
+}
+
+#[test]
+fn client_world_time_per_layer() {
+    let ScenarioSingleClient {
+        mut app,
+        client: survivor,
+        helper: mut survivor_helper,
+        layer: survival,
+    } = ScenarioSingleClient::new();
+
+    app.world.entity_mut(survival).insert(WorldTime::default());
+
+    let lobby = ChunkLayer::new(
+        ident!("overworld"),
+        app.world.resource::<DimensionTypeRegistry>(),
+        app.world.resource::<BiomeRegistry>(),
+        app.world.resource::<Server>(),
+    );
+    let lobby_entities = EntityLayer::new(app.world.resource::<Server>());
+    let lobby = app
+        .world
+        .spawn((lobby, lobby_entities, WorldTime::fixed(6000)))
+        .id();
+
+    let (mut bundle, mut lobby_helper) = create_mock_client("lobbyist");
+    bundle.player.layer.0 = lobby;
+    bundle.visible_chunk_layer.0 = lobby;
+    bundle.visible_entity_layers.0.insert(lobby);
+    app.world.spawn(bundle);
+
+    let last_time = |frames: &crate::testing::PacketFrames| {
+        let pkt = frames
+            .0
+            .iter()
+            .rev()
+            .find_map(|f| f.decode::<WorldTimeUpdateS2c>().ok())
+            .expect("no world time update");
+        (pkt.world_age, pkt.time_of_day)
+    };
+
+    for _ in 0..30 {
+        app.update();
+    }
+
+    // The survival world kept cycling while the lobby stayed at noon.
+    assert_eq!(last_time(&survivor_helper.collect_received()), (20, 20));
+    assert_eq!(last_time(&lobby_helper.collect_received()), (20, -6000));
+
+    assert_eq!(
+        app.world.get::<WorldTime>(survival).unwrap().time_of_day,
+        30
+    );
+    assert_eq!(app.world.get::<WorldTime>(lobby).unwrap().time_of_day, 6000);
+
+    // Switching worlds sends the time of the new world right away.
+    let mut survivor_mut = app.world.entity_mut(survivor);
+    survivor_mut.get_mut::<VisibleChunkLayer>().unwrap().0 = lobby;
+    survivor_mut.get_mut::<EntityLayerId>().unwrap().0 = lobby;
+
+    app.update();
+
+    let frames = survivor_helper.collect_received();
+    frames.assert_count::<WorldTimeUpdateS2c>(1);
+    assert_eq!(last_time(&frames), (31, -6000));
+}
+
+#[test]
+fn client_command_events() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+
+    let mut send = |action, jump_boost| {
+        helper.send(&ClientCommandC2s {
+            entity_id: VarInt(0),
+            action,
+            jump_boost: VarInt(jump_boost),
+        })
+    };
+
+    send(ClientCommand::StartSneaking, 0);
+    send(ClientCommand::StartJumpWithHorse, 250);
+    send(ClientCommand::OpenHorseInventory, 0);
+
+    app.update();
+
+    let sneaks: Vec<_> = app
+        .world
+        .resource::<Events<SneakEvent>>()
+        .iter_current_update_events()
+        .copied()
+        .collect();
+    assert_eq!(
+        sneaks,
+        [SneakEvent {
+            client,
+            state: SneakState::Start
+        }]
+    );
+
+    let jump = app
+        .world
+        .resource::<Events<JumpWithHorseEvent>>()
+        .iter_current_update_events()
+        .next()
+        .copied();
+    assert_eq!(
+        jump.map(|e| e.state),
+        Some(JumpWithHorseState::Start { power: 100 })
+    );
+
+    let opened = app
+        .world
+        .resource::<Events<OpenHorseInventoryEvent>>()
+        .iter_current_update_events()
+        .count();
+    assert_eq!(opened, 1);
+
+    // Sneaking is reflected on the player entity.
+    assert_eq!(
+        app.world.get::<Pose>(client).unwrap().0,
+        crate::entity::Pose::Sneaking
+    );
+    assert!(app.world.get::<Flags>(client).unwrap().sneaking());
+}
+
+#[test]
+fn client_player_loaded_event() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer,
+    } = ScenarioSingleClient::new();
+
+    // Hold the initial teleport back until the chunk under the client is
+    // loaded.
+    app.insert_resource(InitialTeleportSettings {
+        min_chunk_radius: 1,
+        timeout_ticks: 100,
+    });
+
+    let loaded_events = |app: &App| {
+        app.world
+            .resource::<Events<PlayerLoadedEvent>>()
+            .iter_current_update_events()
+            .copied()
+            .collect::<Vec<_>>()
+    };
+
+    app.update();
+    helper.confirm_initial_pending_teleports();
+    app.update();
+
+    assert!(loaded_events(&app).is_empty());
+
+    let mut chunk_layer = app.world.get_mut::<ChunkLayer>(layer).unwrap();
+    for z in -1..=1 {
+        for x in -1..=1 {
+            chunk_layer.insert_chunk([x, z], UnloadedChunk::new());
+        }
+    }
+
+    app.update();
+    helper.confirm_initial_pending_teleports();
+    app.update();
+
+    assert_eq!(loaded_events(&app), [PlayerLoadedEvent { client }]);
+
+    // Later teleports don't fire the event again.
+    app.world
+        .get_mut::<Position>(client)
+        .unwrap()
+        .set([5.0, 70.0, 5.0]);
+    app.update();
+    helper
+        .collect_received()
+        .assert_count::<PlayerPositionLookS2c>(1);
+    helper.send(&TeleportConfirmC2s {
+        teleport_id: 1.into(),
+    });
+    app.update();
+
+    assert!(loaded_events(&app).is_empty());
+    assert!(app.world.get::<Client>(client).is_some());
+}
+
+#[test]
+fn client_relative_teleport() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.world.entity_mut(client).insert(Look {
+        yaw: 10.0,
+        pitch: 20.0,
+    });
+
+    app.update();
+    helper.confirm_initial_pending_teleports();
+    app.update();
+    helper.clear_received();
+
+    let pos = app.world.get::<Position>(client).unwrap().0;
+
+    RelativeTeleport {
+        client,
+        position: pos,
+        yaw: 90.0,
+        pitch: 20.0,
+        flags: PlayerPositionLookFlags::new().with_y_rot(true),
+    }
+    .apply(&mut app.world);
+
+    let look = *app.world.get::<Look>(client).unwrap();
+    assert_eq!(
+        look,
+        Look {
+            yaw: 100.0,
+            pitch: 20.0
+        }
+    );
+    assert_eq!(app.world.get::<Position>(client).unwrap().0, pos);
+
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<PlayerPositionLookS2c>(1);
+
+    let pkt = frames.first::<PlayerPositionLookS2c>();
+    assert_eq!(u8::from(pkt.flags), 0b01000);
+    assert_eq!(pkt.yaw, 90.0);
+    assert_eq!(pkt.teleport_id.0, 1);
+
+    helper.send(&TeleportConfirmC2s {
+        teleport_id: 1.into(),
+    });
+    app.update();
+
+    // The server's position and look already match, so nothing else is sent.
+    helper
+        .collect_received()
+        .assert_count::<PlayerPositionLookS2c>(0);
+    assert!(app.world.get::<Client>(client).is_some());
+}
+
+#[test]
+fn client_default_game_mode_and_difficulty() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.insert_resource(DefaultGameMode(GameMode::Adventure));
+    app.insert_resource(DifficultySettings {
+        difficulty: Difficulty::Hard,
+        locked: false,
+    });
+
+    app.update();
+
+    assert_eq!(
+        app.world.get::<GameMode>(client),
+        Some(&GameMode::Adventure)
+    );
+
+    let frames = helper.collect_received();
+    assert_eq!(frames.first::<GameJoinS2c>().game_mode, GameMode::Adventure);
+    frames.assert_count::<DifficultyS2c>(1);
+    let pkt = frames.first::<DifficultyS2c>();
+    assert_eq!(pkt.difficulty, Difficulty::Hard);
+    assert!(!pkt.locked);
+
+    // Nothing is sent while the difficulty stays the same.
+    app.update();
+    helper.collect_received().assert_count::<DifficultyS2c>(0);
+
+    app.world.resource_mut::<DifficultySettings>().difficulty = Difficulty::Peaceful;
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<DifficultyS2c>(1);
+    assert_eq!(
+        frames.first::<DifficultyS2c>().difficulty,
+        Difficulty::Peaceful
+    );
+}
+
+#[test]
+fn client_play_custom_sound() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    let entity_id = *app.world.get::<EntityId>(client).unwrap();
+    let mut client_comp = app.world.get_mut::<Client>(client).unwrap();
+
+    client_comp.play_sound_id(
+        SoundId::Direct {
+            id: ident!("my_pack:boom").into(),
+            range: None,
+        },
+        SoundCategory::Block,
+        [1.0, 2.0, 3.0],
+        0.5,
+        1.5,
+        42,
+    );
+    client_comp.play_sound_id_from_entity(
+        entity_id,
+        Sound::EntityPlayerLevelup.into(),
+        SoundCategory::Player,
+        1.0,
+        1.0,
+        -3,
+    );
+
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<PlaySoundS2c>(1);
+    frames.assert_count::<PlaySoundFromEntityS2c>(1);
+
+    let pkt = frames.first::<PlaySoundS2c>();
+    assert_eq!(
+        pkt.id,
+        SoundId::Direct {
+            id: ident!("my_pack:boom").into(),
+            range: None,
+        }
+    );
+    assert_eq!(pkt.category, SoundCategory::Block);
+    assert_eq!(pkt.position.to_array(), [8, 16, 24]);
+    assert_eq!(pkt.seed, 42);
+
+    let pkt = frames.first::<PlaySoundFromEntityS2c>();
+    assert_eq!(pkt.entity_id.0, entity_id.get());
+    assert_eq!(pkt.category, SoundCategory::Player);
+    assert_eq!(pkt.seed, -3);
+}
@@ -2,16 +2,20 @@ use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 
 use crate::inventory::{
-    convert_to_player_slot_id, ClickMode, ClientInventoryState, CursorItem, DropItemStackEvent,
-    HeldItem, Inventory, InventoryKind, OpenInventory, SlotChange,
+    convert_to_player_slot_id, AttackCooldown, ClickMode, ClientInventoryState, CursorItem,
+    DropItemStackEvent, HeldItem, InitialInventory, Inventory, InventoryKind, OpenInventory,
+    SlotChange,
 };
+use crate::nbt::compound;
+use crate::protocol::packets::play::open_screen_s2c::WindowType;
 use crate::protocol::packets::play::{
-    ClickSlotC2s, CloseScreenS2c, CreativeInventoryActionC2s, InventoryS2c, OpenScreenS2c,
-    ScreenHandlerSlotUpdateS2c, UpdateSelectedSlotC2s,
+    ClickSlotC2s, ClientStatusC2s, CloseScreenS2c, CreativeInventoryActionC2s, HandSwingC2s,
+    InventoryS2c, OpenScreenS2c, ScreenHandlerSlotUpdateS2c, UpdateSelectedSlotC2s,
+    UpdateSelectedSlotS2c,
 };
 use crate::protocol::VarInt;
 use crate::testing::ScenarioSingleClient;
-use crate::{GameMode, ItemKind, ItemStack};
+use crate::{GameMode, Hand, ItemKind, ItemStack};
 
 #[test]
 fn test_should_open_inventory() {
@@ -256,6 +260,86 @@ fn test_should_sync_entire_player_inventory() {
     sent_packets.assert_count::<InventoryS2c>(1);
 }
 
+fn initial_kit() -> Inventory {
+    let mut kit = Inventory::new(InventoryKind::Player);
+    kit.set_slot(36, ItemStack::new(ItemKind::Compass, 1, None));
+    kit.set_slot(44, ItemStack::new(ItemKind::Clock, 1, None));
+    kit
+}
+
+#[test]
+fn test_should_give_initial_inventory_on_spawn() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.insert_resource(InitialInventory {
+        inventory: Some(initial_kit()),
+        reapply_on_respawn: false,
+    });
+
+    app.update();
+
+    let sent_packets = helper.collect_received();
+    sent_packets.assert_count::<InventoryS2c>(1);
+
+    let pkt = sent_packets.first::<InventoryS2c>();
+    assert_eq!(pkt.window_id, 0);
+    assert_eq!(
+        pkt.slots[36],
+        Some(ItemStack::new(ItemKind::Compass, 1, None))
+    );
+    assert_eq!(
+        pkt.slots[44],
+        Some(ItemStack::new(ItemKind::Clock, 1, None))
+    );
+    assert_eq!(pkt.slots.iter().flatten().count(), 2);
+
+    let inventory = app.world.get::<Inventory>(client).unwrap();
+    assert_eq!(inventory.slot(36), initial_kit().slot(36));
+}
+
+#[test]
+fn test_should_reapply_initial_inventory_on_respawn() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.insert_resource(InitialInventory {
+        inventory: Some(initial_kit()),
+        reapply_on_respawn: true,
+    });
+
+    app.update();
+
+    let mut inventory = app.world.get_mut::<Inventory>(client).unwrap();
+    inventory.set_slot(36, None);
+    inventory.set_slot(0, ItemStack::new(ItemKind::Diamond, 3, None));
+
+    app.update();
+    helper.clear_received();
+
+    helper.send(&ClientStatusC2s::PerformRespawn);
+
+    app.update();
+
+    let sent_packets = helper.collect_received();
+    sent_packets.assert_count::<InventoryS2c>(1);
+
+    let inventory = app.world.get::<Inventory>(client).unwrap();
+    assert_eq!(
+        inventory.slot(36),
+        Some(&ItemStack::new(ItemKind::Compass, 1, None))
+    );
+    assert_eq!(inventory.slot(0), None);
+}
+
 fn set_up_open_inventory(app: &mut App, client_ent: Entity) -> Entity {
     let inventory = Inventory::new(InventoryKind::Generic9x3);
     let inventory_ent = app.world.spawn(inventory).id();
@@ -519,6 +603,50 @@ fn test_window_id_increments() {
     assert_eq!(inv_state.window_id(), 3);
 }
 
+#[test]
+fn test_window_id_wraps_around() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    let inventory = Inventory::new(InventoryKind::Hopper);
+    let inventory_ent = app.world.spawn(inventory).id();
+
+    app.update();
+    helper.clear_received();
+
+    let mut window_ids = vec![];
+
+    for _ in 0..101 {
+        app.world
+            .entity_mut(client)
+            .insert(OpenInventory::new(inventory_ent));
+
+        app.update();
+
+        let sent_packets = helper.collect_received();
+        let open_screen = sent_packets.first::<OpenScreenS2c>();
+        assert_eq!(open_screen.window_type, WindowType::Hopper);
+        assert_eq!(
+            sent_packets.first::<InventoryS2c>().window_id as i32,
+            open_screen.window_id.0
+        );
+        window_ids.push(open_screen.window_id.0);
+
+        app.world.entity_mut(client).remove::<OpenInventory>();
+
+        app.update();
+    }
+
+    // Window IDs cycle through 1..=100 and never use 0, which is the player's
+    // own inventory.
+    assert_eq!(window_ids[..100], (1..=100).collect::<Vec<_>>());
+    assert_eq!(window_ids[100], 1);
+}
+
 #[test]
 fn test_should_handle_set_held_item() {
     let ScenarioSingleClient {
@@ -545,6 +673,60 @@ fn test_should_handle_set_held_item() {
     assert_eq!(held.slot(), 40);
 }
 
+#[test]
+fn should_send_held_item_set_by_server() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    helper.clear_received();
+
+    app.world
+        .get_mut::<HeldItem>(client)
+        .unwrap()
+        .set_hotbar_slot(7);
+
+    app.update();
+
+    let sent_packets = helper.collect_received();
+    sent_packets.assert_count::<UpdateSelectedSlotS2c>(1);
+    assert_eq!(sent_packets.first::<UpdateSelectedSlotS2c>().slot, 7);
+
+    let held = app.world.get::<HeldItem>(client).unwrap();
+    assert_eq!(held.slot(), 43);
+    assert_eq!(held.hotbar_slot(), 7);
+
+    // Slots selected by the client are not sent back.
+    helper.send(&UpdateSelectedSlotC2s { slot: 2 });
+
+    app.update();
+
+    helper
+        .collect_received()
+        .assert_count::<UpdateSelectedSlotS2c>(0);
+    assert_eq!(app.world.get::<HeldItem>(client).unwrap().hotbar_slot(), 2);
+}
+
+#[test]
+#[should_panic]
+fn should_not_set_held_item_outside_hotbar() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::new();
+
+    app.update();
+
+    app.world
+        .get_mut::<HeldItem>(client)
+        .unwrap()
+        .set_hotbar_slot(9);
+}
+
 #[test]
 fn should_not_increment_state_id_on_cursor_item_change() {
     let ScenarioSingleClient {
@@ -1159,3 +1341,64 @@ fn dragging_items() {
         );
     }
 }
+
+#[test]
+fn test_attack_cooldown() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: _,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+
+    app.world
+        .get_mut::<Inventory>(client)
+        .unwrap()
+        .set_slot(36, ItemStack::new(ItemKind::DiamondSword, 1, None));
+
+    helper.send(&HandSwingC2s { hand: Hand::Main });
+    app.update();
+
+    let cooldown = app.world.get::<AttackCooldown>(client).unwrap();
+    assert_eq!(cooldown.cooldown_ticks(), 12.5);
+    assert!(cooldown.progress() < 1.0);
+
+    for _ in 0..11 {
+        app.update();
+    }
+
+    assert!(!app
+        .world
+        .get::<AttackCooldown>(client)
+        .unwrap()
+        .is_fully_charged());
+
+    app.update();
+
+    let cooldown = app.world.get::<AttackCooldown>(client).unwrap();
+    assert!(cooldown.is_fully_charged());
+    assert_eq!(cooldown.progress(), 1.0);
+
+    // Damaging the sword keeps the charge.
+    app.world.get_mut::<Inventory>(client).unwrap().set_slot(
+        36,
+        ItemStack::new(ItemKind::DiamondSword, 1, Some(compound! { "Damage" => 1 })),
+    );
+    app.update();
+
+    assert!(app
+        .world
+        .get::<AttackCooldown>(client)
+        .unwrap()
+        .is_fully_charged());
+
+    // Switching to an empty hand starts the charge over.
+    helper.send(&UpdateSelectedSlotC2s { slot: 1 });
+    app.update();
+
+    let cooldown = app.world.get::<AttackCooldown>(client).unwrap();
+    assert_eq!(cooldown.ticks_since_reset(), 0);
+    assert_eq!(cooldown.cooldown_ticks(), 5.0);
+}
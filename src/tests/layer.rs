@@ -1,19 +1,53 @@
+use std::borrow::Cow;
 use std::collections::BTreeSet;
 
+use bevy_app::{App, Update};
+use bevy_ecs::prelude::{Events, IntoSystemConfigs, Query, ResMut, Resource, With};
+use bevy_ecs::system::SystemState;
 use bevy_ecs::world::EntityMut;
 
-use crate::client::{ViewDistance, VisibleEntityLayers};
-use crate::entity::cow::CowEntityBundle;
-use crate::entity::{EntityLayerId, Position};
-use crate::layer::chunk::UnloadedChunk;
+use crate::block::{BlockEntityKind, PropName, PropValue};
+use crate::client::{HiddenEntities, ViewDistance, VisibleEntityLayers};
+use crate::entity::block_display::{self, BlockDisplayEntityBundle};
+use crate::entity::cow::{CowEntity, CowEntityBundle};
+use crate::entity::entity::Flags;
+use crate::entity::hitbox::HitboxShape;
+use crate::entity::interaction::{self, InteractionEntityBundle};
+use crate::entity::text_display::{self, TextDisplayEntityBundle};
+use crate::entity::zombie::ZombieEntityBundle;
+use crate::entity::{
+    DisplayTransform, EntityId, EntityLayerId, EntityManager, Equipment, EquipmentSlot,
+    FaceMovement, FireTicks, HeadYaw, Look, OldPosition, Passengers, Position, TextAlignment,
+    Vehicle,
+};
+use crate::layer::chunk::{Block, Chunk, DyeColor, MissingChunks, UnloadedChunk};
+use crate::layer::entity::{
+    EntityLimitPolicy, EntityRejectedEvent, EntityUpdateSettings, EntityViewers,
+};
 use crate::layer::{ChunkLayer, EntityLayer};
+use crate::math::{DVec3, Vec3};
+use crate::nbt::{compound, List, Value};
+use crate::protocol::packets::play::particle_s2c::Particle;
+use crate::protocol::packets::play::team_s2c::{
+    CollisionRule, Mode, NameTagVisibility, TeamColor, TeamFlags,
+};
 use crate::protocol::packets::play::{
-    BlockEntityUpdateS2c, ChunkDataS2c, ChunkDeltaUpdateS2c, EntitiesDestroyS2c, EntitySpawnS2c,
-    MoveRelativeS2c, UnloadChunkS2c,
+    BlockBreakingProgressS2c, BlockEntityUpdateS2c, BundleSplitterS2c, ChunkBiomeDataS2c,
+    ChunkDataS2c, ChunkDeltaUpdateS2c, EntitiesDestroyS2c, EntityEquipmentUpdateS2c,
+    EntityPassengersSetS2c, EntityPositionS2c, EntitySpawnS2c, EntityTrackerUpdateS2c,
+    LightUpdateS2c, MoveRelativeS2c, ParticleS2c, PlaySoundFromEntityS2c, TeamS2c, UnloadChunkS2c,
+};
+use crate::protocol::sound::{Sound, SoundCategory, SoundId};
+use crate::protocol::{Packet, VarInt, WritePacket};
+use crate::registry::biome::BiomeId;
+use crate::registry::BiomeRegistry;
+use crate::team_visibility::{HiddenFromTeams, Team};
+use crate::testing::{create_mock_client, ScenarioSingleClient};
+use crate::text::{Color, IntoText};
+use crate::{
+    ident, BlockPos, BlockState, ChunkView, Despawned, Direction, Ident, ItemKind, ItemStack,
+    Server, UniqueId,
 };
-use crate::protocol::Packet;
-use crate::testing::ScenarioSingleClient;
-use crate::{BlockState, ChunkView, Despawned, Server};
 
 #[test]
 fn block_create_destroy() {
@@ -64,6 +98,456 @@ fn block_create_destroy() {
     }
 }
 
+#[test]
+fn block_entities_sent_with_chunk_and_on_change() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+    let min_y = layer.min_y();
+
+    let mut chunk = UnloadedChunk::with_height(layer.height());
+    chunk.set_block(
+        13,
+        5,
+        2,
+        Block::new(
+            BlockState::CHEST,
+            Some(compound! { "CustomName" => "Loot" }),
+        ),
+    );
+    layer.insert_chunk([-1, 0], chunk);
+
+    app.update();
+
+    let recvd = helper.collect_received();
+    let pkt = recvd.first::<ChunkDataS2c>();
+    let [be] = &pkt.block_entities[..] else {
+        panic!("expected a single block entity");
+    };
+    assert_eq!(be.packed_xz as u8, 0xd2);
+    assert_eq!(i32::from(be.y), min_y + 5);
+    assert_eq!(be.kind, BlockEntityKind::Chest);
+    assert_eq!(be.data.as_ref(), &compound! { "CustomName" => "Loot" });
+
+    // Editing the block entity of a viewed chunk sends only the change.
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+    let pos = BlockPos::new(-3, min_y + 5, 2);
+    layer
+        .block_entity_mut(pos)
+        .unwrap()
+        .insert("CustomName", "More loot");
+
+    app.update();
+
+    let recvd = helper.collect_received();
+    recvd.assert_count::<ChunkDataS2c>(0);
+    let pkt = recvd.first::<BlockEntityUpdateS2c>();
+    assert_eq!(pkt.position, pos);
+    assert_eq!(pkt.kind, BlockEntityKind::Chest);
+    assert_eq!(
+        pkt.data.as_ref(),
+        &compound! { "CustomName" => "More loot" }
+    );
+}
+
+#[test]
+fn chunk_layer_place_sign() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        helper: _,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+    layer.insert_chunk([0, 0], UnloadedChunk::new());
+
+    let lines = [
+        "Hello".color(Color::RED),
+        "".into(),
+        "".into(),
+        "World".into(),
+    ];
+
+    layer.place_sign(
+        [1, 2, 3],
+        Direction::North,
+        true,
+        lines.clone(),
+        DyeColor::LightBlue,
+        true,
+    );
+
+    let block = layer.block([1, 2, 3]).unwrap();
+    assert_eq!(
+        block.state,
+        BlockState::OAK_WALL_SIGN.set(PropName::Facing, PropValue::North)
+    );
+
+    let nbt = block.nbt.unwrap();
+    let Some(Value::Compound(front)) = nbt.get("front_text") else {
+        panic!("missing front text");
+    };
+    assert_eq!(
+        front.get("messages"),
+        Some(&Value::List(List::String(lines.map(String::from).into())))
+    );
+    assert_eq!(
+        front.get("color"),
+        Some(&Value::String("light_blue".into()))
+    );
+    assert_eq!(front.get("has_glowing_text"), Some(&Value::Byte(1)));
+
+    layer.place_sign(
+        [1, 2, 3],
+        Direction::East,
+        false,
+        Default::default(),
+        DyeColor::Black,
+        false,
+    );
+
+    assert_eq!(
+        layer.block([1, 2, 3]).unwrap().state,
+        BlockState::OAK_SIGN.set(PropName::Rotation, PropValue::_12)
+    );
+}
+
+#[test]
+#[should_panic]
+fn chunk_layer_place_sign_facing_up() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        helper: _,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+    layer.insert_chunk([0, 0], UnloadedChunk::new());
+
+    layer.place_sign(
+        [1, 2, 3],
+        Direction::Up,
+        true,
+        Default::default(),
+        DyeColor::Black,
+        false,
+    );
+}
+
+#[test]
+fn biome_change_sends_biome_data_only() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+    layer.insert_chunk([0, 0], UnloadedChunk::new());
+
+    app.update();
+    helper.clear_received();
+
+    let (biome, _, _) = app.world.resource::<BiomeRegistry>().iter().nth(1).unwrap();
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+    assert_eq!(layer.set_biome([1, 1, 1], biome), Some(BiomeId::default()));
+    layer.set_biome([9, 20, 9], biome);
+
+    app.update();
+
+    let recvd = helper.collect_received();
+    recvd.assert_count::<ChunkBiomeDataS2c>(1);
+    recvd.assert_count::<ChunkDataS2c>(0);
+
+    // Setting a biome to the one already there sends nothing.
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+    layer.set_biome([1, 1, 1], biome);
+
+    app.update();
+
+    helper
+        .collect_received()
+        .assert_count::<ChunkBiomeDataS2c>(0);
+}
+
+#[test]
+fn sky_light_sent_with_chunk_and_on_change() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+    let sect_count = layer.height() as usize / 16;
+
+    let mut chunk = UnloadedChunk::with_height(layer.height());
+    chunk.fill_sky_light_above(64);
+    layer.insert_chunk([0, 0], chunk);
+
+    app.update();
+
+    let recvd = helper.collect_received();
+    let pkt = recvd.first::<ChunkDataS2c>();
+    // The three bottom sections are dark, and so is the extra section below
+    // the chunk. The extra section above has full light.
+    assert_eq!(pkt.sky_light_arrays.len(), sect_count - 3 + 1);
+    assert_eq!(pkt.empty_sky_light_mask[0], 0b1111);
+    assert!(pkt
+        .sky_light_arrays
+        .last()
+        .unwrap()
+        .0
+        .iter()
+        .all(|&b| b == 0xff));
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+    let chunk = layer.chunk_mut([0, 0]).unwrap();
+    assert_eq!(chunk.set_sky_light(3, 70, 3, 4), 15);
+
+    app.update();
+
+    let recvd = helper.collect_received();
+    recvd.assert_count::<LightUpdateS2c>(1);
+    recvd.assert_count::<ChunkDataS2c>(0);
+
+    // Removing all sky light marks every section as empty so that the client
+    // clears the light it had.
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+    layer.chunk_mut([0, 0]).unwrap().fill_sky_light(0);
+
+    app.update();
+
+    let recvd = helper.collect_received();
+    let pkt = recvd.first::<LightUpdateS2c>();
+    assert_eq!(pkt.sky_light_arrays.len(), 1);
+    assert_eq!(pkt.sky_light_mask[0], 1 << (sect_count + 1));
+    assert_eq!(pkt.empty_sky_light_mask[0], (1 << (sect_count + 1)) - 1);
+}
+
+#[test]
+fn full_bright_chunk_with_block_light() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+    let sect_count = layer.height() as usize / 16;
+
+    let mut chunk = UnloadedChunk::with_height(layer.height());
+    chunk.fill_sky_light(15);
+    chunk.set_block_light(0, 20, 0, 12);
+    layer.insert_chunk([0, 0], chunk);
+
+    app.update();
+
+    let recvd = helper.collect_received();
+    let pkt = recvd.first::<ChunkDataS2c>();
+    // Every section plus the extra one above the chunk is lit by the sky.
+    assert_eq!(pkt.sky_light_arrays.len(), sect_count + 1);
+    assert!(pkt
+        .sky_light_arrays
+        .iter()
+        .all(|arr| arr.0.iter().all(|&b| b == 0xff)));
+    // Only the second section has block light.
+    assert_eq!(pkt.block_light_arrays.len(), 1);
+    assert_eq!(pkt.block_light_mask[0], 0b100);
+    assert_eq!(pkt.block_light_arrays[0].0[4 * 16 * 16 / 2], 12);
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+    let chunk = layer.chunk_mut([0, 0]).unwrap();
+    assert_eq!(chunk.set_block_light(0, 20, 0, 0), 12);
+
+    app.update();
+
+    let recvd = helper.collect_received();
+    recvd.assert_count::<LightUpdateS2c>(1);
+    recvd.assert_count::<ChunkDataS2c>(0);
+
+    // The cleared section has no block light left, so it is sent as empty
+    // along with every other section.
+    let pkt = recvd.first::<LightUpdateS2c>();
+    assert!(pkt.block_light_arrays.is_empty());
+    assert_eq!(pkt.block_light_mask[0], 0);
+    assert_eq!(pkt.empty_block_light_mask[0], (1 << (sect_count + 2)) - 1);
+    assert_eq!(pkt.sky_light_arrays.len(), sect_count + 1);
+}
+
+#[test]
+fn recompute_sky_light() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+
+    let height = layer.height();
+    let min_y = layer.min_y();
+    let surface = (70 - min_y) as u32;
+
+    for pos in [[0, 0], [1, 0]] {
+        let mut chunk = UnloadedChunk::with_height(height);
+
+        for z in 0..16 {
+            for x in 0..16 {
+                chunk.set_block_state(x, surface, z, BlockState::GRASS_BLOCK);
+            }
+        }
+
+        layer.insert_chunk(pos, chunk);
+    }
+
+    app.update();
+    helper.clear_received();
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+
+    // A hole in the ground lets light into the cave below, which is dimmer
+    // the further it is from the hole.
+    layer.set_block([20, 70, 5], BlockState::AIR);
+    layer.set_block([20, 69, 5], BlockState::GLASS);
+    layer.set_block([22, 69, 5], BlockState::STONE);
+
+    layer.recompute_sky_light();
+
+    let chunk = layer.chunk([0, 0]).unwrap();
+    assert_eq!(chunk.sky_light(5, surface + 1, 5), 15);
+    assert_eq!(chunk.sky_light(5, height - 1, 5), 15);
+    assert_eq!(chunk.sky_light(5, surface, 5), 0);
+    assert_eq!(chunk.sky_light(5, surface - 1, 5), 0);
+    assert_eq!(chunk.sky_light(5, 0, 5), 0);
+
+    let chunk = layer.chunk([1, 0]).unwrap();
+    assert_eq!(chunk.sky_light(4, surface, 5), 15);
+    assert_eq!(chunk.sky_light(4, surface - 1, 5), 15);
+    assert_eq!(chunk.sky_light(4, surface - 2, 5), 15);
+    assert_eq!(chunk.sky_light(5, surface - 1, 5), 14);
+    assert_eq!(chunk.sky_light(6, surface - 1, 5), 0);
+    assert_eq!(chunk.sky_light(6, surface - 2, 5), 13);
+    // Light spreads into the neighboring chunk.
+    assert_eq!(chunk.sky_light(0, surface - 1, 5), 11);
+    assert_eq!(
+        layer.chunk([0, 0]).unwrap().sky_light(15, surface - 1, 5),
+        10
+    );
+
+    app.update();
+
+    // Viewers of the chunks are sent the new light.
+    let recvd = helper.collect_received();
+    recvd.assert_count::<LightUpdateS2c>(2);
+}
+
+#[test]
+fn chunk_layer_spawn_on_ground() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        helper: _,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+
+    let height = layer.height();
+    layer.insert_chunk([0, 0], UnloadedChunk::with_height(height));
+
+    layer.set_block([5, 60, 5], BlockState::STONE);
+    layer.set_block([5, 70, 5], BlockState::GRASS_BLOCK);
+    layer.set_block([5, 71, 5], BlockState::GRASS);
+    layer.set_block([6, 80, 6], BlockState::STONE_SLAB);
+
+    assert_eq!(
+        layer.highest_solid_block(5, 5),
+        Some(BlockPos::new(5, 70, 5))
+    );
+    assert_eq!(
+        layer.spawn_on_ground(5, 5),
+        Some(DVec3::new(5.5, 71.0, 5.5))
+    );
+    assert_eq!(
+        layer.spawn_on_ground(6, 6),
+        Some(DVec3::new(6.5, 80.5, 6.5))
+    );
+
+    // Columns of air and unloaded chunks have no ground.
+    assert_eq!(layer.highest_solid_block(7, 7), None);
+    assert_eq!(layer.spawn_on_ground(7, 7), None);
+    assert_eq!(layer.spawn_on_ground(100, 100), None);
+}
+
+#[test]
+fn chunk_layer_set_blocks_across_chunks() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+    layer.insert_chunk([0, 0], UnloadedChunk::new());
+    layer.insert_chunk([1, 0], UnloadedChunk::new());
+
+    app.update();
+    helper.clear_received();
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+
+    let blocks = [
+        (BlockPos::new(14, 64, 3), BlockState::OAK_LOG),
+        (BlockPos::new(15, 64, 3), BlockState::OAK_LOG),
+        (BlockPos::new(16, 64, 3), BlockState::OAK_LOG),
+        (BlockPos::new(17, 64, 3), BlockState::OAK_LEAVES),
+        (BlockPos::new(15, 65, 3), BlockState::OAK_LEAVES),
+        // Outside the layer height.
+        (BlockPos::new(15, 10_000, 3), BlockState::STONE),
+        // In an unloaded chunk.
+        (BlockPos::new(100, 64, 100), BlockState::STONE),
+    ];
+
+    assert_eq!(layer.set_blocks(blocks, MissingChunks::Skip), 5);
+
+    for (pos, state) in &blocks[..5] {
+        assert_eq!(layer.block(*pos).unwrap().state, *state);
+    }
+
+    assert!(layer.chunk([6, 6]).is_none());
+
+    app.update();
+
+    // Each chunk gets its changes in one packet.
+    helper
+        .collect_received()
+        .assert_count::<ChunkDeltaUpdateS2c>(2);
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+
+    let blocks = [(BlockPos::new(100, 64, 100), BlockState::STONE)];
+
+    assert_eq!(layer.set_blocks(blocks, MissingChunks::Insert), 1);
+    assert_eq!(
+        layer.block([100, 64, 100]).unwrap().state,
+        BlockState::STONE
+    );
+}
+
 #[test]
 fn layer_chunk_view_change() {
     fn view(client: &EntityMut) -> ChunkView {
@@ -465,3 +949,1670 @@ fn chunk_entity_spawn_despawn() {
         recvd.assert_count::<EntitiesDestroyS2c>(0);
     }
 }
+
+#[test]
+fn frozen_entity_layer() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let cow_ent = app
+        .world
+        .spawn(CowEntityBundle {
+            position: Position::new([8.0, 0.0, 8.0]),
+            layer: EntityLayerId(layer_ent),
+            ..Default::default()
+        })
+        .id();
+
+    app.update();
+
+    helper.collect_received().assert_count::<EntitySpawnS2c>(1);
+
+    app.world
+        .get_mut::<EntityLayer>(layer_ent)
+        .unwrap()
+        .set_frozen(true);
+
+    // Moving the entity while frozen doesn't send anything.
+    for _ in 0..5 {
+        app.world.get_mut::<Position>(cow_ent).unwrap().0.x += 0.1;
+
+        app.update();
+
+        let recvd = helper.collect_received();
+        recvd.assert_count::<MoveRelativeS2c>(0);
+        recvd.assert_count::<EntityPositionS2c>(0);
+    }
+
+    app.world
+        .get_mut::<EntityLayer>(layer_ent)
+        .unwrap()
+        .set_frozen(false);
+
+    app.update();
+
+    // Unfreezing sends a single absolute position instead of stale deltas.
+    {
+        let recvd = helper.collect_received();
+        recvd.assert_count::<MoveRelativeS2c>(0);
+        recvd.assert_count::<EntityPositionS2c>(1);
+
+        let pkt = recvd.first::<EntityPositionS2c>();
+        assert!((pkt.position.x - 8.5).abs() < 1e-9);
+    }
+
+    // Movement is sent normally afterwards.
+    app.world.get_mut::<Position>(cow_ent).unwrap().0.x += 0.1;
+
+    app.update();
+
+    {
+        let recvd = helper.collect_received();
+        recvd.assert_count::<MoveRelativeS2c>(1);
+        recvd.assert_count::<EntityPositionS2c>(0);
+    }
+}
+
+#[test]
+fn entity_fire_ticks() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let cow = app
+        .world
+        .spawn((
+            CowEntityBundle {
+                layer: EntityLayerId(layer_ent),
+                ..Default::default()
+            },
+            FireTicks::default(),
+        ))
+        .id();
+
+    app.update();
+    helper.clear_received();
+
+    app.world.get_mut::<FireTicks>(cow).unwrap().ignite(3);
+    app.update();
+
+    assert!(app.world.get::<Flags>(cow).unwrap().on_fire());
+    helper
+        .collect_received()
+        .assert_count::<EntityTrackerUpdateS2c>(1);
+
+    // Igniting for a shorter time doesn't shorten or extend the fire.
+    app.world.get_mut::<FireTicks>(cow).unwrap().ignite(1);
+
+    for _ in 0..2 {
+        app.update();
+        assert!(app.world.get::<Flags>(cow).unwrap().on_fire());
+    }
+
+    app.update();
+
+    assert!(!app.world.get::<Flags>(cow).unwrap().on_fire());
+    assert_eq!(app.world.get::<FireTicks>(cow), Some(&FireTicks(0)));
+    helper
+        .collect_received()
+        .assert_count::<EntityTrackerUpdateS2c>(1);
+}
+
+#[test]
+fn entity_face_movement() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        helper: _,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let cow = app
+        .world
+        .spawn((
+            CowEntityBundle {
+                layer: EntityLayerId(layer_ent),
+                head_yaw: HeadYaw(45.0),
+                ..Default::default()
+            },
+            FaceMovement,
+        ))
+        .id();
+
+    app.update();
+
+    // Walk north.
+    app.world.get_mut::<Position>(cow).unwrap().0.z -= 0.2;
+    app.update();
+
+    assert_eq!(app.world.get::<Look>(cow).unwrap().yaw, 180.0);
+    assert_eq!(app.world.get::<HeadYaw>(cow), Some(&HeadYaw(45.0)));
+
+    // Standing still or barely moving keeps the facing.
+    app.update();
+    app.world.get_mut::<Position>(cow).unwrap().0.x += 1e-6;
+    app.update();
+
+    assert_eq!(app.world.get::<Look>(cow).unwrap().yaw, 180.0);
+
+    // Walk east.
+    app.world.get_mut::<Position>(cow).unwrap().0.x += 0.2;
+    app.update();
+
+    assert_eq!(app.world.get::<Look>(cow).unwrap().yaw, -90.0);
+}
+
+#[test]
+fn entity_init_packets_are_bundled() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let mut flags = Flags::default();
+    flags.set_on_fire(true);
+
+    for _ in 0..3 {
+        app.world.spawn(CowEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            entity_flags: flags.clone(),
+            ..Default::default()
+        });
+    }
+
+    app.update();
+
+    let recvd = helper.collect_received();
+
+    let ids: Vec<_> = recvd
+        .0
+        .iter()
+        .map(|f| f.id)
+        .filter(|&id| {
+            id == BundleSplitterS2c::ID
+                || id == EntitySpawnS2c::ID
+                || id == EntityTrackerUpdateS2c::ID
+        })
+        .collect();
+
+    // Every entity is spawned together with its metadata in its own bundle.
+    let expected = [
+        BundleSplitterS2c::ID,
+        EntitySpawnS2c::ID,
+        EntityTrackerUpdateS2c::ID,
+        BundleSplitterS2c::ID,
+    ]
+    .repeat(3);
+
+    assert_eq!(ids, expected);
+}
+
+#[test]
+fn ridden_entity_spawn_is_bundled() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let rider = app
+        .world
+        .spawn(CowEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            ..Default::default()
+        })
+        .id();
+
+    app.world
+        .spawn(CowEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            ..Default::default()
+        })
+        .insert(Passengers::new([rider]));
+
+    app.update();
+
+    let ids: Vec<_> = helper
+        .collect_received()
+        .0
+        .iter()
+        .map(|f| f.id)
+        .filter(|&id| {
+            id == BundleSplitterS2c::ID
+                || id == EntitySpawnS2c::ID
+                || id == EntityPassengersSetS2c::ID
+        })
+        .collect();
+
+    // The passengers are set in the same bundle that spawns the vehicle, so the
+    // rider never appears unmounted.
+    let pos = ids
+        .iter()
+        .position(|&id| id == EntityPassengersSetS2c::ID)
+        .unwrap();
+
+    assert_eq!(
+        ids[pos - 2..=pos + 1],
+        [
+            BundleSplitterS2c::ID,
+            EntitySpawnS2c::ID,
+            EntityPassengersSetS2c::ID,
+            BundleSplitterS2c::ID,
+        ]
+    );
+}
+
+#[test]
+fn entity_passengers() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let rider = app
+        .world
+        .spawn(CowEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            ..Default::default()
+        })
+        .id();
+
+    // A tall seat for a large custom mount.
+    let seat = app
+        .world
+        .spawn(InteractionEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            interaction_height: interaction::Height(4.0),
+            ..Default::default()
+        })
+        .insert(Passengers::new([rider]))
+        .id();
+
+    app.update();
+
+    let seat_id = app.world.get::<EntityId>(seat).unwrap().get();
+    let rider_id = app.world.get::<EntityId>(rider).unwrap().get();
+
+    {
+        let recvd = helper.collect_received();
+
+        let pkt = recvd.first::<EntityPassengersSetS2c>();
+        assert_eq!(pkt.entity_id.0, seat_id);
+        assert_eq!(
+            pkt.passengers.iter().map(|p| p.0).collect::<Vec<_>>(),
+            [rider_id]
+        );
+    }
+
+    assert_eq!(app.world.get::<Vehicle>(rider).unwrap().get(), seat);
+
+    // Unchanged passengers are not sent again.
+    app.update();
+
+    helper
+        .collect_received()
+        .assert_count::<EntityPassengersSetS2c>(0);
+
+    app.world.get_mut::<Passengers>(seat).unwrap().clear();
+
+    app.update();
+
+    assert!(app.world.get::<Vehicle>(rider).is_none());
+
+    {
+        let recvd = helper.collect_received();
+
+        recvd.assert_count::<EntityPassengersSetS2c>(1);
+
+        let pkt = recvd.first::<EntityPassengersSetS2c>();
+        assert_eq!(pkt.entity_id.0, seat_id);
+        assert!(pkt.passengers.is_empty());
+    }
+
+    // The seat height follows the interaction entity's configured dimensions.
+    let shape = app.world.get::<HitboxShape>(seat).unwrap();
+    assert_eq!(shape.passenger_riding_offset(), 3.0);
+
+    // Despawned passengers are dismounted.
+    let other_rider = app
+        .world
+        .spawn(CowEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            ..Default::default()
+        })
+        .id();
+
+    app.world
+        .get_mut::<Passengers>(seat)
+        .unwrap()
+        .set([rider, other_rider]);
+
+    app.update();
+    helper.clear_received();
+
+    let other_rider_id = app.world.get::<EntityId>(other_rider).unwrap().get();
+
+    app.world.entity_mut(rider).insert(Despawned);
+
+    app.update();
+
+    assert_eq!(
+        app.world.get::<Passengers>(seat).unwrap().get(),
+        [other_rider]
+    );
+
+    let recvd = helper.collect_received();
+    recvd.assert_count::<EntityPassengersSetS2c>(1);
+    assert_eq!(
+        recvd
+            .first::<EntityPassengersSetS2c>()
+            .passengers
+            .iter()
+            .map(|p| p.0)
+            .collect::<Vec<_>>(),
+        [other_rider_id]
+    );
+}
+
+#[test]
+fn entity_passengers_rider_spawned_after_vehicle() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    // The rider starts out of view of the client.
+    let rider = app
+        .world
+        .spawn(CowEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            position: Position(DVec3::new(1000.0, 0.0, 0.0)),
+            ..Default::default()
+        })
+        .id();
+
+    let seat = app
+        .world
+        .spawn(InteractionEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            ..Default::default()
+        })
+        .insert(Passengers::new([rider]))
+        .id();
+
+    app.update();
+    helper.clear_received();
+
+    // The rider rides into view of the client.
+    app.world.get_mut::<Position>(rider).unwrap().0 = DVec3::ZERO;
+
+    app.update();
+
+    let seat_id = app.world.get::<EntityId>(seat).unwrap().get();
+    let rider_id = app.world.get::<EntityId>(rider).unwrap().get();
+
+    let recvd = helper.collect_received();
+
+    recvd.assert_count::<EntitySpawnS2c>(1);
+    recvd.assert_count::<EntityPassengersSetS2c>(1);
+    recvd.assert_order::<(EntitySpawnS2c, EntityPassengersSetS2c)>();
+
+    let pkt = recvd.first::<EntityPassengersSetS2c>();
+    assert_eq!(pkt.entity_id.0, seat_id);
+    assert_eq!(
+        pkt.passengers.iter().map(|p| p.0).collect::<Vec<_>>(),
+        [rider_id]
+    );
+}
+
+#[test]
+fn entity_viewers() {
+    let ScenarioSingleClient {
+        mut app,
+        client: near_client,
+        helper: _,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    // A second client far away from the entity.
+    let (mut bundle, _helper) = create_mock_client("far");
+
+    bundle.player.layer.0 = layer_ent;
+    bundle.player.position.set([10_000.0, 64.0, 10_000.0]);
+    bundle.visible_chunk_layer.0 = layer_ent;
+    bundle.visible_entity_layers.0.insert(layer_ent);
+
+    let far_client = app.world.spawn(bundle).id();
+
+    let cow = app
+        .world
+        .spawn(CowEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            ..Default::default()
+        })
+        .id();
+
+    app.update();
+
+    let mut state = SystemState::<EntityViewers>::new(&mut app.world);
+    let viewers = state.get(&app.world);
+
+    assert_eq!(viewers.iter(cow).collect::<Vec<_>>(), [near_client]);
+    assert!(viewers.contains(cow, near_client));
+    assert!(!viewers.contains(cow, far_client));
+
+    // Clients don't see themselves.
+    assert!(!viewers.contains(near_client, near_client));
+    assert!(!viewers.contains(far_client, near_client));
+
+    // The viewers follow the clients as they move.
+    app.world
+        .get_mut::<Position>(near_client)
+        .unwrap()
+        .set([10_000.0, 64.0, 10_000.0]);
+    app.world
+        .get_mut::<Position>(far_client)
+        .unwrap()
+        .set([0.0, 64.0, 0.0]);
+
+    app.update();
+
+    let viewers = state.get(&app.world);
+
+    assert_eq!(viewers.iter(cow).collect::<Vec<_>>(), [far_client]);
+
+    // Despawned clients are forgotten.
+    app.world.entity_mut(far_client).insert(Despawned);
+
+    app.update();
+    app.update();
+
+    let viewers = state.get(&app.world);
+
+    assert_eq!(viewers.iter(cow).count(), 0);
+}
+
+#[test]
+fn particle_reaches_viewers_only() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        helper: mut near_helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let (mut bundle, mut far_helper) = create_mock_client("far");
+
+    bundle.player.layer.0 = layer_ent;
+    bundle.player.position.set([10_000.0, 64.0, 10_000.0]);
+    bundle.visible_chunk_layer.0 = layer_ent;
+    bundle.visible_entity_layers.0.insert(layer_ent);
+
+    app.world.spawn(bundle);
+
+    app.update();
+
+    near_helper.clear_received();
+    far_helper.clear_received();
+
+    app.world
+        .get_mut::<ChunkLayer>(layer_ent)
+        .unwrap()
+        .play_particle(&Particle::Flame, true, [1.0, 65.0, 1.0], [0.5; 3], 0.1, 20);
+
+    app.update();
+
+    let recvd = near_helper.collect_received();
+    recvd.assert_count::<ParticleS2c>(1);
+
+    let pkt = recvd.first::<ParticleS2c>();
+    assert_eq!(*pkt.particle, Particle::Flame);
+    assert!(pkt.long_distance);
+    assert_eq!(pkt.position, DVec3::new(1.0, 65.0, 1.0));
+    assert_eq!(pkt.count, 20);
+
+    far_helper.collect_received().assert_count::<ParticleS2c>(0);
+}
+
+#[test]
+fn block_break_progress_reaches_viewers_only() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        helper: mut near_helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let (mut bundle, mut far_helper) = create_mock_client("far");
+
+    bundle.player.layer.0 = layer_ent;
+    bundle.player.position.set([10_000.0, 64.0, 10_000.0]);
+    bundle.visible_chunk_layer.0 = layer_ent;
+    bundle.visible_entity_layers.0.insert(layer_ent);
+
+    let breaker = app.world.spawn(bundle).id();
+
+    app.update();
+
+    near_helper.clear_received();
+    far_helper.clear_received();
+
+    let breaker_id = *app.world.get::<EntityId>(breaker).unwrap();
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+    layer.set_block_break_progress(breaker_id, [1, 64, 1], 3);
+    layer.set_block_break_progress(breaker_id, [1, 64, 1], 12);
+
+    app.update();
+
+    let recvd = near_helper.collect_received();
+    recvd.assert_count::<BlockBreakingProgressS2c>(2);
+
+    let pkt = recvd.first::<BlockBreakingProgressS2c>();
+    assert_eq!(pkt.entity_id.0, breaker_id.get());
+    assert_eq!(pkt.position, BlockPos::new(1, 64, 1));
+    assert_eq!(pkt.destroy_stage, 3);
+
+    // Stages past 9 are sent as a request to clear the animation.
+    let stages: Vec<_> = recvd
+        .0
+        .iter()
+        .filter_map(|frame| frame.decode::<BlockBreakingProgressS2c>().ok())
+        .map(|pkt| pkt.destroy_stage)
+        .collect();
+    assert_eq!(stages, [3, u8::MAX]);
+
+    far_helper
+        .collect_received()
+        .assert_count::<BlockBreakingProgressS2c>(0);
+}
+
+#[test]
+fn entity_sound_reaches_viewers_only() {
+    let ScenarioSingleClient {
+        mut app,
+        client: near_client,
+        helper: mut near_helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    // A second client that can't see the entity.
+    let (mut bundle, mut far_helper) = create_mock_client("far");
+
+    bundle.player.layer.0 = layer_ent;
+    bundle.player.position.set([10_000.0, 64.0, 10_000.0]);
+    bundle.visible_chunk_layer.0 = layer_ent;
+    bundle.visible_entity_layers.0.insert(layer_ent);
+
+    app.world.spawn(bundle);
+
+    let cow = app
+        .world
+        .spawn(CowEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            position: Position::new([2.0, 64.0, 2.0]),
+            ..Default::default()
+        })
+        .id();
+
+    app.update();
+
+    near_helper.clear_received();
+    far_helper.clear_received();
+
+    let cow_id = *app.world.get::<EntityId>(cow).unwrap();
+
+    app.world
+        .get_mut::<EntityLayer>(layer_ent)
+        .unwrap()
+        .play_sound_from_entity(cow, Sound::EntityCowStep, SoundCategory::Neutral, 0.15, 1.0);
+
+    app.update();
+
+    let recvd = near_helper.collect_received();
+    recvd.assert_count::<PlaySoundFromEntityS2c>(1);
+
+    let pkt = recvd.first::<PlaySoundFromEntityS2c>();
+    assert_eq!(pkt.entity_id.0, cow_id.get());
+    assert_eq!(pkt.category, SoundCategory::Neutral);
+    assert_eq!(pkt.volume, 0.15);
+    assert_eq!(pkt.pitch, 1.0);
+
+    far_helper
+        .collect_received()
+        .assert_count::<PlaySoundFromEntityS2c>(0);
+
+    // Custom sounds are sent by identifier.
+    let moo = Ident::<Cow<str>>::from(ident!("my_pack:entity.cow.moo"));
+
+    app.world
+        .get_mut::<EntityLayer>(layer_ent)
+        .unwrap()
+        .play_sound_from_entity(cow, moo.clone(), SoundCategory::Neutral, 1.0, 1.0);
+
+    app.update();
+
+    let recvd = near_helper.collect_received();
+    recvd.assert_count::<PlaySoundFromEntityS2c>(1);
+
+    let pkt = recvd.first::<PlaySoundFromEntityS2c>();
+    assert_eq!(
+        pkt.id,
+        SoundId::Direct {
+            id: moo,
+            range: None
+        }
+    );
+
+    // Clients the entity is hidden from don't hear it.
+    app.world
+        .get_mut::<HiddenEntities>(near_client)
+        .unwrap()
+        .0
+        .insert(cow);
+
+    app.update();
+    near_helper.clear_received();
+
+    app.world
+        .get_mut::<EntityLayer>(layer_ent)
+        .unwrap()
+        .play_sound_from_entity(cow, Sound::EntityCowStep, SoundCategory::Neutral, 0.15, 1.0);
+
+    app.update();
+
+    near_helper
+        .collect_received()
+        .assert_count::<PlaySoundFromEntityS2c>(0);
+}
+
+#[test]
+fn entity_equipment_reaches_viewers_only() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        helper: mut near_helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    // A second client that can't see the entity.
+    let (mut bundle, mut far_helper) = create_mock_client("far");
+
+    bundle.player.layer.0 = layer_ent;
+    bundle.player.position.set([10_000.0, 64.0, 10_000.0]);
+    bundle.visible_chunk_layer.0 = layer_ent;
+    bundle.visible_entity_layers.0.insert(layer_ent);
+
+    app.world.spawn(bundle);
+
+    let mut equipment = Equipment::new();
+    equipment.set(
+        EquipmentSlot::MainHand,
+        Some(ItemStack::new(ItemKind::DiamondSword, 1, None)),
+    );
+
+    let zombie = app
+        .world
+        .spawn((
+            ZombieEntityBundle {
+                layer: EntityLayerId(layer_ent),
+                position: Position::new([2.0, 64.0, 2.0]),
+                ..Default::default()
+            },
+            equipment,
+        ))
+        .id();
+
+    app.update();
+
+    // The equipment is sent when the entity is spawned.
+    {
+        let recvd = near_helper.collect_received();
+        recvd.assert_count::<EntityEquipmentUpdateS2c>(1);
+
+        let pkt = recvd.first::<EntityEquipmentUpdateS2c>();
+        assert_eq!(pkt.equipment.len(), 1);
+        assert_eq!(pkt.equipment[0].slot, EquipmentSlot::MainHand as i8);
+    }
+
+    far_helper.clear_received();
+
+    let mut equipment = app.world.get_mut::<Equipment>(zombie).unwrap();
+    equipment.set(
+        EquipmentSlot::OffHand,
+        Some(ItemStack::new(ItemKind::Shield, 1, None)),
+    );
+    equipment.set(
+        EquipmentSlot::Head,
+        Some(ItemStack::new(ItemKind::IronHelmet, 1, None)),
+    );
+
+    app.update();
+
+    // Changes in the same tick are batched together.
+    {
+        let recvd = near_helper.collect_received();
+        recvd.assert_count::<EntityEquipmentUpdateS2c>(1);
+
+        let pkt = recvd.first::<EntityEquipmentUpdateS2c>();
+        let slots: Vec<_> = pkt.equipment.iter().map(|e| e.slot).collect();
+        assert_eq!(
+            slots,
+            [EquipmentSlot::OffHand as i8, EquipmentSlot::Head as i8]
+        );
+    }
+
+    far_helper
+        .collect_received()
+        .assert_count::<EntityEquipmentUpdateS2c>(0);
+
+    // Setting the same item again sends nothing.
+    app.world.get_mut::<Equipment>(zombie).unwrap().set(
+        EquipmentSlot::Head,
+        Some(ItemStack::new(ItemKind::IronHelmet, 1, None)),
+    );
+
+    app.update();
+
+    near_helper
+        .collect_received()
+        .assert_count::<EntityEquipmentUpdateS2c>(0);
+}
+
+#[test]
+fn hidden_entity_reaches_other_clients_only() {
+    let ScenarioSingleClient {
+        mut app,
+        client: vanisher_client,
+        helper: mut vanisher_helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    // A second client that keeps seeing the entity.
+    let (mut bundle, mut other_helper) = create_mock_client("other");
+
+    bundle.player.layer.0 = layer_ent;
+    bundle.visible_chunk_layer.0 = layer_ent;
+    bundle.visible_entity_layers.0.insert(layer_ent);
+
+    let other_client = app.world.spawn(bundle).id();
+
+    let cow = app
+        .world
+        .spawn(CowEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            position: Position::new([2.0, 64.0, 2.0]),
+            ..Default::default()
+        })
+        .id();
+
+    app.update();
+
+    vanisher_helper.clear_received();
+    other_helper.clear_received();
+
+    let cow_id = app.world.get::<EntityId>(cow).unwrap().get();
+
+    // Hiding the entity despawns it for that client only.
+    app.world
+        .get_mut::<HiddenEntities>(vanisher_client)
+        .unwrap()
+        .0
+        .insert(cow);
+
+    app.update();
+
+    {
+        let recvd = vanisher_helper.collect_received();
+        recvd.assert_count::<EntitiesDestroyS2c>(1);
+        assert_eq!(
+            &*recvd.first::<EntitiesDestroyS2c>().entity_ids,
+            [VarInt(cow_id)]
+        );
+    }
+
+    other_helper
+        .collect_received()
+        .assert_count::<EntitiesDestroyS2c>(0);
+
+    let mut state = SystemState::<EntityViewers>::new(&mut app.world);
+    let viewers = state.get(&app.world);
+    assert_eq!(viewers.iter(cow).collect::<Vec<_>>(), [other_client]);
+
+    // Movement of the hidden entity isn't sent, even across chunks.
+    for pos in [[3.0, 64.0, 3.0], [9.0, 64.0, 3.0], [16.5, 64.0, 3.0]] {
+        app.world.get_mut::<Position>(cow).unwrap().set(pos);
+
+        app.update();
+
+        let recvd = vanisher_helper.collect_received();
+        assert!(!recvd.0.iter().any(|f| f.id == MoveRelativeS2c::ID
+            || f.id == EntityPositionS2c::ID
+            || f.id == EntitySpawnS2c::ID));
+
+        assert!(other_helper
+            .collect_received()
+            .0
+            .iter()
+            .any(|f| f.id == MoveRelativeS2c::ID));
+    }
+
+    // Showing the entity spawns it at its current position.
+    app.world
+        .get_mut::<HiddenEntities>(vanisher_client)
+        .unwrap()
+        .0
+        .remove(&cow);
+
+    app.update();
+
+    let recvd = vanisher_helper.collect_received();
+    recvd.assert_count::<EntitySpawnS2c>(1);
+
+    let pkt = recvd.first::<EntitySpawnS2c>();
+    assert_eq!(pkt.entity_id.0, cow_id);
+    assert_eq!(pkt.position, DVec3::new(16.5, 64.0, 3.0));
+
+    other_helper
+        .collect_received()
+        .assert_count::<EntitySpawnS2c>(0);
+}
+
+#[test]
+fn hidden_entity_never_spawned() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let spawn_cow = |app: &mut App| {
+        app.world
+            .spawn(CowEntityBundle {
+                layer: EntityLayerId(layer_ent),
+                position: Position::new([2.0, 64.0, 2.0]),
+                ..Default::default()
+            })
+            .id()
+    };
+
+    let hidden_cow = spawn_cow(&mut app);
+    let cow = spawn_cow(&mut app);
+
+    // Hidden before the client joins.
+    app.world
+        .get_mut::<HiddenEntities>(client)
+        .unwrap()
+        .0
+        .insert(hidden_cow);
+
+    app.update();
+
+    let cow_id = app.world.get::<EntityId>(cow).unwrap().get();
+
+    {
+        let recvd = helper.collect_received();
+        recvd.assert_count::<EntitySpawnS2c>(1);
+        assert_eq!(recvd.first::<EntitySpawnS2c>().entity_id.0, cow_id);
+        recvd.assert_count::<EntitiesDestroyS2c>(0);
+    }
+
+    // Hidden in the tick it spawns.
+    let new_cow = spawn_cow(&mut app);
+
+    app.world
+        .get_mut::<HiddenEntities>(client)
+        .unwrap()
+        .0
+        .insert(new_cow);
+
+    app.update();
+
+    helper.collect_received().assert_count::<EntitySpawnS2c>(0);
+}
+
+#[test]
+fn entity_hidden_while_leaving_view() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let other_layer = app.world.spawn(EntityLayer::new(app.world.resource())).id();
+
+    let spawn_cow = |app: &mut App, x| {
+        app.world
+            .spawn(CowEntityBundle {
+                layer: EntityLayerId(layer_ent),
+                position: Position::new([x, 64.0, 2.0]),
+                ..Default::default()
+            })
+            .id()
+    };
+
+    let walking_cow = spawn_cow(&mut app, 2.0);
+    let layer_cow = spawn_cow(&mut app, 2.0);
+    let distant_cow = spawn_cow(&mut app, 1000.0);
+
+    app.update();
+
+    let walking_cow_id = app.world.get::<EntityId>(walking_cow).unwrap().get();
+    let layer_cow_id = app.world.get::<EntityId>(layer_cow).unwrap().get();
+
+    helper.clear_received();
+
+    // The cows are hidden in the tick they leave or enter the client's view.
+    app.world
+        .get_mut::<HiddenEntities>(client)
+        .unwrap()
+        .0
+        .extend([walking_cow, layer_cow, distant_cow]);
+
+    app.world.get_mut::<Position>(walking_cow).unwrap().0 = DVec3::new(1000.0, 64.0, 0.0);
+    app.world.get_mut::<EntityLayerId>(layer_cow).unwrap().0 = other_layer;
+    app.world.get_mut::<Position>(distant_cow).unwrap().0 = DVec3::new(2.0, 64.0, 2.0);
+
+    app.update();
+
+    let recvd = helper.collect_received();
+
+    // The cow that was never spawned is not despawned either.
+    recvd.assert_count::<EntitySpawnS2c>(0);
+
+    let mut destroyed: Vec<_> = recvd
+        .0
+        .iter()
+        .filter(|f| f.id == EntitiesDestroyS2c::ID)
+        .flat_map(|f| {
+            f.decode::<EntitiesDestroyS2c>()
+                .unwrap()
+                .entity_ids
+                .into_owned()
+        })
+        .map(|id| id.0)
+        .collect();
+    destroyed.sort_unstable();
+
+    assert_eq!(destroyed, [walking_cow_id, layer_cow_id]);
+}
+
+#[test]
+fn entity_hidden_from_enemy_team() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    app.world
+        .entity_mut(client)
+        .insert(Team("predators".into()));
+
+    let predator = app
+        .world
+        .spawn((
+            CowEntityBundle {
+                layer: EntityLayerId(layer_ent),
+                position: Position::new([2.0, 64.0, 2.0]),
+                ..Default::default()
+            },
+            Team("predators".into()),
+            HiddenFromTeams(["prey".into()].into()),
+        ))
+        .id();
+
+    app.update();
+
+    // Teammates can see the entity.
+    helper.collect_received().assert_count::<EntitySpawnS2c>(1);
+
+    let predator_id = app.world.get::<EntityId>(predator).unwrap().get();
+
+    // Moving the client to the enemy team hides the entity from it.
+    *app.world.get_mut::<Team>(client).unwrap() = Team("prey".into());
+
+    app.update();
+
+    let recvd = helper.collect_received();
+    recvd.assert_count::<EntitiesDestroyS2c>(1);
+    assert_eq!(
+        &*recvd.first::<EntitiesDestroyS2c>().entity_ids,
+        [VarInt(predator_id)]
+    );
+    assert!(app
+        .world
+        .get::<HiddenEntities>(client)
+        .unwrap()
+        .0
+        .contains(&predator));
+
+    // Entities hidden by other means stay hidden when the team changes back.
+    let cow = app
+        .world
+        .spawn(CowEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            position: Position::new([3.0, 64.0, 3.0]),
+            ..Default::default()
+        })
+        .id();
+
+    app.world
+        .get_mut::<HiddenEntities>(client)
+        .unwrap()
+        .0
+        .insert(cow);
+
+    app.update();
+    helper.clear_received();
+
+    app.world.entity_mut(client).remove::<Team>();
+
+    app.update();
+
+    let recvd = helper.collect_received();
+    recvd.assert_count::<EntitySpawnS2c>(1);
+    assert_eq!(recvd.first::<EntitySpawnS2c>().entity_id.0, predator_id);
+    assert_eq!(
+        app.world.get::<HiddenEntities>(client).unwrap().0,
+        [cow].into()
+    );
+}
+
+#[test]
+fn entity_hidden_from_enemy_team_on_spawn() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    app.world.entity_mut(client).insert(Team("prey".into()));
+
+    app.update();
+    helper.clear_received();
+
+    // The entity is hidden in the same tick it spawns, so the enemy client
+    // must never see it.
+    app.world.spawn((
+        CowEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            position: Position::new([2.0, 64.0, 2.0]),
+            ..Default::default()
+        },
+        Team("predators".into()),
+        HiddenFromTeams(["prey".into()].into()),
+    ));
+
+    app.update();
+
+    helper.collect_received().assert_count::<EntitySpawnS2c>(0);
+
+    app.update();
+
+    helper.collect_received().assert_count::<EntitySpawnS2c>(0);
+}
+
+#[test]
+fn always_teleport_entities() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let cow = app
+        .world
+        .spawn(CowEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            position: Position::new([0.0, 64.0, 0.0]),
+            ..Default::default()
+        })
+        .id();
+
+    app.update();
+    helper.clear_received();
+
+    // Small moves are relative by default.
+    app.world.get_mut::<Position>(cow).unwrap().0.x += 0.5;
+    app.update();
+
+    {
+        let recvd = helper.collect_received();
+        recvd.assert_count::<MoveRelativeS2c>(1);
+        recvd.assert_count::<EntityPositionS2c>(0);
+    }
+
+    app.world
+        .resource_mut::<EntityUpdateSettings>()
+        .always_teleport = true;
+
+    app.world.get_mut::<Position>(cow).unwrap().0.x += 0.5;
+    app.update();
+
+    {
+        let recvd = helper.collect_received();
+        recvd.assert_count::<MoveRelativeS2c>(0);
+        recvd.assert_count::<EntityPositionS2c>(1);
+
+        let pkt = recvd.first::<EntityPositionS2c>();
+        assert_eq!(pkt.position, DVec3::new(1.0, 64.0, 0.0));
+    }
+
+    // Entities that didn't move aren't teleported.
+    app.update();
+    helper
+        .collect_received()
+        .assert_count::<EntityPositionS2c>(0);
+}
+
+#[test]
+fn entity_layer_transfer() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        helper: mut old_helper,
+        layer: l1,
+    } = ScenarioSingleClient::new();
+
+    let l2 = EntityLayer::new(app.world.resource::<Server>());
+    let l2 = app.world.spawn(l2).id();
+
+    // One client sees only the new layer, another sees both layers.
+    let (mut bundle, mut new_helper) = create_mock_client("new");
+    bundle.visible_chunk_layer.0 = l1;
+    bundle.visible_entity_layers.0.insert(l2);
+    app.world.spawn(bundle);
+
+    let (mut bundle, mut both_helper) = create_mock_client("both");
+    bundle.visible_chunk_layer.0 = l1;
+    bundle.visible_entity_layers.0.extend([l1, l2]);
+    app.world.spawn(bundle);
+
+    let boss = app
+        .world
+        .spawn(CowEntityBundle {
+            layer: EntityLayerId(l1),
+            ..Default::default()
+        })
+        .id();
+
+    app.update();
+
+    old_helper.clear_received();
+    new_helper.clear_received();
+    both_helper.clear_received();
+
+    app.world.get_mut::<EntityLayerId>(boss).unwrap().0 = l2;
+
+    app.update();
+
+    {
+        let recvd = old_helper.collect_received();
+        recvd.assert_count::<EntitiesDestroyS2c>(1);
+        recvd.assert_count::<EntitySpawnS2c>(0);
+    }
+
+    {
+        let recvd = new_helper.collect_received();
+        recvd.assert_count::<EntitiesDestroyS2c>(0);
+        recvd.assert_count::<EntitySpawnS2c>(1);
+    }
+
+    {
+        let recvd = both_helper.collect_received();
+        recvd.assert_count::<EntitiesDestroyS2c>(0);
+        recvd.assert_count::<EntitySpawnS2c>(0);
+    }
+
+    // Move the entity back to the old layer and out of view at the same time.
+    // The client viewing both layers must have it despawned.
+    app.world.get_mut::<EntityLayerId>(boss).unwrap().0 = l1;
+    app.world
+        .get_mut::<Position>(boss)
+        .unwrap()
+        .set([10_000.0, 0.0, 10_000.0]);
+
+    app.update();
+
+    {
+        let recvd = both_helper.collect_received();
+        recvd.assert_count::<EntitiesDestroyS2c>(1);
+        recvd.assert_count::<EntitySpawnS2c>(0);
+    }
+
+    new_helper
+        .collect_received()
+        .assert_count::<EntitiesDestroyS2c>(1);
+
+    old_helper
+        .collect_received()
+        .assert_count::<EntitySpawnS2c>(0);
+}
+
+#[test]
+fn entity_layer_limit() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    // The client's own player entity counts towards the limit.
+    app.world
+        .get_mut::<EntityLayer>(layer_ent)
+        .unwrap()
+        .set_entity_limit(Some(3), EntityLimitPolicy::Reject);
+
+    let spawn_cow = |app: &mut App| {
+        app.world
+            .spawn(CowEntityBundle {
+                layer: EntityLayerId(layer_ent),
+                ..Default::default()
+            })
+            .id()
+    };
+
+    let rejected_events = |app: &App| -> Vec<EntityRejectedEvent> {
+        app.world
+            .resource::<Events<EntityRejectedEvent>>()
+            .iter_current_update_events()
+            .copied()
+            .collect()
+    };
+
+    let first = spawn_cow(&mut app);
+    spawn_cow(&mut app);
+
+    app.update();
+
+    helper.collect_received().assert_count::<EntitySpawnS2c>(2);
+
+    let rejected = spawn_cow(&mut app);
+
+    app.update();
+
+    // The new entity was despawned instead of being added.
+    assert!(app.world.get_entity(rejected).is_none());
+    assert_eq!(
+        rejected_events(&app),
+        [EntityRejectedEvent {
+            entity: rejected,
+            layer: layer_ent,
+        }]
+    );
+    assert_eq!(
+        app.world
+            .get::<EntityLayer>(layer_ent)
+            .unwrap()
+            .entity_count(),
+        3
+    );
+    helper.collect_received().assert_count::<EntitySpawnS2c>(0);
+
+    app.world
+        .get_mut::<EntityLayer>(layer_ent)
+        .unwrap()
+        .set_entity_limit(Some(3), EntityLimitPolicy::EvictOldest);
+
+    spawn_cow(&mut app);
+
+    app.update();
+
+    // The oldest cow made room, and the player was left alone.
+    assert!(app.world.get_entity(first).is_none());
+    assert!(app.world.get_entity(client).is_some());
+    assert_eq!(
+        app.world
+            .get::<EntityLayer>(layer_ent)
+            .unwrap()
+            .entity_count(),
+        3
+    );
+
+    let recvd = helper.collect_received();
+    recvd.assert_count::<EntitiesDestroyS2c>(1);
+    recvd.assert_count::<EntitySpawnS2c>(1);
+}
+
+#[test]
+fn entity_layer_limit_rejects_layer_change() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: full,
+    } = ScenarioSingleClient::new();
+
+    let other = EntityLayer::new(app.world.resource::<Server>());
+    let other = app.world.spawn(other).id();
+
+    app.world
+        .get_mut::<VisibleEntityLayers>(client)
+        .unwrap()
+        .0
+        .insert(other);
+
+    // Only the client's own player fits.
+    app.world
+        .get_mut::<EntityLayer>(full)
+        .unwrap()
+        .set_entity_limit(Some(1), EntityLimitPolicy::Reject);
+
+    let cow = app
+        .world
+        .spawn(CowEntityBundle {
+            layer: EntityLayerId(other),
+            ..Default::default()
+        })
+        .id();
+
+    app.update();
+
+    helper.collect_received().assert_count::<EntitySpawnS2c>(1);
+
+    app.world.get_mut::<EntityLayerId>(cow).unwrap().0 = full;
+
+    app.update();
+    app.update();
+
+    // The cow is gone from the layer it came from as well.
+    assert!(app.world.get_entity(cow).is_none());
+    assert_eq!(
+        app.world.get::<EntityLayer>(other).unwrap().entity_count(),
+        0
+    );
+    assert_eq!(
+        app.world.get::<EntityLayer>(full).unwrap().entity_count(),
+        1
+    );
+
+    let recvd = helper.collect_received();
+    recvd.assert_count::<EntitiesDestroyS2c>(1);
+    recvd.assert_count::<EntitySpawnS2c>(0);
+}
+
+#[test]
+fn entity_manager_iteration_order() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        helper: _,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let cows: Vec<_> = (0..5)
+        .map(|_| {
+            app.world
+                .spawn(CowEntityBundle {
+                    layer: EntityLayerId(layer_ent),
+                    ..Default::default()
+                })
+                .id()
+        })
+        .collect();
+
+    app.update();
+
+    let order = |app: &App| {
+        app.world
+            .resource::<EntityManager>()
+            .iter()
+            .map(|(_, entity)| entity)
+            .filter(|&entity| entity != client)
+            .collect::<Vec<_>>()
+    };
+
+    // Entities are in the order they were spawned.
+    let mut expected = cows.clone();
+    assert_eq!(order(&app), expected);
+
+    // Removing an entity doesn't change the order of the others.
+    app.world.entity_mut(cows[1]).insert(Despawned);
+
+    app.update();
+
+    expected.retain(|&e| e != cows[1]);
+    assert_eq!(order(&app), expected);
+}
+
+#[test]
+fn entity_old_position() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        helper: _,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let cow = app
+        .world
+        .spawn(CowEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            position: Position::new([5.0, 64.0, 5.0]),
+            ..Default::default()
+        })
+        .id();
+
+    app.update();
+
+    // A freshly spawned entity hasn't moved.
+    let old_pos = app.world.get::<OldPosition>(cow).unwrap();
+    assert_eq!(old_pos.get(), DVec3::new(5.0, 64.0, 5.0));
+    assert_eq!(
+        old_pos.delta(*app.world.get::<Position>(cow).unwrap()),
+        DVec3::ZERO
+    );
+
+    #[derive(Resource, Default)]
+    struct Moved(DVec3);
+
+    fn move_cow(mut cows: Query<&mut Position, With<CowEntity>>) {
+        for mut pos in &mut cows {
+            pos.0.x += 2.0;
+        }
+    }
+
+    fn read_delta(
+        cows: Query<(&Position, &OldPosition), With<CowEntity>>,
+        mut moved: ResMut<Moved>,
+    ) {
+        for (pos, old_pos) in &cows {
+            moved.0 = old_pos.delta(*pos);
+        }
+    }
+
+    app.init_resource::<Moved>()
+        .add_systems(Update, (move_cow, read_delta).chain());
+
+    app.update();
+
+    // The old position still holds the pre-move value later in the same tick.
+    assert_eq!(app.world.resource::<Moved>().0, DVec3::new(2.0, 0.0, 0.0));
+
+    // And catches up once the tick is over.
+    assert_eq!(
+        app.world.get::<OldPosition>(cow).unwrap().get(),
+        DVec3::new(7.0, 64.0, 5.0)
+    );
+}
+
+#[test]
+fn glowing_entity_team_order() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    let mut flags = Flags::default();
+    flags.set_glowing(true);
+
+    let cow = app
+        .world
+        .spawn(CowEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            entity_flags: flags,
+            ..Default::default()
+        })
+        .id();
+
+    let uuid = app.world.get::<UniqueId>(cow).unwrap().0.to_string();
+
+    fn create_team<'a>(name: &'a str, color: TeamColor, entities: Vec<&'a str>) -> TeamS2c<'a> {
+        TeamS2c {
+            team_name: name,
+            mode: Mode::CreateTeam {
+                team_display_name: "".into_cow_text(),
+                friendly_flags: TeamFlags::new(),
+                name_tag_visibility: NameTagVisibility::Always,
+                collision_rule: CollisionRule::Always,
+                team_color: color,
+                team_prefix: "".into_cow_text(),
+                team_suffix: "".into_cow_text(),
+                entities,
+            },
+        }
+    }
+
+    let mut layer = app.world.get_mut::<EntityLayer>(layer_ent).unwrap();
+    layer.write_packet(&create_team("red", TeamColor::Red, vec![&uuid]));
+    layer.write_packet(&create_team("blue", TeamColor::Blue, vec![]));
+
+    app.update();
+
+    // The team membership arrives before the glowing entity is spawned, so the
+    // outline has the team color from the start.
+    {
+        let recvd = helper.collect_received();
+        recvd.assert_count::<TeamS2c>(2);
+        recvd.assert_count::<EntitySpawnS2c>(1);
+        recvd.assert_order::<(TeamS2c, EntitySpawnS2c, EntityTrackerUpdateS2c)>();
+    }
+
+    // Moving the entity to another team recolors the existing outline without
+    // resending the glowing flag.
+    let mut layer = app.world.get_mut::<EntityLayer>(layer_ent).unwrap();
+    layer.write_packet(&TeamS2c {
+        team_name: "blue",
+        mode: Mode::AddEntities {
+            entities: vec![&uuid],
+        },
+    });
+
+    app.update();
+
+    {
+        let recvd = helper.collect_received();
+        recvd.assert_count::<TeamS2c>(1);
+        recvd.assert_count::<EntitySpawnS2c>(0);
+        recvd.assert_count::<EntityTrackerUpdateS2c>(0);
+    }
+}
+
+#[test]
+fn text_display_background_and_flags() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let mut flags = text_display::TextDisplayFlags::default();
+    flags.set_shadow(true);
+    flags.set_alignment(TextAlignment::Left);
+
+    let hologram = app
+        .world
+        .spawn(TextDisplayEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            text_display_text: text_display::Text("Hello".into_text()),
+            text_display_background: text_display::Background::TRANSPARENT,
+            text_display_text_display_flags: flags,
+            ..Default::default()
+        })
+        .id();
+
+    app.update();
+
+    let hologram_id = app.world.get::<EntityId>(hologram).unwrap().get();
+
+    let recvd = helper.collect_received();
+    let pkt = recvd.first::<EntityTrackerUpdateS2c>();
+    assert_eq!(pkt.entity_id.0, hologram_id);
+
+    // Background is an integer (type 1) at index 24, and the flags are a byte
+    // (type 0) at index 26 with the shadow and left alignment bits set.
+    let data = pkt.tracked_values.0;
+    assert!(data.windows(3).any(|w| w == [24, 1, 0]));
+    assert!(data.windows(3).any(|w| w == [26, 0, 0b01001]));
+}
+
+#[test]
+fn display_transform_scale() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    let display = app
+        .world
+        .spawn(BlockDisplayEntityBundle {
+            layer: EntityLayerId(layer_ent),
+            block_display_block_state: block_display::BlockState(BlockState::STONE),
+            ..Default::default()
+        })
+        .id();
+
+    app.update();
+    helper.clear_received();
+
+    app.world
+        .entity_mut(display)
+        .insert(DisplayTransform::from_scale(Vec3::splat(2.0)).into_components());
+
+    app.update();
+
+    let recvd = helper.collect_received();
+    let pkt = recvd.first::<EntityTrackerUpdateS2c>();
+
+    // The scale is a vector3f (type 26) at index 11. The rest of the transform
+    // is unchanged, but still sent because its components were replaced.
+    let mut scale = vec![11, 26];
+    for _ in 0..3 {
+        scale.extend(2.0_f32.to_be_bytes());
+    }
+
+    assert!(pkt
+        .tracked_values
+        .0
+        .windows(scale.len())
+        .any(|w| w == scale));
+}
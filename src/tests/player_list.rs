@@ -1,7 +1,10 @@
+use bevy_ecs::prelude::DetectChangesMut;
+
 use crate::layer::chunk::UnloadedChunk;
-use crate::protocol::packets::play::{PlayerListS2c, PlayerSpawnS2c};
+use crate::player_list::PlayerListGroup;
+use crate::protocol::packets::play::{PlayerListS2c, PlayerRemoveS2c, PlayerSpawnS2c};
 use crate::testing::{create_mock_client, ScenarioSingleClient};
-use crate::ChunkLayer;
+use crate::{ChunkLayer, GameMode};
 
 #[test]
 fn player_list_arrives_before_player_spawn() {
@@ -64,3 +67,98 @@ fn player_list_arrives_before_player_spawn() {
         assert_eq!(pkt.entries.len(), 2);
     }
 }
+
+#[test]
+fn player_list_groups_are_disjoint() {
+    let ScenarioSingleClient {
+        mut app,
+        client: client_1,
+        helper: mut client_helper_1,
+        layer: lobby,
+    } = ScenarioSingleClient::new();
+
+    let game = app.world.spawn_empty().id();
+
+    app.world
+        .entity_mut(client_1)
+        .insert(PlayerListGroup(lobby));
+
+    let (client_2, mut client_helper_2) = create_mock_client("test_2");
+    let client_2 = app.world.spawn((client_2, PlayerListGroup(game))).id();
+
+    app.update();
+
+    // Each client only sees itself.
+    {
+        let recvd = client_helper_1.collect_received();
+        recvd.assert_count::<PlayerListS2c>(1);
+        assert_eq!(recvd.first::<PlayerListS2c>().entries[0].username, "test");
+    }
+
+    {
+        let recvd = client_helper_2.collect_received();
+        recvd.assert_count::<PlayerListS2c>(1);
+        assert_eq!(recvd.first::<PlayerListS2c>().entries[0].username, "test_2");
+    }
+
+    // Move the second client to the lobby.
+    app.world
+        .entity_mut(client_2)
+        .insert(PlayerListGroup(lobby));
+
+    app.update();
+
+    {
+        let recvd = client_helper_1.collect_received();
+        recvd.assert_count::<PlayerListS2c>(1);
+        recvd.assert_count::<PlayerRemoveS2c>(0);
+        let pkt = recvd.first::<PlayerListS2c>();
+        assert!(pkt.actions.add_player());
+        assert_eq!(pkt.entries.len(), 1);
+        assert_eq!(pkt.entries[0].username, "test_2");
+    }
+
+    {
+        let recvd = client_helper_2.collect_received();
+        recvd.assert_count::<PlayerListS2c>(1);
+        recvd.assert_count::<PlayerRemoveS2c>(0);
+        let pkt = recvd.first::<PlayerListS2c>();
+        assert!(pkt.actions.add_player());
+        assert_eq!(pkt.entries.len(), 1);
+        assert_eq!(pkt.entries[0].username, "test");
+    }
+
+    // Move it back to the game.
+    app.world.entity_mut(client_2).insert(PlayerListGroup(game));
+
+    app.update();
+
+    {
+        let recvd = client_helper_1.collect_received();
+        recvd.assert_count::<PlayerListS2c>(0);
+        recvd.assert_count::<PlayerRemoveS2c>(1);
+        assert_eq!(recvd.first::<PlayerRemoveS2c>().uuids.len(), 1);
+    }
+
+    {
+        let recvd = client_helper_2.collect_received();
+        recvd.assert_count::<PlayerListS2c>(0);
+        recvd.assert_count::<PlayerRemoveS2c>(1);
+        assert_eq!(recvd.first::<PlayerRemoveS2c>().uuids.len(), 1);
+    }
+
+    // Updates to entries only go to clients in the same group.
+    app.world
+        .get_mut::<GameMode>(client_2)
+        .unwrap()
+        .set_if_neq(GameMode::Creative);
+
+    app.update();
+
+    client_helper_1
+        .collect_received()
+        .assert_count::<PlayerListS2c>(0);
+    client_helper_2
+        .collect_received()
+        .assert_count::<PlayerListS2c>(1);
+}
@@ -0,0 +1,128 @@
+use crate::block::{PropName, PropValue};
+use crate::layer::chunk::{Block, UnloadedChunk};
+use crate::layer::ChunkLayer;
+use crate::nbt::{compound, Compound};
+use crate::schem::{ExportSchematicError, LoadSchematicError, Schematic};
+use crate::testing::ScenarioSingleClient;
+use crate::{BlockPos, BlockState};
+
+#[test]
+fn export_then_paste_reproduces_blocks() {
+    let ScenarioSingleClient {
+        mut app,
+        client: _,
+        helper: _,
+        layer,
+    } = ScenarioSingleClient::new();
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer).unwrap();
+
+    for z in -2..2 {
+        for x in -2..2 {
+            layer.insert_chunk([x, z], UnloadedChunk::new());
+        }
+    }
+
+    let stairs = BlockState::OAK_STAIRS
+        .set(PropName::Facing, PropValue::East)
+        .set(PropName::Half, PropValue::Top);
+    let chest_nbt = compound! { "CustomName" => "{\"text\":\"Loot\"}" };
+
+    layer.set_block([-2, 64, -1], BlockState::STONE);
+    layer.set_block([-1, 65, -1], stairs);
+    layer.set_block(
+        [0, 66, 0],
+        Block::new(BlockState::CHEST, Some(chest_nbt.clone())),
+    );
+    // Stone can't have a block entity, so this one is skipped.
+    layer.set_block(
+        [0, 64, 0],
+        Block::new(BlockState::STONE, Some(Compound::new())),
+    );
+
+    let schem = Schematic::export_region(&layer, [0, 66, 0], [-2, 64, -1]).unwrap();
+
+    assert_eq!((schem.width(), schem.height(), schem.length()), (3, 3, 2));
+    assert_eq!(schem.block([0, 0, 0]).unwrap().state, BlockState::STONE);
+    assert_eq!(schem.block([2, 2, 1]).unwrap().nbt, Some(&chest_nbt));
+    assert_eq!(schem.block([2, 0, 1]).unwrap().nbt, None);
+
+    // Round trip through the binary format.
+    let mut buf = vec![];
+    schem.to_nbt().to_binary(&mut buf, "Schematic").unwrap();
+    let (nbt, _) = Compound::from_binary(&mut buf.as_slice()).unwrap();
+    let loaded = Schematic::from_nbt(nbt).unwrap();
+
+    assert_eq!(loaded, schem);
+
+    let origin = BlockPos::new(10, 80, 10);
+    loaded.paste(&mut layer, origin);
+
+    for y in 0..3 {
+        for z in 0..2 {
+            for x in 0..3 {
+                let offset = BlockPos::new(x, y, z);
+                let src = BlockPos::new(-2, 64, -1) + offset;
+
+                let pasted = layer.block(origin + offset).unwrap();
+                assert_eq!(pasted.state, layer.block(src).unwrap().state);
+                assert_eq!(pasted.nbt, schem.block(offset).unwrap().nbt);
+            }
+        }
+    }
+
+    assert_eq!(layer.block([11, 81, 10]).unwrap().state, stairs);
+    assert_eq!(layer.block([12, 82, 11]).unwrap().nbt, Some(&chest_nbt));
+}
+
+#[test]
+fn export_region_too_large() {
+    let ScenarioSingleClient {
+        app,
+        client: _,
+        helper: _,
+        layer,
+    } = ScenarioSingleClient::new();
+
+    let layer = app.world.get::<ChunkLayer>(layer).unwrap();
+
+    let res = Schematic::export_region(layer, [0, 64, 0], [70_000, 64, 0]);
+
+    assert!(matches!(
+        res,
+        Err(ExportSchematicError::RegionTooLarge(70_001))
+    ));
+
+    let res = Schematic::export_region(layer, [0, 0, 0], [9_999, 9_999, 99]);
+
+    assert!(matches!(
+        res,
+        Err(ExportSchematicError::VolumeTooLarge(10_000_000_000))
+    ));
+}
+
+#[test]
+fn load_rejects_bad_sizes() {
+    let schem = |width: i16| {
+        compound! {
+            "Version" => 2,
+            "Width" => width,
+            "Height" => i16::MAX,
+            "Length" => i16::MAX,
+            "Palette" => compound! { "minecraft:air" => 0 },
+            "BlockData" => vec![0_i8],
+        }
+    };
+
+    assert!(matches!(
+        Schematic::from_nbt(schem(-1)),
+        Err(LoadSchematicError::InvalidField("Width"))
+    ));
+
+    // The size doesn't match the block data, so it is rejected before anything is
+    // allocated for it.
+    assert!(matches!(
+        Schematic::from_nbt(schem(i16::MAX)),
+        Err(LoadSchematicError::BadBlockDataLen)
+    ));
+}
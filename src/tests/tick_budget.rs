@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use bevy_app::{App, Update};
+use bevy_ecs::prelude::*;
+
+use crate::entity::zombie::ZombieEntityBundle;
+use crate::entity::{EntityId, EntityLayerId, Position};
+use crate::layer::chunk::UnloadedChunk;
+use crate::layer::ChunkLayer;
+use crate::protocol::packets::play::EntitiesDestroyS2c;
+use crate::testing::ScenarioSingleClient;
+use crate::tick_budget::{AlwaysSimulated, BudgetedTick, TickBudget};
+
+#[derive(Resource, Default)]
+struct ProcessedCounts(HashMap<Entity, u32>);
+
+fn run_ai(npcs: Query<(Entity, &BudgetedTick)>, mut counts: ResMut<ProcessedCounts>) {
+    for (entity, budgeted) in &npcs {
+        if budgeted.is_scheduled() {
+            *counts.0.entry(entity).or_default() += 1;
+        }
+    }
+}
+
+#[test]
+fn tick_budget_round_robin() {
+    let ScenarioSingleClient { mut app, .. } = ScenarioSingleClient::new();
+
+    app.insert_resource(TickBudget {
+        per_tick: 100,
+        max_interval: 0,
+        skip_unviewed: false,
+    })
+    .init_resource::<ProcessedCounts>()
+    .add_systems(Update, run_ai);
+
+    for _ in 0..500 {
+        app.world.spawn(BudgetedTick::default());
+    }
+
+    for tick in 1..=5 {
+        app.update();
+
+        let counts = &app.world.resource::<ProcessedCounts>().0;
+        assert_eq!(counts.values().sum::<u32>(), tick * 100);
+    }
+
+    // Every entity was processed exactly once.
+    let counts = &app.world.resource::<ProcessedCounts>().0;
+    assert_eq!(counts.len(), 500);
+    assert!(counts.values().all(|&n| n == 1));
+
+    // The next round starts over with the first batch.
+    app.update();
+
+    let counts = &app.world.resource::<ProcessedCounts>().0;
+    assert_eq!(counts.values().filter(|&&n| n == 2).count(), 100);
+}
+
+#[test]
+fn tick_budget_max_interval() {
+    let ScenarioSingleClient { mut app, .. } = ScenarioSingleClient::new();
+
+    // Only 10 entities per tick would take 50 ticks, which is over the limit.
+    app.insert_resource(TickBudget {
+        per_tick: 10,
+        max_interval: 4,
+        skip_unviewed: false,
+    })
+    .init_resource::<ProcessedCounts>()
+    .add_systems(Update, run_ai);
+
+    for _ in 0..500 {
+        app.world.spawn(BudgetedTick::default());
+    }
+
+    for _ in 0..4 {
+        app.update();
+    }
+
+    let counts = &app.world.resource::<ProcessedCounts>().0;
+    assert_eq!(counts.len(), 500);
+}
+
+#[test]
+fn tick_budget_skip_unviewed() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer: layer_ent,
+    } = ScenarioSingleClient::new();
+
+    app.insert_resource(TickBudget {
+        skip_unviewed: true,
+        ..Default::default()
+    });
+
+    let mut layer = app.world.get_mut::<ChunkLayer>(layer_ent).unwrap();
+    layer.insert_chunk([0, 0], UnloadedChunk::new());
+    layer.insert_chunk([50, 0], UnloadedChunk::new());
+
+    let mut spawn_zombie = |pos: [f64; 3]| {
+        app.world
+            .spawn((
+                ZombieEntityBundle {
+                    layer: EntityLayerId(layer_ent),
+                    position: Position::new(pos),
+                    ..Default::default()
+                },
+                BudgetedTick::default(),
+            ))
+            .id()
+    };
+
+    let boss = spawn_zombie([8.0, 64.0, 8.0]);
+    let npc = spawn_zombie([8.0, 64.0, 8.0]);
+    let far_npc = spawn_zombie([808.0, 64.0, 8.0]);
+
+    app.world.entity_mut(boss).insert(AlwaysSimulated);
+
+    app.update();
+    app.update();
+
+    let is_scheduled = |app: &App, entity| {
+        app.world
+            .get::<BudgetedTick>(entity)
+            .unwrap()
+            .is_scheduled()
+    };
+
+    assert!(is_scheduled(&app, boss));
+    assert!(is_scheduled(&app, npc));
+    // The far chunk is loaded, but no one is viewing it.
+    assert!(!is_scheduled(&app, far_npc));
+
+    helper.clear_received();
+
+    // Leave the boss behind.
+    app.world
+        .get_mut::<Position>(client)
+        .unwrap()
+        .set([808.0, 64.0, 8.0]);
+
+    app.update();
+    app.update();
+
+    // The client stops seeing the boss even though it keeps simulating.
+    let boss_id = app.world.get::<EntityId>(boss).unwrap().get();
+    let frames = helper.collect_received();
+    frames.assert_count::<EntitiesDestroyS2c>(1);
+    assert!(frames
+        .first::<EntitiesDestroyS2c>()
+        .entity_ids
+        .iter()
+        .any(|id| id.0 == boss_id));
+
+    assert!(is_scheduled(&app, boss));
+    assert!(!is_scheduled(&app, npc));
+    assert!(is_scheduled(&app, far_npc));
+
+    // Removing the marker lets the boss rest again.
+    app.world.entity_mut(boss).remove::<AlwaysSimulated>();
+
+    app.update();
+
+    assert!(!is_scheduled(&app, boss));
+
+    // Unviewed entities are scheduled again once skipping is disabled.
+    app.world.resource_mut::<TickBudget>().skip_unviewed = false;
+
+    app.update();
+
+    assert!(is_scheduled(&app, boss));
+    assert!(is_scheduled(&app, npc));
+}
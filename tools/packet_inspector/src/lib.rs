@@ -209,7 +209,9 @@ impl Proxy {
                     if let Some(handshake) = extrapolate_packet::<HandshakeC2s>(&packet) {
                         *state_lock.write().await = match handshake.next_state {
                             HandshakeNextState::Status => PacketState::Status,
-                            HandshakeNextState::Login => PacketState::Login,
+                            HandshakeNextState::Login | HandshakeNextState::Transfer => {
+                                PacketState::Login
+                            }
                         };
                     }
                 }